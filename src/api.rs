@@ -1,4 +1,8 @@
+pub mod cache;
+pub mod circuit;
 pub mod clients;
+pub mod filter;
+pub mod http_log;
 pub mod polling;
 pub mod traits;
 pub mod types;
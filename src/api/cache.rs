@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a looked-up project or instance is reused before it is fetched again.
+pub const LOOKUP_TTL: Duration = Duration::from_secs(60);
+
+/// A small in-process cache whose entries expire `ttl` after being inserted.
+#[derive(Debug)]
+pub struct TtlCache<V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached value for `key` if it has not expired yet.
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((inserted, value)) if inserted.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: &str, value: V) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(key.to_string(), (Instant::now(), value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_cache_expires_entries() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get("projects/app"), None);
+        cache.insert("projects/app", 1);
+        assert_eq!(cache.get("projects/app"), Some(1));
+
+        let expired = TtlCache::new(Duration::ZERO);
+        expired.insert("projects/app", 1);
+        assert_eq!(expired.get("projects/app"), None);
+    }
+}
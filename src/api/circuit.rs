@@ -0,0 +1,112 @@
+use crate::error::AppError;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Consecutive failed requests that trip the breaker.
+pub const FAILURE_THRESHOLD: u32 = 5;
+/// How long new requests are held back once the breaker trips.
+pub const COOL_DOWN: Duration = Duration::from_secs(30);
+/// Cool-downs in a row without a successful request before giving up.
+pub const MAX_TRIPS: u32 = 3;
+
+/// Stops a batch run from hammering an API that keeps failing (e.g. while Bytebase restarts).
+///
+/// After `threshold` consecutive failures the breaker opens and new requests wait out the
+/// cool-down. The first request after that is a probe: a success closes the breaker, a failure
+/// opens it again. After `max_trips` cool-downs without a success, requests fail immediately.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cool_down: Duration,
+    max_trips: u32,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    trips: u32,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(FAILURE_THRESHOLD, COOL_DOWN, MAX_TRIPS)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cool_down: Duration, max_trips: u32) -> Self {
+        Self {
+            threshold,
+            cool_down,
+            max_trips,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// How long the caller must wait before sending, or an error once the breaker has given up.
+    pub fn wait_time(&self) -> Result<Option<Duration>, AppError> {
+        let state = self.state();
+        if state.trips > self.max_trips {
+            return Err(AppError::ApiError(format!(
+                "Bytebase API is still failing after {} cool-downs. Try again later.",
+                self.max_trips
+            )));
+        }
+        Ok(state
+            .open_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|wait| !wait.is_zero()))
+    }
+
+    pub fn record_success(&self) {
+        *self.state() = State::default();
+    }
+
+    /// Records a failed request. Returns true when this failure tripped the breaker.
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.state();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures < self.threshold {
+            return false;
+        }
+        state.trips += 1;
+        state.open_until = Some(Instant::now() + self.cool_down);
+        // The probe after the cool-down re-trips the breaker on its own if it fails.
+        state.consecutive_failures = self.threshold - 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker_trips_and_recovers() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60), 1);
+        assert!(!breaker.record_failure());
+        assert!(breaker.wait_time().unwrap().is_none());
+        assert!(breaker.record_failure());
+        assert!(breaker.wait_time().unwrap().is_some());
+
+        breaker.record_success();
+        assert!(breaker.wait_time().unwrap().is_none());
+        assert!(!breaker.record_failure());
+    }
+
+    #[test]
+    fn test_circuit_breaker_gives_up_after_max_trips() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO, 1);
+        breaker.record_failure();
+        assert!(breaker.record_failure());
+        assert!(breaker.wait_time().unwrap().is_none());
+        assert!(breaker.record_failure());
+        assert!(breaker.wait_time().is_err());
+    }
+}
@@ -1,18 +1,68 @@
+use crate::api::cache::{LOOKUP_TTL, TtlCache};
+use crate::api::circuit::CircuitBreaker;
+use crate::api::filter::Filter;
+use crate::api::http_log;
 use crate::api::traits::BytebaseApi;
 use crate::api::types::{
-    ChangeDatabaseConfig, ChangeDatabaseConfigType, Changelog, Instance, Issue, IssueName,
-    LoginRequest, LoginResponse, PlanName, PlanStep, PlanStepSpec, PostIssuesResponse,
-    PostPlansRequest, PostPlansResponse, PostSheetsResponse, Project, Revision, Rollout,
-    SheetName, SheetRequest, SqlCheckRequest,
+    ChangeDatabaseConfig, ChangeDatabaseConfigType, Changelog, DatabaseMetadata, ErrorPayload,
+    Instance, Issue, IssueDetails, IssueName, ListChangelogsResponse, ListOptions,
+    ListPlanCheckRunsResponse, ListRolloutsResponse, ListTaskRunsResponse, LoginRequest,
+    LoginResponse, PlanCheckResult, PlanCheckRun, PlanName, PlanStep, PlanStepSpec,
+    PostIssuesResponse, PostPlansRequest, PostPlansResponse, PostSheetsResponse, Project, Revision,
+    Rollout, SearchIssuesResponse, SheetName, SheetRequest, SqlCheckRequest, SqlCheckResponse,
+    TaskRun,
 };
-use crate::config::{ConfigOperations, Credentials};
-use crate::error::AppError;
+use crate::config::{
+    ApiSettings, AppConfig, ConfigOperations, Credentials, DEFAULT_RETRY_BUDGET_SECS,
+    EnvCredentials,
+};
+use crate::error::{AppError, BytebaseErrorCode};
 use async_trait::async_trait;
 use reqwest::header;
 use reqwest::header::{HeaderMap, HeaderValue};
 use serde_json::json;
+use std::io::{BufReader, Seek, SeekFrom, Write};
+use std::time::Duration;
+use tokio::time::sleep;
 use uuid::Uuid;
 
+/// Response bodies larger than this are spooled to disk before being parsed.
+const STREAM_TO_DISK_THRESHOLD: u64 = 8 * 1024 * 1024;
+
+/// Wait used for a 429 response that carries no usable `Retry-After` header.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Parses a `Retry-After` header given either as delay seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Whether a failed request was refused because Bytebase is in maintenance (read-only) mode:
+/// a 503, or the UNAVAILABLE status Bytebase answers writes with in that mode. The wording of
+/// the message is not looked at, so a SQL error that mentions "read only" still fails fast.
+fn is_maintenance(status: reqwest::StatusCode, payload: Option<&ErrorPayload>) -> bool {
+    status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+        || payload.and_then(ErrorPayload::code) == Some(BytebaseErrorCode::Unavailable)
+}
+
+/// Query parameters for the first page of `list_revisions`; the filter is left out when empty.
+fn revisions_query(filter: &Filter) -> Vec<(&'static str, String)> {
+    let mut query = vec![("pageSize", "100".to_string())];
+    if !filter.is_empty() {
+        query.push(("filter", filter.to_string()));
+    }
+    query
+}
+
 pub async fn get_access_token(
     base_url: &str,
     service_account: &str,
@@ -25,18 +75,192 @@ pub async fn get_access_token(
         password: service_key.to_string(),
         web: true,
     };
-    let response = client.post(&login_url).json(&request).send().await?;
+    let response = http_log::send(&client, client.post(&login_url).json(&request)).await?;
     Ok(response.json().await?)
 }
 
+/// Something the client did on its own that the user may want to know about. The client never
+/// prints; it hands these to the handler given to [`LiveApiClient::with_notices`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientNotice {
+    /// The circuit breaker is open, so new requests wait this long.
+    Paused(Duration),
+    /// Repeated failures tripped the circuit breaker.
+    CircuitTripped,
+    /// A rate-limited request is retried after this long.
+    RateLimited(Duration),
+    TokenExpired,
+    /// Another shelltide process already refreshed the expired token.
+    TokenReused,
+    TokenRefreshed,
+}
+
+impl std::fmt::Display for ClientNotice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Paused(wait) => write!(
+                f,
+                "Bytebase API keeps failing; pausing new requests for {}s...",
+                wait.as_secs_f32().ceil()
+            ),
+            Self::CircuitTripped => {
+                write!(
+                    f,
+                    "Circuit breaker tripped after repeated Bytebase API failures."
+                )
+            }
+            Self::RateLimited(delay) => write!(
+                f,
+                "Rate limited by Bytebase, retrying in {}s...",
+                delay.as_secs_f32().ceil()
+            ),
+            Self::TokenExpired => write!(f, "Token expired, attempting to refresh..."),
+            Self::TokenReused => {
+                write!(f, "Using the token refreshed by another shelltide process.")
+            }
+            Self::TokenRefreshed => write!(f, "Token refreshed successfully."),
+        }
+    }
+}
+
 /// A client for interacting with the live Bytebase API.
 #[derive(Debug)]
 pub struct LiveApiClient {
     client: reqwest::Client,
     base_url: String,
+    /// Token sent with every request, to tell whether another process has refreshed it.
+    access_token: String,
+    retry_budget: Duration,
+    breaker: CircuitBreaker,
+    projects: TtlCache<Project>,
+    instances: TtlCache<Instance>,
+    /// Receives the client's notices; ignores them unless set by [`Self::with_notices`].
+    on_notice: fn(&ClientNotice),
 }
 
 impl LiveApiClient {
+    /// Sends a request, logging it when `--debug-http` is enabled. Rate-limited (429) responses
+    /// are retried after their `Retry-After` delay until the retry budget is used up, and
+    /// repeated transport or 5xx failures pause new requests through the circuit breaker.
+    async fn send(
+        &self,
+        mut request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, AppError> {
+        let mut waited = Duration::ZERO;
+        loop {
+            if let Some(wait) = self.breaker.wait_time()? {
+                (self.on_notice)(&ClientNotice::Paused(wait));
+                sleep(wait).await;
+            }
+
+            let retry = request.try_clone();
+            let response = match http_log::send(&self.client, request).await {
+                Ok(response) => response,
+                Err(e) => {
+                    self.record_failure();
+                    return Err(e);
+                }
+            };
+            if response.status().is_server_error() {
+                self.record_failure();
+            } else {
+                self.breaker.record_success();
+            }
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+
+            let delay = retry_after(response.headers()).unwrap_or(DEFAULT_RETRY_AFTER);
+            match retry {
+                Some(retry) if waited + delay <= self.retry_budget => {
+                    (self.on_notice)(&ClientNotice::RateLimited(delay));
+                    sleep(delay).await;
+                    waited += delay;
+                    request = retry;
+                }
+                _ => return Ok(response),
+            }
+        }
+    }
+
+    fn record_failure(&self) {
+        if self.breaker.record_failure() {
+            (self.on_notice)(&ClientNotice::CircuitTripped);
+        }
+    }
+
+    /// Hands the client's notices (retries, token refreshes, ...) to `on_notice`.
+    pub fn with_notices(mut self, on_notice: fn(&ClientNotice)) -> Self {
+        self.on_notice = on_notice;
+        self
+    }
+
+    /// Sets the total time spent waiting on rate-limited requests.
+    pub fn with_settings(mut self, settings: &ApiSettings) -> Self {
+        self.retry_budget = Duration::from_secs(settings.retry_budget());
+        self
+    }
+
+    /// Deserializes a successful response body that may be very large (changelogs carry full
+    /// schema snapshots). Bodies above [`STREAM_TO_DISK_THRESHOLD`] or of unknown length are
+    /// streamed to a temporary file and parsed from disk, so the raw text is never held in memory.
+    async fn read_large_json<T: serde::de::DeserializeOwned + Send + 'static>(
+        mut response: reqwest::Response,
+        operation: &str,
+    ) -> Result<T, AppError> {
+        let parse_error = |e: serde_json::Error| {
+            AppError::ApiError(format!("Failed to parse {operation} response: {e}"))
+        };
+
+        match response.content_length() {
+            Some(len) if len <= STREAM_TO_DISK_THRESHOLD => {
+                let body = response.bytes().await?;
+                serde_json::from_slice(&body).map_err(parse_error)
+            }
+            _ => {
+                let mut spool = tempfile::tempfile()?;
+                while let Some(chunk) = response.chunk().await? {
+                    spool.write_all(&chunk)?;
+                }
+                spool.seek(SeekFrom::Start(0))?;
+                let result = tokio::task::spawn_blocking(move || {
+                    serde_json::from_reader::<_, T>(BufReader::new(spool))
+                })
+                .await
+                .map_err(|e| {
+                    AppError::ApiError(format!("Failed to parse {operation} response: {e}"))
+                })?;
+                result.map_err(parse_error)
+            }
+        }
+    }
+
+    /// Builds the error for a failed request. Maintenance mode (see [`is_maintenance`]) becomes
+    /// [`AppError::ServerMaintenance`], structured Bytebase payloads become
+    /// [`AppError::BytebaseError`]; anything else keeps the raw status and body.
+    fn error_from_response(
+        operation: &str,
+        status: reqwest::StatusCode,
+        response_text: &str,
+    ) -> AppError {
+        let payload = serde_json::from_str::<ErrorPayload>(response_text);
+        let message = payload
+            .as_ref()
+            .map_or(response_text, |payload| payload.message.as_str());
+        if is_maintenance(status, payload.as_ref().ok()) {
+            return AppError::ServerMaintenance(format!("{operation} failed: {}", message.trim()));
+        }
+        match payload {
+            Ok(payload) if payload.code().is_some() => AppError::BytebaseError {
+                code: payload.code().unwrap_or(BytebaseErrorCode::Unknown),
+                message: format!("{operation} failed: {}", payload.message),
+            },
+            _ => AppError::ApiError(format!(
+                "{operation} failed. Status: {status}, Response: {response_text}",
+            )),
+        }
+    }
+
     /// Helper function to handle API responses with consistent error logging
     async fn handle_response<T: serde::de::DeserializeOwned>(
         response: reqwest::Response,
@@ -46,23 +270,54 @@ impl LiveApiClient {
         let response_text = response.text().await?;
 
         if !status.is_success() {
-            println!("{operation} failed - Status: {status}, Response: {response_text}",);
-            return Err(AppError::ApiError(format!(
-                "{operation} failed. Status: {status}, Response: {response_text}",
-            )));
+            return Err(Self::error_from_response(operation, status, &response_text));
         }
 
         match serde_json::from_str::<T>(&response_text) {
             Ok(result) => Ok(result),
-            Err(e) => {
-                println!(
-                    "Failed to parse {operation} response - Status: {status}, Response: {response_text}",
-                );
-                Err(AppError::ApiError(format!(
-                    "Failed to parse {operation} response: {e}",
-                )))
+            Err(e) => Err(AppError::ApiError(format!(
+                "Failed to parse {operation} response: {e}",
+            ))),
+        }
+    }
+
+    /// Lists every resource of a collection such as `projects`, returning the last segment of
+    /// each resource name (e.g. `dev` for `projects/dev`).
+    async fn list_resource_ids(
+        &self,
+        collection: &str,
+        operation: &str,
+    ) -> Result<Vec<String>, AppError> {
+        let mut ids = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let url = format!("{}/v1/{collection}", self.base_url);
+            let mut request = self.client.get(&url).query(&[("pageSize", "100")]);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+
+            let response: serde_json::Value =
+                Self::handle_response(self.send(request).await?, operation).await?;
+            if let Some(items) = response.get(collection).and_then(|v| v.as_array()) {
+                ids.extend(items.iter().filter_map(|item| {
+                    let name = item.get("name")?.as_str()?;
+                    Some(name.rsplit('/').next().unwrap_or(name).to_string())
+                }));
+            }
+
+            page_token = response
+                .get("nextPageToken")
+                .and_then(|token| token.as_str())
+                .filter(|token| !token.is_empty())
+                .map(|s| s.to_string());
+            if page_token.is_none() {
+                break;
             }
         }
+
+        Ok(ids)
     }
 
     /// Creates a new API client with the given credentials.
@@ -86,6 +341,12 @@ impl LiveApiClient {
         Ok(Self {
             client,
             base_url: credentials.url.clone(),
+            access_token: credentials.access_token.clone(),
+            retry_budget: Duration::from_secs(DEFAULT_RETRY_BUDGET_SECS),
+            breaker: CircuitBreaker::default(),
+            projects: TtlCache::new(LOOKUP_TTL),
+            instances: TtlCache::new(LOOKUP_TTL),
+            on_notice: |_| {},
         })
     }
 
@@ -100,37 +361,74 @@ impl LiveApiClient {
         self.client = reqwest::Client::builder()
             .default_headers(headers)
             .build()?;
+        self.access_token = credentials.access_token.clone();
         Ok(())
     }
 
-    /// Ensures the client is authenticated with a valid token, refreshing if necessary
-    pub async fn ensure_authenticated(&mut self) -> Result<(), AppError> {
-        use crate::config::ProductionConfig;
-        let config_ops = ProductionConfig;
-        self.ensure_authenticated_with_config(&config_ops).await
+    /// Whether the current token is accepted, tried on the most basic authenticated endpoint.
+    pub async fn token_is_valid(&self) -> Result<bool, AppError> {
+        let url = format!("{}/v1/projects", self.base_url);
+        let response = self.send(self.client.get(&url)).await?;
+        Ok(response.status() != reqwest::StatusCode::UNAUTHORIZED
+            && response.status() != reqwest::StatusCode::FORBIDDEN)
+    }
+
+    /// Creates an authenticated client for `config`, with the `BYTEBASE_*` environment variables
+    /// applied. Credentials from the environment log in afresh; saved ones are validated and
+    /// refreshed through `config_ops` if necessary. Notices go to `on_notice`.
+    pub async fn connect<C: ConfigOperations>(
+        config: &AppConfig,
+        config_ops: &C,
+        on_notice: fn(&ClientNotice),
+    ) -> Result<Self, AppError> {
+        let env = EnvCredentials::from_env();
+        let mut credentials = config.resolve_credentials(&env)?;
+        if env.has_identity() {
+            if credentials.access_token.is_empty()
+                && let Some(service_key) = &credentials.service_key
+            {
+                credentials.access_token =
+                    get_access_token(&credentials.url, &credentials.service_account, service_key)
+                        .await?
+                        .token;
+            }
+            return Ok(Self::new(&credentials)?
+                .with_settings(&config.api)
+                .with_notices(on_notice));
+        }
+
+        let mut client = Self::new(&credentials)?
+            .with_settings(&config.api)
+            .with_notices(on_notice);
+        client.ensure_authenticated_with_config(config_ops).await?;
+        Ok(client)
     }
 
     pub async fn ensure_authenticated_with_config<C: ConfigOperations>(
         &mut self,
         config_ops: &C,
     ) -> Result<(), AppError> {
-        // Token validation by trying to list projects (most basic authenticated endpoint)
-        let url = format!("{}/v1/projects", self.base_url);
-        let response = self.client.get(&url).send().await?;
-
-        if response.status() == reqwest::StatusCode::UNAUTHORIZED
-            || response.status() == reqwest::StatusCode::FORBIDDEN
-        {
-            println!("Token expired, attempting to refresh...");
+        if !self.token_is_valid().await? {
+            (self.on_notice)(&ClientNotice::TokenExpired);
 
-            // Load current credentials
+            // Load current credentials, holding the lock until the refreshed token is saved.
+            // Parallel processes queue up here, so only the first one logs in again.
+            let _lock = config_ops.lock_config().await?;
             let config = config_ops.load_config().await?;
             let credentials = config.get_credentials()?;
 
+            if credentials.access_token != self.access_token {
+                self.login(credentials)?;
+                if self.token_is_valid().await? {
+                    (self.on_notice)(&ClientNotice::TokenReused);
+                    return Ok(());
+                }
+            }
+
             // Check if we have service_key for refresh
             if let Some(service_key) = &credentials.service_key {
                 let login_response =
-                    get_access_token(&credentials.url, &credentials.service_account, service_key)
+                    get_access_token(&self.base_url, &credentials.service_account, service_key)
                         .await?;
 
                 // Update credentials and save to config
@@ -138,13 +436,13 @@ impl LiveApiClient {
                 updated_credentials.access_token = login_response.token;
 
                 let mut updated_config = config;
-                updated_config.credentials = Some(updated_credentials.clone());
+                updated_config.set_credentials(updated_credentials.clone());
                 config_ops.save_config(&updated_config).await?;
 
                 // Update client with new token
                 self.login(&updated_credentials)?;
 
-                println!("Token refreshed successfully.");
+                (self.on_notice)(&ClientNotice::TokenRefreshed);
                 Ok(())
             } else {
                 Err(AppError::Config(
@@ -161,22 +459,87 @@ impl LiveApiClient {
 #[async_trait]
 impl BytebaseApi for LiveApiClient {
     async fn get_project(&self, project_name: &str) -> Result<Project, AppError> {
+        if let Some(project) = self.projects.get(project_name) {
+            return Ok(project);
+        }
+
         let url = format!("{}/v1/projects/{}", self.base_url, project_name);
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
 
         if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err(AppError::ApiError(format!(
-                "Project '{project_name}' not found."
-            )));
+            return Err(AppError::BytebaseError {
+                code: BytebaseErrorCode::NotFound,
+                message: format!("Project '{project_name}' not found."),
+            });
         }
 
-        Self::handle_response(response, &format!("Get project '{project_name}'")).await
+        let project: Project =
+            Self::handle_response(response, &format!("Get project '{project_name}'")).await?;
+        self.projects.insert(project_name, project.clone());
+        Ok(project)
     }
 
     async fn get_instance(&self, instance_name: &str) -> Result<Instance, AppError> {
+        if let Some(instance) = self.instances.get(instance_name) {
+            return Ok(instance);
+        }
+
         let url = format!("{}/v1/instances/{}", self.base_url, instance_name);
-        let response = self.client.get(&url).send().await?;
-        Self::handle_response(response, &format!("Get instance '{instance_name}'")).await
+        let response = self.send(self.client.get(&url)).await?;
+        let instance: Instance =
+            Self::handle_response(response, &format!("Get instance '{instance_name}'")).await?;
+        self.instances.insert(instance_name, instance.clone());
+        Ok(instance)
+    }
+
+    async fn get_database_metadata(
+        &self,
+        instance: &str,
+        database: &str,
+    ) -> Result<DatabaseMetadata, AppError> {
+        let url = format!(
+            "{}/v1/instances/{instance}/databases/{database}/metadata",
+            self.base_url
+        );
+        let response = self.send(self.client.get(&url)).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let response_text = response.text().await?;
+            return Err(Self::error_from_response(
+                &format!("Get metadata of '{instance}/{database}'"),
+                status,
+                &response_text,
+            ));
+        }
+        Self::read_large_json(response, "Get database metadata").await
+    }
+
+    async fn sync_database(&self, instance: &str, database: &str) -> Result<(), AppError> {
+        let url = format!(
+            "{}/v1/instances/{instance}/databases/{database}:sync",
+            self.base_url
+        );
+        let response = self
+            .send(self.client.post(&url).json(&serde_json::json!({})))
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let response_text = response.text().await?;
+            return Err(Self::error_from_response(
+                &format!("Sync database '{instance}/{database}'"),
+                status,
+                &response_text,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn list_projects(&self) -> Result<Vec<String>, AppError> {
+        self.list_resource_ids("projects", "List projects").await
+    }
+
+    async fn list_instances(&self) -> Result<Vec<String>, AppError> {
+        self.list_resource_ids("instances", "List instances").await
     }
 
     async fn get_done_issues(&self, project_name: &str) -> Result<Vec<Issue>, AppError> {
@@ -185,32 +548,30 @@ impl BytebaseApi for LiveApiClient {
 
         loop {
             let url = format!("{}/v1/projects/{}/issues", self.base_url, project_name);
-            let mut request = self
-                .client
-                .get(&url)
-                .query(&[("filter", "status=\"DONE\""), ("pageSize", "100")]);
+            let mut request = self.client.get(&url).query(&[
+                ("filter", Filter::new().eq("status", "DONE").to_string()),
+                ("pageSize", "100".to_string()),
+            ]);
 
             if let Some(token) = &page_token {
                 request = request.query(&[("pageToken", token)]);
             }
 
-            let response = request.send().await?;
+            let response = self.send(request).await?;
             let status = response.status();
             let response_text = response.text().await?;
 
             if !status.is_success() {
-                println!("Get done issues failed - Status: {status}, Response: {response_text}");
-                return Err(AppError::ApiError(format!(
-                    "Get done issues for project '{project_name}' failed. Status: {status}, Response: {response_text}",
-                )));
+                return Err(Self::error_from_response(
+                    &format!("Get done issues for project '{project_name}'"),
+                    status,
+                    &response_text,
+                ));
             }
 
             let response_value: serde_json::Value = match serde_json::from_str(&response_text) {
                 Ok(value) => value,
                 Err(e) => {
-                    println!(
-                        "Failed to parse done issues response - Status: {status}, Response: {response_text}",
-                    );
                     return Err(AppError::ApiError(format!(
                         "Failed to parse done issues response: {e}",
                     )));
@@ -247,7 +608,7 @@ impl BytebaseApi for LiveApiClient {
             "{}/v1/projects/{}/sheets",
             self.base_url, target_project_name
         );
-        let response = self.client.post(&url).json(&sheet).send().await?;
+        let response = self.send(self.client.post(&url).json(&sheet)).await?;
         Self::handle_response(
             response,
             &format!("Create sheet for project '{target_project_name}'"),
@@ -255,28 +616,31 @@ impl BytebaseApi for LiveApiClient {
         .await
     }
 
-    /// For now, createing a new Database is not supported.  
+    /// For now, createing a new Database is not supported.
+    /// Each sheet becomes one spec of a single step, so the sheets run in the given order.
     async fn create_plan(
         &self,
         project: &str,
         target_instance: &str,
         target_database: &str,
-        sheet_name: SheetName,
+        sheet_names: &[SheetName],
     ) -> Result<PostPlansResponse, AppError> {
         let url = format!("{}/v1/projects/{project}/plans", self.base_url);
-        let steps = vec![PlanStep {
-            specs: vec![PlanStepSpec {
+        let specs = sheet_names
+            .iter()
+            .map(|sheet_name| PlanStepSpec {
                 id: Uuid::new_v4(),
                 change_database_config: ChangeDatabaseConfig {
                     target: format!("instances/{target_instance}/databases/{target_database}"),
-                    sheet: sheet_name,
+                    sheet: sheet_name.clone(),
                     config_type: ChangeDatabaseConfigType::Migrate,
                 },
-            }],
-        }];
+            })
+            .collect();
+        let steps = vec![PlanStep { specs }];
 
         let plan = PostPlansRequest { steps };
-        let response = self.client.post(&url).json(&plan).send().await?;
+        let response = self.send(self.client.post(&url).json(&plan)).await?;
         Self::handle_response(response, &format!("Create plan for project '{project}'")).await
     }
 
@@ -295,7 +659,7 @@ impl BytebaseApi for LiveApiClient {
             "plan": plan_name,
             "issue": issue_name,
         });
-        let response = self.client.post(&url).json(&body).send().await?;
+        let response = self.send(self.client.post(&url).json(&body)).await?;
         Self::handle_response(
             response,
             &format!("Create rollout for project '{target_project_name}'"),
@@ -308,23 +672,144 @@ impl BytebaseApi for LiveApiClient {
             "{}/v1/projects/{}/rollouts/{}",
             self.base_url, project, rollout_id
         );
-        let response = self.client.get(&url).send().await?;
-        Self::handle_response(response, &format!("Get rollout '{project}/rollouts/{rollout_id}'"))
-            .await
+        let response = self.send(self.client.get(&url)).await?;
+        Self::handle_response(
+            response,
+            &format!("Get rollout '{project}/rollouts/{rollout_id}'"),
+        )
+        .await
+    }
+
+    async fn list_rollouts(&self, project: &str, limit: usize) -> Result<Vec<Rollout>, AppError> {
+        let url = format!("{}/v1/projects/{project}/rollouts", self.base_url);
+        let mut rollouts = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        while rollouts.len() < limit {
+            let page_size = (limit - rollouts.len()).min(100).to_string();
+            let mut request = self.client.get(&url).query(&[("pageSize", page_size)]);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+
+            let response = self.send(request).await?;
+            let page: ListRolloutsResponse =
+                Self::handle_response(response, &format!("List rollouts of project '{project}'"))
+                    .await?;
+            rollouts.extend(page.rollouts);
+
+            page_token = page.next_page_token.filter(|token| !token.is_empty());
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        rollouts.truncate(limit);
+        Ok(rollouts)
+    }
+
+    async fn get_issue(&self, project: &str, number: u32) -> Result<IssueDetails, AppError> {
+        let url = format!("{}/v1/projects/{project}/issues/{number}", self.base_url);
+        let response = self.send(self.client.get(&url)).await?;
+        Self::handle_response(response, &format!("Get issue '{project}/issues/{number}'")).await
+    }
+
+    async fn search_issues(
+        &self,
+        project: &str,
+        query: &str,
+        filter: &Filter,
+        limit: usize,
+    ) -> Result<Vec<IssueDetails>, AppError> {
+        let url = format!("{}/v1/projects/{project}/issues:search", self.base_url);
+        let mut issues = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        while issues.len() < limit {
+            let mut body = serde_json::json!({
+                "query": query,
+                "pageSize": (limit - issues.len()).min(100),
+            });
+            if !filter.is_empty() {
+                body["filter"] = serde_json::Value::String(filter.to_string());
+            }
+            if let Some(token) = &page_token {
+                body["pageToken"] = serde_json::Value::String(token.clone());
+            }
+
+            let response = self.send(self.client.post(&url).json(&body)).await?;
+            let page: SearchIssuesResponse =
+                Self::handle_response(response, &format!("Search issues of project '{project}'"))
+                    .await?;
+            issues.extend(page.issues);
+
+            page_token = page.next_page_token.filter(|token| !token.is_empty());
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        issues.truncate(limit);
+        Ok(issues)
+    }
+
+    async fn list_task_runs(
+        &self,
+        project: &str,
+        rollout_id: u32,
+    ) -> Result<Vec<TaskRun>, AppError> {
+        let url = format!(
+            "{}/v1/projects/{}/rollouts/{}/stages/-/tasks/-/taskRuns",
+            self.base_url, project, rollout_id
+        );
+        let mut task_runs = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self.client.get(&url);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+
+            let response = self.send(request).await?;
+            let page: ListTaskRunsResponse = Self::handle_response(
+                response,
+                &format!("List task runs of '{project}/rollouts/{rollout_id}'"),
+            )
+            .await?;
+            task_runs.extend(page.task_runs);
+
+            page_token = page.next_page_token.filter(|token| !token.is_empty());
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(task_runs)
+    }
+
+    async fn list_plan_check_runs(&self, plan: &PlanName) -> Result<Vec<PlanCheckRun>, AppError> {
+        let url = format!("{}/v1/{plan}/planCheckRuns", self.base_url);
+        let response = self.send(self.client.get(&url)).await?;
+        let runs: ListPlanCheckRunsResponse =
+            Self::handle_response(response, &format!("List plan check runs of '{plan}'")).await?;
+        Ok(runs.plan_check_runs)
     }
 
     async fn create_issue(
         &self,
         project_name: &str,
         plan: &PlanName,
+        description: &str,
     ) -> Result<PostIssuesResponse, AppError> {
         let url = format!("{}/v1/projects/{}/issues", self.base_url, project_name);
         let body = json!({
             "plan": plan,
             "title": "auto-generated issue by Shelltide",
+            "description": description,
             "type": "DATABASE_CHANGE",
         });
-        let response = self.client.post(&url).json(&body).send().await?;
+        let response = self.send(self.client.post(&url).json(&body)).await?;
         Self::handle_response(
             response,
             &format!("Create issue for project '{project_name}'"),
@@ -332,6 +817,31 @@ impl BytebaseApi for LiveApiClient {
         .await
     }
 
+    async fn create_issue_comment(
+        &self,
+        project: &str,
+        issue_number: u32,
+        comment: &str,
+    ) -> Result<(), AppError> {
+        let url = format!(
+            "{}/v1/projects/{project}/issues/{issue_number}:comment",
+            self.base_url
+        );
+        let response = self
+            .send(self.client.post(&url).json(&json!({ "comment": comment })))
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let response_text = response.text().await?;
+            return Err(Self::error_from_response(
+                &format!("Comment on issue '{project}/issues/{issue_number}'"),
+                status,
+                &response_text,
+            ));
+        }
+        Ok(())
+    }
+
     async fn check_sql(&self, instance: &str, database: &str, sql: &str) -> Result<(), AppError> {
         let url = format!("{}/v1/sql/check", self.base_url);
         let request = SqlCheckRequest {
@@ -339,15 +849,16 @@ impl BytebaseApi for LiveApiClient {
             statement: sql.to_string(),
         };
 
-        let response = self.client.post(&url).json(&request).send().await?;
+        let response = self.send(self.client.post(&url).json(&request)).await?;
         let status = response.status();
         let response_text = response.text().await?;
 
         if !status.is_success() {
-            println!("SQL check failed - Status: {status}, Response: {response_text}",);
-            return Err(AppError::ApiError(format!(
-                "SQL check failed. Status: {status}, Response: {response_text}",
-            )));
+            return Err(Self::error_from_response(
+                "SQL check",
+                status,
+                &response_text,
+            ));
         }
 
         // 성공하면 빈 오브젝트가옴
@@ -359,22 +870,46 @@ impl BytebaseApi for LiveApiClient {
                     Ok(())
                 }
             }
-            Err(e) => {
-                println!(
-                    "Failed to parse SQL check response - Status: {status}, Response: {response_text}",
-                );
-                Err(AppError::ApiError(format!(
-                    "Failed to parse SQL check response: {e}"
-                )))
-            }
+            Err(e) => Err(AppError::ApiError(format!(
+                "Failed to parse SQL check response: {e}"
+            ))),
         }
     }
 
-    async fn get_latests_revisions(
+    async fn review_sql(
         &self,
         instance: &str,
         database: &str,
-    ) -> Result<Revision, AppError> {
+        sql: &str,
+    ) -> Result<Vec<PlanCheckResult>, AppError> {
+        let url = format!("{}/v1/sql/check", self.base_url);
+        let request = SqlCheckRequest {
+            name: format!("instances/{instance}/databases/{database}"),
+            statement: sql.to_string(),
+        };
+
+        let response = self.send(self.client.post(&url).json(&request)).await?;
+        let status = response.status();
+        let response_text = response.text().await?;
+        if !status.is_success() {
+            return Err(Self::error_from_response(
+                "SQL review",
+                status,
+                &response_text,
+            ));
+        }
+
+        let response: SqlCheckResponse = serde_json::from_str(&response_text)
+            .map_err(|e| AppError::ApiError(format!("Failed to parse SQL check response: {e}")))?;
+        Ok(response.advices)
+    }
+
+    async fn list_revisions(
+        &self,
+        instance: &str,
+        database: &str,
+        filter: &Filter,
+    ) -> Result<Vec<Revision>, AppError> {
         let mut all_revisions = Vec::new();
         let mut page_token: Option<String> = None;
 
@@ -383,33 +918,29 @@ impl BytebaseApi for LiveApiClient {
                 "{}/v1/instances/{instance}/databases/{database}/revisions",
                 self.base_url,
             );
-            let mut request = self.client.get(&url).query(&[("pageSize", "100")]);
+            let mut request = self.client.get(&url).query(&revisions_query(filter));
 
             if let Some(token) = &page_token {
                 request = request.query(&[("pageToken", token)]);
             }
 
-            let response = request.send().await?;
+            let response = self.send(request).await?;
             let status = response.status();
             let response_text = response.text().await?;
 
             if !status.is_success() {
-                println!(
-                    "Get latest revisions failed - Status: {status}, Response: {response_text}",
-                );
-                return Err(AppError::ApiError(format!(
-                    "Get latest revisions failed. Status: {status}, Response: {response_text}",
-                )));
+                return Err(Self::error_from_response(
+                    "List revisions",
+                    status,
+                    &response_text,
+                ));
             }
 
             let response_value: serde_json::Value = match serde_json::from_str(&response_text) {
                 Ok(value) => value,
                 Err(e) => {
-                    println!(
-                        "Failed to parse latest revisions response - Status: {status}, Response: {response_text}",
-                    );
                     return Err(AppError::ApiError(format!(
-                        "Failed to parse latest revisions response: {e}",
+                        "Failed to parse revisions response: {e}",
                     )));
                 }
             };
@@ -434,20 +965,14 @@ impl BytebaseApi for LiveApiClient {
             }
         }
 
-        all_revisions
-            .iter()
-            .filter(|r| r.create_time.is_some())
-            .max_by_key(|r| r.create_time.as_ref().unwrap())
-            .cloned()
-            .ok_or_else(|| {
-                AppError::ApiError("No revisions with valid create_time found".to_string())
-            })
+        Ok(all_revisions)
     }
 
     async fn get_changelogs(
         &self,
         instance: &str,
         database: &str,
+        options: &ListOptions,
     ) -> Result<Vec<Changelog>, AppError> {
         let mut all_changelogs = Vec::new();
         let mut page_token: Option<String> = None;
@@ -457,49 +982,29 @@ impl BytebaseApi for LiveApiClient {
                 "{}/v1/instances/{instance}/databases/{database}/changelogs",
                 self.base_url,
             );
-            let mut request = self
-                .client
-                .get(&url)
-                .query(&[("pageSize", "100"), ("view", "CHANGELOG_VIEW_FULL")]);
+            let mut request = self.client.get(&url).query(&options.query());
 
             if let Some(token) = &page_token {
                 request = request.query(&[("pageToken", token)]);
             }
 
-            let response = request.send().await?;
+            let response = self.send(request).await?;
             let status = response.status();
-            let response_text = response.text().await?;
 
             if !status.is_success() {
-                return Err(AppError::ApiError(format!(
-                    "Get changelogs failed. Status: {status}, Response: {response_text}"
-                )));
+                let response_text = response.text().await?;
+                return Err(Self::error_from_response(
+                    "Get changelogs",
+                    status,
+                    &response_text,
+                ));
             }
 
-            let response_value: serde_json::Value = match serde_json::from_str(&response_text) {
-                Ok(value) => value,
-                Err(e) => {
-                    return Err(AppError::ApiError(format!(
-                        "Failed to parse changelogs response: {e}"
-                    )));
-                }
-            };
-
-            if let Some(changelogs_array) =
-                response_value.get("changelogs").and_then(|v| v.as_array())
-            {
-                let page_changelogs: Vec<Changelog> = changelogs_array
-                    .iter()
-                    .filter_map(|c| serde_json::from_value::<Changelog>(c.clone()).ok())
-                    .filter(|c| c.status == "DONE" && !c.statement.is_empty())
-                    .collect();
-                all_changelogs.extend(page_changelogs);
-            }
+            let page: ListChangelogsResponse =
+                Self::read_large_json(response, "changelogs").await?;
+            all_changelogs.extend(page.changelogs);
 
-            page_token = response_value
-                .get("nextPageToken")
-                .and_then(|token| token.as_str())
-                .map(|s| s.to_string());
+            page_token = page.next_page_token.filter(|token| !token.is_empty());
 
             if page_token.is_none() {
                 break;
@@ -527,12 +1032,11 @@ impl BytebaseApi for LiveApiClient {
             "version": version,
             "sheet": sheet,
         });
-        let response = self.client.post(&url).json(&body).send().await?;
+        let response = self.send(self.client.post(&url).json(&body)).await?;
         let status = response.status();
 
         if !status.is_success() {
             let error_body = response.text().await.unwrap_or_default();
-            println!("Revision creation failed - Status: {status}, Response: {error_body}");
             return Err(AppError::ApiError(format!(
                 "Failed to create revision. Status: {status}, Response: {error_body}",
             )));
@@ -542,9 +1046,6 @@ impl BytebaseApi for LiveApiClient {
         match serde_json::from_str::<Revision>(&response_text) {
             Ok(revision) => Ok(revision),
             Err(e) => {
-                println!(
-                    "Failed to parse revision response - Status: {status}, Response: {response_text}"
-                );
                 let error_msg = format!("Failed to parse revision response: {e}");
                 Err(AppError::ApiError(error_msg))
             }
@@ -563,15 +1064,16 @@ impl BytebaseApi for LiveApiClient {
                 request = request.query(&[("pageToken", token)]);
             }
 
-            let response = request.send().await?;
+            let response = self.send(request).await?;
             let status = response.status();
             let response_text = response.text().await?;
 
             if !status.is_success() {
-                println!("Get databases failed - Status: {status}, Response: {response_text}");
-                return Err(AppError::ApiError(format!(
-                    "Get databases failed. Status: {status}, Response: {response_text}"
-                )));
+                return Err(Self::error_from_response(
+                    "Get databases",
+                    status,
+                    &response_text,
+                ));
             }
 
             // Parse the response to extract database names and next page token
@@ -610,9 +1112,6 @@ impl BytebaseApi for LiveApiClient {
                     }
                 }
                 Err(e) => {
-                    println!(
-                        "Failed to parse databases response - Status: {status}, Response: {response_text}"
-                    );
                     return Err(AppError::ApiError(format!(
                         "Failed to parse databases response: {e}"
                     )));
@@ -622,74 +1121,6 @@ impl BytebaseApi for LiveApiClient {
 
         Ok(all_databases)
     }
-
-    async fn get_latests_revisions_silent(
-        &self,
-        instance: &str,
-        database: &str,
-    ) -> Result<Revision, AppError> {
-        let mut all_revisions = Vec::new();
-        let mut page_token: Option<String> = None;
-
-        loop {
-            let url = format!(
-                "{}/v1/instances/{instance}/databases/{database}/revisions",
-                self.base_url,
-            );
-            let mut request = self.client.get(&url).query(&[("pageSize", "100")]);
-
-            if let Some(token) = &page_token {
-                request = request.query(&[("pageToken", token)]);
-            }
-
-            let response = request.send().await?;
-            let status = response.status();
-            let response_text = response.text().await?;
-
-            if !status.is_success() {
-                return Err(AppError::ApiError(format!(
-                    "Get latest revisions failed. Status: {status}"
-                )));
-            }
-
-            let response_value: serde_json::Value = match serde_json::from_str(&response_text) {
-                Ok(value) => value,
-                Err(e) => {
-                    return Err(AppError::ApiError(format!(
-                        "Failed to parse latest revisions response: {e}"
-                    )));
-                }
-            };
-
-            if let Some(revisions_array) =
-                response_value.get("revisions").and_then(|v| v.as_array())
-            {
-                let page_revisions: Vec<Revision> = revisions_array
-                    .iter()
-                    .filter_map(|r| serde_json::from_value::<Revision>(r.clone()).ok())
-                    .collect();
-                all_revisions.extend(page_revisions);
-            }
-
-            page_token = response_value
-                .get("nextPageToken")
-                .and_then(|token| token.as_str())
-                .map(|s| s.to_string());
-
-            if page_token.is_none() {
-                break;
-            }
-        }
-
-        all_revisions
-            .iter()
-            .filter(|r| r.create_time.is_some())
-            .max_by_key(|r| r.create_time.as_ref().unwrap())
-            .cloned()
-            .ok_or_else(|| {
-                AppError::ApiError("No revisions with valid create_time found".to_string())
-            })
-    }
 }
 
 #[cfg(test)]
@@ -700,11 +1131,12 @@ pub mod tests {
 
     use crate::{
         api::{
+            filter::Filter,
             traits::BytebaseApi,
             types::{
-                Changelog, Instance, Issue, IssueName, PlanName, PostIssuesResponse,
-                PostPlansResponse, PostSheetsResponse, Project, Revision, Rollout, SheetName,
-                SheetRequest,
+                Changelog, DatabaseMetadata, Instance, Issue, IssueDetails, IssueName, ListOptions,
+                PlanCheckResult, PlanCheckRun, PlanName, PostIssuesResponse, PostPlansResponse,
+                PostSheetsResponse, Project, Revision, Rollout, SheetName, SheetRequest, TaskRun,
             },
         },
         error::AppError,
@@ -729,8 +1161,25 @@ pub mod tests {
         async fn get_instance(&self, instance_name: &str) -> Result<Instance, AppError> {
             Ok(Instance {
                 name: instance_name.to_string(),
+                engine: None,
             })
         }
+        async fn get_database_metadata(
+            &self,
+            _instance: &str,
+            _database: &str,
+        ) -> Result<DatabaseMetadata, AppError> {
+            Ok(DatabaseMetadata::default())
+        }
+        async fn sync_database(&self, _instance: &str, _database: &str) -> Result<(), AppError> {
+            Ok(())
+        }
+        async fn list_projects(&self) -> Result<Vec<String>, AppError> {
+            Ok(vec!["existing-project".to_string()])
+        }
+        async fn list_instances(&self) -> Result<Vec<String>, AppError> {
+            Ok(vec!["existing-instance".to_string()])
+        }
         async fn get_done_issues(&self, project_name: &str) -> Result<Vec<Issue>, AppError> {
             self.projects
                 .get(project_name)
@@ -745,12 +1194,20 @@ pub mod tests {
         ) -> Result<(), AppError> {
             unimplemented!()
         }
+        async fn review_sql(
+            &self,
+            _instance: &str,
+            _database: &str,
+            _sql: &str,
+        ) -> Result<Vec<PlanCheckResult>, AppError> {
+            Ok(Vec::new())
+        }
         async fn create_plan(
             &self,
             _project_name: &str,
             _instance: &str,
             _database: &str,
-            _sheet_name: SheetName,
+            _sheet_names: &[SheetName],
         ) -> Result<PostPlansResponse, AppError> {
             unimplemented!()
         }
@@ -772,24 +1229,93 @@ pub mod tests {
         async fn get_rollout(&self, _project: &str, _rollout_id: u32) -> Result<Rollout, AppError> {
             unimplemented!()
         }
+
+        async fn list_rollouts(
+            &self,
+            _project: &str,
+            _limit: usize,
+        ) -> Result<Vec<Rollout>, AppError> {
+            Ok(Vec::new())
+        }
+
+        async fn get_issue(&self, project: &str, number: u32) -> Result<IssueDetails, AppError> {
+            self.projects
+                .get(project)
+                .into_iter()
+                .flatten()
+                .find(|issue| issue.name.number == number)
+                .map(|issue| IssueDetails {
+                    name: issue.name.clone(),
+                    title: format!("Issue {number}"),
+                    status: "DONE".to_string(),
+                    create_time: None,
+                })
+                .ok_or_else(|| AppError::ApiError("Issue not found".to_string()))
+        }
+
+        async fn search_issues(
+            &self,
+            _project: &str,
+            _query: &str,
+            _filter: &Filter,
+            _limit: usize,
+        ) -> Result<Vec<IssueDetails>, AppError> {
+            Ok(Vec::new())
+        }
+
+        async fn list_task_runs(
+            &self,
+            _project: &str,
+            _rollout_id: u32,
+        ) -> Result<Vec<TaskRun>, AppError> {
+            unimplemented!()
+        }
+        async fn list_plan_check_runs(
+            &self,
+            _plan: &PlanName,
+        ) -> Result<Vec<PlanCheckRun>, AppError> {
+            Ok(Vec::new())
+        }
+        async fn create_issue_comment(
+            &self,
+            _project: &str,
+            _issue_number: u32,
+            _comment: &str,
+        ) -> Result<(), AppError> {
+            Ok(())
+        }
         async fn create_issue(
             &self,
             _project_name: &str,
             _plan: &PlanName,
+            _description: &str,
         ) -> Result<PostIssuesResponse, AppError> {
             unimplemented!()
         }
-        async fn get_latests_revisions(
+        async fn list_revisions(
             &self,
             _instance: &str,
             _database: &str,
-        ) -> Result<Revision, AppError> {
-            unimplemented!()
+            _filter: &Filter,
+        ) -> Result<Vec<Revision>, AppError> {
+            use crate::api::types::RevisionVersion;
+            Ok(vec![Revision {
+                create_time: Some(chrono::Utc::now()),
+                version: Some(RevisionVersion {
+                    project_name: "fake-project".to_string(),
+                    number: 100,
+                }),
+                sheet: SheetName {
+                    project_name: "fake-sheet".to_string(),
+                    number: 100,
+                },
+            }])
         }
         async fn get_changelogs(
             &self,
             _instance: &str,
             _database: &str,
+            _options: &ListOptions,
         ) -> Result<Vec<Changelog>, AppError> {
             unimplemented!()
         }
@@ -807,24 +1333,120 @@ pub mod tests {
         async fn get_databases(&self, _instance: &str) -> Result<Vec<String>, AppError> {
             Ok(vec!["bridge".to_string(), "admin".to_string()])
         }
+    }
 
-        async fn get_latests_revisions_silent(
-            &self,
-            _instance: &str,
-            _database: &str,
-        ) -> Result<Revision, AppError> {
-            use crate::api::types::RevisionVersion;
-            Ok(Revision {
-                create_time: Some(chrono::Utc::now()),
-                version: Some(RevisionVersion {
-                    project_name: "fake-project".to_string(),
-                    number: 100,
-                }),
-                sheet: SheetName {
-                    project_name: "fake-sheet".to_string(),
-                    number: 100,
-                },
-            })
+    #[test]
+    fn test_maintenance_errors() {
+        use reqwest::StatusCode;
+
+        let error = super::LiveApiClient::error_from_response(
+            "Create sheet",
+            StatusCode::BAD_REQUEST,
+            r#"{"code": 14, "message": "server is in read-only mode"}"#,
+        );
+        assert!(matches!(error, AppError::ServerMaintenance(_)));
+        assert_eq!(error.exit_code(), 7);
+
+        // Words alone do not make maintenance: these fail fast instead of being retried.
+        for body in [
+            r#"{"code": 9, "message": "Error 1290: The MySQL server is running with the --read-only option"}"#,
+            r#"{"code": 7, "message": "permission denied to modify the maintenance table"}"#,
+            "database is read only",
+        ] {
+            let error = super::LiveApiClient::error_from_response(
+                "Create sheet",
+                StatusCode::BAD_REQUEST,
+                body,
+            );
+            assert!(!matches!(error, AppError::ServerMaintenance(_)), "{body}");
         }
+
+        let error = super::LiveApiClient::error_from_response(
+            "Create plan",
+            StatusCode::SERVICE_UNAVAILABLE,
+            "upstream unavailable",
+        );
+        assert!(matches!(error, AppError::ServerMaintenance(_)));
+
+        let error = super::LiveApiClient::error_from_response(
+            "Create plan",
+            StatusCode::BAD_REQUEST,
+            r#"{"code": 3, "message": "invalid sheet"}"#,
+        );
+        assert!(matches!(error, AppError::BytebaseError { .. }));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+        use std::time::Duration;
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(super::retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(super::retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(super::retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(super::retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_revisions_query() {
+        assert_eq!(
+            super::revisions_query(&Filter::new()),
+            [("pageSize", "100".to_string())]
+        );
+        let since = "2024-01-01T00:00:00Z".parse().unwrap();
+        let filter = Filter::new()
+            .eq("version", "0042\" || true || \"")
+            .created_since(since);
+        assert_eq!(
+            super::revisions_query(&filter),
+            [
+                ("pageSize", "100".to_string()),
+                (
+                    "filter",
+                    r#"version = "0042\" || true || \"" && create_time >= "2024-01-01T00:00:00Z""#
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_latest_revision() {
+        use crate::api::types::LatestBy;
+
+        let revision = |version: &str, create_time: &str| -> Revision {
+            serde_json::from_value(serde_json::json!({
+                "createTime": create_time,
+                "version": version,
+                "sheet": "projects/p/sheets/1",
+            }))
+            .unwrap()
+        };
+        // #12 was restored after #15 was applied, so it is the most recently created.
+        let revisions = || {
+            vec![
+                revision("p#10", "2026-01-01T00:00:00Z"),
+                revision("p#15", "2026-01-03T00:00:00Z"),
+                revision("p#12", "2026-01-05T00:00:00Z"),
+            ]
+        };
+        let number = |r: Option<Revision>| r.unwrap().version.unwrap().number;
+
+        assert_eq!(
+            number(Revision::latest(revisions(), LatestBy::CreateTime)),
+            12
+        );
+        assert_eq!(number(Revision::latest(revisions(), LatestBy::Version)), 15);
+        assert!(Revision::latest(Vec::new(), LatestBy::Version).is_none());
     }
 }
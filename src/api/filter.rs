@@ -0,0 +1,114 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::fmt;
+
+/// A Bytebase list filter: CEL-like conditions joined with `&&`, e.g.
+/// `status = "DONE" && create_time >= "2024-01-01T00:00:00Z"`.
+///
+/// Values are quoted and escaped here, so user input such as a label cannot change the shape of
+/// the expression.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    conditions: Vec<String>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `field = "value"`.
+    pub fn eq(mut self, field: &str, value: &str) -> Self {
+        self.conditions.push(format!("{field} = {}", quote(value)));
+        self
+    }
+
+    /// `field in ["a", "b"]`; nothing when `values` is empty.
+    pub fn any_of<S: AsRef<str>>(mut self, field: &str, values: &[S]) -> Self {
+        if !values.is_empty() {
+            let values: Vec<String> = values.iter().map(|v| quote(v.as_ref())).collect();
+            self.conditions
+                .push(format!("{field} in [{}]", values.join(", ")));
+        }
+        self
+    }
+
+    /// Created at or after `time`.
+    pub fn created_since(mut self, time: DateTime<Utc>) -> Self {
+        self.conditions
+            .push(format!("create_time >= {}", quote(&rfc3339(time))));
+        self
+    }
+
+    /// Created before `time`.
+    pub fn created_before(mut self, time: DateTime<Utc>) -> Self {
+        self.conditions
+            .push(format!("create_time < {}", quote(&rfc3339(time))));
+        self
+    }
+
+    /// Carries every label in `labels`.
+    pub fn labels<S: AsRef<str>>(self, labels: &[S]) -> Self {
+        labels
+            .iter()
+            .fold(self, |filter, label| filter.any_of("labels", &[label]))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.conditions.join(" && "))
+    }
+}
+
+fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// A double-quoted CEL string literal.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        assert_eq!(Filter::new().to_string(), "");
+        assert!(Filter::new().any_of::<&str>("status", &[]).is_empty());
+
+        let since = "2024-01-01T00:00:00Z".parse().unwrap();
+        let before = "2024-02-01T09:30:00+09:00".parse().unwrap();
+        let filter = Filter::new()
+            .any_of("status", &["OPEN", "DONE"])
+            .created_since(since)
+            .created_before(before)
+            .labels(&["hotfix", "a\" || true || \"b\\"]);
+        assert_eq!(
+            filter.to_string(),
+            r#"status in ["OPEN", "DONE"] && create_time >= "2024-01-01T00:00:00Z" && create_time < "2024-02-01T00:30:00Z" && labels in ["hotfix"] && labels in ["a\" || true || \"b\\"]"#
+        );
+        assert_eq!(
+            Filter::new().eq("type", "MIGRATE").to_string(),
+            r#"type = "MIGRATE""#
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use crate::error::AppError;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Maximum number of body bytes printed per request or response.
+const MAX_LOGGED_BODY: usize = 4096;
+
+/// JSON keys whose values are replaced before a body is printed.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "token",
+    "accessToken",
+    "access_token",
+    "serviceKey",
+    "service_key",
+];
+
+/// How much of each API call is written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, clap::ValueEnum)]
+pub enum HttpDebug {
+    /// Nothing is logged.
+    #[default]
+    Off,
+    /// Method, URL, status and latency.
+    Basic,
+    /// Everything in `basic`, plus request and response bodies with secrets redacted.
+    Bodies,
+}
+
+static HTTP_DEBUG: OnceLock<HttpDebug> = OnceLock::new();
+
+/// Number of API calls sent by this process, and the time spent waiting on them, for `stats`.
+static API_CALLS: AtomicU64 = AtomicU64::new(0);
+static API_TIME_MS: AtomicU64 = AtomicU64::new(0);
+
+/// API calls sent so far by this process and the total time spent on them.
+pub fn call_totals() -> (u64, Duration) {
+    (
+        API_CALLS.load(Ordering::Relaxed),
+        Duration::from_millis(API_TIME_MS.load(Ordering::Relaxed)),
+    )
+}
+
+fn count_call(start: Instant) {
+    API_CALLS.fetch_add(1, Ordering::Relaxed);
+    API_TIME_MS.fetch_add(start.elapsed().as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Sets the HTTP debug level for the process. Without an explicit level, `RUST_LOG` is consulted:
+/// `shelltide::api=debug` enables basic logging and `shelltide::api=trace` includes bodies.
+pub fn init(level: Option<HttpDebug>) {
+    let level = level.unwrap_or_else(|| level_from_rust_log(std::env::var("RUST_LOG").ok()));
+    let _ = HTTP_DEBUG.set(level);
+}
+
+fn level() -> HttpDebug {
+    HTTP_DEBUG.get().copied().unwrap_or_default()
+}
+
+fn level_from_rust_log(rust_log: Option<String>) -> HttpDebug {
+    let Some(rust_log) = rust_log else {
+        return HttpDebug::Off;
+    };
+    rust_log
+        .split(',')
+        .filter_map(|directive| directive.trim().split_once('='))
+        .filter(|(target, _)| *target == "shelltide::api" || *target == "shelltide")
+        .map(|(_, level)| match level.to_ascii_lowercase().as_str() {
+            "trace" => HttpDebug::Bodies,
+            "debug" => HttpDebug::Basic,
+            _ => HttpDebug::Off,
+        })
+        .max()
+        .unwrap_or(HttpDebug::Off)
+}
+
+/// Sends `request` with `client`, logging the exchange according to the configured level.
+pub async fn send(
+    client: &reqwest::Client,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, AppError> {
+    let level = level();
+    if level == HttpDebug::Off {
+        let start = Instant::now();
+        let response = request.send().await;
+        count_call(start);
+        return Ok(response?);
+    }
+
+    let request = request.build()?;
+    let method = request.method().clone();
+    let url = request.url().clone();
+    if level == HttpDebug::Bodies
+        && let Some(body) = request.body().and_then(|b| b.as_bytes())
+    {
+        eprintln!("[http] {method} {url} request body: {}", redact(body));
+    }
+
+    let start = Instant::now();
+    let response = client.execute(request).await;
+    count_call(start);
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!(
+                "[http] {method} {url} -> error after {} ms: {e}",
+                start.elapsed().as_millis()
+            );
+            return Err(e.into());
+        }
+    };
+    let status = response.status();
+    eprintln!(
+        "[http] {method} {url} -> {status} ({} ms)",
+        start.elapsed().as_millis()
+    );
+
+    if level < HttpDebug::Bodies {
+        return Ok(response);
+    }
+
+    // Buffer the body to print it, then hand back an equivalent response.
+    let headers = response.headers().clone();
+    let version = response.version();
+    let body = response.bytes().await?;
+    eprintln!("[http] {method} {url} response body: {}", redact(&body));
+
+    let mut builder = http::Response::builder().status(status).version(version);
+    if let Some(h) = builder.headers_mut() {
+        *h = headers;
+    }
+    let rebuilt = builder
+        .body(body)
+        .map_err(|e| AppError::ApiError(format!("Failed to rebuild response: {e}")))?;
+    Ok(reqwest::Response::from(rebuilt))
+}
+
+/// Renders a body for logging: JSON secrets are masked and long bodies are truncated.
+fn redact(body: &[u8]) -> String {
+    let text = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            mask_secrets(&mut value);
+            value.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(body).to_string(),
+    };
+
+    if text.len() <= MAX_LOGGED_BODY {
+        return text;
+    }
+    let mut end = MAX_LOGGED_BODY;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... ({} bytes total)", &text[..end], text.len())
+}
+
+fn mask_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SECRET_KEYS.contains(&key.as_str()) {
+                    *value = serde_json::Value::String("***".to_string());
+                } else {
+                    mask_secrets(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_masks_secrets() {
+        let body =
+            br#"{"email":"sa@service.bytebase.com","password":"hunter2","nested":{"token":"abc"}}"#;
+        let redacted = redact(body);
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("abc"));
+        assert!(redacted.contains("sa@service.bytebase.com"));
+    }
+
+    #[test]
+    fn test_level_from_rust_log() {
+        assert_eq!(level_from_rust_log(None), HttpDebug::Off);
+        assert_eq!(
+            level_from_rust_log(Some("shelltide::api=trace".to_string())),
+            HttpDebug::Bodies
+        );
+        assert_eq!(
+            level_from_rust_log(Some("info,shelltide=debug".to_string())),
+            HttpDebug::Basic
+        );
+        assert_eq!(
+            level_from_rust_log(Some("reqwest=trace".to_string())),
+            HttpDebug::Off
+        );
+    }
+}
@@ -1,15 +1,48 @@
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
 use crate::api::traits::BytebaseApi;
-use crate::api::types::{Rollout, TaskStatus};
+use crate::api::types::{
+    PlanCheckResultStatus, PlanCheckRun, PlanCheckRunStatus, PlanName, Rollout, TaskName, TaskRun,
+    TaskRunStatus, TaskStatus,
+};
 use crate::error::AppError;
 
-const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
-const NOT_STARTED_TIMEOUT: Duration = Duration::from_secs(60); // 1 minute for stuck detection
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub const DEFAULT_NOT_STARTED_TIMEOUT: Duration = Duration::from_secs(60); // 1 minute for stuck detection
+/// Rollouts running longer than this are polled less and less often.
+const BACKOFF_AFTER: Duration = Duration::from_secs(30);
+/// Upper bound for the backed-off poll interval.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
 const MAX_RETRIES: u32 = 5;
 const RETRY_DELAY: Duration = Duration::from_secs(1);
 
+/// How [`wait_for_rollout`] polls.
+#[derive(Debug, Clone)]
+pub struct PollOptions {
+    /// Delay between polls while the rollout is young; it grows for long-running rollouts.
+    pub interval: Duration,
+    /// How long every task may stay NOT_STARTED before the rollout is considered stuck.
+    pub not_started_timeout: Duration,
+    /// Give up with [`AppError::RolloutTimeout`] after this long.
+    pub timeout: Option<Duration>,
+    /// Whether to keep a status line updated in place while waiting. Off when several rollouts
+    /// are waited for at once, whose lines would overwrite each other.
+    pub progress: bool,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_POLL_INTERVAL,
+            not_started_timeout: DEFAULT_NOT_STARTED_TIMEOUT,
+            timeout: None,
+            progress: true,
+        }
+    }
+}
+
 /// Wait for a rollout to complete by polling the API.
 ///
 /// Returns Ok(Rollout) if all tasks succeed, or Err if any task fails or timeout occurs.
@@ -17,9 +50,13 @@ pub async fn wait_for_rollout<T: BytebaseApi>(
     api_client: &T,
     project: &str,
     rollout_id: u32,
+    options: &PollOptions,
 ) -> Result<Rollout, AppError> {
     let start = Instant::now();
     let mut poll_count = 0;
+    let mut interval = options.interval;
+    // Ends the progress line before the final message.
+    let newline = if options.progress { "\n" } else { "" };
 
     println!("  Waiting for rollout {} to complete...", rollout_id);
 
@@ -30,35 +67,129 @@ pub async fn wait_for_rollout<T: BytebaseApi>(
         let rollout = get_rollout_with_retry(api_client, project, rollout_id).await?;
 
         // Get current status summary
-        let status_summary = get_status_summary(&rollout);
-        print_progress(poll_count, start.elapsed(), &status_summary);
+        if options.progress {
+            let status_summary = get_status_summary(&rollout);
+            print_progress(poll_count, start.elapsed(), &status_summary);
+        }
 
         if rollout.is_complete() {
             if rollout.is_success() {
-                println!("\n  Rollout {} completed successfully.", rollout_id);
+                println!("{newline}  Rollout {} completed successfully.", rollout_id);
                 return Ok(rollout);
             } else {
-                // Build detailed error message
-                let error_msg = build_failure_message(&rollout);
-                println!("\n  Rollout {} failed: {}", rollout_id, error_msg);
+                // Build detailed error message, including the database error of each failed task
+                let errors = match api_client.list_task_runs(project, rollout_id).await {
+                    Ok(runs) => latest_failure_details(&runs),
+                    Err(e) => {
+                        eprintln!("  Warning: Failed to fetch task run logs: {e}");
+                        HashMap::new()
+                    }
+                };
+                let error_msg = build_failure_message(&rollout, &errors);
+                println!("{newline}  Rollout {} failed: {}", rollout_id, error_msg);
                 return Err(AppError::ApiError(error_msg));
             }
         }
 
         // Check if stuck in NOT_STARTED state
-        if is_all_not_started(&rollout) && start.elapsed() > NOT_STARTED_TIMEOUT {
+        if is_all_not_started(&rollout) && start.elapsed() > options.not_started_timeout {
             let msg = format!(
                 "Rollout {} stuck in NOT_STARTED state for {:?}. \
                 Check Bytebase UI for approval requirements or configuration issues.",
-                rollout_id, NOT_STARTED_TIMEOUT
+                rollout_id, options.not_started_timeout
             );
-            println!("\n  {}", msg);
+            println!("{newline}  {}", msg);
             return Err(AppError::ApiError(msg));
         }
 
+        if let Some(timeout) = options.timeout
+            && start.elapsed() >= timeout
+        {
+            let msg = format!(
+                "Rollout {} did not finish within {:?}. It keeps running in Bytebase.",
+                rollout_id, timeout
+            );
+            println!("{newline}  {}", msg);
+            return Err(AppError::RolloutTimeout(msg));
+        }
+
         // Wait before next poll
-        sleep(DEFAULT_POLL_INTERVAL).await;
+        sleep(interval).await;
+        interval = next_interval(interval, start.elapsed());
+    }
+}
+
+/// Poll interval after `current`: unchanged for young rollouts, then growing by half each poll
+/// up to [`MAX_POLL_INTERVAL`] to reduce API load during long-running tasks.
+fn next_interval(current: Duration, elapsed: Duration) -> Duration {
+    if elapsed < BACKOFF_AFTER || current >= MAX_POLL_INTERVAL {
+        return current;
+    }
+    (current * 3 / 2).min(MAX_POLL_INTERVAL)
+}
+
+/// Waits for the checks Bytebase runs on `plan` (SQL review, connectivity, GhostSync, ...) to
+/// finish. Fails with [`AppError::PlanCheckFailed`] if a check reports an error or could not run,
+/// so problems surface before a rollout is created. Warnings are printed.
+pub async fn wait_for_plan_checks<T: BytebaseApi>(
+    api_client: &T,
+    plan: &PlanName,
+    options: &PollOptions,
+) -> Result<(), AppError> {
+    let start = Instant::now();
+    loop {
+        let runs = api_client.list_plan_check_runs(plan).await?;
+        if runs
+            .iter()
+            .all(|run| run.status != PlanCheckRunStatus::Running)
+        {
+            let (errors, warnings) = plan_check_findings(&runs);
+            for warning in &warnings {
+                println!("  Plan check warning: {warning}");
+            }
+            if !errors.is_empty() {
+                return Err(AppError::PlanCheckFailed(errors.join("; ")));
+            }
+            if !runs.is_empty() {
+                println!("  Plan checks passed ({} check(s)).", runs.len());
+            }
+            return Ok(());
+        }
+
+        if let Some(timeout) = options.timeout
+            && start.elapsed() >= timeout
+        {
+            return Err(AppError::RolloutTimeout(format!(
+                "plan checks of {plan} still running after {}s",
+                timeout.as_secs()
+            )));
+        }
+        sleep(options.interval).await;
+    }
+}
+
+/// Splits the outcome of finished plan checks into errors and warnings.
+fn plan_check_findings(runs: &[PlanCheckRun]) -> (Vec<String>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    for run in runs {
+        if run.status == PlanCheckRunStatus::Failed {
+            errors.push(format!("{} could not run: {}", run.check_type, run.error));
+        }
+        for result in &run.results {
+            let finding = if result.content.is_empty() {
+                format!("{}: {}", run.check_type, result.title)
+            } else {
+                format!("{}: {} ({})", run.check_type, result.title, result.content)
+            };
+            match result.status {
+                PlanCheckResultStatus::Error => errors.push(finding),
+                PlanCheckResultStatus::Warning => warnings.push(finding),
+                _ => {}
+            }
+        }
     }
+    (errors, warnings)
 }
 
 /// Get rollout with retry logic for transient network errors
@@ -96,7 +227,10 @@ fn is_all_not_started(rollout: &Rollout) -> bool {
         .flat_map(|stage| stage.tasks.iter())
         .collect();
 
-    !tasks.is_empty() && tasks.iter().all(|task| task.status == TaskStatus::NotStarted)
+    !tasks.is_empty()
+        && tasks
+            .iter()
+            .all(|task| task.status == TaskStatus::NotStarted)
 }
 
 /// Get a summary of all task statuses in the rollout
@@ -147,7 +281,40 @@ fn get_status_summary(rollout: &Rollout) -> String {
         parts.push(format!("{} other", other));
     }
 
-    format!("[{}/{}] {}", done + failed + other, total, parts.join(", "))
+    let summary = format!("[{}/{}] {}", done + failed + other, total, parts.join(", "));
+    match get_stage_breakdown(rollout) {
+        Some(stages) => format!("{} | {}", summary, stages),
+        None => summary,
+    }
+}
+
+/// Per-stage progress (`<environment> <finished>/<total>`) for rollouts with several stages
+fn get_stage_breakdown(rollout: &Rollout) -> Option<String> {
+    if rollout.stages.len() < 2 {
+        return None;
+    }
+
+    let stages: Vec<String> = rollout
+        .stages
+        .iter()
+        .enumerate()
+        .map(|(i, stage)| {
+            let label = stage
+                .environment
+                .rsplit('/')
+                .next()
+                .filter(|env| !env.is_empty())
+                .or_else(|| stage.name.rsplit('/').next().filter(|id| !id.is_empty()))
+                .map_or_else(|| format!("stage {}", i + 1), str::to_string);
+            let finished = stage
+                .tasks
+                .iter()
+                .filter(|task| task.status.is_terminal())
+                .count();
+            format!("{} {}/{}", label, finished, stage.tasks.len())
+        })
+        .collect();
+    Some(stages.join(", "))
 }
 
 /// Print progress update (overwrites previous line)
@@ -163,8 +330,25 @@ fn print_progress(poll_count: u32, elapsed: Duration, status: &str) {
     }
 }
 
+/// Map each task to the error detail of its most recent failed run
+fn latest_failure_details(runs: &[TaskRun]) -> HashMap<TaskName, String> {
+    let mut latest: HashMap<&TaskName, &TaskRun> = HashMap::new();
+    for run in runs {
+        let entry = latest.entry(run.task()).or_insert(run);
+        if run.create_time > entry.create_time {
+            *entry = run;
+        }
+    }
+
+    latest
+        .into_iter()
+        .filter(|(_, run)| run.status == TaskRunStatus::Failed && !run.detail.is_empty())
+        .map(|(task, run)| (task.clone(), run.detail.clone()))
+        .collect()
+}
+
 /// Build a detailed error message for a failed rollout
-fn build_failure_message(rollout: &Rollout) -> String {
+fn build_failure_message(rollout: &Rollout, errors: &HashMap<TaskName, String>) -> String {
     let failed_tasks: Vec<_> = rollout
         .stages
         .iter()
@@ -178,7 +362,15 @@ fn build_failure_message(rollout: &Rollout) -> String {
 
     let task_details: Vec<String> = failed_tasks
         .iter()
-        .map(|task| format!("Task '{}' (target: {})", task.name, task.target))
+        .map(|task| match errors.get(&task.name) {
+            Some(error) => format!(
+                "Task '{}' (target: {}): {}",
+                task.name,
+                task.target,
+                error.trim()
+            ),
+            None => format!("Task '{}' (target: {})", task.name, task.target),
+        })
         .collect();
 
     format!(
@@ -187,3 +379,125 @@ fn build_failure_message(rollout: &Rollout) -> String {
         task_details.join("; ")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_summary_shows_stages() {
+        let rollout: Rollout = serde_json::from_str(
+            r#"{
+                "name": "projects/p/rollouts/1",
+                "stages": [
+                    {
+                        "name": "projects/p/rollouts/1/stages/2",
+                        "environment": "environments/staging",
+                        "tasks": [{"name": "projects/p/rollouts/1/stages/2/tasks/4", "status": "DONE", "target": "db"}]
+                    },
+                    {
+                        "name": "projects/p/rollouts/1/stages/3",
+                        "environment": "environments/prod",
+                        "tasks": [
+                            {"name": "projects/p/rollouts/1/stages/3/tasks/5", "status": "DONE", "target": "db"},
+                            {"name": "projects/p/rollouts/1/stages/3/tasks/6", "status": "RUNNING", "target": "db"}
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_status_summary(&rollout),
+            "[2/3] 2 done, 1 running | staging 1/1, prod 1/2"
+        );
+    }
+
+    #[test]
+    fn test_next_interval_backs_off() {
+        let base = Duration::from_secs(2);
+        assert_eq!(next_interval(base, Duration::from_secs(10)), base);
+        assert_eq!(
+            next_interval(base, Duration::from_secs(40)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            next_interval(Duration::from_secs(25), Duration::from_secs(600)),
+            MAX_POLL_INTERVAL
+        );
+        // A configured interval above the cap is kept as is.
+        let slow = Duration::from_secs(60);
+        assert_eq!(next_interval(slow, Duration::from_secs(600)), slow);
+    }
+
+    #[test]
+    fn test_plan_check_findings() {
+        let runs: Vec<PlanCheckRun> = serde_json::from_str(
+            r#"[
+                {
+                    "type": "DATABASE_STATEMENT_ADVISE",
+                    "status": "DONE",
+                    "results": [
+                        {"status": "WARNING", "title": "column.require-default", "content": "Column `note` has no default"},
+                        {"status": "SUCCESS", "title": "OK"}
+                    ]
+                },
+                {"type": "DATABASE_CONNECT", "status": "FAILED", "error": "connection refused"},
+                {"type": "DATABASE_GHOST_SYNC", "status": "DONE", "results": [{"status": "ERROR", "title": "binlog disabled"}]}
+            ]"#,
+        )
+        .unwrap();
+
+        let (errors, warnings) = plan_check_findings(&runs);
+        assert_eq!(
+            errors,
+            vec![
+                "DATABASE_CONNECT could not run: connection refused",
+                "DATABASE_GHOST_SYNC: binlog disabled"
+            ]
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                "DATABASE_STATEMENT_ADVISE: column.require-default (Column `note` has no default)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failure_message_includes_task_run_error() {
+        let runs: Vec<TaskRun> = serde_json::from_str(
+            r#"[
+                {
+                    "name": "projects/p/rollouts/1/stages/2/tasks/3/taskRuns/1",
+                    "status": "FAILED",
+                    "createTime": "2026-01-27T09:00:00Z",
+                    "detail": "Lock wait timeout exceeded"
+                },
+                {
+                    "name": "projects/p/rollouts/1/stages/2/tasks/3/taskRuns/2",
+                    "status": "FAILED",
+                    "createTime": "2026-01-27T09:05:00Z",
+                    "detail": "Duplicate column name 'email'"
+                }
+            ]"#,
+        )
+        .unwrap();
+        let rollout: Rollout = serde_json::from_str(
+            r#"{
+                "name": "projects/p/rollouts/1",
+                "stages": [{"tasks": [{
+                    "name": "projects/p/rollouts/1/stages/2/tasks/3",
+                    "status": "FAILED",
+                    "target": "instances/i/databases/db"
+                }]}]
+            }"#,
+        )
+        .unwrap();
+
+        let message = build_failure_message(&rollout, &latest_failure_details(&runs));
+        assert!(message.contains("Duplicate column name 'email'"));
+        assert!(!message.contains("Lock wait timeout"));
+    }
+}
@@ -1,6 +1,8 @@
+use crate::api::filter::Filter;
 use crate::api::types::{
-    Changelog, Instance, Issue, IssueName, PlanName, PostIssuesResponse, PostPlansResponse,
-    PostSheetsResponse, Project, Revision, Rollout, SheetName, SheetRequest,
+    Changelog, DatabaseMetadata, Instance, Issue, IssueDetails, IssueName, LatestBy, ListOptions,
+    PlanCheckResult, PlanCheckRun, PlanName, PostIssuesResponse, PostPlansResponse,
+    PostSheetsResponse, Project, Revision, Rollout, SheetName, SheetRequest, TaskRun,
 };
 use crate::error::AppError;
 use async_trait::async_trait;
@@ -9,23 +11,65 @@ use async_trait::async_trait;
 pub trait BytebaseApi: Send + Sync {
     async fn get_project(&self, project_name: &str) -> Result<Project, AppError>;
     async fn get_instance(&self, instance_name: &str) -> Result<Instance, AppError>;
+    /// Gets the schema metadata, including table sizes, of a database.
+    async fn get_database_metadata(
+        &self,
+        instance: &str,
+        database: &str,
+    ) -> Result<DatabaseMetadata, AppError>;
+    /// Makes Bytebase re-read the schema and metadata of a database from the instance.
+    async fn sync_database(&self, instance: &str, database: &str) -> Result<(), AppError>;
+    /// Lists the IDs of every project visible to the service account.
+    async fn list_projects(&self) -> Result<Vec<String>, AppError>;
+    /// Lists the IDs of every instance visible to the service account.
+    async fn list_instances(&self) -> Result<Vec<String>, AppError>;
     async fn get_done_issues(&self, project_name: &str) -> Result<Vec<Issue>, AppError>;
-    async fn get_latests_revisions(
+    async fn get_issue(&self, project: &str, number: u32) -> Result<IssueDetails, AppError>;
+    /// Lists up to `limit` issues of a project whose title or description matches `query` and
+    /// that pass `filter`.
+    async fn search_issues(
+        &self,
+        project: &str,
+        query: &str,
+        filter: &Filter,
+        limit: usize,
+    ) -> Result<Vec<IssueDetails>, AppError>;
+    /// Lists the revisions of a database matching `filter` (all of them when it is empty), in
+    /// no particular order.
+    async fn list_revisions(
         &self,
         instance: &str,
         database: &str,
-    ) -> Result<Revision, AppError>;
+        filter: &Filter,
+    ) -> Result<Vec<Revision>, AppError>;
+    /// The latest revision of a database by `by`, or [`AppError::NoRevision`] if it has none.
+    async fn latest_revision(
+        &self,
+        instance: &str,
+        database: &str,
+        by: LatestBy,
+    ) -> Result<Revision, AppError> {
+        Revision::latest(
+            self.list_revisions(instance, database, &Filter::new())
+                .await?,
+            by,
+        )
+        .ok_or_else(|| AppError::NoRevision(format!("'{instance}/{database}'")))
+    }
+    /// Lists every changelog of a database, including unfinished ones and ones without a
+    /// statement; callers pick what they need. `options` sets the page size, view and filter.
     async fn get_changelogs(
         &self,
         instance: &str,
         database: &str,
+        options: &ListOptions,
     ) -> Result<Vec<Changelog>, AppError>;
     async fn create_plan(
         &self,
         project_name: &str,
         instance: &str,
         database: &str,
-        sheet_name: SheetName,
+        sheet_names: &[SheetName],
     ) -> Result<PostPlansResponse, AppError>;
     async fn create_sheet(
         &self,
@@ -38,12 +82,32 @@ pub trait BytebaseApi: Send + Sync {
         plan_name: PlanName,
         issue_name: IssueName,
     ) -> Result<Rollout, AppError>;
+    /// A rollout with all of its stages and tasks. Bytebase returns them inline in one
+    /// response; unlike the rollout list, they are not paginated.
     async fn get_rollout(&self, project: &str, rollout_id: u32) -> Result<Rollout, AppError>;
+    /// Lists up to `limit` of the most recent rollouts of a project, following pagination.
+    async fn list_rollouts(&self, project: &str, limit: usize) -> Result<Vec<Rollout>, AppError>;
+    /// Lists the task runs of every task in a rollout.
+    async fn list_task_runs(
+        &self,
+        project: &str,
+        rollout_id: u32,
+    ) -> Result<Vec<TaskRun>, AppError>;
+    /// Lists the checks Bytebase ran (or is running) against a plan.
+    async fn list_plan_check_runs(&self, plan: &PlanName) -> Result<Vec<PlanCheckRun>, AppError>;
     async fn create_issue(
         &self,
         project_name: &str,
         plan: &PlanName,
+        description: &str,
     ) -> Result<PostIssuesResponse, AppError>;
+    /// Adds a comment to an issue.
+    async fn create_issue_comment(
+        &self,
+        project: &str,
+        issue_number: u32,
+        comment: &str,
+    ) -> Result<(), AppError>;
     async fn create_revision(
         &self,
         instance: &str,
@@ -53,11 +117,13 @@ pub trait BytebaseApi: Send + Sync {
         sheet: &str,
     ) -> Result<Revision, AppError>;
     async fn check_sql(&self, instance: &str, database: &str, sql: &str) -> Result<(), AppError>;
-    async fn get_databases(&self, instance: &str) -> Result<Vec<String>, AppError>;
-    /// Get latest revisions without error logging (for status command)
-    async fn get_latests_revisions_silent(
+    /// Runs Bytebase's SQL review of `sql` against a database without changing anything, and
+    /// returns its advices.
+    async fn review_sql(
         &self,
         instance: &str,
         database: &str,
-    ) -> Result<Revision, AppError>;
+        sql: &str,
+    ) -> Result<Vec<PlanCheckResult>, AppError>;
+    async fn get_databases(&self, instance: &str) -> Result<Vec<String>, AppError>;
 }
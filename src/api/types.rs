@@ -1,4 +1,5 @@
-use crate::error::AppError;
+use crate::api::filter::Filter;
+use crate::error::{AppError, BytebaseErrorCode};
 use base64::{Engine, engine::general_purpose};
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 use uuid::Uuid;
@@ -15,7 +16,7 @@ pub struct LoginResponse {
     pub token: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Project {
     pub title: String,
 }
@@ -37,11 +38,40 @@ pub struct SqlCheckRequest {
     pub statement: String,
 }
 
+/// Verdict of `/v1/sql/check`: one advice per rule the statement breaks (or passes).
+#[derive(Deserialize, Debug, Default)]
+pub struct SqlCheckResponse {
+    #[serde(default)]
+    pub advices: Vec<PlanCheckResult>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct Issue {
     pub name: IssueName,
 }
 
+/// An issue with the fields shown to operators, e.g. by `issues search`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct IssueDetails {
+    pub name: IssueName,
+    #[serde(default)]
+    pub title: String,
+    /// e.g. `OPEN`, `DONE` or `CANCELED`.
+    #[serde(default)]
+    pub status: String,
+    #[serde(rename = "createTime", default)]
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// One page of `POST /v1/projects/{project}/issues:search`.
+#[derive(Deserialize, Debug, Default)]
+pub struct SearchIssuesResponse {
+    #[serde(default)]
+    pub issues: Vec<IssueDetails>,
+    #[serde(rename = "nextPageToken", default)]
+    pub next_page_token: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RevisionVersion {
     pub project_name: String,
@@ -58,6 +88,12 @@ impl<'de> Deserialize<'de> for RevisionVersion {
     }
 }
 
+impl std::fmt::Display for RevisionVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.project_name, self.number)
+    }
+}
+
 impl RevisionVersion {
     pub fn new(version: String) -> Result<Self, AppError> {
         let split = version.split('#').collect::<Vec<&str>>();
@@ -87,7 +123,93 @@ pub struct Revision {
     pub sheet: SheetName,
 }
 
-#[derive(Debug, Clone)]
+/// Which revision counts as the latest of a database (`revision.latest_by`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LatestBy {
+    /// The most recently created revision. After a restore this can be an older version.
+    #[default]
+    CreateTime,
+    /// The revision with the highest version number, whenever it was created.
+    Version,
+}
+
+impl std::str::FromStr for LatestBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create_time" => Ok(Self::CreateTime),
+            "version" => Ok(Self::Version),
+            _ => Err(format!(
+                "Unknown revision order '{s}'. Use create_time or version."
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for LatestBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::CreateTime => "create_time",
+            Self::Version => "version",
+        })
+    }
+}
+
+impl Revision {
+    /// The latest of `revisions` by `by`. Revisions without a create time or version are
+    /// skipped, as they cannot be ordered.
+    pub fn latest(revisions: Vec<Revision>, by: LatestBy) -> Option<Revision> {
+        match by {
+            LatestBy::CreateTime => revisions
+                .into_iter()
+                .filter(|r| r.create_time.is_some())
+                .max_by_key(|r| r.create_time),
+            LatestBy::Version => revisions
+                .into_iter()
+                .filter(|r| r.version.is_some())
+                .max_by_key(|r| (r.version.as_ref().map(|v| v.number), r.create_time)),
+        }
+    }
+}
+
+/// Splits a resource name such as `projects/{project}/issues/{issue}` into its ids, checking
+/// that every collection segment matches `collections` and that no id is empty.
+fn parse_resource_name<'a, const N: usize>(
+    raw: &'a str,
+    collections: [&str; N],
+) -> Result<[&'a str; N], String> {
+    let invalid = || {
+        let expected: Vec<String> = collections
+            .iter()
+            .map(|c| format!("{c}/{{{}}}", c.strip_suffix('s').unwrap_or(c)))
+            .collect();
+        format!(
+            "invalid resource name '{raw}', expected '{}'",
+            expected.join("/")
+        )
+    };
+    let segments: Vec<&str> = raw.split('/').collect();
+    if segments.len() != N * 2 {
+        return Err(invalid());
+    }
+    let mut ids = [""; N];
+    for (i, pair) in segments.chunks(2).enumerate() {
+        if pair[0] != collections[i] || pair[1].is_empty() {
+            return Err(invalid());
+        }
+        ids[i] = pair[1];
+    }
+    Ok(ids)
+}
+
+/// Parses the numeric id of a resource name, e.g. the issue number of an issue name.
+fn parse_resource_id(raw: &str, what: &str, id: &str) -> Result<u32, String> {
+    id.parse()
+        .map_err(|_| format!("invalid {what} '{id}' in resource name '{raw}'"))
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IssueName {
     pub project: String,
     pub number: u32,
@@ -99,17 +221,12 @@ impl<'de> Deserialize<'de> for IssueName {
         D: Deserializer<'de>,
     {
         let raw = String::deserialize(deserializer)?;
-        let mut split = raw.split('/');
-        let project = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find project name"))?
-            .to_string();
-        let number = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find issue number"))?
-            .parse()
-            .map_err(|_| de::Error::custom("invalid issue number"))?;
-        Ok(Self { project, number })
+        let [project, number] =
+            parse_resource_name(&raw, ["projects", "issues"]).map_err(de::Error::custom)?;
+        Ok(Self {
+            project: project.to_string(),
+            number: parse_resource_id(&raw, "issue number", number).map_err(de::Error::custom)?,
+        })
     }
 }
 
@@ -141,26 +258,14 @@ impl<'de> Deserialize<'de> for ChangeLogName {
         D: Deserializer<'de>,
     {
         let raw = String::deserialize(deserializer)?;
-
-        let mut split = raw.split('/');
-        let instance = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find instance name"))?
-            .to_string();
-        let database = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find database name"))?
-            .to_string();
-        let number = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find changelog number"))?
-            .parse()
-            .map_err(|_| de::Error::custom("invalid changelog number"))?;
-
+        let [instance, database, number] =
+            parse_resource_name(&raw, ["instances", "databases", "changelogs"])
+                .map_err(de::Error::custom)?;
         Ok(Self {
-            instance,
-            database,
-            number,
+            instance: instance.to_string(),
+            database: database.to_string(),
+            number: parse_resource_id(&raw, "changelog number", number)
+                .map_err(de::Error::custom)?,
         })
     }
 }
@@ -168,10 +273,47 @@ impl<'de> Deserialize<'de> for ChangeLogName {
 #[derive(Deserialize, Debug, Clone, Default)]
 pub struct StringStatement(pub String);
 
+/// Start of the comment line shelltide puts on top of every sheet it creates, so the
+/// changelogs they leave in a target can be told apart from work done there.
+pub const PROVENANCE_MARKER: &str = "-- shelltide-provenance:";
+
 impl StringStatement {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Whether shelltide created this statement by promoting it from another environment.
+    pub fn is_promoted(&self) -> bool {
+        self.0.trim_start().starts_with(PROVENANCE_MARKER)
+    }
+
+    /// The provenance comment of a promoted statement, without the marker, e.g.
+    /// `shelltide run r1, issue #42`. Every sheet of one promoted changelog carries the same one.
+    pub fn provenance(&self) -> Option<&str> {
+        let comment = self.0.trim_start().strip_prefix(PROVENANCE_MARKER)?;
+        Some(comment.lines().next().unwrap_or_default().trim())
+    }
+
+    /// The source issue a promoted statement was created for, read from the end of its
+    /// provenance comment (`..., issue #42`).
+    pub fn provenance_issue(&self) -> Option<u32> {
+        self.provenance()?
+            .rsplit_once("issue #")?
+            .1
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// The statement without shelltide's provenance comment, trimmed, for comparing a promoted
+    /// statement with its original.
+    pub fn without_provenance(&self) -> &str {
+        let statement = self.0.trim();
+        match statement.strip_prefix(PROVENANCE_MARKER) {
+            Some(rest) => rest.split_once('\n').map_or("", |(_, sql)| sql.trim()),
+            None => statement,
+        }
+    }
 }
 
 impl std::fmt::Display for StringStatement {
@@ -180,6 +322,16 @@ impl std::fmt::Display for StringStatement {
     }
 }
 
+impl std::fmt::Display for ChangeLogName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "instances/{}/databases/{}/changelogs/{}",
+            self.instance, self.database, self.number
+        )
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct Changelog {
     pub name: ChangeLogName,
@@ -189,11 +341,178 @@ pub struct Changelog {
     pub status: String,
     #[serde(default)]
     pub statement: StringStatement,
-    pub issue: IssueName,
+    /// Issue that made the change. Baselines, which only record the schema found on the
+    /// database, have none.
+    #[serde(default, deserialize_with = "deserialize_optional_issue")]
+    pub issue: Option<IssueName>,
+    #[serde(rename = "changedResources", default)]
+    pub changed_resources: ChangedResource,
     #[serde(rename = "type", default)]
     pub changelog_type: Option<ChangelogType>,
     #[serde(default)]
     pub schema: String,
+    /// Sheet holding the statement, as `projects/<project>/sheets/<number>`.
+    #[serde(rename = "statementSheet", default)]
+    pub statement_sheet: Option<String>,
+}
+
+impl Changelog {
+    /// Number of the issue that made the change, or 0 for changelogs without one. Those never
+    /// reach the pending set of a migrate.
+    pub fn issue_number(&self) -> u32 {
+        self.issue.as_ref().map_or(0, |issue| issue.number)
+    }
+
+    /// `#<issue>`, or `changelog <number>` for changelogs without an issue.
+    pub fn label(&self) -> String {
+        match &self.issue {
+            Some(issue) => format!("#{}", issue.number),
+            None => format!("changelog {}", self.name.number),
+        }
+    }
+
+    /// Whether this changelog is a baseline: a snapshot of the schema as Bytebase found it,
+    /// with nothing to apply.
+    pub fn is_baseline(&self) -> bool {
+        self.changelog_type == Some(ChangelogType::Baseline)
+    }
+}
+
+/// Deserializes an optional issue name, treating `""` (as sent for baselines) as absent.
+fn deserialize_optional_issue<'de, D>(deserializer: D) -> Result<Option<IssueName>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(raw) if !raw.is_empty() => {
+            IssueName::deserialize(de::value::StringDeserializer::<D::Error>::new(raw)).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// How much of each changelog `get_changelogs` returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChangelogView {
+    /// Metadata only; the statement and schema are left out or truncated.
+    #[default]
+    Basic,
+    /// Everything, including the full statement and schema.
+    Full,
+}
+
+impl ChangelogView {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Basic => "CHANGELOG_VIEW_BASIC",
+            Self::Full => "CHANGELOG_VIEW_FULL",
+        }
+    }
+}
+
+/// Paging, detail and filtering of a list call such as `get_changelogs`.
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Items per page; [`ListOptions::DEFAULT_PAGE_SIZE`] when `None`.
+    pub page_size: Option<u32>,
+    pub view: ChangelogView,
+    /// Bytebase list filter, e.g. `type = "MIGRATE"`.
+    pub filter: Option<Filter>,
+}
+
+impl ListOptions {
+    pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
+    /// Full view with the default page size, for callers that read statements or schemas.
+    pub fn full() -> Self {
+        Self {
+            view: ChangelogView::Full,
+            ..Self::default()
+        }
+    }
+
+    /// Query parameters for the first page.
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        let page_size = self.page_size.unwrap_or(Self::DEFAULT_PAGE_SIZE);
+        let mut query = vec![
+            ("pageSize", page_size.to_string()),
+            ("view", self.view.as_str().to_string()),
+        ];
+        if let Some(filter) = self.filter.as_ref().filter(|filter| !filter.is_empty()) {
+            query.push(("filter", filter.to_string()));
+        }
+        query
+    }
+}
+
+/// One page of `GET .../changelogs`. Entries that fail to deserialize are skipped rather than
+/// failing the whole page.
+#[derive(Deserialize, Debug, Default)]
+pub struct ListChangelogsResponse {
+    #[serde(default, deserialize_with = "deserialize_lenient_vec")]
+    pub changelogs: Vec<Changelog>,
+    #[serde(rename = "nextPageToken", default)]
+    pub next_page_token: Option<String>,
+}
+
+/// Deserializes a sequence, dropping elements that do not match `T`.
+fn deserialize_lenient_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    struct LenientVisitor<T>(std::marker::PhantomData<T>);
+
+    impl<'de, T: serde::de::DeserializeOwned> de::Visitor<'de> for LenientVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a sequence")
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            // Elements are buffered one at a time, so only a single entry is ever held twice.
+            while let Some(value) = seq.next_element::<serde_json::Value>()? {
+                if let Ok(item) = serde_json::from_value(value) {
+                    items.push(item);
+                }
+            }
+            Ok(items)
+        }
+    }
+
+    deserializer.deserialize_seq(LenientVisitor(std::marker::PhantomData))
+}
+
+/// Tables touched by a changelog, as reported by Bytebase's `changedResources`.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ChangedResource {
+    #[serde(default)]
+    pub databases: Vec<ChangedDatabase>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[allow(dead_code)]
+pub struct ChangedDatabase {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub schemas: Vec<ChangedSchema>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ChangedSchema {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub tables: Vec<ChangedTable>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ChangedTable {
+    #[serde(default)]
+    pub name: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -205,10 +524,11 @@ pub enum ChangelogType {
 }
 
 /// All supported SQL dialects. ref: https://docs.bytebase.com/api-reference/sheetservice/post-v1projects-sheets#body-engine
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 #[allow(dead_code)]
 pub enum SQLDialect {
+    #[serde(rename = "ENGINE_UNSPECIFIED")]
     EngineUnspecified,
     MySQL,
     PostgreSQL,
@@ -238,6 +558,47 @@ pub enum SQLDialect {
     Cassandra,
 }
 
+impl SQLDialect {
+    /// Whether Bytebase's SQL review (`/v1/sql/check`) can check statements for this engine.
+    pub fn supports_sql_check(&self) -> bool {
+        matches!(
+            self,
+            Self::MySQL
+                | Self::TiDB
+                | Self::MariaDB
+                | Self::OceanBase
+                | Self::PostgreSQL
+                | Self::Postgres
+                | Self::Oracle
+                | Self::MsSQL
+                | Self::Snowflake
+        )
+    }
+}
+
+impl std::str::FromStr for SQLDialect {
+    type Err = String;
+
+    /// Parses an engine name case-insensitively, e.g. `mysql`, `POSTGRES` or `tidb`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let engine = serde_json::from_value::<Self>(serde_json::Value::String(s.to_uppercase()))
+            .map_err(|e| e.to_string())?;
+        match engine {
+            Self::EngineUnspecified => Err(format!("Unknown engine '{s}'")),
+            engine => Ok(engine),
+        }
+    }
+}
+
+impl std::fmt::Display for SQLDialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(name)) => f.write_str(&name),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct EncodedStatement(String);
 
@@ -250,12 +611,14 @@ impl From<StringStatement> for EncodedStatement {
 
 #[derive(Serialize, Debug, Clone)]
 pub struct SheetRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     #[serde(rename = "content")]
     pub sql_statement: EncodedStatement,
     pub engine: SQLDialect,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SheetName {
     pub project_name: String,
     pub number: u32,
@@ -267,17 +630,10 @@ impl<'de> Deserialize<'de> for SheetName {
         D: Deserializer<'de>,
     {
         let raw = String::deserialize(deserializer)?;
-        let mut split = raw.split('/');
-        let project_name = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find project name"))?
-            .to_string();
-        let number = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find sheet number"))?
-            .parse()
-            .map_err(|_| de::Error::custom("invalid sheet number"))?;
-
+        let [project_name, number] =
+            parse_resource_name(&raw, ["projects", "sheets"]).map_err(de::Error::custom)?;
+        let project_name = project_name.to_string();
+        let number = parse_resource_id(&raw, "sheet number", number).map_err(de::Error::custom)?;
         Ok(Self {
             project_name,
             number,
@@ -324,7 +680,7 @@ pub struct PostPlansRequest {
     pub steps: Vec<PlanStep>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PlanName {
     pub project_name: String,
     pub number: u32,
@@ -336,16 +692,10 @@ impl<'de> Deserialize<'de> for PlanName {
         D: Deserializer<'de>,
     {
         let raw = String::deserialize(deserializer)?;
-        let mut split = raw.split('/');
-        let project_name = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find project name"))?
-            .to_string();
-        let number = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find plan number"))?
-            .parse()
-            .map_err(|_| de::Error::custom("invalid plan number"))?;
+        let [project_name, number] =
+            parse_resource_name(&raw, ["projects", "plans"]).map_err(de::Error::custom)?;
+        let project_name = project_name.to_string();
+        let number = parse_resource_id(&raw, "plan number", number).map_err(de::Error::custom)?;
         Ok(Self {
             project_name,
             number,
@@ -392,20 +742,12 @@ impl<'de> Deserialize<'de> for RolloutName {
         D: Deserializer<'de>,
     {
         let raw = String::deserialize(deserializer)?;
-        // Format: "projects/{project}/rollouts/{rollout_id}"
-        let mut split = raw.split('/');
-        let project = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find project name"))?
-            .to_string();
-        let rollout_id = split
-            .nth(1)
-            .ok_or(de::Error::custom("cannot find rollout id"))?
-            .parse()
-            .map_err(|_| de::Error::custom("invalid rollout id"))?;
+        let [project, rollout_id] =
+            parse_resource_name(&raw, ["projects", "rollouts"]).map_err(de::Error::custom)?;
         Ok(Self {
-            project,
-            rollout_id,
+            project: project.to_string(),
+            rollout_id: parse_resource_id(&raw, "rollout id", rollout_id)
+                .map_err(de::Error::custom)?,
         })
     }
 }
@@ -425,6 +767,101 @@ impl Serialize for RolloutName {
     }
 }
 
+/// Name of a rollout task: `projects/{project}/rollouts/{rollout_id}/stages/{stage}/tasks/{task_id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TaskName {
+    pub project: String,
+    pub rollout_id: u32,
+    pub stage: String,
+    pub task_id: u32,
+}
+
+impl TaskName {
+    fn from_ids(
+        raw: &str,
+        [project, rollout_id, stage, task_id]: [&str; 4],
+    ) -> Result<Self, String> {
+        Ok(Self {
+            project: project.to_string(),
+            rollout_id: parse_resource_id(raw, "rollout id", rollout_id)?,
+            stage: stage.to_string(),
+            task_id: parse_resource_id(raw, "task id", task_id)?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        parse_resource_name(&raw, ["projects", "rollouts", "stages", "tasks"])
+            .and_then(|ids| Self::from_ids(&raw, ids))
+            .map_err(de::Error::custom)
+    }
+}
+
+impl std::fmt::Display for TaskName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "projects/{}/rollouts/{}/stages/{}/tasks/{}",
+            self.project, self.rollout_id, self.stage, self.task_id
+        )
+    }
+}
+
+impl Serialize for TaskName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Name of one execution attempt of a task: `{task}/taskRuns/{run_id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TaskRunName {
+    pub task: TaskName,
+    pub run_id: u32,
+}
+
+impl<'de> Deserialize<'de> for TaskRunName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        let [project, rollout_id, stage, task_id, run_id] = parse_resource_name(
+            &raw,
+            ["projects", "rollouts", "stages", "tasks", "taskRuns"],
+        )
+        .map_err(de::Error::custom)?;
+        Ok(Self {
+            task: TaskName::from_ids(&raw, [project, rollout_id, stage, task_id])
+                .map_err(de::Error::custom)?,
+            run_id: parse_resource_id(&raw, "task run id", run_id).map_err(de::Error::custom)?,
+        })
+    }
+}
+
+impl std::fmt::Display for TaskRunName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/taskRuns/{}", self.task, self.run_id)
+    }
+}
+
+impl Serialize for TaskRunName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TaskStatus {
@@ -450,22 +887,53 @@ impl TaskStatus {
     }
 }
 
+/// Statement a schema-update task runs.
+#[derive(Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct DatabaseSchemaUpdate {
+    pub sheet: SheetName,
+}
+
 #[derive(Deserialize, Debug, Clone)]
+#[allow(dead_code)]
 pub struct RolloutTask {
-    pub name: String,
+    pub name: TaskName,
     pub status: TaskStatus,
     pub target: String,
+    #[serde(rename = "specId", default)]
+    pub spec_id: String,
+    #[serde(rename = "type", default)]
+    pub task_type: String,
+    #[serde(rename = "skippedReason", default)]
+    pub skipped_reason: String,
+    #[serde(rename = "databaseSchemaUpdate", default)]
+    pub database_schema_update: Option<DatabaseSchemaUpdate>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct RolloutStage {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub environment: String,
     pub tasks: Vec<RolloutTask>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[allow(dead_code)]
 pub struct Rollout {
     pub name: RolloutName,
     #[serde(default)]
+    pub plan: Option<PlanName>,
+    #[serde(default)]
+    pub issue: Option<IssueName>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub creator: String,
+    #[serde(rename = "createTime", default)]
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
     pub stages: Vec<RolloutStage>,
 }
 
@@ -483,11 +951,250 @@ impl Rollout {
             .iter()
             .all(|stage| stage.tasks.iter().all(|task| task.status.is_success()))
     }
+
+    /// Overall status for listings: the worst task status, e.g. `FAILED` if any task failed.
+    pub fn status(&self) -> &'static str {
+        let statuses: Vec<&TaskStatus> = self
+            .stages
+            .iter()
+            .flat_map(|stage| stage.tasks.iter().map(|task| &task.status))
+            .collect();
+        let any = |status: TaskStatus| statuses.contains(&&status);
+        if any(TaskStatus::Failed) {
+            "FAILED"
+        } else if any(TaskStatus::Canceled) {
+            "CANCELED"
+        } else if self.is_complete() {
+            "DONE"
+        } else if any(TaskStatus::Running) {
+            "RUNNING"
+        } else if any(TaskStatus::Pending) || any(TaskStatus::Done) {
+            "PENDING"
+        } else {
+            "NOT_STARTED"
+        }
+    }
+}
+
+/// One page of `GET /v1/projects/{project}/rollouts`, newest first.
+#[derive(Deserialize, Debug, Default)]
+pub struct ListRolloutsResponse {
+    #[serde(default)]
+    pub rollouts: Vec<Rollout>,
+    #[serde(rename = "nextPageToken", default)]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaskRunStatus {
+    NotStarted,
+    Pending,
+    Available,
+    Running,
+    Done,
+    Failed,
+    Canceled,
+    Skipped,
+    #[serde(other)]
+    Unspecified,
+}
+
+/// One execution attempt of a rollout task. Failed runs carry the database error in `detail`.
+#[derive(Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct TaskRun {
+    pub name: TaskRunName,
+    pub status: TaskRunStatus,
+    #[serde(default)]
+    pub detail: String,
+    #[serde(default)]
+    pub creator: String,
+    #[serde(rename = "createTime", default)]
+    pub create_time: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "startTime", default)]
+    pub start_time: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(rename = "updateTime", default)]
+    pub update_time: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub sheet: Option<SheetName>,
+    #[serde(default)]
+    pub changelog: String,
+}
+
+impl TaskRun {
+    /// The task this run belongs to.
+    pub fn task(&self) -> &TaskName {
+        &self.name.task
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListTaskRunsResponse {
+    #[serde(rename = "taskRuns", default)]
+    pub task_runs: Vec<TaskRun>,
+    #[serde(rename = "nextPageToken", default)]
+    pub next_page_token: Option<String>,
+}
+
+// ===== Plan Check Types =====
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PlanCheckRunStatus {
+    Running,
+    Done,
+    Failed,
+    Canceled,
+    #[serde(other)]
+    Unspecified,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PlanCheckResultStatus {
+    Error,
+    Warning,
+    Success,
+    #[serde(other)]
+    Unspecified,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlanCheckResult {
+    pub status: PlanCheckResultStatus,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+/// One check (SQL review, connectivity, GhostSync, ...) Bytebase ran against a plan.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlanCheckRun {
+    #[serde(rename = "type", default)]
+    pub check_type: String,
+    pub status: PlanCheckRunStatus,
+    #[serde(default)]
+    pub results: Vec<PlanCheckResult>,
+    /// Why the check itself could not run, for `FAILED` runs.
+    #[serde(default)]
+    pub error: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListPlanCheckRunsResponse {
+    #[serde(rename = "planCheckRuns", default)]
+    pub plan_check_runs: Vec<PlanCheckRun>,
+}
+
+/// Body of a failed Bytebase request: `{code, message, details}`. `code` is the numeric gRPC
+/// status, although some gateways send its name instead.
+#[derive(Deserialize, Debug)]
+pub struct ErrorPayload {
+    pub code: serde_json::Value,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl ErrorPayload {
+    pub fn code(&self) -> Option<BytebaseErrorCode> {
+        match &self.code {
+            serde_json::Value::Number(n) => n.as_i64().and_then(BytebaseErrorCode::from_number),
+            serde_json::Value::String(name) => BytebaseErrorCode::from_name(name),
+            _ => None,
+        }
+    }
+}
+
+/// Schema metadata of a database, `GET .../databases/{db}/metadata`. Only table sizes, columns
+/// and comments are kept.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DatabaseMetadata {
+    #[serde(default)]
+    pub schemas: Vec<SchemaMetadata>,
+}
+
+impl DatabaseMetadata {
+    /// Finds `table` in `schema` (empty for engines without schemas, such as MySQL).
+    pub fn table(&self, schema: &str, table: &str) -> Option<&TableMetadata> {
+        self.schemas
+            .iter()
+            .filter(|s| s.name == schema)
+            .flat_map(|s| &s.tables)
+            .find(|t| t.name == table)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SchemaMetadata {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub tables: Vec<TableMetadata>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TableMetadata {
+    pub name: String,
+    /// Estimated row count as reported by the engine.
+    #[serde(rename = "rowCount", default, deserialize_with = "deserialize_int64")]
+    pub row_count: i64,
+    /// Data size in bytes.
+    #[serde(rename = "dataSize", default, deserialize_with = "deserialize_int64")]
+    pub data_size: i64,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub columns: Vec<ColumnMetadata>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ColumnMetadata {
+    pub name: String,
+    /// Column type as the engine reports it, e.g. `bigint` or `varchar(255)`.
+    #[serde(rename = "type", default)]
+    pub column_type: String,
+    #[serde(default)]
+    pub nullable: bool,
+    /// Default expression, empty when the column has none.
+    #[serde(default)]
+    pub default: String,
+    #[serde(default)]
+    pub comment: String,
+}
+
+/// Deserializes a protobuf `int64`, which JSON carries as a string but some gateways send as a
+/// number.
+fn deserialize_int64<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .ok_or_else(|| de::Error::custom(format!("invalid int64 {n}"))),
+        serde_json::Value::String(s) => s
+            .parse()
+            .map_err(|_| de::Error::custom(format!("invalid int64 '{s}'"))),
+        _ => Ok(0),
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Instance {
     pub name: String,
+    /// `None` for engines this CLI does not know.
+    #[serde(default, deserialize_with = "deserialize_known_engine")]
+    pub engine: Option<SQLDialect>,
+}
+
+fn deserialize_known_engine<'de, D>(deserializer: D) -> Result<Option<SQLDialect>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Option::<String>::deserialize(deserializer)?;
+    Ok(raw.and_then(|engine| engine.parse().ok()))
 }
 
 #[test]
@@ -501,10 +1208,24 @@ fn test_issue_name_deserialization() {
     let unhappy_inputs = vec![
         (
             "instances/my-instance/databases/my-db/changelogs/101",
-            "invalid issue number",
+            "expected 'projects/{project}/issues/{issue}'",
+        ),
+        (
+            "projects/dev-project1/issues",
+            "expected 'projects/{project}/issues/{issue}'",
+        ),
+        (
+            "projects/issues/103",
+            "expected 'projects/{project}/issues/{issue}'",
+        ),
+        (
+            "projects/dev-project1/plans/103",
+            "expected 'projects/{project}/issues/{issue}'",
+        ),
+        (
+            "projects/dev-project1/issues/abc",
+            "invalid issue number 'abc'",
         ),
-        ("projects/dev-project1/issues", "cannot find issue number"),
-        ("projects/issues/103", "cannot find issue number"),
     ];
 
     for input in happy_inputs {
@@ -549,15 +1270,15 @@ fn test_changelog_name_deserialization() {
     let unhappy_inputs = vec![
         (
             "instances/my-instance/databases/my-db/changelogs",
-            "cannot find changelog number",
+            "expected 'instances/{instance}/databases/{database}/changelogs/{changelog}'",
         ),
         (
             "projects/dev-project1/issues/101",
-            "cannot find changelog number",
+            "expected 'instances/{instance}/databases/{database}/changelogs/{changelog}'",
         ),
         (
             "instances/my-instance/changelogs/102",
-            "cannot find changelog number",
+            "expected 'instances/{instance}/databases/{database}/changelogs/{changelog}'",
         ),
     ];
 
@@ -767,8 +1488,11 @@ fn test_changelog_deserialization() {
     assert_eq!(changelogs[0].name.database, "bridge");
     assert_eq!(changelogs[0].name.number, 672);
     assert_eq!(changelogs[0].statement.0, "SELECT 1".to_string());
-    assert_eq!(changelogs[0].issue.project, "eclipse-daily-project");
-    assert_eq!(changelogs[0].issue.number, 723);
+    assert_eq!(
+        changelogs[0].issue.as_ref().unwrap().project,
+        "eclipse-daily-project"
+    );
+    assert_eq!(changelogs[0].issue_number(), 723);
     assert_eq!(
         changelogs[0]
             .create_time
@@ -777,6 +1501,33 @@ fn test_changelog_deserialization() {
     );
 }
 
+#[test]
+fn test_list_changelogs_response_skips_invalid_entries() {
+    let page_json = r#"
+    {
+        "changelogs": [
+            {
+                "name": "instances/daily-admin/databases/bridge/changelogs/672",
+                "createTime": "2025-08-08T12:28:10.353882Z",
+                "status": "DONE",
+                "statement": "SELECT 1",
+                "issue": "projects/eclipse-daily-project/issues/723"
+            },
+            {
+                "name": "instances/daily-admin/databases/bridge/changelogs/673",
+                "status": "DONE"
+            }
+        ],
+        "nextPageToken": "abc"
+    }
+    "#;
+
+    let page: ListChangelogsResponse = serde_json::from_str(page_json).unwrap();
+    assert_eq!(page.changelogs.len(), 1);
+    assert_eq!(page.changelogs[0].name.number, 672);
+    assert_eq!(page.next_page_token.as_deref(), Some("abc"));
+}
+
 #[test]
 fn test_revision_version_deserialization() {
     let happy_inputs = vec![
@@ -880,8 +1631,11 @@ fn test_rollout_deserialization() {
     // Test tasks
     assert_eq!(rollout.stages[0].tasks.len(), 1);
     let task = &rollout.stages[0].tasks[0];
+    assert_eq!(task.name.rollout_id, 2404);
+    assert_eq!(task.name.stage, "2405");
+    assert_eq!(task.name.task_id, 2440);
     assert_eq!(
-        task.name,
+        task.name.to_string(),
         "projects/on-prem-stage/rollouts/2404/stages/2405/tasks/2440"
     );
     assert_eq!(task.status, TaskStatus::Done);
@@ -927,6 +1681,7 @@ fn test_rollout_failed_status() {
     assert_eq!(rollout.stages[0].tasks[0].status, TaskStatus::Failed);
     assert!(rollout.is_complete());
     assert!(!rollout.is_success());
+    assert_eq!(rollout.status(), "FAILED");
 }
 
 #[test]
@@ -960,4 +1715,103 @@ fn test_rollout_not_started_status() {
     assert_eq!(rollout.stages[0].tasks[0].status, TaskStatus::NotStarted);
     assert!(!rollout.is_complete()); // NOT_STARTED is not terminal
     assert!(!rollout.is_success());
+    assert_eq!(rollout.status(), "NOT_STARTED");
+}
+
+#[test]
+fn test_error_payload_code() {
+    let numeric: ErrorPayload =
+        serde_json::from_str(r#"{"code":5,"message":"project not found","details":[]}"#).unwrap();
+    assert_eq!(numeric.code(), Some(BytebaseErrorCode::NotFound));
+    assert_eq!(numeric.message, "project not found");
+
+    let named: ErrorPayload =
+        serde_json::from_str(r#"{"code":"FAILED_PRECONDITION","message":"x"}"#).unwrap();
+    assert_eq!(named.code(), Some(BytebaseErrorCode::FailedPrecondition));
+
+    let unknown: ErrorPayload = serde_json::from_str(r#"{"code":99,"message":"x"}"#).unwrap();
+    assert_eq!(unknown.code(), None);
+}
+
+#[test]
+fn test_task_run_deserialization() {
+    let json = r#"
+    {
+        "taskRuns": [
+            {
+                "name": "projects/test/rollouts/100/stages/101/tasks/102/taskRuns/7",
+                "creator": "users/terraform@service.bytebase.com",
+                "createTime": "2026-01-27T09:11:27.329087Z",
+                "updateTime": "2026-01-27T09:11:29Z",
+                "status": "FAILED",
+                "detail": "Duplicate column name 'email'",
+                "sheet": "projects/test/sheets/55",
+                "schedulerInfo": {}
+            },
+            {
+                "name": "projects/test/rollouts/100/stages/101/tasks/103/taskRuns/8",
+                "status": "SOMETHING_NEW"
+            }
+        ],
+        "nextPageToken": "next"
+    }
+    "#;
+
+    let response: ListTaskRunsResponse = serde_json::from_str(json).unwrap();
+    assert_eq!(response.task_runs.len(), 2);
+    assert_eq!(response.next_page_token.as_deref(), Some("next"));
+    let failed = &response.task_runs[0];
+    assert_eq!(failed.status, TaskRunStatus::Failed);
+    assert_eq!(failed.detail, "Duplicate column name 'email'");
+    assert_eq!(failed.name.run_id, 7);
+    assert_eq!(
+        failed.task().to_string(),
+        "projects/test/rollouts/100/stages/101/tasks/102"
+    );
+    assert_ne!(failed.task(), response.task_runs[1].task());
+    assert_eq!(response.task_runs[1].status, TaskRunStatus::Unspecified);
+    assert_eq!(
+        serde_json::to_value(&response.task_runs[1].name).unwrap(),
+        "projects/test/rollouts/100/stages/101/tasks/103/taskRuns/8"
+    );
+    assert!(serde_json::from_str::<TaskRunName>(r#""projects/test/rollouts/100""#).is_err());
+}
+
+#[test]
+fn test_sql_dialect_names() {
+    assert_eq!("tidb".parse::<SQLDialect>(), Ok(SQLDialect::TiDB));
+    assert_eq!("POSTGRES".parse::<SQLDialect>(), Ok(SQLDialect::Postgres));
+    assert!("nosuchdb".parse::<SQLDialect>().is_err());
+    assert_eq!(SQLDialect::MsSQL.to_string(), "MSSQL");
+
+    let instance: Instance =
+        serde_json::from_str(r#"{"name": "instances/pg", "engine": "NEWENGINE"}"#).unwrap();
+    assert_eq!(instance.engine, None);
+}
+
+#[test]
+fn test_list_options_query() {
+    let query = |options: ListOptions| {
+        options
+            .query()
+            .into_iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        query(ListOptions::full()),
+        ["pageSize=100", "view=CHANGELOG_VIEW_FULL"]
+    );
+    assert_eq!(
+        query(ListOptions {
+            page_size: Some(20),
+            filter: Some(Filter::new().eq("type", "MIGRATE")),
+            ..ListOptions::default()
+        }),
+        [
+            "pageSize=20",
+            "view=CHANGELOG_VIEW_BASIC",
+            "filter=type = \"MIGRATE\""
+        ]
+    );
 }
@@ -0,0 +1,441 @@
+use crate::api::types::Changelog;
+use crate::input;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use tokio::fs;
+
+/// File name of the manifest written alongside the SQL files of a bundle.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Describes an offline migration bundle: where the changes came from, where they are
+/// going, and the ordered list of SQL files to apply.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Manifest {
+    pub source_env: String,
+    pub source_project: String,
+    pub source_database: String,
+    /// Target the bundle was generated for, if it was generated against a specific target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_database: Option<String>,
+    /// Issue number the bundle starts after (exclusive).
+    pub from_issue: u32,
+    /// Issue number the bundle migrates the target to (inclusive).
+    pub to_issue: u32,
+    pub created_at: DateTime<Utc>,
+    /// Service account that generated the bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Version of shelltide that generated the bundle.
+    #[serde(default)]
+    pub shelltide_version: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// A single SQL file of the bundle, in apply order.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ManifestEntry {
+    pub file: String,
+    pub issue: u32,
+    /// Full resource name of the source changelog.
+    pub changelog: String,
+    pub executed_at: DateTime<Utc>,
+    /// Hex-encoded SHA-256 of the SQL file contents.
+    pub sha256: String,
+}
+
+/// A bundle loaded into memory: its manifest and the SQL of each entry, in apply order.
+#[derive(Debug)]
+pub struct Bundle {
+    pub manifest: Manifest,
+    pub statements: Vec<String>,
+}
+
+/// Returns the file name used for the `index`-th (0-based) changelog of a bundle.
+pub fn entry_file_name(index: usize, changelog: &Changelog) -> String {
+    format!("{:04}_issue-{}.sql", index + 1, changelog.issue_number())
+}
+
+/// Builds the manifest entries for the given ordered changelogs.
+pub fn build_entries(changelogs: &[Changelog]) -> Vec<ManifestEntry> {
+    changelogs
+        .iter()
+        .enumerate()
+        .map(|(index, changelog)| ManifestEntry {
+            file: entry_file_name(index, changelog),
+            issue: changelog.issue_number(),
+            changelog: changelog.name.to_string(),
+            executed_at: changelog.create_time,
+            sha256: sha256_hex(changelog.statement.0.as_bytes()),
+        })
+        .collect()
+}
+
+/// Returns the hex-encoded SHA-256 digest of `data`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Writes the ordered SQL files and the manifest into `out_dir`, creating it if needed.
+pub async fn write_dir(
+    out_dir: &Path,
+    manifest: &Manifest,
+    changelogs: &[Changelog],
+) -> Result<()> {
+    fs::create_dir_all(out_dir)
+        .await
+        .with_context(|| format!("Failed to create bundle directory at {out_dir:?}"))?;
+
+    for (entry, changelog) in manifest.entries.iter().zip(changelogs) {
+        let path = out_dir.join(&entry.file);
+        fs::write(&path, changelog.statement.to_string())
+            .await
+            .with_context(|| format!("Failed to write SQL file to {path:?}"))?;
+    }
+
+    let manifest_path = out_dir.join(MANIFEST_FILE);
+    let content =
+        serde_json::to_string_pretty(manifest).context("Failed to serialize bundle manifest")?;
+    fs::write(&manifest_path, content)
+        .await
+        .with_context(|| format!("Failed to write manifest to {manifest_path:?}"))?;
+
+    Ok(())
+}
+
+/// Reads the manifest of a bundle directory.
+#[cfg(test)]
+pub async fn read_manifest(dir: &Path) -> Result<Manifest> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let content = fs::read_to_string(&manifest_path)
+        .await
+        .with_context(|| format!("Failed to read manifest at {manifest_path:?}"))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse manifest at {manifest_path:?}"))
+}
+
+/// Reads a directory written by [`write_dir`] without verifying it, returning the manifest and
+/// every other file in the directory keyed by its name.
+pub async fn read_dir_files(dir: &Path) -> Result<(Manifest, HashMap<String, String>)> {
+    let mut files = HashMap::new();
+    let mut entries = fs::read_dir(dir)
+        .await
+        .with_context(|| format!("Failed to read directory {dir:?}"))?;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .with_context(|| format!("Failed to read directory {dir:?}"))?
+    {
+        if !entry.file_type().await?.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let content = fs::read_to_string(entry.path())
+            .await
+            .with_context(|| format!("Failed to read '{name}' in {dir:?}"))?;
+        files.insert(name, content);
+    }
+
+    let manifest_json = files
+        .remove(MANIFEST_FILE)
+        .with_context(|| format!("Directory {dir:?} has no {MANIFEST_FILE}"))?;
+    let manifest: Manifest = serde_json::from_str(&manifest_json)
+        .with_context(|| format!("Failed to parse manifest in {dir:?}"))?;
+
+    Ok((manifest, files))
+}
+
+/// Writes the manifest and the ordered SQL files as a gzip-compressed tar archive.
+pub async fn write_archive(
+    path: &Path,
+    manifest: &Manifest,
+    changelogs: &[Changelog],
+) -> Result<()> {
+    let manifest_json =
+        serde_json::to_vec_pretty(manifest).context("Failed to serialize bundle manifest")?;
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    append_file(&mut builder, MANIFEST_FILE, &manifest_json)?;
+    for (entry, changelog) in manifest.entries.iter().zip(changelogs) {
+        append_file(&mut builder, &entry.file, changelog.statement.0.as_bytes())?;
+    }
+    let archive = builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .context("Failed to build bundle archive")?;
+
+    fs::write(path, archive)
+        .await
+        .with_context(|| format!("Failed to write bundle archive to {path:?}"))?;
+    Ok(())
+}
+
+fn append_file<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, name, data)
+        .with_context(|| format!("Failed to add '{name}' to bundle archive"))
+}
+
+/// Reads a bundle archive and verifies every entry against the manifest checksums.
+pub async fn read_archive(path: &Path) -> Result<Bundle> {
+    let (manifest, mut files) = read_archive_files(path).await?;
+
+    let problems = verify_files(&manifest, &files);
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "Bundle archive {} failed verification:\n  {}",
+            input::describe(path),
+            problems.join("\n  ")
+        );
+    }
+
+    let statements = manifest
+        .entries
+        .iter()
+        .map(|entry| files.remove(&entry.file).unwrap_or_default())
+        .collect();
+    Ok(Bundle {
+        manifest,
+        statements,
+    })
+}
+
+/// Reads a bundle archive without verifying it, returning the manifest and the remaining
+/// files keyed by their path inside the archive. `-` reads the archive from stdin.
+pub async fn read_archive_files(path: &Path) -> Result<(Manifest, HashMap<String, String>)> {
+    let source = input::describe(path);
+    let archive = input::read(path)
+        .await
+        .with_context(|| format!("Failed to read bundle archive from {source}"))?;
+
+    let mut files = HashMap::new();
+    let mut tar = tar::Archive::new(GzDecoder::new(archive.as_slice()));
+    for file in tar
+        .entries()
+        .context("Failed to read bundle archive entries")?
+    {
+        let mut file = file.context("Failed to read bundle archive entry")?;
+        let name = file
+            .path()
+            .context("Invalid path in bundle archive")?
+            .to_string_lossy()
+            .to_string();
+        let mut content = String::new();
+        file.read_to_string(&mut content)
+            .with_context(|| format!("Failed to read '{name}' from bundle archive"))?;
+        files.insert(name, content);
+    }
+
+    let manifest_json = files
+        .remove(MANIFEST_FILE)
+        .with_context(|| format!("Bundle archive {source} has no {MANIFEST_FILE}"))?;
+    let manifest: Manifest = serde_json::from_str(&manifest_json)
+        .with_context(|| format!("Failed to parse manifest in {source}"))?;
+
+    Ok((manifest, files))
+}
+
+/// Checks every manifest entry against `files`, returning one message per problem found.
+pub fn verify_files(manifest: &Manifest, files: &HashMap<String, String>) -> Vec<String> {
+    let mut problems = Vec::new();
+    for entry in &manifest.entries {
+        match files.get(&entry.file) {
+            None => problems.push(format!("'{}' is missing", entry.file)),
+            Some(content) => {
+                let actual = sha256_hex(content.as_bytes());
+                if actual != entry.sha256 {
+                    problems.push(format!(
+                        "Checksum mismatch for '{}': expected {}, got {actual}",
+                        entry.file, entry.sha256
+                    ));
+                }
+            }
+        }
+    }
+    for name in files.keys() {
+        if !manifest.entries.iter().any(|entry| &entry.file == name) {
+            problems.push(format!("'{name}' is not listed in the manifest"));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::{
+        ChangeLogName, ChangedResource, ChangelogType, IssueName, StringStatement,
+    };
+    use tempfile::tempdir;
+
+    fn changelog(number: u32, statement: &str) -> Changelog {
+        Changelog {
+            name: ChangeLogName {
+                instance: "dev-instance".to_string(),
+                database: "bridge".to_string(),
+                number,
+            },
+            create_time: Utc::now(),
+            status: "DONE".to_string(),
+            statement: StringStatement(statement.to_string()),
+            schema: String::new(),
+            issue: Some(IssueName {
+                project: "dev-project".to_string(),
+                number,
+            }),
+            changed_resources: ChangedResource::default(),
+            changelog_type: Some(ChangelogType::Migrate),
+            statement_sheet: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_dir_roundtrip() {
+        let temp_dir = tempdir().unwrap();
+        let out_dir = temp_dir.path().join("bundle");
+        let changelogs = vec![changelog(101, "SELECT 1;"), changelog(102, "SELECT 2;")];
+        let manifest = Manifest {
+            source_env: "dev".to_string(),
+            source_project: "dev-project".to_string(),
+            source_database: "bridge".to_string(),
+            target_env: Some("prod".to_string()),
+            target_database: Some("bridge".to_string()),
+            from_issue: 100,
+            to_issue: 102,
+            created_at: Utc::now(),
+            created_by: None,
+            shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: build_entries(&changelogs),
+        };
+
+        write_dir(&out_dir, &manifest, &changelogs).await.unwrap();
+
+        let loaded = read_manifest(&out_dir).await.unwrap();
+        assert_eq!(loaded.entries.len(), 2);
+        assert_eq!(loaded.entries[0].file, "0001_issue-101.sql");
+        assert_eq!(
+            loaded.entries[1].changelog,
+            "instances/dev-instance/databases/bridge/changelogs/102"
+        );
+        let sql = std::fs::read_to_string(out_dir.join("0002_issue-102.sql")).unwrap();
+        assert_eq!(sql, "SELECT 2;");
+        assert_eq!(loaded.entries[1].sha256, sha256_hex(b"SELECT 2;"));
+    }
+
+    #[tokio::test]
+    async fn test_read_dir_files_detects_hand_edits() {
+        let temp_dir = tempdir().unwrap();
+        let out_dir = temp_dir.path().join("sql");
+        let changelogs = vec![changelog(101, "SELECT 1;"), changelog(102, "SELECT 2;")];
+        let manifest = Manifest {
+            source_env: "dev".to_string(),
+            source_project: "dev-project".to_string(),
+            source_database: "bridge".to_string(),
+            target_env: None,
+            target_database: None,
+            from_issue: 100,
+            to_issue: 102,
+            created_at: Utc::now(),
+            created_by: None,
+            shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: build_entries(&changelogs),
+        };
+        write_dir(&out_dir, &manifest, &changelogs).await.unwrap();
+
+        let (loaded, files) = read_dir_files(&out_dir).await.unwrap();
+        assert!(verify_files(&loaded, &files).is_empty());
+
+        std::fs::write(out_dir.join("0002_issue-102.sql"), "SELECT 3;").unwrap();
+        let (loaded, files) = read_dir_files(&out_dir).await.unwrap();
+        let problems = verify_files(&loaded, &files);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("Checksum mismatch for '0002_issue-102.sql'"));
+    }
+
+    #[tokio::test]
+    async fn test_archive_roundtrip_and_tamper_detection() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("rel.tar.gz");
+        let changelogs = vec![changelog(101, "SELECT 1;"), changelog(102, "SELECT 2;")];
+        let mut manifest = Manifest {
+            source_env: "dev".to_string(),
+            source_project: "dev-project".to_string(),
+            source_database: "bridge".to_string(),
+            target_env: None,
+            target_database: None,
+            from_issue: 0,
+            to_issue: 102,
+            created_at: Utc::now(),
+            created_by: None,
+            shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: build_entries(&changelogs),
+        };
+
+        write_archive(&path, &manifest, &changelogs).await.unwrap();
+        let bundle = read_archive(&path).await.unwrap();
+        assert_eq!(bundle.manifest.to_issue, 102);
+        assert_eq!(bundle.statements, vec!["SELECT 1;", "SELECT 2;"]);
+
+        manifest.entries[0].sha256 = sha256_hex(b"DROP TABLE users;");
+        write_archive(&path, &manifest, &changelogs).await.unwrap();
+        let err = read_archive(&path).await.unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn test_verify_files_reports_every_problem() {
+        let changelogs = vec![changelog(101, "SELECT 1;"), changelog(102, "SELECT 2;")];
+        let manifest = Manifest {
+            source_env: "dev".to_string(),
+            source_project: "dev-project".to_string(),
+            source_database: "bridge".to_string(),
+            target_env: None,
+            target_database: None,
+            from_issue: 0,
+            to_issue: 102,
+            created_at: Utc::now(),
+            created_by: None,
+            shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: build_entries(&changelogs),
+        };
+
+        let mut files = HashMap::new();
+        files.insert("0001_issue-101.sql".to_string(), "SELECT 1;".to_string());
+        let problems = verify_files(&manifest, &files);
+        assert_eq!(
+            problems,
+            vec!["'0002_issue-102.sql' is missing".to_string()]
+        );
+
+        files.insert("0002_issue-102.sql".to_string(), "SELECT 2;".to_string());
+        assert!(verify_files(&manifest, &files).is_empty());
+
+        files.insert("0001_issue-101.sql".to_string(), "SELECT 3;".to_string());
+        files.insert("extra.sql".to_string(), "DROP TABLE users;".to_string());
+        let problems = verify_files(&manifest, &files);
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().any(|p| p.contains("Checksum mismatch")));
+        assert!(problems.iter().any(|p| p.contains("extra.sql")));
+    }
+}
@@ -1,5 +1,15 @@
+use crate::api::http_log::HttpDebug;
+use crate::api::types::SQLDialect;
+use crate::commands::report::ReportFormat;
+use crate::commands::status::StatusColumn;
+use crate::events::EventFormat;
+use crate::jira::parse_issue_key;
+use crate::output::OutputFormat;
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// A CLI for managing database migrations with Bytebase.
 #[derive(Parser, Debug)]
@@ -7,10 +17,35 @@ use clap_complete::Shell;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Log every Bytebase API call to stderr; `--debug-http=bodies` also prints redacted payloads.
+    /// Defaults to the `RUST_LOG=shelltide::api=debug|trace` setting when omitted.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "basic"
+    )]
+    pub debug_http: Option<HttpDebug>,
+
+    /// How `status` and `env list` print their results: aligned columns, or a JSON array of
+    /// the rows for scripts
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+
+    /// Use the credentials saved under this profile by `login --profile`, for another Bytebase
+    /// server, instead of the default ones
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// Interactively log in and set up your first environments
+    Init,
+
     /// Log in to a Bytebase instance
     Login(LoginArgs),
 
@@ -23,6 +58,9 @@ pub enum Commands {
     /// Apply migrations to a target environment
     Migrate(MigrateArgs),
 
+    /// Promote a database through a chain of environments, one after another
+    Promote(PromoteArgs),
+
     /// Show the current migration status of all environments
     Status(StatusArgs),
 
@@ -34,6 +72,42 @@ pub enum Commands {
 
     /// Dump complete database schema at a specific issue
     Dump(DumpArgs),
+
+    /// Package and apply release bundles for air-gapped promotion
+    Bundle(BundleArgs),
+
+    /// Re-attach to an interrupted migrate run and finish its bookkeeping
+    Resume(ResumeArgs),
+
+    /// Inspect or correct the revision records of a database
+    Revision(RevisionArgs),
+
+    /// List recent rollouts of an environment's project
+    Rollouts(RolloutsArgs),
+
+    /// List the changelogs applied to a database, newest first
+    History(HistoryArgs),
+
+    /// Explain step by step how the pending changelogs of a database are computed
+    Explain(ExplainArgs),
+
+    /// Find issues of an environment's project
+    Issues(IssuesArgs),
+
+    /// Compare configured releases
+    Release(ReleaseArgs),
+
+    /// Preview what Bytebase would make of a migration without creating anything
+    Plan(PlanArgs),
+
+    /// Open an issue or database in the Bytebase web UI
+    Open(OpenArgs),
+
+    /// Generate Markdown or HTML documentation of a database's tables and columns
+    Report(ReportArgs),
+
+    /// Summarize the durations, API calls and applied changelogs of recent runs
+    Stats(StatsArgs),
 }
 
 // --- Argument Structs ---
@@ -41,14 +115,21 @@ pub enum Commands {
 #[derive(Parser, Debug)]
 pub struct LoginArgs {
     /// The URL of the Bytebase instance
-    #[arg(long)]
-    pub url: String,
+    #[arg(long, required_unless_present = "validate_only")]
+    pub url: Option<String>,
     /// The service account email (e.g., "your-sa@service.bytebase.com")
-    #[arg(long)]
-    pub service_account: String,
+    #[arg(long, required_unless_present = "validate_only")]
+    pub service_account: Option<String>,
     /// The service key associated with the service account
-    #[arg(long)]
-    pub service_key: String,
+    #[arg(long, required_unless_present = "validate_only")]
+    pub service_key: Option<String>,
+    /// Check the stored credentials without changing them: token validity, expiry and project
+    /// access. Exits with code 4 when something is wrong
+    #[arg(long, conflicts_with_all = ["url", "service_account", "service_key"])]
+    pub validate_only: bool,
+    /// With --validate-only, also report a token that expires within this long, e.g. "72h"
+    #[arg(long, value_parser = parse_duration, requires = "validate_only")]
+    pub warn_within: Option<Duration>,
 }
 
 #[derive(Parser, Debug)]
@@ -71,6 +152,14 @@ pub enum ConfigCommand {
         /// The configuration key to retrieve
         key: String,
     },
+    /// Restore config.json from one of its automatic backups
+    Restore {
+        /// Backup file name (or path) to restore; defaults to the newest backup
+        backup: Option<String>,
+        /// List available backups instead of restoring
+        #[arg(long, conflicts_with = "backup")]
+        list: bool,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -84,11 +173,47 @@ pub enum EnvCommand {
     /// Add a new environment
     Add {
         /// A short, memorable name for the environment (e.g., "staging")
-        name: String,
+        #[arg(required_unless_present = "from_template")]
+        name: Option<String>,
         /// The full name of the corresponding Bytebase project
-        project: String,
+        #[arg(required_unless_present = "from_template")]
+        project: Option<String>,
         /// The instance name
-        instance: String,
+        #[arg(required_unless_present = "from_template")]
+        instance: Option<String>,
+        /// Database engine (e.g. MYSQL, POSTGRES, TIDB); detected from the instance if omitted
+        #[arg(long)]
+        engine: Option<SQLDialect>,
+        /// Fill project and instance from a configured `env_template`, with KEY=VALUE variables
+        /// for its placeholders; `name` sets the environment name (e.g. `prod-template name=prod-jp`)
+        #[arg(
+            long,
+            num_args = 1..,
+            value_names = ["TEMPLATE", "KEY=VALUE"],
+            conflicts_with_all = ["name", "project", "instance"]
+        )]
+        from_template: Option<Vec<String>>,
+        /// Add the environment even if another one already uses the same instance
+        #[arg(long)]
+        allow_duplicate: bool,
+    },
+    /// Change the project, instance, engine or pinned version of an existing environment
+    Set {
+        /// The name of the environment to change
+        name: String,
+        /// New Bytebase project
+        #[arg(long)]
+        project: Option<String>,
+        /// New instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// New database engine (e.g. MYSQL, POSTGRES, TIDB)
+        #[arg(long)]
+        engine: Option<SQLDialect>,
+        /// Issue number or release name `migrate` uses for this environment when no --to is
+        /// given; "" removes the pin
+        #[arg(long)]
+        pin_to: Option<String>,
     },
     /// List all configured environments
     List,
@@ -97,6 +222,17 @@ pub enum EnvCommand {
         /// The name of the environment to remove
         name: String,
     },
+    /// Export configured environments (without credentials) to YAML
+    Export {
+        /// File to write; prints to stdout if omitted
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Import environments from a YAML file produced by `env export`
+    Import {
+        /// The YAML file to import, or "-" for stdin
+        file: PathBuf,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -105,6 +241,12 @@ pub struct EnvDb {
     pub db: String,
 }
 
+impl std::fmt::Display for EnvDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.env, self.db)
+    }
+}
+
 impl std::str::FromStr for EnvDb {
     type Err = String;
 
@@ -124,12 +266,370 @@ impl std::str::FromStr for EnvDb {
 pub struct MigrateArgs {
     /// Source database name
     pub source_db: String,
-    /// Target as "<env>/<database>"
-    pub target: EnvDb,
+    /// Target as "<env>/<database>", or "<env>/db1,db2,db3" for several databases of one
+    /// environment. Several targets are migrated one after another (see --concurrency); a
+    /// failing one does not stop the rest, and the results are summarized at the end
+    #[arg(required = true)]
+    pub targets: Vec<EnvDb>,
+
+    /// Migrate every database of each target's instance whose name starts with the target's
+    /// database, e.g. "prod/shard_" for all shards or "prod/" for every database
+    #[arg(long)]
+    pub all_databases: bool,
+
+    /// Migrate up to N targets at the same time. Their prompts cannot share the terminal, so
+    /// this needs --yes
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "yes",
+        conflicts_with_all = ["interactive", "offline"]
+    )]
+    pub concurrency: u32,
+
+    /// The version to migrate to, number or "LATEST"; "-" reads it from stdin. Defaults to the
+    /// version the target's environment is pinned to (`env set --pin-to`)
+    #[arg(long, short)]
+    pub to: Option<String>,
+
+    /// Migrate to the source as it was when the changelog of this issue was created: issues up
+    /// to it, leaving out changelogs that reached the source later. Replaces --to, e.g. to
+    /// promote exactly what staging got at a release cut after the source has moved on
+    #[arg(long, value_name = "ISSUE", conflicts_with = "to")]
+    pub source_at: Option<u32>,
+
+    /// Apply a numbered `--to` without showing its issue and asking for confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+
+    /// Write the ordered SQL and a manifest to `--out` instead of applying through Bytebase
+    #[arg(long, requires = "out")]
+    pub offline: bool,
+
+    /// Output directory for `--offline`
+    #[arg(long, requires = "offline")]
+    pub out: Option<PathBuf>,
+
+    /// Select the changelogs and print the revision the run would write if they all apply, and
+    /// if each one fails, without creating anything
+    #[arg(long, conflicts_with_all = ["offline", "interactive"])]
+    pub dry_run: bool,
+
+    /// Block until the rollout finishes (default)
+    #[arg(long, overrides_with = "no_wait")]
+    pub wait: bool,
+
+    /// Return right after the last rollout is created instead of waiting for it
+    #[arg(long, overrides_with = "wait")]
+    pub no_wait: bool,
+
+    /// Give up waiting on a rollout after this long, e.g. "90s", "30m" or "1h"
+    #[arg(long, value_parser = parse_duration, conflicts_with = "no_wait")]
+    pub wait_timeout: Option<Duration>,
+
+    /// Print every source changelog left out of the selection and why
+    #[arg(long, short)]
+    pub verbose: bool,
+
+    /// Review each pending changelog and choose which ones to apply, like `git add -p`
+    #[arg(long, short, conflicts_with = "offline")]
+    pub interactive: bool,
+
+    /// Treat a target database without any revision as empty instead of failing, and apply
+    /// the source history from the beginning to bootstrap it
+    #[arg(long)]
+    pub assume_empty_revision: bool,
+
+    /// With --assume-empty-revision, start after this issue instead, for a new database whose
+    /// schema already matches it
+    #[arg(long, value_name = "ISSUE", requires = "assume_empty_revision")]
+    pub baseline: Option<u32>,
 
-    /// The version to migrate to, number or "LATEST"
+    /// Skip syncing the target database in Bytebase before selecting changelogs
+    #[arg(long)]
+    pub no_sync: bool,
+
+    /// Comment the applied range on this tracking issue when the promotion completes, as
+    /// "<number>" in the source project or "<project>#<number>" (default: notify.issue)
+    #[arg(long, conflicts_with = "offline")]
+    pub notify_issue: Option<String>,
+
+    /// Comment on this Jira issue (e.g. PROJ-123) when the promotion succeeds or fails, and
+    /// apply the configured notify.jira.on_success / on_failure transition
+    #[arg(long, value_parser = parse_issue_key, conflicts_with = "offline")]
+    pub jira: Option<String>,
+
+    /// Create rollouts without waiting for Bytebase's plan checks (SQL review, GhostSync, ...)
+    #[arg(long, conflicts_with = "offline")]
+    pub skip_plan_checks: bool,
+
+    /// Refuse to apply more than this many changelogs in one run unless --confirm-large-run is
+    /// given (default: migrate.max_changes, unlimited if unset)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_changes: Option<u32>,
+
+    /// Apply the changelogs even if there are more than --max-changes of them
+    #[arg(long)]
+    pub confirm_large_run: bool,
+
+    /// Keep retrying while Bytebase is in maintenance mode, for up to this long (default 1h)
+    #[arg(
+        long,
+        value_parser = parse_duration,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "1h",
+        conflicts_with = "offline"
+    )]
+    pub wait_for_server: Option<Duration>,
+
+    /// Emit one line per lifecycle event (selection, check, sheet, rollout, revision) to
+    /// --events-file
+    #[arg(long, value_enum, conflicts_with = "offline", requires = "events_file")]
+    pub events: Option<EventFormat>,
+
+    /// File `--events` are written to; required, as stdout carries the human-readable output
+    #[arg(long, requires = "events")]
+    pub events_file: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct PromoteArgs {
+    /// Database to promote
+    pub source_db: String,
+
+    /// Environments in promotion order, e.g. "dev,staging,prod". The first is the source; each
+    /// following one is migrated only once the one before it has finished
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub chain: Vec<String>,
+
+    /// The version to promote to, number or "LATEST"; "-" reads it from stdin. Resolved once,
+    /// so every environment of the chain gets the same issues
     #[arg(long, short)]
     pub to: String,
+
+    /// Promote without showing the target issue and asking for confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+
+    /// Give up waiting on a rollout after this long, e.g. "90s", "30m" or "1h"
+    #[arg(long, value_parser = parse_duration)]
+    pub wait_timeout: Option<Duration>,
+
+    /// Print every source changelog left out of the selection and why
+    #[arg(long, short)]
+    pub verbose: bool,
+
+    /// Skip syncing the target databases in Bytebase before selecting changelogs
+    #[arg(long)]
+    pub no_sync: bool,
+
+    /// Create rollouts without waiting for Bytebase's plan checks (SQL review, GhostSync, ...)
+    #[arg(long)]
+    pub skip_plan_checks: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct ResumeArgs {
+    /// Run id printed when the migrate run started
+    pub run_id: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct RolloutsArgs {
+    /// Environment whose project's rollouts are listed
+    pub env: String,
+
+    /// Maximum number of rollouts to show, newest first
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(Parser, Debug)]
+pub struct HistoryArgs {
+    /// Database as "<env>/<database>"
+    pub target: EnvDb,
+
+    /// Maximum number of changelogs to show, newest first
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Print the complete SQL of each changelog instead of a table with its first statement
+    #[arg(long)]
+    pub full: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct ExplainArgs {
+    /// Target as "<env>/<database>", with the database named as in the source environment
+    pub target: EnvDb,
+
+    /// The version `migrate --to` would be given, number or "LATEST"; "-" reads it from stdin
+    #[arg(long, short, default_value = "LATEST")]
+    pub to: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct RevisionArgs {
+    #[command(subcommand)]
+    pub command: RevisionCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RevisionCommand {
+    /// Explicitly record a revision for a database, after confirmation
+    Set {
+        /// Target as "<env>/<database>"
+        target: EnvDb,
+        /// Revision version as "<project>#<issue>"
+        #[arg(long)]
+        version: String,
+        /// Sheet the revision points at, as "projects/<project>/sheets/<number>"
+        #[arg(long)]
+        sheet: String,
+        /// Show the revision that would be created without creating it
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Check a database's latest revision against the changelogs shelltide actually applied
+    /// to it, and record a corrected revision after confirmation if they disagree
+    Repair {
+        /// Target as "<env>/<database>"
+        target: EnvDb,
+        /// Show the problem and the corrected revision without creating it
+        #[arg(long)]
+        dry_run: bool,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct ReleaseArgs {
+    #[command(subcommand)]
+    pub command: ReleaseCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReleaseCommand {
+    /// Show the tables and columns of a database that changed between two releases
+    Diff {
+        /// Older release name
+        from: String,
+        /// Newer release name
+        to: String,
+        /// Database to compare, named as in the releases' source environments
+        #[arg(long)]
+        db: String,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct PlanArgs {
+    #[command(subcommand)]
+    pub command: PlanCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PlanCommand {
+    /// Split the pending changelogs into the sheets `migrate` would create and run Bytebase's
+    /// SQL review on each against the target, without creating sheets, plans or issues
+    Preview {
+        /// Target as "<env>/<database>", with the database named as in the source environment
+        target: EnvDb,
+
+        /// The version `migrate --to` would be given, number or "LATEST"; "-" reads it from stdin
+        #[arg(long, short, default_value = "LATEST")]
+        to: String,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct OpenArgs {
+    #[command(subcommand)]
+    pub command: OpenCommand,
+
+    /// Print the URL instead of launching the browser, e.g. over SSH
+    #[arg(long, global = true)]
+    pub print: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum OpenCommand {
+    /// Open an issue of an environment's project
+    Issue {
+        /// Environment whose project the issue belongs to
+        env: String,
+        /// Issue number
+        number: u32,
+    },
+    /// Open a database
+    Db {
+        /// Database as "<env>/<database>", named as in the source environment
+        target: EnvDb,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct ReportArgs {
+    /// Database as "<env>/<database>"
+    pub target: EnvDb,
+
+    /// File to write the report to; prints it when omitted
+    #[arg(long, short)]
+    pub out: Option<PathBuf>,
+
+    /// Report format; defaults to HTML for a `--out` ending in .html, Markdown otherwise
+    #[arg(long, value_enum)]
+    pub format: Option<ReportFormat>,
+}
+
+#[derive(Parser, Debug)]
+pub struct StatsArgs {
+    /// Only include runs from the last this many days
+    #[arg(long, default_value_t = 30)]
+    pub days: u32,
+    /// Only include runs of this command, e.g. "migrate" or "status history"
+    #[arg(long)]
+    pub command: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct IssuesArgs {
+    #[command(subcommand)]
+    pub command: IssuesCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IssuesCommand {
+    /// Find issues whose title or description matches a query, e.g. to pick a `--to` issue
+    Search {
+        /// Environment whose project is searched
+        env: String,
+        /// Text to look for in issue titles and descriptions
+        query: String,
+        /// Only issues with one of these statuses, e.g. OPEN,DONE
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<String>,
+        /// Only issues created at or after this time (RFC 3339)
+        #[arg(long)]
+        since: Option<DateTime<Utc>>,
+        /// Only issues created before this time (RFC 3339)
+        #[arg(long)]
+        before: Option<DateTime<Utc>>,
+        /// Only issues carrying this label; repeat to require several
+        #[arg(long)]
+        label: Vec<String>,
+        /// Maximum number of issues to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -145,20 +645,73 @@ pub struct RevertArgs {
 #[derive(Parser, Debug)]
 pub struct CompletionArgs {
     /// The shell to generate completions for
-    #[clap(value_enum)]
-    pub shell: Shell,
+    #[clap(value_enum, required_unless_present = "complete_target")]
+    pub shell: Option<Shell>,
+    /// Print the environments or `<env>/<database>` values completing this word (used by the
+    /// generated scripts)
+    #[arg(long, hide = true, conflicts_with = "shell")]
+    pub complete_target: Option<String>,
 }
 
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct StatusArgs {
+    #[command(subcommand)]
+    pub command: Option<StatusCommand>,
     /// Optional filter for specific environment/database as "<env>/<database>" or just "<env>"
     pub filter: Option<String>,
+    /// Hide databases that are UP TO DATE and show only those needing attention
+    #[arg(long)]
+    pub only_outdated: bool,
+    /// Never write to ~/.shelltide: a refreshed token is kept in memory and no history is recorded
+    #[arg(long)]
+    pub read_only: bool,
+    /// Ask Bytebase to sync each database before reading its revisions, e.g. after a restore
+    #[arg(long)]
+    pub refresh_revisions: bool,
+    /// Print the status as JSON, including the reference environment and issue
+    #[arg(long)]
+    pub json: bool,
+    /// Show a matrix of databases (rows) by environments (columns) with the applied issue
+    #[arg(long, conflicts_with = "json")]
+    pub by_issue: bool,
+    /// Columns of the status table, comma-separated
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "schema,env,status",
+        conflicts_with_all = ["json", "by_issue"]
+    )]
+    pub columns: Vec<StatusColumn>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StatusCommand {
+    /// Show how far a database lagged behind the reference over past `status` runs
+    History {
+        /// Database as "<env>/<database>"
+        target: EnvDb,
+        /// Number of most recent snapshots to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
 }
 
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct DiffArgs {
+    #[command(subcommand)]
+    pub command: Option<DiffCommand>,
+
     /// Target database as "<env>/<database>"
-    pub target: EnvDb,
+    #[arg(required = true)]
+    pub target: Option<EnvDb>,
+
+    /// Database to compare against as "<env>/<database>": only the changelogs of the target
+    /// that it has not applied yet are output, i.e. what `migrate` would apply to it
+    #[arg(conflicts_with = "incremental")]
+    pub against: Option<EnvDb>,
 
     /// Starting issue number (inclusive)
     #[arg(long)]
@@ -168,21 +721,148 @@ pub struct DiffArgs {
     #[arg(long)]
     pub to: Option<u32>,
 
-    /// Exit with code 2 if no migration scripts are found
+    /// Only output changelogs newer than the last `--incremental` run for this database, and
+    /// remember the newest one output for the next run
+    #[arg(long, conflicts_with = "from")]
+    pub incremental: bool,
+
+    /// State file of `--incremental` (default `~/.shelltide/export-state.json`)
+    #[arg(long, requires = "incremental")]
+    pub state_file: Option<PathBuf>,
+
+    /// Write one SQL file per changelog and a manifest with their SHA-256 checksums into this
+    /// directory instead of printing a script
+    #[arg(long, conflicts_with = "incremental")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Exit with code 8 if no migration scripts are found
     #[arg(long)]
     pub fail_if_empty: bool,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum DiffCommand {
+    /// Check that the SQL files of a `diff --out-dir` or `migrate --offline` directory still
+    /// match its manifest, e.g. before applying them elsewhere
+    Verify {
+        /// Directory containing `manifest.json`
+        dir: PathBuf,
+    },
+}
+
+/// An issue number, or the latest one for "LATEST".
+#[derive(Debug, Clone, Copy)]
+pub struct IssueOrLatest(pub Option<u32>);
+
+impl std::str::FromStr for IssueOrLatest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("LATEST") {
+            return Ok(IssueOrLatest(None));
+        }
+        s.parse()
+            .map(|issue| IssueOrLatest(Some(issue)))
+            .map_err(|_| format!("Invalid value '{s}'. Use an issue number or 'LATEST'"))
+    }
+}
+
 #[derive(Parser, Debug)]
 pub struct DumpArgs {
     /// Target database as "<env>/<database>"
     pub target: EnvDb,
 
-    /// Issue number to dump schema at (uses latest migration <= this issue)
+    /// Issue number to dump schema at (uses latest migration <= this issue), or "LATEST"
     #[arg(long)]
-    pub at_issue: Option<u32>,
+    pub at_issue: Option<IssueOrLatest>,
+
+    /// Write the dump to this file instead of stdout
+    #[arg(long, short)]
+    pub out: Option<PathBuf>,
 
-    /// Exit with code 2 if no schema dump is available
+    /// Exit with code 8 if no schema dump is available
     #[arg(long)]
     pub fail_if_empty: bool,
 }
+
+#[derive(Parser, Debug)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BundleCommand {
+    /// Package source changelogs into a bundle archive
+    Create {
+        /// Source as "<env>/<database>"
+        source: EnvDb,
+
+        /// Issue number to start after (exclusive), defaults to the beginning of history
+        #[arg(long)]
+        from: Option<u32>,
+
+        /// The version to bundle up to, number or "LATEST"; "-" reads it from stdin
+        #[arg(long, short)]
+        to: String,
+
+        /// Path of the bundle archive to write (e.g. rel.tar.gz)
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Apply a bundle archive to a target database
+    Apply {
+        /// Path of the bundle archive, or "-" for stdin
+        archive: PathBuf,
+
+        /// Target as "<env>/<database>"
+        target: EnvDb,
+    },
+    /// Validate a bundle archive's checksums and show its metadata without applying it
+    Verify {
+        /// Path of the bundle archive, or "-" for stdin
+        archive: PathBuf,
+    },
+}
+
+/// Parses a duration such as `45`, `90s`, `30m` or `2h`. A bare number is taken as seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((value, ""), |i| value.split_at(i));
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{value}'"))?;
+    let unit_secs = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration unit in '{value}' (use s, m or h)"
+            ));
+        }
+    };
+    let secs = number
+        .checked_mul(unit_secs)
+        .ok_or_else(|| format!("invalid duration '{value}'"))?;
+    Ok(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * 60 * 60)));
+        assert!(parse_duration("18446744073709551615m").is_err());
+        assert!(parse_duration("5124095576030432h").is_err());
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+}
@@ -1,8 +1,24 @@
+pub mod bundle;
 pub mod completion;
 pub mod config;
 pub mod diff;
 pub mod dump;
 pub mod env;
+pub mod explain;
+pub mod history;
+pub mod init;
+pub mod issues;
 pub mod login;
 pub mod migrate;
+pub mod open;
+pub mod plan;
+pub mod promote;
+pub mod prompt;
+pub mod release;
+pub mod report;
+pub mod resume;
+pub mod review;
+pub mod revision;
+pub mod rollouts;
+pub mod stats;
 pub mod status;
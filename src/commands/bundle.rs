@@ -0,0 +1,279 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::StringStatement;
+use crate::bundle::{self, Manifest};
+use crate::cli::BundleCommand;
+use crate::commands::migrate::{
+    ApplyOptions, SourceChange, apply_changelog, get_latest_done_issue_no, parse_target_version,
+    record_revision, resolve_engine, select_changelogs,
+};
+use crate::config::{self, ConfigOperations, DisplayTimezone, ProductionConfig};
+use crate::error::AppError;
+use crate::input;
+use crate::issue_template::{self, IssueTemplate};
+use crate::stats;
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Handles the `bundle` command.
+pub async fn handle_bundle_command<T: BytebaseApi>(
+    command: BundleCommand,
+    client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_bundle_command_with_config(command, client, &config_ops).await
+}
+
+pub async fn handle_bundle_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    command: BundleCommand,
+    client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    match command {
+        BundleCommand::Create {
+            source,
+            from,
+            to,
+            out,
+        } => {
+            create_bundle(
+                client,
+                config_ops,
+                &source.env,
+                &source.db,
+                from.unwrap_or(0),
+                &to,
+                &out,
+            )
+            .await
+        }
+        BundleCommand::Apply { archive, target } => {
+            apply_bundle(client, config_ops, &archive, &target.env, &target.db).await
+        }
+        BundleCommand::Verify { archive } => verify_bundle(&archive).await,
+    }
+}
+
+/// Validates a bundle archive's checksums and prints its metadata without applying anything.
+pub async fn verify_bundle(archive: &Path) -> Result<()> {
+    let (manifest, files) = bundle::read_archive_files(archive).await?;
+    report_verification("Bundle", archive, &manifest, &files).await
+}
+
+/// Prints the metadata of a manifest and checks `files` against it; fails if anything does
+/// not match. `label` names what was read, e.g. `Bundle` for an archive.
+pub(crate) async fn report_verification(
+    label: &str,
+    path: &Path,
+    manifest: &Manifest,
+    files: &HashMap<String, String>,
+) -> Result<()> {
+    // Verification must work without a usable config, so fall back to UTC.
+    let timezone = config::load_config()
+        .await
+        .map_or(DisplayTimezone::Utc, |c| c.display.timezone());
+
+    println!("{:<14}{path:?}", format!("{label}:"));
+    println!(
+        "Source:       {}/{} (project '{}')",
+        manifest.source_env, manifest.source_database, manifest.source_project
+    );
+    if let (Some(env), Some(db)) = (&manifest.target_env, &manifest.target_database) {
+        println!("Target:       {env}/{db}");
+    }
+    println!(
+        "Issue range:  #{} (exclusive) to #{} ({} changelog(s))",
+        manifest.from_issue,
+        manifest.to_issue,
+        manifest.entries.len()
+    );
+    println!(
+        "Created:      {} by {} (shelltide {})",
+        timezone.format(manifest.created_at),
+        manifest.created_by.as_deref().unwrap_or("unknown"),
+        if manifest.shelltide_version.is_empty() {
+            "unknown"
+        } else {
+            &manifest.shelltide_version
+        }
+    );
+    println!();
+
+    let problems = bundle::verify_files(manifest, files);
+    if problems.is_empty() {
+        println!(
+            "✅ All {} file(s) match the manifest.",
+            manifest.entries.len()
+        );
+        return Ok(());
+    }
+
+    println!("❌ Verification failed:");
+    for problem in &problems {
+        println!("  {problem}");
+    }
+    Err(anyhow::anyhow!(
+        "{label} verification failed with {} problem(s)",
+        problems.len()
+    ))
+}
+
+async fn create_bundle<T: BytebaseApi, C: ConfigOperations>(
+    api_client: &T,
+    config_ops: &C,
+    source_env_name: &str,
+    source_database: &str,
+    from_issue: u32,
+    to: &str,
+    out: &Path,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let source_env = config
+        .environments
+        .get(source_env_name)
+        .ok_or_else(|| AppError::EnvNotFound(source_env_name.to_string()))?;
+
+    let latest = get_latest_done_issue_no(api_client, &source_env.project).await?;
+    let to_issue = parse_target_version(&input::resolve_arg(to)?, latest)?;
+    let changelogs = select_changelogs(
+        api_client,
+        source_env,
+        source_database,
+        from_issue,
+        to_issue,
+        None,
+        false,
+    )
+    .await?;
+
+    let manifest = Manifest {
+        source_env: source_env_name.to_string(),
+        source_project: source_env.project.clone(),
+        source_database: source_database.to_string(),
+        target_env: None,
+        target_database: None,
+        from_issue,
+        to_issue,
+        created_at: Utc::now(),
+        created_by: config
+            .active_credentials()
+            .map(|c| c.service_account.clone()),
+        shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+        entries: bundle::build_entries(&changelogs),
+    };
+    bundle::write_archive(out, &manifest, &changelogs).await?;
+
+    println!(
+        "Bundled {} changelog(s) from '{source_env_name}/{source_database}' (issues #{from_issue}..#{to_issue}] into {out:?}",
+        changelogs.len()
+    );
+    Ok(())
+}
+
+async fn apply_bundle<T: BytebaseApi, C: ConfigOperations>(
+    api_client: &T,
+    config_ops: &C,
+    archive: &Path,
+    target_env_name: &str,
+    target_database: &str,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let target_env = config
+        .environments
+        .get(target_env_name)
+        .ok_or_else(|| AppError::EnvNotFound(target_env_name.to_string()))?;
+
+    let bundle = bundle::read_archive(archive).await?;
+    let manifest = &bundle.manifest;
+    println!(
+        "Applying bundle from '{}/{}' (issues #{}..#{}] to '{target_env_name}/{target_database}'...",
+        manifest.source_env, manifest.source_database, manifest.from_issue, manifest.to_issue
+    );
+
+    let target_revision = api_client
+        .latest_revision(
+            &target_env.instance,
+            target_database,
+            config.revision.latest_by(),
+        )
+        .await?;
+    let current_version = target_revision.version.as_ref().map_or(0, |v| v.number);
+    if current_version >= manifest.to_issue {
+        println!("Target is already at issue #{current_version}. Nothing to apply.");
+        return Ok(());
+    }
+
+    println!("--- Applying Migrations ---");
+    let mut options = ApplyOptions::from_config(&config);
+    options.engine = resolve_engine(api_client, target_env).await?;
+    options.issue_template = IssueTemplate::from_config(&config)?;
+    if let Some(template) = &mut options.issue_template {
+        let issues: Vec<u32> = manifest
+            .entries
+            .iter()
+            .map(|entry| entry.issue)
+            .filter(|issue| *issue > current_version)
+            .collect();
+        template.set(
+            "source_env",
+            format!("{}/{}", manifest.source_env, manifest.source_database),
+        );
+        template.set("target", format!("{target_env_name}/{target_database}"));
+        template.set("issues", issue_template::issue_list(&issues));
+        template.set("operator", issue_template::operator(&config));
+    }
+    let mut last_applied = None;
+    let mut all_successful = true;
+    for (entry, statement) in manifest.entries.iter().zip(&bundle.statements) {
+        if entry.issue <= current_version {
+            continue;
+        }
+        let statement = StringStatement(statement.clone());
+        match apply_changelog(
+            api_client,
+            target_env,
+            target_database,
+            SourceChange::Issue(entry.issue),
+            &statement,
+            &options,
+        )
+        .await
+        {
+            Ok(sheet) => {
+                println!("Applied {} (issue #{})", entry.file, entry.issue);
+                stats::count_applied_changelog();
+                last_applied = Some((entry.issue, sheet.name));
+            }
+            Err(e) => {
+                eprintln!("Error applying {}: {e}", entry.file);
+                all_successful = false;
+                break;
+            }
+        }
+    }
+
+    let Some((last_issue, last_sheet)) = last_applied else {
+        println!("nothing to migrate");
+        return Ok(());
+    };
+    let revision_issue_number = if all_successful {
+        manifest.to_issue
+    } else {
+        last_issue
+    };
+
+    println!("Migrated to issue #{last_issue}. Creating revision...");
+    record_revision(
+        api_client,
+        target_env,
+        target_database,
+        &manifest.source_project,
+        revision_issue_number,
+        &last_sheet,
+    )
+    .await?;
+
+    println!("--- Migration Complete ---\n");
+    Ok(())
+}
@@ -1,12 +1,205 @@
+use crate::api::traits::BytebaseApi;
 use crate::cli::Cli;
+use crate::config::{ConfigOperations, ProductionConfig};
 use anyhow::Result;
 use clap::CommandFactory;
 use clap_complete::{Shell, generate};
-use std::io;
+use std::future::Future;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Subcommands whose positional arguments are `<env>` or `<env>/<database>`.
+const TARGET_COMMANDS: &str =
+    "status|migrate|diff|dump|bundle|revision|rollouts|history|explain|issues|report|open";
+
+/// How long a completion may wait on Bytebase for database names before giving up.
+const DATABASE_LOOKUP_TIMEOUT: Duration = Duration::from_secs(3);
 
 pub fn handle_completion_command(shell: Shell) -> Result<()> {
     let mut cmd = Cli::command();
     let cmd_name = cmd.get_name().to_string();
-    generate(shell, &mut cmd, cmd_name, &mut io::stdout());
+    let mut script = Vec::new();
+    generate(shell, &mut cmd, cmd_name, &mut script);
+    let script = String::from_utf8(script)?;
+    io::stdout().write_all(with_dynamic_targets(shell, script).as_bytes())?;
     Ok(())
 }
+
+/// Adds completion of `<env>` and `<env>/<database>` arguments to a generated script. The
+/// shell asks `shelltide completion --complete-target <word>` for the candidates.
+fn with_dynamic_targets(shell: Shell, script: String) -> String {
+    match shell {
+        Shell::Bash => {
+            let script = script.replace("complete -F _shelltide ", "complete -F _shelltide_dynamic ");
+            format!(
+                r#"{script}
+_shelltide_dynamic() {{
+    _shelltide "$@"
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    if [[ ${{COMP_CWORD}} -gt 1 && "${{cur}}" != -* && "${{COMP_WORDS[COMP_CWORD-1]}}" != -* ]]; then
+        case "${{COMP_WORDS[1]}}" in
+            {TARGET_COMMANDS})
+                COMPREPLY+=($(shelltide completion --complete-target "${{cur}}" 2>/dev/null))
+                ;;
+        esac
+    fi
+}}
+"#
+            )
+        }
+        Shell::Zsh => {
+            let mut out = String::new();
+            for line in script.lines() {
+                let is_target = [":target -- ", ":source -- ", "::filter -- ", ":env -- "]
+                    .iter()
+                    .any(|spec| line.starts_with(&format!("'{spec}")));
+                match line.strip_suffix(":_default' \\") {
+                    Some(spec) if is_target => {
+                        out.push_str(&format!("{spec}:_shelltide_targets' \\\n"))
+                    }
+                    _ => out.push_str(&format!("{line}\n")),
+                }
+            }
+            format!(
+                r#"(( $+functions[_shelltide_targets] )) ||
+_shelltide_targets() {{
+    local -a targets
+    targets=(${{(f)"$(shelltide completion --complete-target "$PREFIX" 2>/dev/null)"}})
+    compadd -a targets
+}}
+
+{out}"#
+            )
+        }
+        Shell::PowerShell => script.replacen(
+            "    $completions.Where{",
+            &format!(
+                r#"    $previous = if ($wordToComplete) {{ $commandElements[-2] }} else {{ $commandElements[-1] }}
+    if ($command -match '^shelltide;({TARGET_COMMANDS})(;|$)' -and
+        -not $wordToComplete.StartsWith('-') -and
+        -not "$previous".StartsWith('-')) {{
+        $completions += @(shelltide completion --complete-target "$wordToComplete" 2>$null) |
+            Where-Object {{ $_ }} |
+            ForEach-Object {{ [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }}
+    }}
+
+    $completions.Where{{"#
+            ),
+            1,
+        ),
+        _ => script,
+    }
+}
+
+/// `completion --complete-target <word>`: prints the configured environments starting with
+/// `word`, or, once it contains `/`, the databases of that environment's instance. Nothing is
+/// printed on errors, since the output goes straight into the shell's completions.
+pub async fn handle_complete_target<T, F>(word: &str, connect: F) -> Result<()>
+where
+    T: BytebaseApi,
+    F: Future<Output = Result<T>>,
+{
+    complete_target_with_config(word, connect, &ProductionConfig).await
+}
+
+pub async fn complete_target_with_config<T, F, C>(
+    word: &str,
+    connect: F,
+    config_ops: &C,
+) -> Result<()>
+where
+    T: BytebaseApi,
+    F: Future<Output = Result<T>>,
+    C: ConfigOperations,
+{
+    let Ok(config) = config_ops.load_config().await else {
+        return Ok(());
+    };
+    let mut envs: Vec<&str> = config.environments.keys().map(String::as_str).collect();
+    envs.sort_unstable();
+
+    let databases = match word.split_once('/') {
+        Some((env, _)) => {
+            let Some(env) = config.environments.get(env) else {
+                return Ok(());
+            };
+            let lookup = async {
+                let client = connect.await?;
+                anyhow::Ok(client.get_databases(&env.instance).await?)
+            };
+            match tokio::time::timeout(DATABASE_LOOKUP_TIMEOUT, lookup).await {
+                Ok(Ok(databases)) => databases,
+                _ => return Ok(()),
+            }
+        }
+        None => Vec::new(),
+    };
+    for candidate in target_candidates(word, &envs, &databases) {
+        println!("{candidate}");
+    }
+    Ok(())
+}
+
+/// Completions of `word` from the environment names, or from the `databases` of the
+/// environment before the `/`.
+fn target_candidates(word: &str, envs: &[&str], databases: &[String]) -> Vec<String> {
+    match word.split_once('/') {
+        Some((env, db)) => {
+            let mut databases: Vec<&String> = databases
+                .iter()
+                .filter(|name| name.starts_with(db))
+                .collect();
+            databases.sort();
+            databases
+                .into_iter()
+                .map(|name| format!("{env}/{name}"))
+                .collect()
+        }
+        None => envs
+            .iter()
+            .filter(|env| env.starts_with(word))
+            .map(|env| env.to_string())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_candidates() {
+        let envs = ["dev", "prod", "prod-jp"];
+        let databases = ["bridge".to_string(), "admin".to_string()];
+        assert_eq!(
+            target_candidates("pr", &envs, &databases),
+            ["prod", "prod-jp"]
+        );
+        assert_eq!(
+            target_candidates("prod/", &envs, &databases),
+            ["prod/admin", "prod/bridge"]
+        );
+        assert_eq!(
+            target_candidates("prod/b", &envs, &databases),
+            ["prod/bridge"]
+        );
+    }
+
+    #[test]
+    fn test_scripts_complete_targets() {
+        let generate_for = |shell| {
+            let mut script = Vec::new();
+            generate(shell, &mut Cli::command(), "shelltide", &mut script);
+            with_dynamic_targets(shell, String::from_utf8(script).unwrap())
+        };
+
+        let powershell = generate_for(Shell::PowerShell);
+        assert!(powershell.contains("shelltide completion --complete-target \"$wordToComplete\""));
+        assert!(powershell.contains("$completions.Where{"));
+        let bash = generate_for(Shell::Bash);
+        assert!(bash.contains("complete -F _shelltide_dynamic "));
+        assert!(!bash.contains("complete -F _shelltide -"));
+        let zsh = generate_for(Shell::Zsh);
+        assert!(zsh.contains(":target -- Target as \"<env>/<database>\":_shelltide_targets' \\"));
+    }
+}
@@ -1,8 +1,16 @@
 use anyhow::Result;
 
+use std::path::{Path, PathBuf};
+
 use crate::{
-    cli::ConfigCommand,
-    config::{ConfigOperations, ProductionConfig},
+    api::types::{LatestBy, SQLDialect},
+    cli::{ConfigCommand, parse_duration},
+    commands::migrate::parse_tracking_issue,
+    config::{
+        ConfigOperations, CredentialsBackend, DisplayTimezone, ProductionConfig,
+        list_config_backups, restore_config_backup,
+    },
+    issue_template::IssueTemplate,
 };
 
 /// Handles the `config` command.
@@ -18,7 +26,49 @@ pub async fn config_with_ops<C: ConfigOperations>(
     match command {
         ConfigCommand::Set { key, value } => set_config_with_ops(config_ops, &key, value).await,
         ConfigCommand::Get { key } => get_config_with_ops(config_ops, &key).await,
+        ConfigCommand::Restore { backup, list } => {
+            restore_config_with_ops(config_ops, backup.as_deref(), list).await
+        }
+    }
+}
+
+async fn restore_config_with_ops<C: ConfigOperations>(
+    config_ops: &C,
+    backup: Option<&str>,
+    list: bool,
+) -> Result<()> {
+    let config_path = config_ops.config_path()?;
+    let backups = list_config_backups(&config_path).await?;
+
+    if list {
+        if backups.is_empty() {
+            println!("No config backups found.");
+        }
+        for path in &backups {
+            if let Some(name) = path.file_name() {
+                println!("{}", name.to_string_lossy());
+            }
+        }
+        return Ok(());
     }
+
+    let backup_path = match backup {
+        Some(name) if Path::new(name).exists() => PathBuf::from(name),
+        Some(name) => backups
+            .iter()
+            .find(|path| path.file_name().is_some_and(|n| n == name))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Config backup '{}' not found.", name))?,
+        None => backups
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No config backups found."))?,
+    };
+
+    let _lock = config_ops.lock_config().await?;
+    restore_config_backup(&config_path, &backup_path).await?;
+    println!("Restored config from {backup_path:?}");
+    Ok(())
 }
 
 async fn set_config_with_ops<C: ConfigOperations>(
@@ -26,6 +76,7 @@ async fn set_config_with_ops<C: ConfigOperations>(
     key: &str,
     value: String,
 ) -> Result<()> {
+    let _lock = config_ops.lock_config().await?;
     let mut config = config_ops.load_config().await?;
 
     match key {
@@ -39,9 +90,236 @@ async fn set_config_with_ops<C: ConfigOperations>(
                 config.default_source_env.as_ref().unwrap()
             );
         }
+        "sheet.max_size" => {
+            let max_size = value
+                .parse::<usize>()
+                .ok()
+                .filter(|size| *size > 0)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Invalid value '{}'. Must be a positive number of bytes.",
+                        value
+                    )
+                })?;
+            config.sheet.max_size = Some(max_size);
+            println!("Set `sheet.max_size` to {max_size} bytes");
+        }
+        "api.retry_budget" => {
+            let budget = value.parse::<u64>().map_err(|_| {
+                anyhow::anyhow!("Invalid value '{}'. Must be a number of seconds.", value)
+            })?;
+            config.api.retry_budget = Some(budget);
+            println!("Set `api.retry_budget` to {budget} seconds");
+        }
+        "rollout.poll_interval" | "rollout.not_started_timeout" | "rollout.slow_threshold" => {
+            let duration = parse_duration(&value)
+                .ok()
+                .filter(|d| !d.is_zero())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Invalid value '{}'. Must be a duration such as 5s, 2m or 1h.",
+                        value
+                    )
+                })?;
+            let secs = Some(duration.as_secs());
+            match key {
+                "rollout.poll_interval" => config.rollout.poll_interval = secs,
+                "rollout.not_started_timeout" => config.rollout.not_started_timeout = secs,
+                _ => config.rollout.slow_threshold = secs,
+            }
+            println!("Set `{key}` to {} seconds", duration.as_secs());
+        }
+        "team.config_url" => {
+            // The file decides which project and instance each environment points to, so it
+            // must not be open to tampering on the way.
+            if !value.starts_with("https://") {
+                return Err(anyhow::anyhow!(
+                    "Invalid value '{}'. Must be an https URL.",
+                    value
+                ));
+            }
+            config.team.config_url = Some(value);
+            println!(
+                "Set `team.config_url` to '{}'",
+                config.team.config_url.as_ref().unwrap()
+            );
+        }
+        "migrate.max_changes" => {
+            if value.is_empty() {
+                config.migrate.max_changes = None;
+                println!("Removed `migrate.max_changes`");
+            } else {
+                let max_changes = value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|max| *max > 0)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Invalid value '{}'. Must be a positive number of changelogs.",
+                            value
+                        )
+                    })?;
+                config.migrate.max_changes = Some(max_changes);
+                println!("Set `migrate.max_changes` to {max_changes} changelogs");
+            }
+        }
+        "credentials.backend" => {
+            let backend = value
+                .parse::<CredentialsBackend>()
+                .map_err(|e| anyhow::anyhow!(e))?;
+            println!("Set `credentials.backend` to '{backend}'");
+            config.credential_store.backend = Some(backend.to_string());
+            match backend {
+                CredentialsBackend::Keyring => {
+                    println!("Saved service keys and access tokens are moved to the OS keychain.")
+                }
+                CredentialsBackend::Plaintext => {
+                    println!("Saved service keys and access tokens are moved to config.json.")
+                }
+            }
+        }
+        "revision.latest_by" => {
+            let latest_by = value.parse::<LatestBy>().map_err(|e| anyhow::anyhow!(e))?;
+            println!("Set `revision.latest_by` to '{latest_by}'");
+            config.revision.latest_by = Some(latest_by.to_string());
+        }
+        "issue.description_template" => {
+            if value.is_empty() {
+                config.issue.description_template = None;
+                println!("Removed `issue.description_template`");
+            } else {
+                IssueTemplate::load(Path::new(&value))?;
+                println!("Set `issue.description_template` to '{value}'");
+                config.issue.description_template = Some(value);
+            }
+        }
+        "notify.issue" => {
+            if value.is_empty() {
+                config.notify.issue = None;
+                println!("Removed `notify.issue`");
+            } else {
+                parse_tracking_issue(&value, "")?;
+                println!("Set `notify.issue` to '{value}'");
+                config.notify.issue = Some(value);
+            }
+        }
+        "notify.jira.url"
+        | "notify.jira.token"
+        | "notify.jira.on_success"
+        | "notify.jira.on_failure" => {
+            if key == "notify.jira.url"
+                && !value.is_empty()
+                && !value.starts_with("http://")
+                && !value.starts_with("https://")
+            {
+                return Err(anyhow::anyhow!(
+                    "Invalid value '{}'. Must be an http(s) URL.",
+                    value
+                ));
+            }
+            let jira = &mut config.notify.jira;
+            let field = match key {
+                "notify.jira.url" => &mut jira.url,
+                "notify.jira.token" => &mut jira.token,
+                "notify.jira.on_success" => &mut jira.on_success,
+                _ => &mut jira.on_failure,
+            };
+            if value.is_empty() {
+                *field = None;
+                println!("Removed `{key}`");
+            } else {
+                // The token is a secret; keep it out of terminal scrollback.
+                if key == "notify.jira.token" {
+                    println!("Set `{key}`");
+                } else {
+                    println!("Set `{key}` to '{value}'");
+                }
+                *field = Some(value);
+            }
+        }
+        "display.timezone" => {
+            value
+                .parse::<DisplayTimezone>()
+                .map_err(|e| anyhow::anyhow!(e))?;
+            println!("Set `display.timezone` to '{value}'");
+            config.display.timezone = Some(value);
+        }
+        _ if key.starts_with("db_map.") => {
+            let (env, source_db) = parse_db_map_key(key)?;
+            if !config.environments.contains_key(env) {
+                return Err(anyhow::anyhow!("Environment '{}' not found.", env));
+            }
+            let map = config.db_map.entry(env.to_string()).or_default();
+            if value.is_empty() {
+                map.remove(source_db);
+                println!("Removed `{key}`");
+            } else {
+                println!("Set `{key}` to '{value}'");
+                map.insert(source_db.to_string(), value);
+            }
+            config.db_map.retain(|_, map| !map.is_empty());
+        }
+        _ if key.starts_with("source_env.") => {
+            let env = parse_source_env_key(key)?;
+            if !config.environments.contains_key(env) {
+                return Err(anyhow::anyhow!("Environment '{}' not found.", env));
+            }
+            if value.is_empty() {
+                config.source_envs.remove(env);
+                println!("Removed `{key}`");
+            } else {
+                if !config.environments.contains_key(&value) {
+                    return Err(anyhow::anyhow!("Environment '{}' not found.", value));
+                }
+                println!("Set `{key}` to '{value}'");
+                config.source_envs.insert(env.to_string(), value);
+            }
+        }
+        _ if key.starts_with("engine.") => {
+            let env = parse_engine_key(key)?;
+            let environment = config
+                .environments
+                .get_mut(env)
+                .ok_or_else(|| anyhow::anyhow!("Environment '{}' not found.", env))?;
+            if value.is_empty() {
+                environment.engine = None;
+                println!("Removed `{key}`; the engine is detected from the instance");
+            } else {
+                let engine = value
+                    .parse::<SQLDialect>()
+                    .map_err(|e| anyhow::anyhow!("Invalid engine for `{key}`: {e}"))?;
+                println!("Set `{key}` to '{engine}'");
+                environment.engine = Some(engine);
+            }
+        }
+        _ if key.starts_with("env_template.") => {
+            let (name, field) = parse_env_template_key(key)?;
+            let template = config.env_templates.entry(name.to_string()).or_default();
+            let value = (!value.is_empty()).then_some(value);
+            match field {
+                "project" => template.project = value.clone(),
+                "instance" => template.instance = value.clone(),
+                _ => {
+                    template.engine = value
+                        .as_deref()
+                        .map(str::parse::<SQLDialect>)
+                        .transpose()
+                        .map_err(|e| anyhow::anyhow!("Invalid engine for `{key}`: {e}"))?
+                }
+            }
+            match value {
+                Some(value) => println!("Set `{key}` to '{value}'"),
+                None => println!("Removed `{key}`"),
+            }
+            config
+                .env_templates
+                .retain(|_, template| !template.is_empty());
+        }
         _ => {
             println!("Error: Unknown configuration key '{key}'");
-            println!("Available keys: default.source_env");
+            println!(
+                "Available keys: default.source_env, sheet.max_size, api.retry_budget, rollout.poll_interval, rollout.not_started_timeout, rollout.slow_threshold, team.config_url, migrate.max_changes, revision.latest_by, credentials.backend, issue.description_template, notify.issue, notify.jira.<url|token|on_success|on_failure>, display.timezone, db_map.<env>.<source_db>, source_env.<env>, engine.<env>, env_template.<template>.<project|instance|engine>"
+            );
             // In a real app, you might return an error here.
             // For now, we just print a message.
             return Ok(());
@@ -52,6 +330,42 @@ async fn set_config_with_ops<C: ConfigOperations>(
     Ok(())
 }
 
+/// Splits a `db_map.<env>.<source_db>` key into the environment and source database names.
+fn parse_db_map_key(key: &str) -> Result<(&str, &str)> {
+    key.strip_prefix("db_map.")
+        .and_then(|rest| rest.split_once('.'))
+        .filter(|(env, db)| !env.is_empty() && !db.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Invalid key '{key}'. Use 'db_map.<env>.<source_db>'."))
+}
+
+/// The environment name of a `source_env.<env>` key.
+fn parse_source_env_key(key: &str) -> Result<&str> {
+    key.strip_prefix("source_env.")
+        .filter(|env| !env.is_empty() && !env.contains('.'))
+        .ok_or_else(|| anyhow::anyhow!("Invalid key '{key}'. Use 'source_env.<env>'."))
+}
+
+/// The environment name of an `engine.<env>` key.
+fn parse_engine_key(key: &str) -> Result<&str> {
+    key.strip_prefix("engine.")
+        .filter(|env| !env.is_empty() && !env.contains('.'))
+        .ok_or_else(|| anyhow::anyhow!("Invalid key '{key}'. Use 'engine.<env>'."))
+}
+
+/// Splits an `env_template.<template>.<field>` key into the template name and field.
+fn parse_env_template_key(key: &str) -> Result<(&str, &str)> {
+    key.strip_prefix("env_template.")
+        .and_then(|rest| rest.rsplit_once('.'))
+        .filter(|(name, field)| {
+            !name.is_empty() && matches!(*field, "project" | "instance" | "engine")
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid key '{key}'. Use 'env_template.<template>.project', '.instance' or '.engine'."
+            )
+        })
+}
+
 async fn get_config_with_ops<C: ConfigOperations>(config_ops: &C, key: &str) -> Result<()> {
     let config = config_ops.load_config().await?;
 
@@ -63,6 +377,108 @@ async fn get_config_with_ops<C: ConfigOperations>(config_ops: &C, key: &str) ->
                 println!("'default.source_env' is not set.");
             }
         }
+        "sheet.max_size" => {
+            println!("{}", config.sheet.max_size());
+        }
+        "api.retry_budget" => {
+            println!("{}", config.api.retry_budget());
+        }
+        "rollout.poll_interval" => {
+            println!("{}", config.rollout.poll_interval().as_secs());
+        }
+        "rollout.not_started_timeout" => {
+            println!("{}", config.rollout.not_started_timeout().as_secs());
+        }
+        "rollout.slow_threshold" => {
+            println!("{}", config.rollout.slow_threshold().as_secs());
+        }
+        "team.config_url" => {
+            if let Some(value) = config.team.config_url {
+                println!("{value}");
+            } else {
+                println!("'team.config_url' is not set.");
+            }
+        }
+        "migrate.max_changes" => {
+            if let Some(value) = config.migrate.max_changes {
+                println!("{value}");
+            } else {
+                println!("'migrate.max_changes' is not set.");
+            }
+        }
+        "revision.latest_by" => {
+            println!("{}", config.revision.latest_by());
+        }
+        "credentials.backend" => {
+            println!("{}", config.credential_store.backend());
+        }
+        "issue.description_template" => {
+            if let Some(value) = config.issue.description_template {
+                println!("{value}");
+            } else {
+                println!("'issue.description_template' is not set.");
+            }
+        }
+        "notify.issue" => {
+            if let Some(value) = config.notify.issue {
+                println!("{value}");
+            } else {
+                println!("'notify.issue' is not set.");
+            }
+        }
+        "notify.jira.url" | "notify.jira.on_success" | "notify.jira.on_failure" => {
+            let jira = config.notify.jira;
+            let value = match key {
+                "notify.jira.url" => jira.url,
+                "notify.jira.on_success" => jira.on_success,
+                _ => jira.on_failure,
+            };
+            match value {
+                Some(value) => println!("{value}"),
+                None => println!("'{key}' is not set."),
+            }
+        }
+        "notify.jira.token" => {
+            if config.notify.jira.token.is_some() {
+                println!("(set)");
+            } else {
+                println!("'notify.jira.token' is not set.");
+            }
+        }
+        "display.timezone" => {
+            println!("{}", config.display.timezone.as_deref().unwrap_or("UTC"));
+        }
+        _ if key.starts_with("db_map.") => {
+            let (env, source_db) = parse_db_map_key(key)?;
+            println!("{}", config.mapped_database(env, source_db));
+        }
+        _ if key.starts_with("source_env.") => {
+            let env = parse_source_env_key(key)?;
+            match config.source_env_for(env) {
+                Some(value) => println!("{value}"),
+                None => println!("'{key}' is not set."),
+            }
+        }
+        _ if key.starts_with("engine.") => {
+            let env = parse_engine_key(key)?;
+            match config.environments.get(env).and_then(|e| e.engine.as_ref()) {
+                Some(engine) => println!("{engine}"),
+                None => println!("'{key}' is not set; the engine is detected from the instance."),
+            }
+        }
+        _ if key.starts_with("env_template.") => {
+            let (name, field) = parse_env_template_key(key)?;
+            let template = config.env_templates.get(name);
+            let value = match field {
+                "project" => template.and_then(|t| t.project.clone()),
+                "instance" => template.and_then(|t| t.instance.clone()),
+                _ => template.and_then(|t| t.engine.as_ref().map(|e| e.to_string())),
+            };
+            match value {
+                Some(value) => println!("{value}"),
+                None => println!("'{key}' is not set."),
+            }
+        }
         _ => {
             println!("Error: Unknown configuration key '{key}'");
         }
@@ -79,6 +495,7 @@ mod tests {
     use crate::api::clients::tests::FakeApiClient;
     use crate::cli::{ConfigCommand, EnvCommand};
     use crate::commands;
+    use crate::output::OutputFormat;
     use tempfile::tempdir;
 
     // Helper function to create a temporary home directory for testing.
@@ -91,26 +508,24 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let home_path = temp_dir.path().to_path_buf();
 
-        // Override the home directory for the `dirs` crate.
-        // This is a bit of a hack, but necessary for this kind of test.
-        // In a larger application, you might abstract config loading further.
-        let original_home = std::env::var("HOME");
+        // Point the production config directory into the temp dir. `SHELLTIDE_HOME` rather
+        // than `HOME`, which the `dirs` crate ignores on Windows.
+        let original_dir = std::env::var_os(crate::config::CONFIG_DIR_ENV);
         // SAFETY: We are running tests in a single-threaded context for this part,
         // or each test runs in a separate process space, making this safe.
         // We are also restoring the environment variable after the test.
         unsafe {
-            std::env::set_var("HOME", &home_path);
+            std::env::set_var(crate::config::CONFIG_DIR_ENV, home_path.join(".shelltide"));
         }
 
         test_body(home_path).await;
 
-        // Restore original HOME variable to not affect other tests.
+        // Restore the original variable to not affect other tests.
         // SAFETY: See above.
         unsafe {
-            if let Ok(val) = original_home {
-                std::env::set_var("HOME", val);
-            } else {
-                std::env::remove_var("HOME");
+            match original_dir {
+                Some(val) => std::env::set_var(crate::config::CONFIG_DIR_ENV, val),
+                None => std::env::remove_var(crate::config::CONFIG_DIR_ENV),
             }
         }
     }
@@ -124,9 +539,12 @@ mod tests {
                 projects: HashMap::new(),
             };
             let env_command = EnvCommand::Add {
-                name: "test-dev".to_string(),
-                project: "existing-project".to_string(),
-                instance: "test-instance".to_string(),
+                name: Some("test-dev".to_string()),
+                project: Some("existing-project".to_string()),
+                instance: Some("test-instance".to_string()),
+                engine: None,
+                from_template: None,
+                allow_duplicate: false,
             };
             // Create test config for isolated testing
             let test_config = crate::config::TestConfig {
@@ -135,6 +553,7 @@ mod tests {
             let result = commands::env::handle_env_command_with_config(
                 env_command,
                 &fake_client,
+                OutputFormat::Table,
                 &test_config,
             )
             .await;
@@ -189,4 +608,132 @@ mod tests {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn test_db_map_set_and_remove() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = crate::config::TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let mut config = crate::config::AppConfig::default();
+        config.environments.insert(
+            "prod".to_string(),
+            crate::config::Environment {
+                project: "prod-project".to_string(),
+                instance: "prod-instance".to_string(),
+                engine: None,
+                pin_to: None,
+            },
+        );
+        test_config.save_config(&config).await.unwrap();
+        let set = |key: &str, value: &str| ConfigCommand::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+
+        config_with_ops(set("db_map.prod.bridge_dev", "bridge"), &test_config)
+            .await
+            .unwrap();
+        let config = test_config.load_config().await.unwrap();
+        assert_eq!(config.mapped_database("prod", "bridge_dev"), "bridge");
+        assert_eq!(config.mapped_database("prod", "chat"), "chat");
+        assert_eq!(config.mapped_database("dev", "bridge_dev"), "bridge_dev");
+
+        assert!(
+            config_with_ops(set("db_map.staging.bridge_dev", "bridge"), &test_config)
+                .await
+                .is_err()
+        );
+        assert!(
+            config_with_ops(set("db_map.prod", "bridge"), &test_config)
+                .await
+                .is_err()
+        );
+
+        config_with_ops(set("db_map.prod.bridge_dev", ""), &test_config)
+            .await
+            .unwrap();
+        assert!(test_config.load_config().await.unwrap().db_map.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_team_config_url_requires_https() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = crate::config::TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let set = |value: &str| ConfigCommand::Set {
+            key: "team.config_url".to_string(),
+            value: value.to_string(),
+        };
+
+        assert!(
+            config_with_ops(set("http://git.internal/envs.yaml"), &test_config)
+                .await
+                .is_err()
+        );
+        config_with_ops(set("https://git.internal/envs.yaml"), &test_config)
+            .await
+            .unwrap();
+        assert_eq!(
+            test_config
+                .load_config()
+                .await
+                .unwrap()
+                .team
+                .config_url
+                .as_deref(),
+            Some("https://git.internal/envs.yaml")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_engine_set_and_remove() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = crate::config::TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let mut config = crate::config::AppConfig::default();
+        config.environments.insert(
+            "prod".to_string(),
+            crate::config::Environment {
+                project: "prod-project".to_string(),
+                instance: "prod-instance".to_string(),
+                engine: None,
+                pin_to: None,
+            },
+        );
+        test_config.save_config(&config).await.unwrap();
+        let set = |key: &str, value: &str| ConfigCommand::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let engine = || async {
+            test_config.load_config().await.unwrap().environments["prod"]
+                .engine
+                .clone()
+        };
+
+        config_with_ops(set("engine.prod", "postgres"), &test_config)
+            .await
+            .unwrap();
+        assert_eq!(engine().await, Some(SQLDialect::Postgres));
+
+        assert!(
+            config_with_ops(set("engine.prod", "cobol"), &test_config)
+                .await
+                .is_err()
+        );
+        assert!(
+            config_with_ops(set("engine.staging", "mysql"), &test_config)
+                .await
+                .is_err()
+        );
+        assert_eq!(engine().await, Some(SQLDialect::Postgres));
+
+        config_with_ops(set("engine.prod", ""), &test_config)
+            .await
+            .unwrap();
+        assert_eq!(engine().await, None);
+    }
 }
@@ -1,10 +1,16 @@
 use crate::api::clients::LiveApiClient;
 use crate::api::traits::BytebaseApi;
-use crate::api::types::{Changelog, ChangelogType};
+use crate::api::types::{Changelog, ChangelogType, ListOptions};
+use crate::bundle::{self, Manifest};
 use crate::cli::DiffArgs;
-use crate::config::{ConfigOperations, ProductionConfig};
-use crate::error::AppError;
+use crate::commands::bundle::report_verification;
+use crate::commands::prompt::print_notice;
+use crate::config::{ConfigOperations, DisplayTimezone, ProductionConfig};
+use crate::error::{AppError, NOTHING_TO_OUTPUT_EXIT_CODE};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
 
 pub async fn handle_diff(args: DiffArgs) -> Result<(), AppError> {
     let config_ops = ProductionConfig;
@@ -15,33 +21,159 @@ pub async fn handle_diff_with_config<C: ConfigOperations>(
     args: DiffArgs,
     config_ops: &C,
 ) -> Result<(), AppError> {
+    let target = args
+        .target
+        .as_ref()
+        .ok_or_else(|| AppError::InvalidArgs("diff requires a target '<env>/<database>'".into()))?;
     let config = config_ops.load_config().await?;
-    let credentials = config.get_credentials()?;
-    let mut client = LiveApiClient::new(credentials)?;
-
-    client.ensure_authenticated_with_config(config_ops).await?;
+    let client = LiveApiClient::connect(&config, config_ops, print_notice).await?;
 
     let env_config = config
         .environments
-        .get(&args.target.env)
-        .ok_or_else(|| AppError::Config(format!("Environment '{}' not found", args.target.env)))?;
+        .get(&target.env)
+        .ok_or_else(|| AppError::Config(format!("Environment '{}' not found", target.env)))?;
 
     let changelogs = client
-        .get_changelogs(&env_config.instance, &args.target.db)
+        .get_changelogs(&env_config.instance, &target.db, &ListOptions::full())
         .await?;
 
-    let filtered_changelogs = filter_changelogs(changelogs, args.from, args.to)?;
+    let mut filtered_changelogs = filter_changelogs(changelogs, args.from, args.to)?;
+    let mut range_description = range_description(args.from, args.to);
+
+    if let Some(against) = &args.against {
+        let against_config = config
+            .environments
+            .get(&against.env)
+            .ok_or_else(|| AppError::Config(format!("Environment '{}' not found", against.env)))?;
+        let applied = client
+            .get_changelogs(&against_config.instance, &against.db, &ListOptions::full())
+            .await?;
+        filtered_changelogs = missing_from(filtered_changelogs, &applied);
+        range_description = format!("{range_description} not yet applied to {against}");
+    }
+
+    let state_path = match &args.state_file {
+        Some(path) => path.clone(),
+        None => config_ops
+            .config_path()?
+            .with_file_name("export-state.json"),
+    };
+    let key = format!("{}/{}", target.env, target.db);
+    let mut state = if args.incremental {
+        load_export_state(&state_path)?
+    } else {
+        ExportState::default()
+    };
+    if let Some(mark) = state.databases.get(&key).filter(|_| args.incremental) {
+        filtered_changelogs.retain(|changelog| mark.is_before(changelog));
+        range_description = format!(
+            "{range_description} after changelog {} (exported {})",
+            mark.changelog,
+            mark.create_time.to_rfc3339()
+        );
+    }
 
     if filtered_changelogs.is_empty() && args.fail_if_empty {
         eprintln!("No migration scripts found in the specified range");
-        std::process::exit(2);
+        std::process::exit(NOTHING_TO_OUTPUT_EXIT_CODE);
     }
 
-    output_sql_script(&filtered_changelogs, args.from, args.to)?;
+    if let Some(out_dir) = &args.out_dir {
+        let manifest = Manifest {
+            source_env: target.env.clone(),
+            source_project: env_config.project.clone(),
+            source_database: target.db.clone(),
+            target_env: args.against.as_ref().map(|against| against.env.clone()),
+            target_database: args.against.as_ref().map(|against| against.db.clone()),
+            // `--from` is inclusive, the manifest's start is not.
+            from_issue: args.from.map_or(0, |from| from.saturating_sub(1)),
+            to_issue: filtered_changelogs
+                .iter()
+                .map(Changelog::issue_number)
+                .max()
+                .unwrap_or(0),
+            created_at: Utc::now(),
+            created_by: config
+                .active_credentials()
+                .map(|c| c.service_account.clone()),
+            shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: bundle::build_entries(&filtered_changelogs),
+        };
+        bundle::write_dir(out_dir, &manifest, &filtered_changelogs).await?;
+        println!(
+            "Wrote {} SQL file(s) and {} to {out_dir:?}",
+            manifest.entries.len(),
+            bundle::MANIFEST_FILE
+        );
+    } else {
+        output_sql_script(
+            &filtered_changelogs,
+            &range_description,
+            config.display.timezone(),
+        )?;
+    }
+
+    if args.incremental
+        && let Some(last) = filtered_changelogs.last()
+    {
+        state.databases.insert(
+            key,
+            ExportMark {
+                changelog: last.name.number,
+                create_time: last.create_time,
+            },
+        );
+        save_export_state(&state_path, &state)?;
+    }
 
     Ok(())
 }
 
+/// Handles `diff verify`: checks the SQL files of a directory against its manifest.
+pub async fn verify_dir(dir: &Path) -> anyhow::Result<()> {
+    let (manifest, files) = bundle::read_dir_files(dir).await?;
+    report_verification("Directory", dir, &manifest, &files).await
+}
+
+/// Where `diff --incremental` left off, by `<env>/<database>`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct ExportState {
+    #[serde(default)]
+    databases: BTreeMap<String, ExportMark>,
+}
+
+/// The newest changelog output by the last incremental run of a database.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ExportMark {
+    changelog: u32,
+    create_time: DateTime<Utc>,
+}
+
+impl ExportMark {
+    /// Whether `changelog` is newer than this mark and so not exported yet.
+    fn is_before(&self, changelog: &Changelog) -> bool {
+        (changelog.create_time, changelog.name.number) > (self.create_time, self.changelog)
+    }
+}
+
+fn load_export_state(path: &Path) -> Result<ExportState, AppError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| {
+            AppError::Config(format!("Invalid export state file {}: {e}", path.display()))
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ExportState::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save_export_state(path: &Path, state: &ExportState) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(state)? + "\n")?;
+    Ok(())
+}
+
 fn filter_changelogs(
     changelogs: Vec<Changelog>,
     from_issue: Option<u32>,
@@ -56,10 +188,10 @@ fn filter_changelogs(
         })
         .filter(|changelog| match (from_issue, to_issue) {
             (Some(from), Some(to)) => {
-                changelog.issue.number >= from && changelog.issue.number <= to
+                changelog.issue_number() >= from && changelog.issue_number() <= to
             }
-            (Some(from), None) => changelog.issue.number >= from,
-            (None, Some(to)) => changelog.issue.number <= to,
+            (Some(from), None) => changelog.issue_number() >= from,
+            (None, Some(to)) => changelog.issue_number() <= to,
             (None, None) => true,
         })
         .collect();
@@ -68,18 +200,44 @@ fn filter_changelogs(
     Ok(filtered)
 }
 
-fn output_sql_script(
-    changelogs: &[Changelog],
-    from_issue: Option<u32>,
-    to_issue: Option<u32>,
-) -> Result<(), AppError> {
-    let range_description = match (from_issue, to_issue) {
+/// The changelogs of `source` that none of the `applied` changelogs of another database
+/// carries over: neither the same issue, when both databases share a project, nor the same
+/// statement, as `migrate` copies it into an issue of the target's project.
+fn missing_from(source: Vec<Changelog>, applied: &[Changelog]) -> Vec<Changelog> {
+    let done: Vec<&Changelog> = applied
+        .iter()
+        .filter(|changelog| changelog.status == "DONE" && !changelog.is_baseline())
+        .collect();
+    source
+        .into_iter()
+        .filter(|changelog| {
+            !done.iter().any(|other| {
+                let same_issue = match (&changelog.issue, &other.issue) {
+                    (Some(a), Some(b)) => a.project == b.project && a.number == b.number,
+                    _ => false,
+                };
+                same_issue
+                    || other.statement.without_provenance()
+                        == changelog.statement.without_provenance()
+            })
+        })
+        .collect()
+}
+
+fn range_description(from_issue: Option<u32>, to_issue: Option<u32>) -> String {
+    match (from_issue, to_issue) {
         (Some(from), Some(to)) => format!("from issue #{from} to #{to}"),
         (Some(from), None) => format!("from issue #{from} to latest"),
         (None, Some(to)) => format!("up to issue #{to}"),
         (None, None) => "all changes".to_string(),
-    };
+    }
+}
 
+fn output_sql_script(
+    changelogs: &[Changelog],
+    range_description: &str,
+    timezone: DisplayTimezone,
+) -> Result<(), AppError> {
     let now = Utc::now().format("%Y-%m-%d");
     println!("-- Schema changes {range_description}");
     println!("-- Generated by shelltide on {now}");
@@ -87,8 +245,8 @@ fn output_sql_script(
 
     // Output each changelog
     for changelog in changelogs {
-        let issue_number = changelog.issue.number;
-        let formatted_time = format_timestamp(changelog.create_time);
+        let issue_number = changelog.issue_number();
+        let formatted_time = timezone.format(changelog.create_time);
 
         println!("-- Issue #{issue_number}");
         println!("-- Executed: {formatted_time}");
@@ -101,10 +259,6 @@ fn output_sql_script(
     Ok(())
 }
 
-fn format_timestamp(timestamp: DateTime<Utc>) -> String {
-    timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string()
-}
-
 fn ensure_semicolon(statement: &str) -> String {
     let trimmed = statement.trim();
     if trimmed.is_empty() {
@@ -131,4 +285,79 @@ mod tests {
         assert_eq!(ensure_semicolon(""), "");
         assert_eq!(ensure_semicolon("   "), "");
     }
+
+    #[test]
+    fn test_export_state_resumes_after_last_changelog() {
+        let changelog = |number: u32, minute: u32| -> Changelog {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("instances/dev/databases/app/changelogs/{number}"),
+                "createTime": format!("2024-01-01T00:{minute:02}:00Z"),
+                "status": "DONE",
+                "statement": "ALTER TABLE t ADD COLUMN c INT;",
+                "issue": format!("projects/dev/issues/{number}"),
+                "type": "MIGRATE"
+            }))
+            .unwrap()
+        };
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("state").join("export-state.json");
+        assert!(load_export_state(&path).unwrap().databases.is_empty());
+
+        let mut state = ExportState::default();
+        let last = changelog(11, 2);
+        state.databases.insert(
+            "dev/app".to_string(),
+            ExportMark {
+                changelog: last.name.number,
+                create_time: last.create_time,
+            },
+        );
+        save_export_state(&path, &state).unwrap();
+
+        let mark = load_export_state(&path).unwrap().databases["dev/app"].clone();
+        let new: Vec<u32> = [
+            changelog(10, 1),
+            changelog(11, 2),
+            changelog(13, 2),
+            changelog(12, 3),
+        ]
+        .iter()
+        .filter(|changelog| mark.is_before(changelog))
+        .map(|changelog| changelog.name.number)
+        .collect();
+        assert_eq!(new, [13, 12]);
+    }
+
+    #[test]
+    fn test_missing_from_skips_carried_over_changelogs() {
+        let changelog = |instance: &str, project: &str, number: u32, statement: &str| {
+            serde_json::from_value::<Changelog>(serde_json::json!({
+                "name": format!("instances/{instance}/databases/app/changelogs/{number}"),
+                "createTime": format!("2024-01-01T00:{number:02}:00Z"),
+                "status": "DONE",
+                "statement": statement,
+                "issue": format!("projects/{project}/issues/{number}"),
+                "type": "MIGRATE"
+            }))
+            .unwrap()
+        };
+        let source = vec![
+            changelog("dev", "dev", 1, "CREATE TABLE a (id INT);"),
+            changelog("dev", "dev", 2, "CREATE TABLE b (id INT);"),
+            changelog("dev", "shared", 3, "CREATE TABLE c (id INT);"),
+            changelog("dev", "dev", 4, "CREATE TABLE d (id INT);"),
+        ];
+        let applied = [
+            // Copied by `migrate` into an issue of the target's project.
+            changelog("prod", "prod", 7, "CREATE TABLE a (id INT);\n"),
+            // Rolled out to both databases by the same issue.
+            changelog("prod", "shared", 3, "CREATE TABLE c (id INT); -- edited"),
+        ];
+
+        let missing: Vec<u32> = missing_from(source, &applied)
+            .iter()
+            .map(Changelog::issue_number)
+            .collect();
+        assert_eq!(missing, [2, 4]);
+    }
 }
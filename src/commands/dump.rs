@@ -1,10 +1,11 @@
 use crate::api::clients::LiveApiClient;
 use crate::api::traits::BytebaseApi;
-use crate::api::types::{Changelog, ChangelogType};
+use crate::api::types::{Changelog, ChangelogType, ListOptions};
 use crate::cli::DumpArgs;
-use crate::config::{ConfigOperations, ProductionConfig};
-use crate::error::AppError;
-use chrono::{DateTime, Utc};
+use crate::commands::prompt::print_notice;
+use crate::config::{ConfigOperations, DisplayTimezone, ProductionConfig};
+use crate::error::{AppError, NOTHING_TO_OUTPUT_EXIT_CODE};
+use chrono::Utc;
 
 pub async fn handle_dump(args: DumpArgs) -> Result<(), AppError> {
     let config_ops = ProductionConfig;
@@ -16,11 +17,7 @@ pub async fn handle_dump_with_config<C: ConfigOperations>(
     config_ops: &C,
 ) -> Result<(), AppError> {
     let config = config_ops.load_config().await?;
-    let credentials = config.get_credentials()?;
-    let mut client = LiveApiClient::new(credentials)?;
-
-    // Ensure authentication
-    client.ensure_authenticated_with_config(config_ops).await?;
+    let client = LiveApiClient::connect(&config, config_ops, print_notice).await?;
 
     // Get environment configuration
     let env_config = config
@@ -29,46 +26,55 @@ pub async fn handle_dump_with_config<C: ConfigOperations>(
         .ok_or_else(|| AppError::Config(format!("Environment '{}' not found", args.target.env)))?;
 
     let changelogs = client
-        .get_changelogs(&env_config.instance, &args.target.db)
+        .get_changelogs(&env_config.instance, &args.target.db, &ListOptions::full())
         .await?;
 
-    let target_changelog = find_target_changelog(changelogs, args.at_issue)?;
+    let at_issue = args.at_issue.and_then(|at| at.0);
+    let target_changelog = find_target_changelog(changelogs, at_issue)?;
 
-    match target_changelog {
-        Some(changelog) => {
-            output_schema_dump(&changelog, args.at_issue)?;
-        }
+    let dump = match target_changelog {
+        Some(changelog) => schema_dump(&changelog, at_issue, config.display.timezone()),
         None => {
             if args.fail_if_empty {
                 eprintln!("No suitable MIGRATE changelog found");
-                if args.at_issue.is_some() {
-                    eprintln!(
-                        "No migrations found at or before issue #{}",
-                        args.at_issue.unwrap()
-                    );
+                if let Some(issue) = at_issue {
+                    eprintln!("No migrations found at or before issue #{issue}");
                 } else {
                     eprintln!("No migrations found in the database");
                 }
-                std::process::exit(2);
-            } else {
-                // Just print empty dump with header
-                let issue_description = match args.at_issue {
-                    Some(issue) => format!("at or before issue #{issue}"),
-                    None => "at latest migration".to_string(),
-                };
-                let now = chrono::Utc::now().format("%Y-%m-%d");
-                println!("-- Database schema dump {issue_description}");
-                println!("-- No migrations found");
-                println!("-- Generated by shelltide on {now}");
-                println!();
+                std::process::exit(NOTHING_TO_OUTPUT_EXIT_CODE);
+            }
+            // Just an empty dump with header
+            format!(
+                "-- Database schema dump {}\n-- No migrations found\n-- Generated by shelltide on {}\n\n",
+                dump_description(at_issue),
+                Utc::now().format("%Y-%m-%d")
+            )
+        }
+    };
+
+    match &args.out {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
             }
+            std::fs::write(path, &dump)?;
+            println!("Wrote the schema of '{}' to {path:?}", args.target);
         }
+        None => print!("{dump}"),
     }
 
     Ok(())
 }
 
-fn find_target_changelog(
+fn dump_description(target_issue: Option<u32>) -> String {
+    match target_issue {
+        Some(issue) => format!("at or before issue #{issue}"),
+        None => "at latest migration".to_string(),
+    }
+}
+
+pub(crate) fn find_target_changelog(
     changelogs: Vec<Changelog>,
     target_issue: Option<u32>,
 ) -> Result<Option<Changelog>, AppError> {
@@ -76,6 +82,7 @@ fn find_target_changelog(
         .into_iter()
         .filter(|changelog| {
             changelog.changelog_type == Some(ChangelogType::Migrate)
+                && !changelog.statement.is_empty()
                 && !changelog.schema.is_empty()
                 && changelog.status == "DONE"
         })
@@ -89,7 +96,7 @@ fn find_target_changelog(
         Some(issue_num) => {
             let filtered: Vec<Changelog> = migrate_changelogs
                 .into_iter()
-                .filter(|changelog| changelog.issue.number <= issue_num)
+                .filter(|changelog| changelog.issue_number() <= issue_num)
                 .collect();
 
             Ok(filtered.into_iter().next())
@@ -98,28 +105,20 @@ fn find_target_changelog(
     }
 }
 
-fn output_schema_dump(changelog: &Changelog, target_issue: Option<u32>) -> Result<(), AppError> {
-    let issue_description = match target_issue {
-        Some(issue) => format!("at or before issue #{issue}"),
-        None => "at latest migration".to_string(),
-    };
-
-    let actual_issue = changelog.issue.number;
-    let formatted_time = format_timestamp(changelog.create_time);
-    let now = Utc::now().format("%Y-%m-%d");
-
-    println!("-- Database schema dump {issue_description}");
-    println!("-- Actual issue: #{actual_issue}");
-    println!("-- Migration executed: {formatted_time}");
-    println!("-- Generated by shelltide on {now}");
-    println!();
-    print!("{}", changelog.schema);
-
-    Ok(())
-}
-
-fn format_timestamp(timestamp: DateTime<Utc>) -> String {
-    timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+/// The schema recorded with `changelog`, under a header saying where it comes from.
+fn schema_dump(
+    changelog: &Changelog,
+    target_issue: Option<u32>,
+    timezone: DisplayTimezone,
+) -> String {
+    format!(
+        "-- Database schema dump {}\n-- Actual issue: #{}\n-- Migration executed: {}\n-- Generated by shelltide on {}\n\n{}",
+        dump_description(target_issue),
+        changelog.issue_number(),
+        timezone.format(changelog.create_time),
+        Utc::now().format("%Y-%m-%d"),
+        changelog.schema
+    )
 }
 
 #[cfg(test)]
@@ -144,12 +143,13 @@ mod tests {
             } else {
                 "".to_string()
             },
-            issue: IssueName {
+            issue: Some(IssueName {
                 project: "test-project".to_string(),
                 number: issue_number,
-            },
+            }),
             changed_resources: ChangedResource::default(),
             changelog_type: Some(ChangelogType::Migrate),
+            statement_sheet: None,
         }
     }
 
@@ -164,7 +164,7 @@ mod tests {
 
         let result = find_target_changelog(changelogs, Some(950)).unwrap();
         assert!(result.is_some());
-        assert_eq!(result.unwrap().issue.number, 950);
+        assert_eq!(result.unwrap().issue_number(), 950);
     }
 
     #[test]
@@ -176,7 +176,7 @@ mod tests {
 
         let result = find_target_changelog(changelogs, None).unwrap();
         assert!(result.is_some());
-        assert_eq!(result.unwrap().issue.number, 1000);
+        assert_eq!(result.unwrap().issue_number(), 1000);
     }
 
     #[test]
@@ -189,4 +189,18 @@ mod tests {
         let result = find_target_changelog(changelogs, None).unwrap();
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_schema_dump_header() {
+        let changelog = create_test_changelog(950, true);
+        let dump = schema_dump(&changelog, Some(1000), DisplayTimezone::Utc);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], "-- Database schema dump at or before issue #1000");
+        assert_eq!(lines[1], "-- Actual issue: #950");
+        assert_eq!(lines.last(), Some(&"CREATE TABLE test();"));
+        assert!(
+            schema_dump(&changelog, None, DisplayTimezone::Utc)
+                .starts_with("-- Database schema dump at latest migration\n")
+        );
+    }
 }
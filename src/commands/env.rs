@@ -1,27 +1,77 @@
 use crate::api::traits::BytebaseApi;
+use crate::api::types::SQLDialect;
 use crate::cli::EnvCommand;
-use crate::config::{ConfigOperations, Environment, ProductionConfig};
-use anyhow::Result;
+use crate::config::{ConfigOperations, Environment, EnvironmentFile, ProductionConfig};
+use crate::error::AppError;
+use crate::input;
+use crate::output::{self, OutputFormat};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
 
 /// Handles the `env` command by creating a live API client and dispatching to the appropriate sub-command.
-pub async fn handle_env_command<T: BytebaseApi>(command: EnvCommand, client: &T) -> Result<()> {
+pub async fn handle_env_command<T: BytebaseApi>(
+    command: EnvCommand,
+    client: &T,
+    output: OutputFormat,
+) -> Result<()> {
     let config_ops = ProductionConfig;
-    handle_env_command_with_config(command, client, &config_ops).await
+    handle_env_command_with_config(command, client, output, &config_ops).await
 }
 
 pub async fn handle_env_command_with_config<T: BytebaseApi, C: ConfigOperations>(
     command: EnvCommand,
     client: &T,
+    output: OutputFormat,
     config_ops: &C,
 ) -> Result<()> {
     match command {
         EnvCommand::Add {
+            from_template: Some(args),
+            engine,
+            allow_duplicate,
+            ..
+        } => add_env_from_template(client, config_ops, &args, engine, allow_duplicate).await,
+        EnvCommand::Add {
+            name: Some(name),
+            project: Some(project),
+            instance: Some(instance),
+            engine,
+            allow_duplicate,
+            ..
+        } => {
+            let new_env = Environment {
+                project,
+                instance,
+                engine,
+                pin_to: None,
+            };
+            add_env_with_config(client, config_ops, &name, new_env, allow_duplicate).await
+        }
+        EnvCommand::Add { .. } => Err(AppError::InvalidArgs(
+            "env add needs <name> <project> <instance> or --from-template".to_string(),
+        )
+        .into()),
+        EnvCommand::Set {
             name,
             project,
             instance,
-        } => add_env_with_config(client, config_ops, &name, &project, &instance).await,
-        EnvCommand::List => list_envs_with_config(config_ops).await,
+            engine,
+            pin_to,
+        } => {
+            let changes = EnvChanges {
+                project,
+                instance,
+                engine,
+                pin_to,
+            };
+            set_env_with_config(client, config_ops, &name, changes).await
+        }
+        EnvCommand::List => list_envs_with_config(config_ops, output).await,
         EnvCommand::Remove { name } => remove_env_with_config(config_ops, &name).await,
+        EnvCommand::Export { out } => export_envs_with_config(config_ops, out.as_deref()).await,
+        EnvCommand::Import { file } => import_envs_with_config(config_ops, &file).await,
     }
 }
 
@@ -29,9 +79,184 @@ async fn add_env_with_config<T: BytebaseApi, C: ConfigOperations>(
     api_client: &T,
     config_ops: &C,
     name: &str,
+    mut new_env: Environment,
+    allow_duplicate: bool,
+) -> Result<()> {
+    let detected_engine =
+        verify_project_and_instance(api_client, &new_env.project, &new_env.instance).await?;
+    new_env.engine = new_env.engine.or(detected_engine);
+    print_engine(new_env.engine.as_ref());
+
+    let _lock = config_ops.lock_config().await?;
+    let mut config = config_ops.load_config().await?;
+    if let Some((other, _)) = config
+        .environments
+        .iter()
+        .find(|(other, env)| *other != name && env.instance == new_env.instance)
+    {
+        if !allow_duplicate {
+            return Err(AppError::InvalidArgs(format!(
+                "Environment '{other}' already uses instance '{}'; applying to one would also change the other. Pass --allow-duplicate to add it anyway.",
+                new_env.instance
+            ))
+            .into());
+        }
+        println!(
+            "Warning: environment '{other}' already uses instance '{}'.",
+            new_env.instance
+        );
+    }
+    let project = new_env.project.clone();
+    config.environments.insert(name.to_string(), new_env);
+    config_ops.save_config(&config).await?;
+
+    println!("\nSuccessfully added environment '{name}' for project '{project}'.");
+    Ok(())
+}
+
+/// Adds an environment named by the `name=` variable, with project and instance filled in from
+/// the template `args[0]` and the remaining `KEY=VALUE` arguments.
+async fn add_env_from_template<T: BytebaseApi, C: ConfigOperations>(
+    api_client: &T,
+    config_ops: &C,
+    args: &[String],
+    engine: Option<SQLDialect>,
+    allow_duplicate: bool,
+) -> Result<()> {
+    let (template_name, assignments) = args
+        .split_first()
+        .ok_or_else(|| AppError::InvalidArgs("--from-template needs a template name".into()))?;
+    let vars = assignments
+        .iter()
+        .map(|assignment| {
+            assignment
+                .split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .ok_or_else(|| {
+                    AppError::InvalidArgs(format!(
+                        "Invalid template variable '{assignment}'. Use KEY=VALUE."
+                    ))
+                })
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let name = vars.get("name").ok_or_else(|| {
+        AppError::InvalidArgs("--from-template needs the environment name as name=<env>".into())
+    })?;
+
+    let config = config_ops.load_config().await?;
+    let template = config.env_templates.get(template_name).ok_or_else(|| {
+        let known: Vec<&str> = config.env_templates.keys().map(String::as_str).collect();
+        AppError::Config(format!(
+            "Environment template '{template_name}' not found. Known templates: {}",
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        ))
+    })?;
+    let mut env = template
+        .instantiate(&vars)
+        .with_context(|| format!("Cannot use environment template '{template_name}'"))?;
+    println!(
+        "Template '{template_name}': project '{}', instance '{}'.",
+        env.project, env.instance
+    );
+
+    env.engine = engine.or(env.engine);
+    add_env_with_config(api_client, config_ops, name, env, allow_duplicate).await
+}
+
+/// The fields `env set` changes; `None` keeps the current value.
+struct EnvChanges {
+    project: Option<String>,
+    instance: Option<String>,
+    engine: Option<SQLDialect>,
+    /// An empty pin removes it.
+    pin_to: Option<String>,
+}
+
+/// Changes the project, instance, engine and/or pinned version of an existing environment,
+/// re-verifying a changed project or instance against the API before saving.
+async fn set_env_with_config<T: BytebaseApi, C: ConfigOperations>(
+    api_client: &T,
+    config_ops: &C,
+    name: &str,
+    changes: EnvChanges,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let current = config
+        .environments
+        .get(name)
+        .cloned()
+        .ok_or_else(|| AppError::EnvNotFound(name.to_string()))?;
+    let EnvChanges {
+        project,
+        instance,
+        engine,
+        pin_to,
+    } = changes;
+    let instance_changed = instance
+        .as_ref()
+        .is_some_and(|instance| *instance != current.instance);
+    let pin_to = match pin_to {
+        Some(pin) if pin.is_empty() => None,
+        Some(pin) => {
+            let issue = config.resolve_pin(&pin)?;
+            println!("Pinning '{name}' to '{pin}' (issue #{issue}).");
+            Some(pin)
+        }
+        None => current.pin_to.clone(),
+    };
+    let mut updated = Environment {
+        project: project.unwrap_or(current.project.clone()),
+        instance: instance.unwrap_or(current.instance.clone()),
+        engine: engine.clone().or(current.engine.clone()),
+        pin_to,
+    };
+    if updated == current {
+        println!("Environment '{name}' is unchanged.");
+        return Ok(());
+    }
+
+    let location_changed = updated.project != current.project
+        || updated.instance != current.instance
+        || updated.engine != current.engine;
+    if location_changed {
+        let detected_engine =
+            verify_project_and_instance(api_client, &updated.project, &updated.instance).await?;
+        // A different instance may run a different engine, so re-detect unless one was given.
+        if instance_changed && engine.is_none() {
+            updated.engine = detected_engine;
+        }
+        print_engine(updated.engine.as_ref());
+    }
+
+    let _lock = config_ops.lock_config().await?;
+    let mut config = config_ops.load_config().await?;
+    config
+        .environments
+        .insert(name.to_string(), updated.clone());
+    config_ops.save_config(&config).await?;
+
+    println!(
+        "\nUpdated environment '{name}': project '{}', instance '{}'.",
+        updated.project, updated.instance
+    );
+    match &updated.pin_to {
+        Some(pin) => println!("`migrate` without --to uses '{pin}' for '{name}'."),
+        None if current.pin_to.is_some() => println!("Removed the pinned version of '{name}'."),
+        None => {}
+    }
+    Ok(())
+}
+
+/// Checks that `project` and `instance` exist, returning the instance's engine if reported.
+async fn verify_project_and_instance<T: BytebaseApi>(
+    api_client: &T,
     project: &str,
     instance: &str,
-) -> Result<()> {
+) -> Result<Option<SQLDialect>> {
     print!("Verifying project '{project}'...");
     match api_client.get_project(project).await {
         Ok(p) => println!(" ✅ Found project '{}'.", p.title),
@@ -43,51 +268,155 @@ async fn add_env_with_config<T: BytebaseApi, C: ConfigOperations>(
 
     print!("Verifying instance '{instance}'...");
     match api_client.get_instance(instance).await {
-        Ok(i) => println!(" ✅ Found instance '{}'.", i.name),
+        Ok(i) => {
+            println!(" ✅ Found instance '{}'.", i.name);
+            Ok(i.engine)
+        }
         Err(e) => {
             println!(" ❌ FAILED");
-            return Err(e.into());
+            Err(e.into())
         }
     }
+}
 
-    let mut config = config_ops.load_config().await?;
-    let new_env = Environment {
-        project: project.to_string(),
-        instance: instance.to_string(),
-    };
-    config.environments.insert(name.to_string(), new_env);
-    config_ops.save_config(&config).await?;
-
-    println!("\nSuccessfully added environment '{name}' for project '{project}'.");
-    Ok(())
+fn print_engine(engine: Option<&SQLDialect>) {
+    match engine {
+        Some(engine) => println!("Using engine {engine}."),
+        None => println!("Could not detect the instance engine; MySQL will be assumed."),
+    }
 }
 
-async fn list_envs_with_config<C: ConfigOperations>(config_ops: &C) -> Result<()> {
+async fn list_envs_with_config<C: ConfigOperations>(
+    config_ops: &C,
+    output: OutputFormat,
+) -> Result<()> {
     let config = config_ops.load_config().await?;
-    if config.environments.is_empty() {
+    let mut envs: Vec<EnvRow> = config
+        .environments
+        .into_iter()
+        .map(|(name, env)| EnvRow { name, env })
+        .collect();
+    envs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if output == OutputFormat::Json {
+        return output::print_json(&envs);
+    }
+    if envs.is_empty() {
         println!("No environments configured. Use `env add` to add one.");
         return Ok(());
     }
-
-    println!("{:<15} {:<30}", "NAME", "PROJECT");
-    println!("{:-<15} {:-<30}", "", "");
-    for (name, env) in config.environments {
-        println!("{:<15} {:<30}", name, env.project);
+    for line in env_table_lines(&envs) {
+        println!("{line}");
     }
     Ok(())
 }
 
+/// An environment as listed by `env list --output json`.
+#[derive(Serialize)]
+struct EnvRow {
+    name: String,
+    #[serde(flatten)]
+    env: Environment,
+}
+
+fn env_table_lines(envs: &[EnvRow]) -> Vec<String> {
+    let rows: Vec<[String; 5]> = envs
+        .iter()
+        .map(|EnvRow { name, env }| {
+            [
+                name.clone(),
+                env.project.clone(),
+                env.instance.clone(),
+                env.engine
+                    .as_ref()
+                    .map_or("-".to_string(), |e| e.to_string()),
+                env.pin_to.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    output::table_lines(
+        ["NAME", "PROJECT", "INSTANCE", "ENGINE", "PINNED TO"],
+        &rows,
+    )
+}
+
 async fn remove_env_with_config<C: ConfigOperations>(config_ops: &C, name: &str) -> Result<()> {
+    let _lock = config_ops.lock_config().await?;
     let mut config = config_ops.load_config().await?;
+    // A shared environment is merged back from `team.config_url` on every load.
+    let shared = config.shared_environments.contains_key(name);
+    if shared && config.shared_environments.get(name) == config.environments.get(name) {
+        return Err(anyhow::anyhow!(
+            "Environment '{name}' comes from team.config_url and cannot be removed locally; remove it from the shared file instead."
+        ));
+    }
     if config.environments.remove(name).is_some() {
         config_ops.save_config(&config).await?;
-        println!("Removed environment '{name}'.");
+        if shared {
+            println!(
+                "Removed the local definition of '{name}'; the one from team.config_url applies again."
+            );
+        } else {
+            println!("Removed environment '{name}'.");
+        }
     } else {
         println!("Error: Environment '{name}' not found.");
     }
     Ok(())
 }
 
+async fn export_envs_with_config<C: ConfigOperations>(
+    config_ops: &C,
+    out: Option<&Path>,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let export = EnvironmentFile {
+        environments: config.environments.into_iter().collect(),
+    };
+    let yaml = serde_yaml::to_string(&export).context("Failed to serialize environments")?;
+
+    match out {
+        Some(path) => {
+            tokio::fs::write(path, yaml)
+                .await
+                .with_context(|| format!("Failed to write environments to {path:?}"))?;
+            println!(
+                "Exported {} environment(s) to {path:?}.",
+                export.environments.len()
+            );
+        }
+        None => print!("{yaml}"),
+    }
+    Ok(())
+}
+
+async fn import_envs_with_config<C: ConfigOperations>(config_ops: &C, file: &Path) -> Result<()> {
+    let source = input::describe(file);
+    let content = input::read_to_string(file)
+        .await
+        .with_context(|| format!("Failed to read {source}"))?;
+    let import: EnvironmentFile = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse environments from {source}"))?;
+
+    let _lock = config_ops.lock_config().await?;
+    let mut config = config_ops.load_config().await?;
+    let (mut added, mut updated) = (0, 0);
+    for (name, env) in import.environments {
+        match config.environments.insert(name.clone(), env.clone()) {
+            None => added += 1,
+            Some(previous) if previous != env => {
+                println!("Updated environment '{name}'.");
+                updated += 1;
+            }
+            Some(_) => {}
+        }
+    }
+    config_ops.save_config(&config).await?;
+
+    println!("Imported environments: {added} added, {updated} updated.");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -106,13 +435,15 @@ mod tests {
         };
 
         // Initialize test config with credentials
-        let mut config = config::AppConfig::default();
-        config.credentials = Some(Credentials {
-            url: "https://fake-url.com".to_string(),
-            service_account: "fake-service-account".to_string(),
-            service_key: Some("fake-service-key".to_string()),
-            access_token: "fake-access-token".to_string(),
-        });
+        let config = config::AppConfig {
+            credentials: Some(Credentials {
+                url: "https://fake-url.com".to_string(),
+                service_account: "fake-service-account".to_string(),
+                service_key: Some("fake-service-key".to_string()),
+                access_token: "fake-access-token".to_string(),
+            }),
+            ..Default::default()
+        };
         test_config.save_config(&config).await.unwrap();
 
         // Test the add_env function with dependency injection
@@ -121,13 +452,22 @@ mod tests {
         };
 
         let add_command = EnvCommand::Add {
-            name: "dev".to_string(),
-            project: "existing-project".to_string(),
-            instance: "existing-instance".to_string(),
+            name: Some("dev".to_string()),
+            project: Some("existing-project".to_string()),
+            instance: Some("existing-instance".to_string()),
+            engine: None,
+            from_template: None,
+            allow_duplicate: false,
         };
 
         // This should now work completely in isolation
-        let result = handle_env_command_with_config(add_command, &fake_client, &test_config).await;
+        let result = handle_env_command_with_config(
+            add_command,
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await;
         assert!(result.is_ok());
 
         // Verify the environment was added correctly to the test config
@@ -137,6 +477,39 @@ mod tests {
             loaded_config.environments.get("dev").unwrap().project,
             "existing-project"
         );
+
+        // A second environment on the same instance needs --allow-duplicate.
+        let add_duplicate = |allow_duplicate| EnvCommand::Add {
+            name: Some("dev-copy".to_string()),
+            project: Some("existing-project".to_string()),
+            instance: Some("existing-instance".to_string()),
+            engine: None,
+            from_template: None,
+            allow_duplicate,
+        };
+        let result = handle_env_command_with_config(
+            add_duplicate(false),
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("--allow-duplicate")
+        );
+        handle_env_command_with_config(
+            add_duplicate(true),
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await
+        .unwrap();
+        let loaded_config = test_config.load_config().await.unwrap();
+        assert!(loaded_config.environments.contains_key("dev-copy"));
     }
 
     #[tokio::test]
@@ -148,13 +521,15 @@ mod tests {
         };
 
         // Initialize test config with credentials
-        let mut config = config::AppConfig::default();
-        config.credentials = Some(Credentials {
-            url: "https://fake-url.com".to_string(),
-            service_account: "fake-service-account".to_string(),
-            service_key: Some("fake-service-key".to_string()),
-            access_token: "fake-access-token".to_string(),
-        });
+        let config = config::AppConfig {
+            credentials: Some(Credentials {
+                url: "https://fake-url.com".to_string(),
+                service_account: "fake-service-account".to_string(),
+                service_key: Some("fake-service-key".to_string()),
+                access_token: "fake-access-token".to_string(),
+            }),
+            ..Default::default()
+        };
         test_config.save_config(&config).await.unwrap();
 
         // Test that adding non-existing project fails
@@ -163,17 +538,361 @@ mod tests {
         };
 
         let add_command = EnvCommand::Add {
-            name: "dev".to_string(),
-            project: "non-existing-project".to_string(),
-            instance: "existing-instance".to_string(),
+            name: Some("dev".to_string()),
+            project: Some("non-existing-project".to_string()),
+            instance: Some("existing-instance".to_string()),
+            engine: None,
+            from_template: None,
+            allow_duplicate: false,
         };
 
         // This should fail because the project doesn't exist in FakeApiClient
-        let result = handle_env_command_with_config(add_command, &fake_client, &test_config).await;
+        let result = handle_env_command_with_config(
+            add_command,
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await;
         assert!(result.is_err());
 
         // Verify no environment was added to the test config
         let loaded_config = test_config.load_config().await.unwrap();
         assert!(!loaded_config.environments.contains_key("dev"));
     }
+
+    #[tokio::test]
+    async fn test_add_from_template() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let mut config = config::AppConfig::default();
+        config.env_templates.insert(
+            "prod-template".to_string(),
+            config::EnvTemplate {
+                project: Some("existing-{kind}".to_string()),
+                instance: Some("{name}-mysql".to_string()),
+                engine: None,
+            },
+        );
+        test_config.save_config(&config).await.unwrap();
+        let fake_client = FakeApiClient::default();
+        let add = |args: &[&str]| EnvCommand::Add {
+            name: None,
+            project: None,
+            instance: None,
+            engine: None,
+            from_template: Some(args.iter().map(|a| a.to_string()).collect()),
+            allow_duplicate: false,
+        };
+
+        handle_env_command_with_config(
+            add(&["prod-template", "name=prod-jp", "kind=project"]),
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await
+        .unwrap();
+        let loaded = test_config.load_config().await.unwrap();
+        let env = &loaded.environments["prod-jp"];
+        assert_eq!(env.project, "existing-project");
+        assert_eq!(env.instance, "prod-jp-mysql");
+
+        let missing_var = handle_env_command_with_config(
+            add(&["prod-template", "name=prod-kr"]),
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{missing_var:#}").contains("kind=<value>"));
+        assert!(
+            handle_env_command_with_config(
+                add(&["nope", "name=x"]),
+                &fake_client,
+                OutputFormat::Table,
+                &test_config
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let source_dir = tempdir().unwrap();
+        let source = TestConfig {
+            test_dir: source_dir.path().to_path_buf(),
+        };
+        let mut config = config::AppConfig {
+            credentials: Some(Credentials {
+                url: "https://fake-url.com".to_string(),
+                service_account: "fake-service-account".to_string(),
+                service_key: Some("fake-service-key".to_string()),
+                access_token: "fake-access-token".to_string(),
+            }),
+            ..Default::default()
+        };
+        config.environments.insert(
+            "prod".to_string(),
+            Environment {
+                project: "prod-project".to_string(),
+                instance: "prod-instance".to_string(),
+                engine: Some(SQLDialect::Postgres),
+                pin_to: None,
+            },
+        );
+        source.save_config(&config).await.unwrap();
+
+        let out = source_dir.path().join("envs.yaml");
+        export_envs_with_config(&source, Some(&out)).await.unwrap();
+        let yaml = std::fs::read_to_string(&out).unwrap();
+        assert!(!yaml.contains("fake-service-key"));
+
+        let target_dir = tempdir().unwrap();
+        let target = TestConfig {
+            test_dir: target_dir.path().to_path_buf(),
+        };
+        target
+            .save_config(&config::AppConfig::default())
+            .await
+            .unwrap();
+        import_envs_with_config(&target, &out).await.unwrap();
+
+        let imported = target.load_config().await.unwrap();
+        assert_eq!(imported.environments, config.environments);
+        assert!(imported.credentials.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_set_env_reverifies_and_keeps_name() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let mut config = config::AppConfig {
+            default_source_env: Some("dev".to_string()),
+            ..Default::default()
+        };
+        config.environments.insert(
+            "dev".to_string(),
+            Environment {
+                project: "existing-project".to_string(),
+                instance: "old-instance".to_string(),
+                engine: Some(SQLDialect::MySQL),
+                pin_to: None,
+            },
+        );
+        test_config.save_config(&config).await.unwrap();
+        let fake_client = FakeApiClient::default();
+
+        let set_command = EnvCommand::Set {
+            name: "dev".to_string(),
+            project: Some("non-existing-project".to_string()),
+            instance: None,
+            engine: None,
+            pin_to: None,
+        };
+        assert!(
+            handle_env_command_with_config(
+                set_command,
+                &fake_client,
+                OutputFormat::Table,
+                &test_config
+            )
+            .await
+            .is_err()
+        );
+
+        let set_command = EnvCommand::Set {
+            name: "dev".to_string(),
+            project: None,
+            instance: Some("new-instance".to_string()),
+            engine: Some(SQLDialect::TiDB),
+            pin_to: None,
+        };
+        handle_env_command_with_config(
+            set_command,
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await
+        .unwrap();
+
+        let loaded = test_config.load_config().await.unwrap();
+        assert_eq!(
+            loaded.environments["dev"],
+            Environment {
+                project: "existing-project".to_string(),
+                instance: "new-instance".to_string(),
+                engine: Some(SQLDialect::TiDB),
+                pin_to: None,
+            }
+        );
+        assert_eq!(loaded.default_source_env.as_deref(), Some("dev"));
+    }
+
+    #[test]
+    fn test_env_rows() {
+        let env = |engine, pin_to: Option<&str>| Environment {
+            project: "game-prod".to_string(),
+            instance: "prod-mysql".to_string(),
+            engine,
+            pin_to: pin_to.map(str::to_string),
+        };
+        let envs = [
+            EnvRow {
+                name: "prod".to_string(),
+                env: env(Some(SQLDialect::MySQL), Some("v1.4.0")),
+            },
+            EnvRow {
+                name: "staging".to_string(),
+                env: env(None, None),
+            },
+        ];
+
+        assert_eq!(
+            env_table_lines(&envs),
+            [
+                "NAME    PROJECT   INSTANCE   ENGINE PINNED TO",
+                "------- --------- ---------- ------ ---------",
+                "prod    game-prod prod-mysql MYSQL  v1.4.0",
+                "staging game-prod prod-mysql -      -",
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&envs[1]).unwrap(),
+            serde_json::json!({"name": "staging", "project": "game-prod", "instance": "prod-mysql"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_env_pins_version() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let mut config = config::AppConfig::default();
+        config.environments.insert(
+            "prod".to_string(),
+            Environment {
+                project: "prod-project".to_string(),
+                instance: "prod-instance".to_string(),
+                engine: None,
+                pin_to: None,
+            },
+        );
+        config.releases.insert(
+            "v1.4.0".to_string(),
+            config::Release {
+                from_env: "dev".to_string(),
+                issue_number: 450,
+                source_project: "dev-project".to_string(),
+            },
+        );
+        test_config.save_config(&config).await.unwrap();
+        // No API call is made when only the pin changes.
+        let fake_client = FakeApiClient::default();
+        let pin = |pin: &str| EnvCommand::Set {
+            name: "prod".to_string(),
+            project: None,
+            instance: None,
+            engine: None,
+            pin_to: Some(pin.to_string()),
+        };
+
+        handle_env_command_with_config(
+            pin("v1.4.0"),
+            &fake_client,
+            OutputFormat::Table,
+            &test_config,
+        )
+        .await
+        .unwrap();
+        let loaded = test_config.load_config().await.unwrap();
+        assert_eq!(loaded.pinned_version("prod").unwrap(), Some(450));
+
+        assert!(
+            handle_env_command_with_config(
+                pin("v9"),
+                &fake_client,
+                OutputFormat::Table,
+                &test_config
+            )
+            .await
+            .is_err()
+        );
+
+        handle_env_command_with_config(pin(""), &fake_client, OutputFormat::Table, &test_config)
+            .await
+            .unwrap();
+        let loaded = test_config.load_config().await.unwrap();
+        assert_eq!(loaded.pinned_version("prod").unwrap(), None);
+    }
+
+    /// Config operations that merge `shared` in on load, as `team.config_url` does.
+    struct SharedConfig {
+        local: TestConfig,
+        shared: BTreeMap<String, Environment>,
+    }
+
+    #[async_trait::async_trait]
+    impl ConfigOperations for SharedConfig {
+        async fn load_config(&self) -> Result<config::AppConfig> {
+            let mut config = self.local.load_config().await?;
+            config.merge_shared_environments(self.shared.clone());
+            Ok(config)
+        }
+
+        async fn save_config(&self, config: &config::AppConfig) -> Result<()> {
+            self.local.save_config(config).await
+        }
+
+        fn config_path(&self) -> Result<std::path::PathBuf> {
+            self.local.config_path()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_shared_env() {
+        let temp_dir = tempdir().unwrap();
+        let env = |instance: &str| Environment {
+            project: "prod-project".to_string(),
+            instance: instance.to_string(),
+            engine: None,
+            pin_to: None,
+        };
+        let config_ops = SharedConfig {
+            local: TestConfig {
+                test_dir: temp_dir.path().to_path_buf(),
+            },
+            shared: BTreeMap::from([("prod".to_string(), env("prod-instance"))]),
+        };
+
+        assert!(remove_env_with_config(&config_ops, "prod").await.is_err());
+        assert!(
+            config_ops
+                .load_config()
+                .await
+                .unwrap()
+                .environments
+                .contains_key("prod")
+        );
+
+        // A local override can be removed, which brings the shared definition back.
+        let mut config = config::AppConfig::default();
+        config
+            .environments
+            .insert("prod".to_string(), env("prod-override"));
+        config_ops.save_config(&config).await.unwrap();
+        let loaded = config_ops.load_config().await.unwrap();
+        assert_eq!(loaded.environments["prod"].instance, "prod-override");
+        remove_env_with_config(&config_ops, "prod").await.unwrap();
+        let loaded = config_ops.load_config().await.unwrap();
+        assert_eq!(loaded.environments["prod"].instance, "prod-instance");
+    }
 }
@@ -0,0 +1,233 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::{Changelog, ListOptions};
+use crate::cli::ExplainArgs;
+use crate::commands::migrate::{Selector, parse_target_version};
+use crate::commands::status::revision_status;
+use crate::config::{ConfigOperations, ProductionConfig};
+use crate::error::AppError;
+use crate::input;
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Handles the `explain` command.
+pub async fn handle_explain_command<T: BytebaseApi>(
+    args: ExplainArgs,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_explain_command_with_config(args, api_client, &config_ops).await
+}
+
+/// Prints, step by step, how `status` and `migrate` decide what is pending for a target.
+pub async fn handle_explain_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    args: ExplainArgs,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let source_env_name = config.default_source_env.as_deref().ok_or_else(|| {
+        AppError::Config(
+            "default.source_env not set. Please run: shelltide config set default.source_env <env-name>"
+                .to_string(),
+        )
+    })?;
+    let source_env = config
+        .environments
+        .get(source_env_name)
+        .ok_or_else(|| AppError::EnvNotFound(source_env_name.to_string()))?;
+    let target_env = config
+        .environments
+        .get(&args.target.env)
+        .ok_or_else(|| AppError::EnvNotFound(args.target.env.clone()))?;
+    let source_db = &args.target.db;
+    let target_db = config.mapped_database(&args.target.env, source_db);
+
+    println!(
+        "1. Source: environment '{source_env_name}' (project '{}'), database '{}/{source_db}'",
+        source_env.project, source_env.instance
+    );
+
+    let done_issues: HashSet<u32> = api_client
+        .get_done_issues(&source_env.project)
+        .await?
+        .iter()
+        .map(|issue| issue.name.number)
+        .collect();
+    let source_latest = done_issues.iter().max().copied().unwrap_or(0);
+    println!(
+        "2. Source latest DONE issue: #{source_latest} ({} DONE issue(s) in '{}')",
+        done_issues.len(),
+        source_env.project
+    );
+
+    if target_db == *source_db {
+        println!(
+            "3. Target: database '{}/{target_db}' in '{}'",
+            target_env.instance, args.target.env
+        );
+    } else {
+        println!(
+            "3. Target: database '{}/{target_db}' in '{}' (mapped from '{source_db}' by db_map)",
+            target_env.instance, args.target.env
+        );
+    }
+
+    let current_version = match api_client
+        .latest_revision(&target_env.instance, target_db, config.revision.latest_by())
+        .await
+    {
+        Ok(revision) => match &revision.version {
+            Some(version) => {
+                let recorded = revision.create_time.map_or(String::new(), |time| {
+                    format!(", recorded {}", config.display.timezone().format(time))
+                });
+                println!(
+                    "4. Target revision: {version}{recorded} -> status {}",
+                    revision_status(version.number, &done_issues)
+                );
+                version.number
+            }
+            None => {
+                println!(
+                    "4. Target revision: none -> status NO VERSION; every changelog is pending"
+                );
+                0
+            }
+        },
+        Err(e) => {
+            println!("4. Target revision: could not be read ({e}) -> status NOT EXIST");
+            return Ok(());
+        }
+    };
+
+    let target_version = parse_target_version(&input::resolve_arg(&args.to)?, source_latest)?;
+    println!("5. Target version: '{}' -> #{target_version}", args.to);
+
+    let changelogs = api_client
+        .get_changelogs(&source_env.instance, source_db, &ListOptions::full())
+        .await?;
+    println!(
+        "6. Source changelogs: {} changelog(s) in '{}/{source_db}'",
+        changelogs.len(),
+        source_env.instance
+    );
+    let mut selector = Selector {
+        source_database: source_db,
+        current_version,
+        target_version,
+        baseline: None,
+        source_at: None,
+    };
+    selector.baseline = selector.replay_baseline(&changelogs);
+    let (lines, pending) = changelog_lines(&changelogs, &selector);
+    for line in lines {
+        println!("   {line}");
+    }
+
+    if let Some(baseline) = selector.crossed_baseline(&changelogs) {
+        println!(
+            "7. Blocked: baseline {} lies between the target's revision and the pending changelogs; \
+             `migrate` refuses to cross it because the changes it captured are in no changelog",
+            baseline.label()
+        );
+    } else if let Some(baseline) = selector.baseline {
+        let issues: Vec<String> = pending.iter().map(|n| format!("#{n}")).collect();
+        println!(
+            "7. Pending: the schema of baseline {} to rebuild the target without revision, then {} changelog(s): {}",
+            baseline.label(),
+            pending.len(),
+            issues.join(", ")
+        );
+    } else if pending.is_empty() {
+        println!("7. Pending: nothing; `migrate` would report the target as up to date");
+    } else {
+        let issues: Vec<String> = pending.iter().map(|n| format!("#{n}")).collect();
+        println!(
+            "7. Pending: {} changelog(s), applied in create-time order: {}",
+            pending.len(),
+            issues.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// One line per changelog saying whether it is pending and why not, plus the pending issues
+/// in the order `migrate` applies them.
+fn changelog_lines(changelogs: &[Changelog], selector: &Selector) -> (Vec<String>, Vec<u32>) {
+    let mut changelogs: Vec<&Changelog> = changelogs.iter().collect();
+    changelogs.sort_by_key(|cl| cl.create_time);
+
+    let mut pending = Vec::new();
+    let lines = changelogs
+        .iter()
+        .map(|cl| match selector.exclusion_reason(cl) {
+            None if cl.is_baseline() => {
+                format!("{} included: its schema rebuilds the target", cl.label())
+            }
+            None => {
+                pending.push(cl.issue_number());
+                format!("{} included", cl.label())
+            }
+            Some(reason) => format!("{} excluded: {reason}", cl.label()),
+        })
+        .collect();
+    (lines, pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changelog(issue: u32, minute: u32, statement: &str, status: &str) -> Changelog {
+        serde_json::from_value(serde_json::json!({
+            "name": format!("instances/dev/databases/app/changelogs/{issue}"),
+            "createTime": format!("2024-01-01T00:{minute:02}:00Z"),
+            "status": status,
+            "statement": statement,
+            "issue": format!("projects/dev/issues/{issue}")
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_changelog_lines() {
+        let alter = "ALTER TABLE t ADD COLUMN c INT;";
+        let changelogs = vec![
+            changelog(12, 3, alter, "DONE"),
+            changelog(10, 1, alter, "DONE"),
+            changelog(11, 2, alter, "DONE"),
+            changelog(13, 4, alter, "DONE"),
+            changelog(14, 5, "", "DONE"),
+            changelog(15, 6, alter, "FAILED"),
+        ];
+        let selector = Selector {
+            source_database: "app",
+            current_version: 10,
+            target_version: 12,
+            baseline: None,
+            source_at: None,
+        };
+
+        let (lines, pending) = changelog_lines(&changelogs, &selector);
+        assert_eq!(
+            lines,
+            [
+                "#10 excluded: already applied (target revision is #10)",
+                "#11 included",
+                "#12 included",
+                "#13 excluded: beyond the target version #12",
+                "#14 excluded: empty statement",
+                "#15 excluded: not done (status \"FAILED\")",
+            ]
+        );
+        let other = Selector {
+            source_database: "log",
+            ..selector
+        };
+        assert_eq!(
+            other.exclusion_reason(&changelogs[0]).unwrap(),
+            "different database 'app', expected 'log'"
+        );
+        assert_eq!(pending, [11, 12]);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::{Changelog, ChangelogType, ListOptions};
+use crate::cli::HistoryArgs;
+use crate::commands::migrate::display_syntax;
+use crate::commands::review::statement_summary;
+use crate::config::{ConfigOperations, DisplayTimezone, ProductionConfig};
+use crate::error::AppError;
+use crate::output;
+use crate::sql::Syntax;
+use anyhow::Result;
+
+/// Handles the `history` command.
+pub async fn handle_history_command<T: BytebaseApi>(
+    args: HistoryArgs,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_history_command_with_config(args, api_client, &config_ops).await
+}
+
+pub async fn handle_history_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    args: HistoryArgs,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let env = config
+        .environments
+        .get(&args.target.env)
+        .ok_or_else(|| AppError::EnvNotFound(args.target.env.clone()))?;
+    let database = config.mapped_database(&args.target.env, &args.target.db);
+
+    let mut changelogs = api_client
+        .get_changelogs(&env.instance, database, &ListOptions::full())
+        .await?;
+    if changelogs.is_empty() {
+        println!("No changelogs for '{}/{database}'.", args.target.env);
+        return Ok(());
+    }
+    changelogs.sort_by_key(|cl| std::cmp::Reverse(cl.create_time));
+    changelogs.truncate(args.limit);
+
+    let timezone = config.display.timezone();
+    if args.full {
+        for cl in &changelogs {
+            println!(
+                "--- {} {} {} ---",
+                cl.label(),
+                changelog_type(cl),
+                timezone.format(cl.create_time)
+            );
+            println!("{}\n", cl.statement.0.trim_end());
+        }
+        return Ok(());
+    }
+    let syntax = display_syntax(api_client, env).await;
+    for line in history_lines(&changelogs, &timezone, syntax) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Formats `changelogs` as a table with a header, one line per changelog with the first
+/// statement of its SQL.
+fn history_lines(
+    changelogs: &[Changelog],
+    timezone: &DisplayTimezone,
+    syntax: Syntax,
+) -> Vec<String> {
+    let rows: Vec<[String; 4]> = changelogs
+        .iter()
+        .map(|cl| {
+            [
+                cl.label(),
+                changelog_type(cl).to_string(),
+                timezone.format(cl.create_time),
+                statement_summary(cl.statement.without_provenance(), syntax),
+            ]
+        })
+        .collect();
+    output::table_lines(["ISSUE", "TYPE", "CREATED", "STATEMENT"], &rows)
+}
+
+fn changelog_type(changelog: &Changelog) -> &'static str {
+    match changelog.changelog_type {
+        Some(ChangelogType::Migrate) => "MIGRATE",
+        Some(ChangelogType::Baseline) => "BASELINE",
+        Some(ChangelogType::Data) => "DATA",
+        None => "-",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_lines() {
+        let changelog = |number: u32, kind: &str, statement: &str| -> Changelog {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("instances/prod/databases/app/changelogs/{number}"),
+                "createTime": format!("2024-01-01T00:{number:02}:00Z"),
+                "status": "DONE",
+                "type": kind,
+                "statement": statement,
+                "issue": format!("projects/prod/issues/{}", number + 100),
+            }))
+            .unwrap()
+        };
+        let changelogs = [
+            changelog(
+                2,
+                "MIGRATE",
+                "-- shelltide-provenance: shelltide run r1, issue #42\nALTER TABLE t ADD c INT;\nALTER TABLE t ADD d INT;",
+            ),
+            changelog(1, "BASELINE", ""),
+        ];
+
+        let lines = history_lines(&changelogs, &DisplayTimezone::Utc, Syntax::MySql);
+        assert_eq!(
+            lines,
+            [
+                "ISSUE TYPE     CREATED              STATEMENT",
+                "----- -------- -------------------- ----------------------------------",
+                "#102  MIGRATE  2024-01-01T00:02:00Z ALTER TABLE t ADD c INT; (+1 more)",
+                "#101  BASELINE 2024-01-01T00:01:00Z (no statements)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use crate::api::clients::LiveApiClient;
+use crate::api::traits::BytebaseApi;
+use crate::cli::LoginArgs;
+use crate::commands::login::login_with_config;
+use crate::commands::prompt::{choose, confirm, print_notice, prompt};
+use crate::config::{ConfigOperations, Environment, ProductionConfig};
+use anyhow::{Context, Result};
+use std::io::BufRead;
+
+/// Handles the `init` command: login, environment discovery and `default.source_env`.
+pub async fn handle_init() -> Result<()> {
+    let config_ops = ProductionConfig;
+    let mut input = std::io::stdin().lock();
+
+    ensure_logged_in(&mut input, &config_ops).await?;
+
+    let config = config_ops.load_config().await?;
+    let client = LiveApiClient::new(config.get_credentials()?)?
+        .with_settings(&config.api)
+        .with_notices(print_notice);
+    setup_environments(&mut input, &client, &config_ops).await?;
+
+    println!("\nSetup complete. Run `shelltide status` to see your environments.");
+    Ok(())
+}
+
+async fn ensure_logged_in<R: BufRead, C: ConfigOperations>(
+    input: &mut R,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    if let Some(credentials) = config.active_credentials() {
+        let question = format!(
+            "Already logged in to {} as {}. Keep these credentials?",
+            credentials.url, credentials.service_account
+        );
+        if confirm(input, &question, true)? {
+            return Ok(());
+        }
+    }
+
+    println!("Step 1: Log in to Bytebase");
+    let url = prompt(input, "Bytebase URL", None)?;
+    let service_account = prompt(input, "Service account", None)?;
+    let service_key =
+        rpassword::prompt_password("Service key: ").context("Failed to read service key")?;
+    login_with_config(
+        LoginArgs {
+            url: Some(url),
+            service_account: Some(service_account),
+            service_key: Some(service_key),
+            validate_only: false,
+            warn_within: None,
+        },
+        config_ops,
+    )
+    .await
+}
+
+/// Walks through creating environments from the discovered projects and instances, then sets
+/// `default.source_env`.
+async fn setup_environments<R: BufRead, T: BytebaseApi, C: ConfigOperations>(
+    input: &mut R,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    println!("\nStep 2: Create environments");
+    let projects = api_client.list_projects().await?;
+    let instances = api_client.list_instances().await?;
+    if projects.is_empty() || instances.is_empty() {
+        anyhow::bail!(
+            "The service account cannot see any projects or instances. Check its permissions in Bytebase."
+        );
+    }
+
+    let mut added = Vec::new();
+    loop {
+        let project = choose(input, "Project", &projects)?;
+        let instance = choose(input, "Instance", &instances)?;
+        let engine = api_client.get_instance(&instance).await?.engine;
+        let name = prompt(input, "Environment name", Some(&project))?;
+        added.push((
+            name,
+            Environment {
+                project,
+                instance,
+                engine,
+                pin_to: None,
+            },
+        ));
+        if !confirm(input, "Add another environment?", false)? {
+            break;
+        }
+    }
+
+    let _lock = config_ops.lock_config().await?;
+    let mut config = config_ops.load_config().await?;
+    for (name, env) in added {
+        println!("Added environment '{name}' for project '{}'.", env.project);
+        config.environments.insert(name, env);
+    }
+
+    println!("\nStep 3: Choose the default source environment");
+    let mut names: Vec<String> = config.environments.keys().cloned().collect();
+    names.sort();
+    let default_env = choose(input, "Default source environment", &names)?;
+    println!("Set `default.source_env` to '{default_env}'");
+    config.default_source_env = Some(default_env);
+
+    config_ops.save_config(&config).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::clients::tests::FakeApiClient;
+    use crate::config::TestConfig;
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_setup_environments_from_answers() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let fake_client = FakeApiClient::default();
+
+        // First env keeps the suggested name, second is named "prod", then "prod" is the default.
+        let mut input = Cursor::new("\ny\nprod\nn\n2\n");
+        setup_environments(&mut input, &fake_client, &test_config)
+            .await
+            .unwrap();
+
+        let config = test_config.load_config().await.unwrap();
+        assert_eq!(config.environments.len(), 2);
+        assert_eq!(
+            config.environments["existing-project"].instance,
+            "existing-instance"
+        );
+        assert_eq!(config.environments["prod"].project, "existing-project");
+        assert_eq!(config.default_source_env.as_deref(), Some("prod"));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::api::filter::Filter;
+use crate::api::traits::BytebaseApi;
+use crate::api::types::IssueDetails;
+use crate::cli::IssuesCommand;
+use crate::config::{ConfigOperations, DisplayTimezone, ProductionConfig};
+use crate::error::AppError;
+use anyhow::Result;
+
+/// Handles the `issues` command.
+pub async fn handle_issues_command<T: BytebaseApi>(
+    command: IssuesCommand,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_issues_command_with_config(command, api_client, &config_ops).await
+}
+
+pub async fn handle_issues_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    command: IssuesCommand,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    match command {
+        IssuesCommand::Search {
+            env,
+            query,
+            status,
+            since,
+            before,
+            label,
+            limit,
+        } => {
+            let config = config_ops.load_config().await?;
+            let env_config = config
+                .environments
+                .get(&env)
+                .ok_or_else(|| AppError::EnvNotFound(env.clone()))?;
+
+            let mut filter = Filter::new().any_of("status", &status).labels(&label);
+            if let Some(since) = since {
+                filter = filter.created_since(since);
+            }
+            if let Some(before) = before {
+                filter = filter.created_before(before);
+            }
+            let issues = api_client
+                .search_issues(&env_config.project, &query, &filter, limit)
+                .await?;
+            if issues.is_empty() {
+                println!(
+                    "No issues matching '{query}' in project '{}'.",
+                    env_config.project
+                );
+                return Ok(());
+            }
+            for line in issue_lines(&issues, &config.display.timezone()) {
+                println!("{line}");
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Formats `issues` as a table with a header, one line per issue.
+fn issue_lines(issues: &[IssueDetails], timezone: &DisplayTimezone) -> Vec<String> {
+    let rows: Vec<[String; 4]> = issues
+        .iter()
+        .map(|issue| {
+            [
+                format!("#{}", issue.name.number),
+                issue.status.clone(),
+                issue
+                    .create_time
+                    .map_or("-".to_string(), |time| timezone.format(time)),
+                issue.title.clone(),
+            ]
+        })
+        .collect();
+    let header = ["ISSUE", "STATUS", "CREATED", "TITLE"].map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: &[String; 4]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line(&header), line(&widths.map(|w| "-".repeat(w)))];
+    lines.extend(rows.iter().map(line));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_lines() {
+        let issues: Vec<IssueDetails> = serde_json::from_value(serde_json::json!([
+            {
+                "name": "projects/dev/issues/354",
+                "title": "Add itembox table",
+                "description": "itembox for the event shop",
+                "status": "DONE",
+                "createTime": "2026-01-27T09:11:27Z"
+            },
+            {"name": "projects/dev/issues/1021", "title": "Drop old itembox index"}
+        ]))
+        .unwrap();
+
+        let lines = issue_lines(&issues, &DisplayTimezone::Utc);
+        assert_eq!(lines[0], "ISSUE STATUS CREATED              TITLE");
+        assert_eq!(
+            lines[2],
+            "#354  DONE   2026-01-27T09:11:27Z Add itembox table"
+        );
+        assert_eq!(
+            lines[3],
+            "#1021        -                    Drop old itembox index"
+        );
+    }
+}
@@ -1,35 +1,216 @@
-use crate::api::clients::get_access_token;
+use crate::api::clients::{LiveApiClient, get_access_token};
+use crate::api::traits::BytebaseApi;
 use crate::cli::LoginArgs;
-use crate::config::{ConfigOperations, Credentials, ProductionConfig};
+use crate::commands::prompt::print_notice;
+use crate::commands::status::format_lag;
+use crate::config::{ConfigOperations, Credentials, EnvCredentials, ProductionConfig};
+use crate::error::{AppError, BytebaseErrorCode};
 use anyhow::Result;
+use base64::{Engine, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
 /// Handles the `login` command.
 pub async fn login(args: LoginArgs) -> Result<()> {
     let config_ops = ProductionConfig;
+    if args.validate_only {
+        return validate_with_config(args.warn_within, &config_ops).await;
+    }
     login_with_config(args, &config_ops).await
 }
 
 pub async fn login_with_config<C: ConfigOperations>(args: LoginArgs, config_ops: &C) -> Result<()> {
-    println!("Attempting to log in to {}...", &args.url);
-    let login_response = get_access_token(
-        &args.url,
-        &args.service_account.clone(),
-        &args.service_key.clone(),
-    )
-    .await?;
+    let (Some(url), Some(service_account), Some(service_key)) =
+        (args.url, args.service_account, args.service_key)
+    else {
+        anyhow::bail!("--url, --service-account and --service-key are required to log in");
+    };
+    println!("Attempting to log in to {url}...");
+    let login_response = get_access_token(&url, &service_account, &service_key).await?;
 
     println!("Successfully authenticated. Saving credentials...");
+    let _lock = config_ops.lock_config().await?;
     let mut config = config_ops.load_config().await.unwrap_or_default();
 
-    config.credentials = Some(Credentials {
-        url: args.url,
-        service_account: args.service_account.clone(),
-        service_key: Some(args.service_key.clone()),
+    config.set_credentials(Credentials {
+        url,
+        service_account,
+        service_key: Some(service_key),
         access_token: login_response.token,
     });
     config_ops.save_config(&config).await?;
 
-    println!("Credentials saved successfully.");
+    match &config.profile {
+        Some(profile) => println!("Credentials saved successfully for profile '{profile}'."),
+        None => println!("Credentials saved successfully."),
+    }
 
     Ok(())
 }
+
+/// `login --validate-only`: checks the stored (or `BYTEBASE_*`) credentials without changing
+/// anything and reports token validity, expiry and access to every configured project. Any
+/// problem makes it fail with exit code 4, so it can run from cron as a health probe.
+pub async fn validate_with_config<C: ConfigOperations>(
+    warn_within: Option<Duration>,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let mut credentials = config.resolve_credentials(&EnvCredentials::from_env())?;
+    let mut problems = Vec::new();
+
+    println!("URL:     {}", credentials.url);
+    println!("Account: {}", credentials.service_account);
+    if credentials.access_token.is_empty()
+        && let Some(service_key) = &credentials.service_key
+    {
+        match get_access_token(&credentials.url, &credentials.service_account, service_key).await {
+            Ok(response) => credentials.access_token = response.token,
+            Err(e) => problems.push(format!("login with the service key failed: {e}")),
+        }
+    }
+
+    let mut client = LiveApiClient::new(&credentials)?
+        .with_settings(&config.api)
+        .with_notices(print_notice);
+    let mut accepted = !credentials.access_token.is_empty() && client.token_is_valid().await?;
+    let expiry = token_expiry(&credentials.access_token);
+    println!(
+        "Token:   {}",
+        token_summary(accepted, expiry, Utc::now(), warn_within, &mut problems)
+    );
+    // A rejected token is only a problem if it cannot be refreshed on the next run.
+    if !accepted
+        && !credentials.access_token.is_empty()
+        && let Some(service_key) = &credentials.service_key
+    {
+        match get_access_token(&credentials.url, &credentials.service_account, service_key).await {
+            Ok(response) => {
+                credentials.access_token = response.token;
+                client.login(&credentials)?;
+                accepted = client.token_is_valid().await?;
+                if accepted {
+                    problems.retain(|problem| !problem.starts_with(REJECTED));
+                    println!("         the service key still logs in; it is refreshed on next use");
+                }
+            }
+            Err(e) => problems.push(format!("login with the service key failed: {e}")),
+        }
+    }
+    println!(
+        "Refresh: {}",
+        if credentials.service_key.is_some() {
+            "possible with the service key"
+        } else {
+            "not possible, no service key"
+        }
+    );
+
+    if accepted {
+        let mut projects: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (name, env) in &config.environments {
+            projects.entry(&env.project).or_default().push(name);
+        }
+        if !projects.is_empty() {
+            println!("Projects:");
+        }
+        for (project, envs) in projects {
+            let access = match client.get_project(project).await {
+                Ok(_) => "ok".to_string(),
+                Err(e) => {
+                    problems.push(format!("no access to project '{project}': {e}"));
+                    format!("no access ({e})")
+                }
+            };
+            println!("  {project} ({}): {access}", envs.join(", "));
+        }
+    }
+
+    if problems.is_empty() {
+        println!("Credentials are healthy.");
+        return Ok(());
+    }
+    let code = if accepted {
+        BytebaseErrorCode::PermissionDenied
+    } else {
+        BytebaseErrorCode::Unauthenticated
+    };
+    Err(AppError::BytebaseError {
+        code,
+        message: format!("Credential check failed:\n  - {}", problems.join("\n  - ")),
+    }
+    .into())
+}
+
+/// Start of the problem reported for a token Bytebase does not accept.
+const REJECTED: &str = "the token is not accepted by Bytebase";
+
+/// Expiry time of a JWT access token, read from its `exp` claim without verifying it.
+fn token_expiry(token: &str) -> Option<DateTime<Utc>> {
+    let payload = token.split('.').nth(1)?;
+    let claims = general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&claims).ok()?;
+    DateTime::from_timestamp(claims.get("exp")?.as_i64()?, 0)
+}
+
+/// Describes the token for `--validate-only`, adding anything wrong with it to `problems`.
+fn token_summary(
+    accepted: bool,
+    expiry: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    warn_within: Option<Duration>,
+    problems: &mut Vec<String>,
+) -> String {
+    let expires = match expiry {
+        Some(expiry) if expiry <= now => format!("expired at {}", expiry.to_rfc3339()),
+        Some(expiry) => format!(
+            "expires at {} (in {})",
+            expiry.to_rfc3339(),
+            format_lag(expiry - now)
+        ),
+        None => "expiry unknown".to_string(),
+    };
+    if !accepted {
+        problems.push(format!("{REJECTED} ({expires})"));
+        return format!("rejected, {expires}");
+    }
+    if let (Some(expiry), Some(warn_within)) = (expiry, warn_within)
+        && chrono::Duration::from_std(warn_within).is_ok_and(|within| expiry - now <= within)
+    {
+        problems.push(format!("the token {expires}"));
+    }
+    format!("valid, {expires}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_expiry_and_summary() {
+        let claims = general_purpose::URL_SAFE_NO_PAD.encode(r#"{"sub":"sa","exp":86400}"#);
+        let token = format!("header.{claims}.signature");
+        let expiry = token_expiry(&token).unwrap();
+        assert_eq!(expiry.timestamp(), 86400);
+        assert!(token_expiry("not-a-jwt").is_none());
+
+        let now = DateTime::from_timestamp(3600, 0).unwrap();
+        let mut problems = Vec::new();
+        let summary = token_summary(true, Some(expiry), now, None, &mut problems);
+        assert_eq!(
+            summary,
+            "valid, expires at 1970-01-02T00:00:00+00:00 (in 23h 0m)"
+        );
+        assert!(problems.is_empty());
+
+        let warn_within = Some(Duration::from_secs(48 * 3600));
+        token_summary(true, Some(expiry), now, warn_within, &mut problems);
+        assert_eq!(problems.len(), 1);
+        let summary = token_summary(false, None, now, None, &mut problems);
+        assert_eq!(summary, "rejected, expiry unknown");
+        assert_eq!(problems.len(), 2);
+    }
+}
@@ -1,12 +1,30 @@
-use crate::api::polling::wait_for_rollout;
+use crate::api::polling::{PollOptions, wait_for_plan_checks, wait_for_rollout};
 use crate::api::traits::BytebaseApi;
 use crate::api::types::{
-    Changelog, IssueName, PostSheetsResponse, Revision, SQLDialect, SheetName, SheetRequest,
+    Changelog, IssueDetails, IssueName, ListOptions, PROVENANCE_MARKER, PostSheetsResponse,
+    Revision, SQLDialect, SheetName, SheetRequest, StringStatement,
 };
-use crate::cli::MigrateArgs;
-use crate::config::{ConfigOperations, Environment, ProductionConfig};
+use crate::bundle::{self, Manifest};
+use crate::cli::{EnvDb, MigrateArgs};
+use crate::commands::review::{confirm_changelogs, review_changelogs};
+use crate::config::{AppConfig, ConfigOperations, DisplayTimezone, Environment, ProductionConfig};
 use crate::error::AppError;
+use crate::events::{EventSink, MigrateEvent};
+use crate::impact;
+use crate::input;
+use crate::issue_template::{self, IssueTemplate};
+use crate::jira::JiraClient;
+use crate::journal::{self, Journal, JournalEvent};
+use crate::output;
+use crate::sql::{self, Syntax};
+use crate::stats;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, stream};
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
 
 pub async fn handle_migrate_command<T: BytebaseApi>(
     args: MigrateArgs,
@@ -20,115 +38,861 @@ pub async fn handle_migrate_command_with_config<T: BytebaseApi, C: ConfigOperati
     args: MigrateArgs,
     api_client: &T,
     config_ops: &C,
+) -> Result<()> {
+    if args.interactive && args.to.as_deref() == Some(input::STDIN) {
+        return Err(AppError::InvalidArgs(
+            "--interactive reads answers from stdin, so --to cannot be '-'".to_string(),
+        )
+        .into());
+    }
+    let targets = if args.all_databases {
+        let config = config_ops.load_config().await?;
+        let targets = all_databases(&args.targets, api_client, &config).await?;
+        let names: Vec<String> = targets.iter().map(EnvDb::to_string).collect();
+        println!(
+            "Migrating {} database(s): {}",
+            targets.len(),
+            names.join(", ")
+        );
+        targets
+    } else {
+        split_database_lists(&args.targets)?
+    };
+    if args.offline && targets.len() > 1 {
+        return Err(AppError::InvalidArgs(
+            "--offline writes a single target's SQL to --out; pass one target".to_string(),
+        )
+        .into());
+    }
+    // Read once: with `--to -` every target shares what stdin held.
+    let to = args.to.as_deref().map(input::resolve_arg).transpose()?;
+    let to = to.as_deref();
+    // Opened once: every target appends its own tagged lines to the same file.
+    let events = args
+        .events_file
+        .as_deref()
+        .map(EventSink::file)
+        .transpose()?;
+    let events = events.as_ref();
+    if let [target] = targets.as_slice() {
+        // Nothing runs alongside a single target.
+        let args = MigrateArgs {
+            concurrency: 1,
+            ..args
+        };
+        return migrate_target(&args, None, target, to, events, api_client, config_ops).await;
+    }
+
+    let concurrency = args.concurrency as usize;
+    let args = &args;
+    let results: Vec<TargetResult> = stream::iter(&targets)
+        .map(|target| async move {
+            if concurrency == 1 {
+                println!("\n=== {target} ===");
+            } else {
+                println!("=== {target}: started ===");
+            }
+            let started = Instant::now();
+            let result =
+                migrate_target(args, None, target, to, events, api_client, config_ops).await;
+            if let Err(e) = &result {
+                eprintln!("Error: Migrating '{target}' failed: {e:#}");
+            } else if concurrency > 1 {
+                println!("=== {target}: done ===");
+            }
+            (target, started.elapsed(), result)
+        })
+        .buffered(concurrency)
+        .collect()
+        .await;
+    println!("\n{}", run_summary(&results));
+    match results
+        .into_iter()
+        .find_map(|(target, _, result)| Some((target, result.err()?)))
+    {
+        None => Ok(()),
+        // The first failure keeps its own exit code, e.g. 6 for a timed-out rollout.
+        Some((target, first)) => Err(first.context(format!(
+            "Migrating '{target}' failed, and possibly other targets; see the report above"
+        ))),
+    }
+}
+
+/// How one target of a multi-target run went, and how long it took.
+type TargetResult<'a> = (&'a EnvDb, Duration, Result<()>);
+
+/// Expands targets written as "<env>/db1,db2,db3" into one target per database.
+fn split_database_lists(targets: &[EnvDb]) -> Result<Vec<EnvDb>, AppError> {
+    let mut split = Vec::new();
+    for target in targets {
+        for db in target.db.split(',').map(str::trim) {
+            if db.is_empty() {
+                return Err(AppError::InvalidArgs(format!(
+                    "Empty database name in target '{target}'"
+                )));
+            }
+            split.push(EnvDb {
+                env: target.env.clone(),
+                db: db.to_string(),
+            });
+        }
+    }
+    Ok(split)
+}
+
+/// `--all-databases`: every database of each target's instance whose name starts with the
+/// target's database, in name order.
+async fn all_databases<T: BytebaseApi>(
+    targets: &[EnvDb],
+    api_client: &T,
+    config: &AppConfig,
+) -> Result<Vec<EnvDb>, AppError> {
+    let mut expanded = Vec::new();
+    for target in targets {
+        let env = config
+            .environments
+            .get(&target.env)
+            .ok_or_else(|| AppError::EnvNotFound(target.env.clone()))?;
+        let mut databases: Vec<String> = api_client
+            .get_databases(&env.instance)
+            .await?
+            .into_iter()
+            .filter(|db| db.starts_with(&target.db))
+            .collect();
+        if databases.is_empty() {
+            return Err(AppError::InvalidArgs(format!(
+                "No database of '{}' ({}) starts with '{}'",
+                target.env, env.instance, target.db
+            )));
+        }
+        databases.sort();
+        expanded.extend(databases.into_iter().map(|db| EnvDb {
+            env: target.env.clone(),
+            db,
+        }));
+    }
+    Ok(expanded)
+}
+
+/// Summary of a multi-target run: how many targets succeeded, then a table of every target
+/// with its result, duration and, for failures, the cause.
+fn run_summary(results: &[TargetResult]) -> String {
+    let failed = results.iter().filter(|(_, _, r)| r.is_err()).count();
+    let mut summary = format!(
+        "--- {} of {} target(s) migrated, {failed} failed ---",
+        results.len() - failed,
+        results.len()
+    );
+    let width = results
+        .iter()
+        .map(|(target, _, _)| target.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("TARGET".len());
+    summary.push_str(&format!("\n{:<width$} {:<8} TIME", "TARGET", "RESULT"));
+    for (target, elapsed, result) in results {
+        let target = target.to_string();
+        let line = match result {
+            Ok(()) => format!(
+                "{target:<width$} {:<8} {}",
+                "migrated",
+                format_duration(*elapsed)
+            ),
+            Err(e) => format!(
+                "{target:<width$} {:<8} {:<6} {e:#}",
+                "failed",
+                format_duration(*elapsed)
+            ),
+        };
+        summary.push_str(&format!("\n{}", line.trim_end()));
+    }
+    summary
+}
+
+/// Migrates `source_db` from `source` (default: `default.source_env`) to a single target, up
+/// to `to` or else the version its environment is pinned to.
+pub(crate) async fn migrate_target<T: BytebaseApi, C: ConfigOperations>(
+    args: &MigrateArgs,
+    source: Option<&str>,
+    target: &EnvDb,
+    to: Option<&str>,
+    events: Option<&EventSink>,
+    api_client: &T,
+    config_ops: &C,
 ) -> Result<()> {
     let config = config_ops.load_config().await?;
+    let to = match (to, args.source_at, config.pinned_version(&target.env)?) {
+        (Some(to), _, _) => to.to_string(),
+        (None, Some(issue), _) => issue.to_string(),
+        (None, None, Some(pinned)) => {
+            println!(
+                "No --to given; using issue #{pinned}, the version '{}' is pinned to.",
+                target.env
+            );
+            pinned.to_string()
+        }
+        (None, None, None) => {
+            return Err(AppError::InvalidArgs(format!(
+                "--to is required: '{}' has no pinned version (see `shelltide env set --pin-to`)",
+                target.env
+            ))
+            .into());
+        }
+    };
+    let to = to.as_str();
 
-    // Get default source environment - must be configured
-    let default_source_env = config.default_source_env.as_deref()
-        .ok_or_else(|| AppError::Config(
+    // Without an explicit source, the default source environment must be configured
+    let source_env_name = match source {
+        Some(name) => name,
+        None => config.default_source_env.as_deref().ok_or_else(|| AppError::Config(
             "default.source_env not set. Please run: shelltide config set default.source_env <env-name>".to_string()
-        ))?;
+        ))?,
+    };
     let source_env = config
         .environments
-        .get(default_source_env)
-        .ok_or_else(|| AppError::Config(
-            format!(
-                "Default source environment '{default_source_env}' not found. Please set a valid source environment: shelltide config set default.source_env <env-name>"
-            )
-        ))?;
+        .get(source_env_name)
+        .ok_or_else(|| match source {
+            Some(name) => AppError::EnvNotFound(name.to_string()),
+            None => AppError::Config(format!(
+                "Default source environment '{source_env_name}' not found. Please set a valid source environment: shelltide config set default.source_env <env-name>"
+            )),
+        })?;
     let target_env = config
         .environments
-        .get(&args.target.env)
-        .ok_or_else(|| AppError::EnvNotFound(args.target.env.clone()))?;
+        .get(&target.env)
+        .ok_or_else(|| AppError::EnvNotFound(target.env.clone()))?;
+    let target_db = config.mapped_database(&target.env, &target.db);
 
     println!(
         "Attempting to apply migrations from '{}' to '{}'...",
-        default_source_env, &args.target.env
+        source_env_name, &target.env
     );
+    if target_db != target.db {
+        println!(
+            "Database '{}' is mapped to '{target_db}' in '{}' (db_map).",
+            target.db, target.env
+        );
+    }
+
+    // A restored or hand-altered target must be judged by its current schema, not Bytebase's
+    // cached copy of it. A dry run changes nothing, so it does not ask Bytebase to sync either.
+    if !args.no_sync && !args.dry_run {
+        println!("Syncing '{}/{target_db}' in Bytebase...", target.env);
+        if let Err(e) = api_client
+            .sync_database(&target_env.instance, target_db)
+            .await
+        {
+            eprintln!("Warning: Failed to sync the target database: {e}");
+        }
+    }
 
     let source_latest_no = get_latest_done_issue_no(api_client, &source_env.project).await?;
-    let target_revision = api_client
-        .get_latests_revisions(&target_env.instance, &args.target.db)
-        .await?;
-    let target_latest_no = target_revision
-        .version
-        .as_ref()
-        .ok_or_else(|| AppError::ApiError("Target revision missing version".to_string()))?
-        .number;
+    let target_latest_no = match api_client
+        .latest_revision(&target_env.instance, target_db, config.revision.latest_by())
+        .await
+    {
+        Ok(revision) => {
+            revision
+                .version
+                .as_ref()
+                .ok_or_else(|| AppError::ApiError("Target revision missing version".to_string()))?
+                .number
+        }
+        // A database shelltide never touched has no revision to start from.
+        Err(AppError::NoRevision(database)) if args.assume_empty_revision => {
+            let baseline = args.baseline.unwrap_or(0);
+            println!(
+                "{database} has no revision yet; treating it as at issue #{baseline} (--assume-empty-revision)."
+            );
+            baseline
+        }
+        Err(AppError::NoRevision(database)) => {
+            return Err(AppError::NoRevision(format!(
+                "{database}. For a new database, pass --assume-empty-revision to apply the \
+                 history from the beginning, adding --baseline <issue> if its schema already \
+                 matches that issue."
+            ))
+            .into());
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     println!(
         "Source '{}' is at issue #{}, Target '{}' is at issue #{}.",
-        default_source_env, source_latest_no, &args.target.env, target_latest_no
+        source_env_name, source_latest_no, &target.env, target_latest_no
     );
 
-    let target_version = if args.to.eq_ignore_ascii_case("LATEST") {
-        source_latest_no
-    } else {
-        args.to.parse::<u32>().map_err(|_| {
-            AppError::InvalidArgs(format!(
-                "Invalid version '{}'. Must be an integer or 'LATEST'.",
-                args.to
-            ))
-        })?
-    };
+    let target_version = parse_target_version(to, source_latest_no)?;
 
     if target_latest_no == target_version {
         println!(
             "Target environment '{}' is already up-to-date. Nothing to apply.",
-            &args.target.env
+            &target.env
         );
         return Ok(());
     }
 
+    // A hand-typed issue number is easy to get wrong; show what it refers to before the
+    // changelogs it selects are confirmed.
+    if !to.eq_ignore_ascii_case("LATEST") {
+        match api_client
+            .get_issue(&source_env.project, target_version)
+            .await
+        {
+            Ok(issue) => println!(
+                "Target issue: {}",
+                issue_summary(&issue, &config.display.timezone())
+            ),
+            Err(e) => eprintln!("Warning: Could not read issue #{target_version}: {e}"),
+        }
+    }
+
+    let engine = resolve_engine(api_client, target_env).await?;
+
+    let max_changes = if args.confirm_large_run {
+        None
+    } else {
+        args.max_changes.or(config.migrate.max_changes)
+    };
+
+    if args.offline {
+        let out_dir = args
+            .out
+            .as_deref()
+            .ok_or_else(|| AppError::InvalidArgs("--offline requires --out".to_string()))?;
+        let current_version = target_latest_no;
+        let changelogs = select_changelogs(
+            api_client,
+            source_env,
+            &args.source_db,
+            current_version,
+            target_version,
+            args.source_at,
+            args.verbose,
+        )
+        .await?;
+        if changelogs.is_empty() {
+            println!("nothing to migrate");
+            return Ok(());
+        }
+        check_change_cap(changelogs.len(), max_changes)?;
+
+        println!("--- Checking Migrations ---");
+        if !engine.supports_sql_check() {
+            println!("Skipping SQL check: not supported for {engine} targets.");
+        }
+        for cl in changelogs.iter().filter(|_| engine.supports_sql_check()) {
+            api_client
+                .check_sql(&target_env.instance, target_db, &cl.statement.to_string())
+                .await?;
+            println!("Checked changelog for issue #{}", cl.issue_number());
+        }
+
+        let manifest = Manifest {
+            source_env: source_env_name.to_string(),
+            source_project: source_env.project.clone(),
+            source_database: args.source_db.clone(),
+            target_env: Some(target.env.clone()),
+            target_database: Some(target_db.to_string()),
+            from_issue: current_version,
+            to_issue: target_version,
+            created_at: Utc::now(),
+            created_by: config
+                .active_credentials()
+                .map(|c| c.service_account.clone()),
+            shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: bundle::build_entries(&changelogs),
+        };
+        bundle::write_dir(out_dir, &manifest, &changelogs).await?;
+        println!(
+            "Wrote {} changelog(s) and {} to {:?}",
+            changelogs.len(),
+            bundle::MANIFEST_FILE,
+            out_dir
+        );
+        return Ok(());
+    }
+
+    if args.dry_run {
+        let changelogs = select_changelogs(
+            api_client,
+            source_env,
+            &args.source_db,
+            target_latest_no,
+            target_version,
+            args.source_at,
+            args.verbose,
+        )
+        .await?;
+        if changelogs.is_empty() {
+            println!("nothing to migrate");
+            return Ok(());
+        }
+        check_change_cap(changelogs.len(), max_changes)?;
+        for cl in &changelogs {
+            println!("Would apply {}", cl.label());
+        }
+        println!("\n--- Revision (dry run, nothing was created) ---");
+        for line in revision_plan(
+            &changelogs,
+            &target_env.project,
+            target_latest_no,
+            target_version,
+            !args.no_wait,
+        ) {
+            println!("{line}");
+        }
+        return Ok(());
+    }
+
+    // Checked up front so a typo does not surface only after the rollouts.
+    let tracking_issue = args
+        .notify_issue
+        .as_ref()
+        .or(config.notify.issue.as_ref())
+        .map(|issue| parse_tracking_issue(issue, &source_env.project))
+        .transpose()?;
+    let jira = match &args.jira {
+        Some(key) => Some((JiraClient::new(&config.notify.jira)?, key)),
+        None => None,
+    };
+
     // Execute migrations
     println!("--- Applying Migrations ---");
-    let migrate_result = migrate(
+    let mut options = ApplyOptions::from_config(&config);
+    options.engine = engine;
+    options.wait = !args.no_wait;
+    options.poll.timeout = args.wait_timeout;
+    // Targets running side by side would overwrite each other's progress line; the summary
+    // table reports them instead.
+    options.poll.progress = args.concurrency == 1;
+    options.wait_for_server = args.wait_for_server;
+    options.plan_checks = !args.skip_plan_checks;
+    options.interactive = args.interactive;
+    options.verbose = args.verbose;
+    options.max_changes = max_changes;
+    options.source_at = args.source_at;
+    // Without a terminal (CI, or `--to -`) there is nobody to ask.
+    if !args.yes
+        && !args.interactive
+        && args.to.as_deref() != Some(input::STDIN)
+        && std::io::stdin().is_terminal()
+    {
+        options.confirm_target = Some(format!("{}/{target_db}", target.env));
+    }
+    options.issue_template = IssueTemplate::from_config(&config)?;
+    if let Some(template) = &mut options.issue_template {
+        template.set(
+            "source_env",
+            format!("{source_env_name}/{}", args.source_db),
+        );
+        template.set("target", format!("{}/{target_db}", target.env));
+        template.set("operator", issue_template::operator(&config));
+        if let Some(key) = &args.jira
+            && let Some(url) = &config.notify.jira.url
+        {
+            template.set(
+                "ticket_url",
+                format!("{}/browse/{key}", url.trim_end_matches('/')),
+            );
+        }
+    }
+    let journal = Journal::create(&journal::runs_dir(&config_ops.config_path()?))?;
+    if let Some(events) = events {
+        options.events = Some(Arc::new(
+            events.tagged(&target.to_string(), &journal.run_id),
+        ));
+    }
+    journal.record(&JournalEvent::Started {
+        source_env: source_env_name.to_string(),
+        source_database: args.source_db.clone(),
+        target_env: target.env.clone(),
+        target_database: target_db.to_string(),
+        target_project: target_env.project.clone(),
+        target_instance: target_env.instance.clone(),
+        source_project: source_env.project.clone(),
+        target_version,
+        started_at: Utc::now(),
+    })?;
+    println!(
+        "Run id: {} (if interrupted, continue with `shelltide resume {}`)",
+        journal.run_id, journal.run_id
+    );
+    options.run_id = Some(journal.run_id.clone());
+    if let Some(template) = &mut options.issue_template {
+        template.set("run_id", journal.run_id.clone());
+    }
+    options.journal = Some(journal);
+    let outcome = migrate(
         api_client,
         source_env,
         &args.source_db,
         target_env,
-        &args.target.db,
-        &target_revision,
-        &SQLDialect::MySQL,
+        target_db,
+        target_latest_no,
+        &options,
         target_version,
     )
     .await;
+    if outcome.aborted {
+        options.record(JournalEvent::Finished)?;
+        println!("Aborted. Nothing was applied.");
+        return Ok(());
+    }
+    print_summary(&outcome.summary, options.slow_threshold);
+
+    if let Some((jira, key)) = &jira {
+        let succeeded = outcome.error.is_none() && !outcome.skipped;
+        let comment = jira_comment(
+            &format!("{source_env_name}/{}", args.source_db),
+            &format!("{}/{target_db}", target.env),
+            target_latest_no,
+            outcome.last_applied.as_ref().map(|(issue, _)| issue.number),
+            target_version,
+            outcome.error.as_ref(),
+            options.run_id.as_deref().unwrap_or_default(),
+        );
+        let transition = if succeeded {
+            &config.notify.jira.on_success
+        } else {
+            &config.notify.jira.on_failure
+        };
+        match notify_jira(jira, key, &comment, transition.as_deref()).await {
+            Ok(()) => println!("Reported the promotion on Jira issue {key}."),
+            Err(e) => {
+                eprintln!("Warning: Failed to report the promotion on Jira issue {key}: {e:#}")
+            }
+        }
+    }
 
     // create revision - use target version if all successful, otherwise use last applied issue
-    let Some((last_issue, last_sheet, all_successful)) = migrate_result else {
+    let run_id = options.run_id.clone().unwrap_or_default();
+    let Some((last_issue, last_sheet)) = outcome.last_applied else {
+        if let Some(e) = outcome.error {
+            return Err(e.into());
+        }
+        if let Some(issue) = outcome.in_flight {
+            println!("--- Rollout Started (not waiting for completion) ---\n");
+            println!(
+                "The revision is recorded once the rollout of issue #{issue} is done: run `shelltide resume {run_id}`."
+            );
+            return Ok(());
+        }
+        options.record(JournalEvent::Finished)?;
         println!("nothing to migrate");
         return Ok(());
     };
-    let revision_issue_number = if all_successful {
-        target_version
-    } else {
-        last_issue.number
-    };
+    let completed = outcome.error.is_none() && !outcome.skipped;
+    let revision_issue_number = revision_issue(
+        completed,
+        outcome.in_flight,
+        last_issue.number,
+        target_version,
+    );
 
-    let revision_name = format!("{}#{}", last_issue.project, revision_issue_number);
-    let revision_version = format!("{}#{}", last_issue.project, revision_issue_number);
-    let revision_sheet = last_sheet.to_string();
     println!(
         "Migrated to issue #{}. Creating revision...",
         last_issue.number
     );
+    options
+        .during_maintenance(|| {
+            record_revision(
+                api_client,
+                target_env,
+                target_db,
+                &last_issue.project,
+                revision_issue_number,
+                &last_sheet,
+            )
+        })
+        .await?;
+    options.record(JournalEvent::RevisionRecorded {
+        issue: revision_issue_number,
+    })?;
+    options.emit(MigrateEvent::RevisionWritten {
+        issue: revision_issue_number,
+    })?;
+
+    // A timed-out rollout may still finish later and a server in maintenance needs a re-run;
+    // surface both with their own exit codes.
+    if let Some(e @ (AppError::RolloutTimeout(_) | AppError::ServerMaintenance(_))) = outcome.error
+    {
+        return Err(e.into());
+    }
+
+    if let Some(tracking) = &tracking_issue {
+        let comment = promotion_comment(
+            &format!("{source_env_name}/{}", args.source_db),
+            &format!("{}/{target_db}", target.env),
+            target_latest_no,
+            if completed {
+                target_version
+            } else {
+                revision_issue_number
+            },
+            target_version,
+            options.wait,
+        );
+        match api_client
+            .create_issue_comment(&tracking.0, tracking.1, &comment)
+            .await
+        {
+            Ok(()) => println!("Commented on tracking issue {}#{}.", tracking.0, tracking.1),
+            Err(e) => eprintln!(
+                "Warning: Failed to comment on tracking issue {}#{}: {e}",
+                tracking.0, tracking.1
+            ),
+        }
+    }
+
+    // A failed changelog fails the run, so a multi-target run reports it with the others.
+    if let Some(e) = outcome.error {
+        println!("--- Migration Stopped at a Failed Changelog ---\n");
+        return Err(e.into());
+    }
+    if options.wait {
+        options.record(JournalEvent::Finished)?;
+        println!("--- Migration Complete ---\n");
+    } else {
+        println!("--- Rollout Started (not waiting for completion) ---\n");
+        if let Some(issue) = outcome.in_flight {
+            println!(
+                "The revision stops at issue #{revision_issue_number} until the rollout of issue #{issue} is done: run `shelltide resume {run_id}`."
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The issue the revision of a run records: `target_version` when every selected changelog
+/// was applied, else the last one whose rollout finished. A rollout left running under
+/// `--no-wait` may still fail, so it never advances the revision.
+fn revision_issue(
+    completed: bool,
+    in_flight: Option<u32>,
+    last_applied: u32,
+    target_version: u32,
+) -> u32 {
+    if completed && in_flight.is_none() {
+        target_version
+    } else {
+        last_applied
+    }
+}
+
+/// The engine of `env`: as configured, else as reported by its instance. There is no fallback:
+/// a guessed engine would split the SQL and create the sheets with the wrong dialect.
+pub(crate) async fn resolve_engine<T: BytebaseApi>(
+    api_client: &T,
+    env: &Environment,
+) -> Result<SQLDialect, AppError> {
+    if let Some(engine) = &env.engine {
+        return Ok(engine.clone());
+    }
+    let instance = api_client.get_instance(&env.instance).await?;
+    instance.engine.ok_or_else(|| {
+        AppError::Config(format!(
+            "Instance '{}' does not report an engine shelltide knows; set it with `shelltide env set <env> --engine <ENGINE>`",
+            env.instance
+        ))
+    })
+}
+
+/// The syntax `env`'s statements are split with for display only, where a wrong guess is
+/// harmless: an engine that cannot be resolved falls back to MySQL with a warning.
+pub(crate) async fn display_syntax<T: BytebaseApi>(api_client: &T, env: &Environment) -> Syntax {
+    match resolve_engine(api_client, env).await {
+        Ok(engine) => Syntax::for_engine(&engine),
+        Err(e) => {
+            eprintln!("Warning: {e}; reading the statements as MySQL.");
+            Syntax::MySql
+        }
+    }
+}
+
+/// Creates the revision that marks `target_database` as migrated up to `project#issue_number`.
+pub(crate) async fn record_revision<T: BytebaseApi>(
+    api_client: &T,
+    target_env: &Environment,
+    target_database: &str,
+    project: &str,
+    issue_number: u32,
+    sheet: &SheetName,
+) -> Result<Revision, AppError> {
+    let revision_name = format!("{project}#{issue_number}");
+    let revision_version = format!("{project}#{issue_number}");
     api_client
         .create_revision(
             &target_env.instance,
-            &args.target.db,
+            target_database,
             &revision_name,
             &revision_version,
-            &revision_sheet,
+            &sheet.to_string(),
         )
-        .await?;
+        .await
+}
+
+/// The revision `migrate` would write for each way applying `changelogs` can end, for
+/// `--dry-run`: all of them applied, or each one failing after those before it were applied.
+/// Mirrors the bookkeeping after [`migrate`]; sheet names are only known once created.
+fn revision_plan(
+    changelogs: &[Changelog],
+    target_project: &str,
+    current_version: u32,
+    target_version: u32,
+    wait: bool,
+) -> Vec<String> {
+    let revision = |applied: &[Changelog], completed: bool| -> [String; 2] {
+        match applied.last().and_then(|cl| Some((cl.issue.as_ref()?, cl))) {
+            None => [
+                "-".to_string(),
+                format!("none written, the target stays at #{current_version}"),
+            ],
+            Some((issue, cl)) => {
+                let number = if completed {
+                    target_version
+                } else {
+                    issue.number
+                };
+                [
+                    format!("{}#{number}", issue.project),
+                    format!(
+                        "projects/{target_project}/sheets/<last new sheet of {}>",
+                        cl.label()
+                    ),
+                ]
+            }
+        }
+    };
+
+    let mut rows = Vec::new();
+    // Without waiting, the last rollout is left running and the revision stops before it.
+    let (label, [version, sheet]) = if wait {
+        ("all applied".to_string(), revision(changelogs, true))
+    } else {
+        (
+            "all started (--no-wait)".to_string(),
+            revision(&changelogs[..changelogs.len() - 1], false),
+        )
+    };
+    rows.push([label, version, sheet]);
+    for (index, cl) in changelogs.iter().enumerate() {
+        let [version, sheet] = revision(&changelogs[..index], false);
+        rows.push([format!("{} fails", cl.label()), version, sheet]);
+    }
+    output::table_lines(["OUTCOME", "VERSION", "SHEET"], &rows)
+}
 
-    println!("--- Migration Complete ---\n");
+/// Parses a tracking issue, `<number>` in `default_project` or `<project>#<number>`.
+pub(crate) fn parse_tracking_issue(
+    value: &str,
+    default_project: &str,
+) -> Result<(String, u32), AppError> {
+    let (project, number) = match value.split_once('#') {
+        Some((project, number)) if !project.is_empty() => (project, number),
+        _ => (default_project, value.trim_start_matches('#')),
+    };
+    let number = number.parse::<u32>().map_err(|_| {
+        AppError::InvalidArgs(format!(
+            "Invalid tracking issue '{value}'. Use '<number>' or '<project>#<number>'."
+        ))
+    })?;
+    Ok((project.to_string(), number))
+}
 
+/// The comment left on the tracking issue after promoting `source` to `target`, where the
+/// target went from `from_issue` to `to_issue` of the requested `target_version`.
+fn promotion_comment(
+    source: &str,
+    target: &str,
+    from_issue: u32,
+    to_issue: u32,
+    target_version: u32,
+    waited: bool,
+) -> String {
+    let mut comment = format!(
+        "shelltide promoted {source} to {target}: issues after #{from_issue} up to #{to_issue}."
+    );
+    if to_issue < target_version {
+        comment.push_str(&format!(
+            " Stopped before the requested #{target_version}; see the migrate output for what was not applied."
+        ));
+    }
+    if !waited {
+        comment.push_str(" Rollouts were started without waiting for them to finish.");
+    }
+    comment
+}
+
+/// Comments `comment` on Jira issue `key`, then applies `transition` if one is configured.
+async fn notify_jira(
+    jira: &JiraClient,
+    key: &str,
+    comment: &str,
+    transition: Option<&str>,
+) -> Result<()> {
+    jira.comment(key, comment).await?;
+    if let Some(transition) = transition {
+        jira.transition(key, transition).await?;
+    }
     Ok(())
 }
 
+/// The Jira comment for promoting `source` to `target` from `from_issue` towards
+/// `target_version`, having applied up to `last_applied` before stopping at `error`, if any.
+fn jira_comment(
+    source: &str,
+    target: &str,
+    from_issue: u32,
+    last_applied: Option<u32>,
+    target_version: u32,
+    error: Option<&AppError>,
+    run_id: &str,
+) -> String {
+    let applied = match last_applied {
+        Some(last) => format!("applied issues after #{from_issue} up to #{last}"),
+        None => "applied nothing".to_string(),
+    };
+    let mut comment = match error {
+        None => {
+            format!("shelltide promoted {source} to {target} towards #{target_version}: {applied}.")
+        }
+        Some(e) => format!(
+            "shelltide failed to promote {source} to {target} towards #{target_version}: {applied}, then stopped with: {e}"
+        ),
+    };
+    if !run_id.is_empty() {
+        comment.push_str(&format!("\nRun id: {run_id}"));
+    }
+    comment
+}
+
+/// e.g. `#354 "Add itembox table" (DONE, created 2026-01-27T09:11:27Z)`.
+pub(crate) fn issue_summary(issue: &IssueDetails, timezone: &DisplayTimezone) -> String {
+    let created = issue.create_time.map_or(String::new(), |time| {
+        format!(", created {}", timezone.format(time))
+    });
+    format!(
+        "#{} \"{}\" ({}{created})",
+        issue.name.number, issue.title, issue.status
+    )
+}
+
+/// Resolves a `--to` argument (an issue number or "LATEST") to an issue number.
+pub(crate) fn parse_target_version(to: &str, latest: u32) -> Result<u32, AppError> {
+    if to.eq_ignore_ascii_case("LATEST") {
+        return Ok(latest);
+    }
+    to.parse::<u32>().map_err(|_| {
+        AppError::InvalidArgs(format!(
+            "Invalid version '{to}'. Must be an integer or 'LATEST'."
+        ))
+    })
+}
+
 /// A helper function to get the highest "DONE" issue number for a project.
-async fn get_latest_done_issue_no<T: BytebaseApi>(
+pub(crate) async fn get_latest_done_issue_no<T: BytebaseApi>(
     api_client: &T,
     project: &str,
 ) -> Result<u32, AppError> {
@@ -136,53 +900,1016 @@ async fn get_latest_done_issue_no<T: BytebaseApi>(
     Ok(issues.iter().map(|i| i.name.number).max().unwrap_or(0))
 }
 
-async fn apply_changelog<T: BytebaseApi>(
+/// Whether a source changelog belongs to the pending set of a target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Selection {
+    Pending,
+    /// The changelog has not finished (or failed) in the source.
+    NotDone,
+    /// A snapshot of the schema Bytebase found on the source; it has nothing to apply.
+    Baseline,
+    /// The changelog records no statement, so there is nothing to apply.
+    EmptyStatement,
+    /// shelltide created it by promoting a change from another environment; that change is
+    /// promoted from its own source, not again from here.
+    Promoted,
+    /// The changelog is not linked to an issue, so no revision can refer to it.
+    NoIssue,
+    /// The changelog belongs to another database than the one asked for.
+    OtherDatabase,
+    /// The target revision is already at or past its issue.
+    AlreadyApplied,
+    /// Its issue is newer than the version being migrated to.
+    BeyondTarget,
+    /// It reached the source after the `--source-at` issue did.
+    AfterSourceAt,
+    /// The baseline a fresh target is rebuilt from; its schema is applied first.
+    ReplayedBaseline,
+    /// It predates the baseline a fresh target is rebuilt from, whose schema already has it.
+    CoveredByBaseline,
+}
+
+/// Decides which changelogs of `source_database` are pending for a target whose revision is at
+/// `current_version` and that is migrated to `target_version`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Selector<'a> {
+    pub source_database: &'a str,
+    pub current_version: u32,
+    pub target_version: u32,
+    /// The baseline a target without revision is rebuilt from, see [`Self::replay_baseline`].
+    pub baseline: Option<&'a Changelog>,
+    /// With `--source-at`, the issue whose changelog marks the source state to migrate to and
+    /// that changelog's create time; later changelogs are left out.
+    pub source_at: Option<(u32, DateTime<Utc>)>,
+}
+
+impl Selector<'_> {
+    pub(crate) fn select(&self, changelog: &Changelog) -> Selection {
+        let replayed = self.baseline.is_some_and(|baseline| {
+            baseline.name.database == changelog.name.database
+                && baseline.name.number == changelog.name.number
+        });
+        if changelog.status != "DONE" {
+            Selection::NotDone
+        } else if replayed {
+            Selection::ReplayedBaseline
+        } else if changelog.is_baseline() {
+            Selection::Baseline
+        } else if changelog.statement.is_empty() {
+            Selection::EmptyStatement
+        } else if changelog.statement.is_promoted() {
+            Selection::Promoted
+        } else if changelog.issue.is_none() {
+            Selection::NoIssue
+        } else if changelog.name.database != self.source_database {
+            Selection::OtherDatabase
+        } else if changelog.issue_number() <= self.current_version {
+            Selection::AlreadyApplied
+        } else if changelog.issue_number() > self.target_version {
+            Selection::BeyondTarget
+        } else if self
+            .source_at
+            .is_some_and(|(_, cutoff)| changelog.create_time > cutoff)
+        {
+            Selection::AfterSourceAt
+        } else if self
+            .baseline
+            .is_some_and(|baseline| changelog.create_time < baseline.create_time)
+        {
+            Selection::CoveredByBaseline
+        } else {
+            Selection::Pending
+        }
+    }
+
+    /// Why `changelog` is left out of the pending set, or `None` if it is pending.
+    pub(crate) fn exclusion_reason(&self, changelog: &Changelog) -> Option<String> {
+        let reason = match self.select(changelog) {
+            Selection::Pending | Selection::ReplayedBaseline => return None,
+            Selection::NotDone => format!("not done (status {:?})", changelog.status),
+            Selection::Baseline => "baseline (schema snapshot, nothing to apply)".to_string(),
+            Selection::EmptyStatement => "empty statement".to_string(),
+            Selection::Promoted => {
+                "promoted here by shelltide from another environment".to_string()
+            }
+            Selection::NoIssue => "not linked to an issue".to_string(),
+            Selection::OtherDatabase => format!(
+                "different database '{}', expected '{}'",
+                changelog.name.database, self.source_database
+            ),
+            Selection::AlreadyApplied => format!(
+                "already applied (target revision is #{})",
+                self.current_version
+            ),
+            Selection::BeyondTarget => {
+                format!("beyond the target version #{}", self.target_version)
+            }
+            Selection::AfterSourceAt => format!(
+                "created after the changelog of issue #{} (--source-at)",
+                self.source_at.map_or(0, |(issue, _)| issue)
+            ),
+            Selection::CoveredByBaseline => format!(
+                "covered by the schema of the replayed baseline ({})",
+                self.baseline
+                    .map_or(String::new(), |baseline| baseline.label())
+            ),
+        };
+        Some(reason)
+    }
+
+    /// The baseline a target without any revision is rebuilt from: the latest baseline of the
+    /// source taken before its last pending changelog. Its schema has everything before it,
+    /// including changes made outside of any changelog, so replaying the older changelogs
+    /// instead would build a different schema.
+    pub(crate) fn replay_baseline<'c>(&self, changelogs: &'c [Changelog]) -> Option<&'c Changelog> {
+        if self.current_version != 0 {
+            return None;
+        }
+        let last_pending = changelogs
+            .iter()
+            .filter(|cl| self.select(cl) == Selection::Pending)
+            .map(|cl| cl.create_time)
+            .max()?;
+        changelogs
+            .iter()
+            .filter(|cl| {
+                self.select(cl) == Selection::Baseline
+                    && cl.name.database == self.source_database
+                    && !cl.schema.is_empty()
+                    && cl.create_time < last_pending
+            })
+            .max_by_key(|cl| cl.create_time)
+    }
+
+    /// The latest baseline of the source taken between the target's last applied changelog
+    /// and a pending one. Changes captured by such a baseline were made outside of any
+    /// changelog, so replaying the pending changelogs would skip them.
+    pub(crate) fn crossed_baseline<'c>(
+        &self,
+        changelogs: &'c [Changelog],
+    ) -> Option<&'c Changelog> {
+        let times = |selection| {
+            changelogs
+                .iter()
+                .filter(move |cl| self.select(cl) == selection)
+                .map(|cl| cl.create_time)
+        };
+        let last_applied = times(Selection::AlreadyApplied).max()?;
+        let first_pending = times(Selection::Pending).min()?;
+        changelogs
+            .iter()
+            .filter(|cl| {
+                self.select(cl) == Selection::Baseline
+                    && cl.name.database == self.source_database
+                    && last_applied < cl.create_time
+                    && cl.create_time < first_pending
+            })
+            .max_by_key(|cl| cl.create_time)
+    }
+}
+
+/// Fetches the source changelogs in `(current_version, target_version]`, ordered by create time.
+/// With `source_at`, only those the source had when the changelog of that issue was created
+/// are kept. With `verbose`, every changelog left out is printed with the reason.
+pub(crate) async fn select_changelogs<T: BytebaseApi>(
+    api_client: &T,
+    source_env: &Environment,
+    source_database: &str,
+    current_version: u32,
+    target_version: u32,
+    source_at: Option<u32>,
+    verbose: bool,
+) -> Result<Vec<Changelog>, AppError> {
+    let mut changelogs = api_client
+        .get_changelogs(&source_env.instance, source_database, &ListOptions::full())
+        .await?;
+    changelogs.sort_by_key(|c| c.create_time);
+    let source_at = source_at
+        .map(|issue| source_state_at(&changelogs, source_database, issue))
+        .transpose()
+        .map_err(|issue| {
+            AppError::InvalidArgs(format!(
+                "'{}/{source_database}' has no DONE changelog of issue #{issue} to take the source state from (--source-at)",
+                source_env.instance
+            ))
+        })?;
+    let mut selector = Selector {
+        source_database,
+        current_version,
+        target_version,
+        baseline: None,
+        source_at,
+    };
+
+    let baseline = selector.replay_baseline(&changelogs).cloned();
+    if let Some(baseline) = &baseline {
+        println!(
+            "The target has no revision and '{}/{source_database}' has a baseline ({}) at {}: \
+             its schema is applied first, followed by the changelogs after it.",
+            source_env.instance,
+            baseline.label(),
+            baseline.create_time.to_rfc3339(),
+        );
+    }
+    selector.baseline = baseline.as_ref();
+
+    if let Some(baseline) = selector.crossed_baseline(&changelogs) {
+        return Err(AppError::BaselineCrossed(format!(
+            "'{}/{source_database}' got a baseline ({}) at {} after issue #{current_version}, the target's revision. \
+             Changes captured by the baseline are in no changelog, so they cannot be migrated. \
+             Bring the target's schema in line (e.g. from `shelltide dump`), then record its revision with `shelltide revision set`.",
+            source_env.instance,
+            baseline.name,
+            baseline.create_time.to_rfc3339(),
+        )));
+    }
+
+    if let Some((issue, _)) = source_at {
+        let late: Vec<String> = changelogs
+            .iter()
+            .filter(|cl| selector.select(cl) == Selection::AfterSourceAt)
+            .map(Changelog::label)
+            .collect();
+        if !late.is_empty() {
+            eprintln!(
+                "Warning: {} reached the source after issue #{issue} and {} left out (--source-at). \
+                 The revision moves past them, so later runs will not apply them either.",
+                late.join(", "),
+                if late.len() == 1 { "is" } else { "are" }
+            );
+        }
+    }
+    changelogs.retain(|cl| match selector.exclusion_reason(cl) {
+        None => true,
+        Some(reason) => {
+            if verbose {
+                println!("  Excluded {}: {reason}", cl.label());
+            }
+            false
+        }
+    });
+    for cl in &mut changelogs {
+        if cl.is_baseline() {
+            cl.statement = StringStatement(cl.schema.clone());
+        }
+    }
+    Ok(changelogs)
+}
+
+/// `issue` and the create time of its latest DONE changelog in `source_database`, the point
+/// `--source-at` takes the source state at. Fails with `issue` if it has no such changelog.
+fn source_state_at(
+    changelogs: &[Changelog],
+    source_database: &str,
+    issue: u32,
+) -> Result<(u32, DateTime<Utc>), u32> {
+    changelogs
+        .iter()
+        .filter(|cl| {
+            cl.status == "DONE"
+                && cl.issue.is_some()
+                && cl.issue_number() == issue
+                && cl.name.database == source_database
+        })
+        .map(|cl| cl.create_time)
+        .max()
+        .map(|cutoff| (issue, cutoff))
+        .ok_or(issue)
+}
+
+/// Refuses a run of `count` changelogs above the `max_changes` cap, so an accidental
+/// `--to LATEST` against a target far behind does not apply hundreds of them.
+fn check_change_cap(count: usize, max_changes: Option<u32>) -> Result<(), AppError> {
+    match max_changes {
+        Some(max) if count > max as usize => Err(AppError::InvalidArgs(format!(
+            "{count} changelogs are pending, more than the limit of {max} per run \
+             (--max-changes / migrate.max_changes). Nothing was applied. Re-run with \
+             --confirm-large-run to apply them all, or pick a nearer --to."
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// How often a write refused for maintenance is retried under `--wait-for-server`.
+const MAINTENANCE_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Settings that shape how each changelog is applied to the target.
+#[derive(Debug, Clone)]
+pub(crate) struct ApplyOptions {
+    pub engine: SQLDialect,
+    /// Statements larger than this many bytes are split across several sheets.
+    pub max_sheet_size: usize,
+    /// Whether to block until the final rollout finishes.
+    pub wait: bool,
+    /// How rollouts are polled while waiting.
+    pub poll: PollOptions,
+    /// Journal that every step is recorded to, for `shelltide resume`.
+    pub journal: Option<Journal>,
+    /// Where `--events` lifecycle events are written.
+    pub events: Option<Arc<EventSink>>,
+    /// Whether to wait for Bytebase's plan checks and stop on failed ones before rolling out.
+    pub plan_checks: bool,
+    /// Rollouts taking longer than this are flagged in the summary.
+    pub slow_threshold: Duration,
+    /// How long to keep retrying writes while Bytebase is in maintenance mode.
+    pub wait_for_server: Option<Duration>,
+    /// Id of this migrate invocation, embedded in the sheets and issues it creates.
+    pub run_id: Option<String>,
+    /// Whether each selected changelog is shown and confirmed before anything is applied.
+    pub interactive: bool,
+    /// Whether changelogs left out of the selection are printed with the reason.
+    pub verbose: bool,
+    /// Template the descriptions of the created issues are filled in from.
+    pub issue_template: Option<IssueTemplate>,
+    /// Most changelogs a run may apply, see [`check_change_cap`].
+    pub max_changes: Option<u32>,
+    /// Source issue whose state the run migrates to (`--source-at`), see [`Selector::source_at`].
+    pub source_at: Option<u32>,
+    /// Bytebase web UI address (`credentials.url`) that links to created resources start with.
+    pub web_url: Option<String>,
+    /// Target the selected changelogs are listed and confirmed for before anything is applied;
+    /// no confirmation when `None`.
+    pub confirm_target: Option<String>,
+}
+
+impl ApplyOptions {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            engine: SQLDialect::MySQL,
+            max_sheet_size: config.sheet.max_size(),
+            wait: true,
+            poll: PollOptions {
+                interval: config.rollout.poll_interval(),
+                not_started_timeout: config.rollout.not_started_timeout(),
+                timeout: None,
+                progress: true,
+            },
+            journal: None,
+            events: None,
+            plan_checks: true,
+            slow_threshold: config.rollout.slow_threshold(),
+            wait_for_server: None,
+            run_id: None,
+            interactive: false,
+            verbose: false,
+            issue_template: None,
+            max_changes: None,
+            source_at: None,
+            web_url: config
+                .active_credentials()
+                .map(|c| c.url.trim_end_matches('/').to_string()),
+            confirm_target: None,
+        }
+    }
+
+    /// The Bytebase UI page of resource `name`, such as `projects/prod/issues/12`.
+    fn link(&self, name: &impl std::fmt::Display) -> Option<String> {
+        self.web_url.as_ref().map(|url| format!("{url}/{name}"))
+    }
+
+    /// Tags Bytebase objects created for `change`, e.g.
+    /// `shelltide run 20240101120000-1a2b3c4d, issue #42`.
+    fn label(&self, change: SourceChange) -> String {
+        match &self.run_id {
+            Some(run_id) => format!("shelltide run {run_id}, {change}"),
+            None => format!("shelltide, {change}"),
+        }
+    }
+
+    /// Description of the issue created for `change`: the filled-in `issue_template`, if any,
+    /// followed by the provenance label.
+    fn issue_description(&self, change: SourceChange) -> String {
+        let footer = format!("Created by {}.", self.label(change));
+        let reference = match change {
+            SourceChange::Issue(issue) => format!("#{issue}"),
+            SourceChange::Baseline(_) => change.to_string(),
+        };
+        match &self.issue_template {
+            Some(template) => format!("{}\n\n{footer}", template.render(&reference).trim_end()),
+            None => footer,
+        }
+    }
+
+    /// Runs `call`, retrying it every [`MAINTENANCE_RETRY_INTERVAL`] while Bytebase is in
+    /// maintenance mode, for at most `wait_for_server`. Without it the error is returned as is.
+    pub(crate) async fn during_maintenance<R, F, Fut>(&self, mut call: F) -> Result<R, AppError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, AppError>>,
+    {
+        let started = Instant::now();
+        loop {
+            match call().await {
+                Err(AppError::ServerMaintenance(message)) => {
+                    let waiting = self
+                        .wait_for_server
+                        .is_some_and(|limit| started.elapsed() < limit);
+                    if !waiting {
+                        if self.wait_for_server.is_none() {
+                            eprintln!(
+                                "  Bytebase is in maintenance mode. Re-run once it accepts writes again, or pass --wait-for-server to wait for it."
+                            );
+                        }
+                        return Err(AppError::ServerMaintenance(message));
+                    }
+                    eprintln!(
+                        "  Bytebase is in maintenance mode ({message}); retrying in {}s...",
+                        MAINTENANCE_RETRY_INTERVAL.as_secs()
+                    );
+                    sleep(MAINTENANCE_RETRY_INTERVAL).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Records `event` to the run journal, if there is one.
+    fn record(&self, event: JournalEvent) -> Result<(), AppError> {
+        match &self.journal {
+            Some(journal) => journal.record(&event),
+            None => Ok(()),
+        }
+    }
+
+    /// Emits `event` to the `--events` stream, if there is one.
+    fn emit(&self, event: MigrateEvent) -> Result<(), AppError> {
+        match &self.events {
+            Some(events) => events.emit(&event),
+            None => Ok(()),
+        }
+    }
+
+    /// Records the journal event `event` builds for the issue of `change`. The journal tracks
+    /// the revision, which the baseline cannot move, so nothing is recorded for it.
+    fn record_for(
+        &self,
+        change: SourceChange,
+        event: impl FnOnce(u32) -> JournalEvent,
+    ) -> Result<(), AppError> {
+        change
+            .issue()
+            .map_or(Ok(()), |issue| self.record(event(issue)))
+    }
+
+    /// Emits the event `event` builds for the issue of `change`. Events are keyed by source
+    /// issue, so none are emitted for the baseline.
+    fn emit_for(
+        &self,
+        change: SourceChange,
+        event: impl FnOnce(u32) -> MigrateEvent,
+    ) -> Result<(), AppError> {
+        change
+            .issue()
+            .map_or(Ok(()), |issue| self.emit(event(issue)))
+    }
+}
+
+/// What a changelog applies: a source issue, or the replayed source baseline, which has no
+/// issue and goes by its changelog number instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SourceChange {
+    Issue(u32),
+    Baseline(u32),
+}
+
+impl SourceChange {
+    fn of(changelog: &Changelog) -> Self {
+        match &changelog.issue {
+            Some(issue) => SourceChange::Issue(issue.number),
+            None => SourceChange::Baseline(changelog.name.number),
+        }
+    }
+
+    /// The source issue, or `None` for the baseline.
+    fn issue(self) -> Option<u32> {
+        match self {
+            SourceChange::Issue(issue) => Some(issue),
+            SourceChange::Baseline(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SourceChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceChange::Issue(issue) => write!(f, "issue #{issue}"),
+            SourceChange::Baseline(changelog) => write!(f, "baseline changelog {changelog}"),
+        }
+    }
+}
+
+/// Checks `statement` (the changelog of `change`) against the target and runs it
+/// through the Sheet → Plan → Issue → Rollout workflow, waiting for the rollout to finish.
+pub(crate) async fn apply_changelog<T: BytebaseApi>(
     api_client: &T,
     target_env: &Environment,
     target_database: &str,
-    source_changelog: &Changelog,
-    engine: &SQLDialect,
+    change: SourceChange,
+    statement: &StringStatement,
+    options: &ApplyOptions,
 ) -> Result<PostSheetsResponse, AppError> {
-    // SQL check in target project
-    api_client
-        .check_sql(
-            &target_env.instance,
-            target_database,
-            &source_changelog.statement.to_string(),
-        )
-        .await?;
+    let sheet_names = prepare_changelog(
+        api_client,
+        target_env,
+        target_database,
+        change,
+        statement,
+        options,
+    )
+    .await?;
+    execute_changelog(
+        api_client,
+        target_env,
+        target_database,
+        change,
+        sheet_names,
+        options,
+        true,
+        &mut None,
+    )
+    .await
+}
 
-    let sheet_req = SheetRequest {
-        sql_statement: source_changelog.statement.clone().into(),
-        engine: engine.clone(),
+/// Checks `statement` against the target and creates its sheets. Statements above the sheet
+/// size limit are split on statement boundaries into several sheets, returned in run order.
+async fn prepare_changelog<T: BytebaseApi>(
+    api_client: &T,
+    target_env: &Environment,
+    target_database: &str,
+    change: SourceChange,
+    statement: &StringStatement,
+    options: &ApplyOptions,
+) -> Result<Vec<SheetName>, AppError> {
+    let chunks = chunk_changelog(change, statement, options)?;
+    print_split(statement, &chunks);
+    check_chunks(
+        api_client,
+        target_env,
+        target_database,
+        change,
+        &chunks,
+        options,
+    )
+    .await?;
+    create_sheets(api_client, target_env, change, chunks, options).await
+}
+
+/// The provenance comment every sheet of `change` starts with, so a target used as
+/// a source later does not offer these changes as its own.
+fn provenance(change: SourceChange, options: &ApplyOptions) -> String {
+    format!("{PROVENANCE_MARKER} {}\n", options.label(change))
+}
+
+/// Splits `statement` on statement boundaries into chunks that fit a sheet together with the
+/// provenance comment.
+fn chunk_changelog(
+    change: SourceChange,
+    statement: &StringStatement,
+    options: &ApplyOptions,
+) -> Result<Vec<String>, AppError> {
+    sql::chunk_statements(
+        &statement.0,
+        options
+            .max_sheet_size
+            .saturating_sub(provenance(change, options).len())
+            .max(1),
+        Syntax::for_engine(&options.engine),
+    )
+}
+
+fn print_split(statement: &StringStatement, chunks: &[String]) {
+    if chunks.len() > 1 {
+        println!(
+            "  Statement is {} bytes; splitting into {} sheets.",
+            statement.0.len(),
+            chunks.len()
+        );
+    }
+}
+
+/// SQL check of `chunks` in the target, for engines Bytebase can review. It must only run once
+/// the changelogs before this one were applied, so it sees the schema they produce.
+async fn check_chunks<T: BytebaseApi>(
+    api_client: &T,
+    target_env: &Environment,
+    target_database: &str,
+    change: SourceChange,
+    chunks: &[String],
+    options: &ApplyOptions,
+) -> Result<(), AppError> {
+    if !options.engine.supports_sql_check() {
+        println!(
+            "  Skipping SQL check: not supported for {} targets.",
+            options.engine
+        );
+        return Ok(());
+    }
+    for chunk in chunks {
+        options
+            .during_maintenance(|| {
+                api_client.check_sql(&target_env.instance, target_database, chunk)
+            })
+            .await?;
+    }
+    options.emit_for(change, |issue| MigrateEvent::CheckPassed { issue })
+}
+
+/// Creates one sheet per chunk of `change`, in run order. Creating a sheet does not
+/// depend on the target schema, so `migrate` does it ahead of the changelogs before this one.
+async fn create_sheets<T: BytebaseApi>(
+    api_client: &T,
+    target_env: &Environment,
+    change: SourceChange,
+    chunks: Vec<String>,
+    options: &ApplyOptions,
+) -> Result<Vec<SheetName>, AppError> {
+    let provenance = provenance(change, options);
+    let mut sheet_names = Vec::with_capacity(chunks.len());
+    let chunk_count = chunks.len();
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        let title = if chunk_count > 1 {
+            format!("{} ({}/{chunk_count})", options.label(change), i + 1)
+        } else {
+            options.label(change)
+        };
+        let sheet_req = SheetRequest {
+            title: Some(title),
+            sql_statement: StringStatement(format!("{provenance}{chunk}")).into(),
+            engine: options.engine.clone(),
+        };
+        let sheet_response = options
+            .during_maintenance(|| api_client.create_sheet(&target_env.project, sheet_req.clone()))
+            .await?;
+        options.emit_for(change, |issue| MigrateEvent::SheetCreated {
+            issue,
+            sheet: sheet_response.name.clone(),
+        })?;
+        sheet_names.push(sheet_response.name);
+    }
+    options.record_for(change, |issue| JournalEvent::SheetsCreated {
+        issue,
+        sheets: sheet_names.clone(),
+    })?;
+    Ok(sheet_names)
+}
+
+/// Most changelogs whose sheets are being created ahead of the changelog being applied.
+const SHEET_LOOKAHEAD: usize = 4;
+
+/// Runs `prepare` on `items` in order, up to `lookahead` at a time and ahead of whoever reads
+/// the returned receiver, which gets the results in item order. The returned future does the
+/// work and must be polled alongside the reader; it stops once the receiver is dropped.
+fn prepare_ahead<'a, I, R, F, Fut>(
+    items: I,
+    lookahead: usize,
+    prepare: F,
+) -> (
+    impl Future<Output = ()> + 'a,
+    tokio::sync::mpsc::Receiver<R>,
+)
+where
+    I: IntoIterator + 'a,
+    F: FnMut(I::Item) -> Fut + 'a,
+    Fut: Future<Output = R> + 'a,
+    R: 'a,
+{
+    let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    let work = async move {
+        let mut prepared = stream::iter(items).map(prepare).buffered(lookahead);
+        while let Some(result) = prepared.next().await {
+            if sender.send(result).await.is_err() {
+                break;
+            }
+        }
     };
+    (work, receiver)
+}
 
-    let sheet_response = api_client
-        .create_sheet(&target_env.project, sheet_req)
-        .await?;
-    let plan_response = api_client
-        .create_plan(
-            &target_env.project,
-            &target_env.instance,
-            target_database,
-            sheet_response.clone().name,
-        )
+/// Runs prepared sheets through Plan → Issue → Rollout and, when `wait` is set, waits for the
+/// rollout to finish. The sheets become ordered specs of a single plan. The id of the rollout is
+/// stored in `rollout` as soon as it exists, so it is known even if the rollout fails.
+#[allow(clippy::too_many_arguments)]
+async fn execute_changelog<T: BytebaseApi>(
+    api_client: &T,
+    target_env: &Environment,
+    target_database: &str,
+    change: SourceChange,
+    sheet_names: Vec<SheetName>,
+    options: &ApplyOptions,
+    wait: bool,
+    rollout: &mut Option<StartedRollout>,
+) -> Result<PostSheetsResponse, AppError> {
+    let plan_response = options
+        .during_maintenance(|| {
+            api_client.create_plan(
+                &target_env.project,
+                &target_env.instance,
+                target_database,
+                &sheet_names,
+            )
+        })
         .await?;
-    let issue_response = api_client
-        .create_issue(&target_env.project, &plan_response.name)
+    options.record_for(change, |issue| JournalEvent::PlanCreated {
+        issue,
+        plan: plan_response.name.to_string(),
+    })?;
+    let url = options.link(&plan_response.name);
+    if let Some(url) = &url {
+        println!("  Plan:    {url}");
+    }
+    options.emit_for(change, |issue| MigrateEvent::PlanCreated {
+        issue,
+        plan: plan_response.name.clone(),
+        url,
+    })?;
+    if options.plan_checks {
+        wait_for_plan_checks(api_client, &plan_response.name, &options.poll).await?;
+    }
+    let sheet_response = PostSheetsResponse {
+        name: sheet_names
+            .last()
+            .cloned()
+            .ok_or_else(|| AppError::ApiError("No sheet created for changelog".to_string()))?,
+    };
+    let description = options.issue_description(change);
+    let issue_response = options
+        .during_maintenance(|| {
+            api_client.create_issue(&target_env.project, &plan_response.name, &description)
+        })
         .await?;
+    let url = options.link(&issue_response.name);
+    if let Some(url) = &url {
+        println!("  Issue:   {url}");
+    }
+    options.emit_for(change, |issue| MigrateEvent::IssueCreated {
+        issue,
+        target_issue: issue_response.name.clone(),
+        url,
+    })?;
 
     // Create rollout and wait for completion
-    let rollout = api_client
-        .create_rollout(&target_env.project, plan_response.name, issue_response.name)
+    let created = options
+        .during_maintenance(|| {
+            api_client.create_rollout(
+                &target_env.project,
+                plan_response.name.clone(),
+                issue_response.name.clone(),
+            )
+        })
         .await?;
+    let rollout_id = created.name.rollout_id;
+    let started = rollout.insert(StartedRollout {
+        id: rollout_id,
+        duration: None,
+    });
+    options.record_for(change, |issue| JournalEvent::RolloutStarted {
+        issue,
+        rollout_id,
+    })?;
+    let url = options.link(&created.name);
+    if let Some(url) = &url {
+        println!("  Rollout: {url}");
+    }
+    options.emit_for(change, |issue| MigrateEvent::RolloutStarted {
+        issue,
+        rollout_id,
+        url,
+    })?;
 
     // Poll until rollout completes (success or failure)
-    wait_for_rollout(api_client, &target_env.project, rollout.name.rollout_id).await?;
+    if wait {
+        let started_at = Instant::now();
+        let result =
+            wait_for_rollout(api_client, &target_env.project, rollout_id, &options.poll).await;
+        let duration = started_at.elapsed();
+        started.duration = Some(duration);
+        // A timed-out rollout is still running; leave it in flight for `resume`.
+        if !matches!(result, Err(AppError::RolloutTimeout(_))) {
+            options.record_for(change, |issue| JournalEvent::RolloutFinished {
+                issue,
+                success: result.is_ok(),
+                error: result.as_ref().err().map(ToString::to_string),
+            })?;
+        }
+        options.emit_for(change, |issue| MigrateEvent::RolloutFinished {
+            issue,
+            rollout_id,
+            success: result.is_ok(),
+            error: result.as_ref().err().map(ToString::to_string),
+            duration_secs: duration.as_secs_f64(),
+        })?;
+        result?;
+    } else {
+        println!("  Created rollout {} (not waiting).", created.name);
+    }
 
     Ok(sheet_response)
 }
 
+/// Prints the size of every existing target table the pending changelogs touch, so large
+/// changes can be scheduled appropriately. Metadata errors only produce a warning.
+async fn print_impact_estimate<T: BytebaseApi>(
+    api_client: &T,
+    target_env: &Environment,
+    target_database: &str,
+    changelogs: &[Changelog],
+) {
+    if changelogs.is_empty() {
+        return;
+    }
+    let metadata = match api_client
+        .get_database_metadata(&target_env.instance, target_database)
+        .await
+    {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            eprintln!("  Warning: Skipping impact estimate: {e}");
+            return;
+        }
+    };
+    let impacts = impact::estimate(changelogs, &metadata);
+    if impacts.is_empty() {
+        return;
+    }
+    println!("--- Impact Estimate ---");
+    for impact in impacts {
+        println!("  {impact}");
+    }
+}
+
+/// How far `migrate` got before it finished or stopped.
+#[derive(Debug, Default)]
+struct MigrateOutcome {
+    /// Issue and last sheet of the most recently applied changelog whose rollout reached DONE.
+    last_applied: Option<(IssueName, SheetName)>,
+    /// Source issue whose rollout was started but not waited for (`--no-wait`). The revision
+    /// stops short of it; `resume` records it once the rollout is done.
+    in_flight: Option<u32>,
+    /// The error that stopped the run, if any.
+    error: Option<AppError>,
+    /// One row per selected changelog, in apply order.
+    summary: Vec<SummaryRow>,
+    /// Whether changelogs were skipped in `--interactive` review, so the run stopped short of
+    /// the target version even without an error.
+    skipped: bool,
+    /// Whether the changelogs were declined at the confirmation, so nothing was applied.
+    aborted: bool,
+}
+
+/// How one changelog of a migrate run ended, for the summary table.
+#[derive(Debug, Clone, PartialEq)]
+struct SummaryRow {
+    /// The changelog's [`Changelog::label`], e.g. `#42`.
+    changelog: String,
+    database: String,
+    /// Last sheet created for the changelog.
+    sheet: Option<SheetName>,
+    rollout: Option<StartedRollout>,
+    result: ChangelogResult,
+    duration: Duration,
+}
+
+/// A rollout created for a changelog.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct StartedRollout {
+    id: u32,
+    /// How long the rollout took to finish; `None` when it was not waited for.
+    duration: Option<Duration>,
+}
+
+impl SummaryRow {
+    /// Whether the rollout of this changelog took longer than `threshold`.
+    fn is_slow(&self, threshold: Duration) -> bool {
+        self.rollout
+            .and_then(|rollout| rollout.duration)
+            .is_some_and(|duration| duration > threshold)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangelogResult {
+    Applied,
+    /// The rollout was created but not waited for (`--no-wait`).
+    Started,
+    Failed,
+    /// Not attempted because an earlier changelog failed.
+    Skipped,
+}
+
+impl std::fmt::Display for ChangelogResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChangelogResult::Applied => "APPLIED",
+            ChangelogResult::Started => "STARTED",
+            ChangelogResult::Failed => "FAILED",
+            ChangelogResult::Skipped => "SKIPPED",
+        })
+    }
+}
+
+/// Prints the per-changelog results of a run as a table, followed by the totals and the
+/// rollouts that took longer than `slow_threshold`.
+fn print_summary(rows: &[SummaryRow], slow_threshold: Duration) {
+    if rows.is_empty() {
+        return;
+    }
+    let cells: Vec<[String; 7]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.changelog.clone(),
+                row.database.clone(),
+                row.sheet
+                    .as_ref()
+                    .map_or("-".to_string(), ToString::to_string),
+                row.rollout
+                    .map_or("-".to_string(), |rollout| rollout.id.to_string()),
+                match row.rollout.and_then(|rollout| rollout.duration) {
+                    Some(duration) if row.is_slow(slow_threshold) => {
+                        format!("{} SLOW", format_duration(duration))
+                    }
+                    Some(duration) => format_duration(duration),
+                    None => "-".to_string(),
+                },
+                row.result.to_string(),
+                format_duration(row.duration),
+            ]
+        })
+        .collect();
+    let header = [
+        "ISSUE",
+        "DATABASE",
+        "SHEET",
+        "ROLLOUT",
+        "ROLLOUT TIME",
+        "RESULT",
+        "DURATION",
+    ];
+    let mut widths = header.map(str::len);
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let print_row = |cells: [&str; 7]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join(" ").trim_end());
+    };
+    println!("\n--- Summary ---");
+    print_row(header);
+    let dashes = widths.map(|width| "-".repeat(width));
+    print_row(dashes.each_ref().map(String::as_str));
+    for row in &cells {
+        print_row(row.each_ref().map(String::as_str));
+    }
+    println!("\n{}", summary_totals(rows));
+
+    let slow: Vec<String> = rows
+        .iter()
+        .filter(|row| row.is_slow(slow_threshold))
+        .map(|row| row.changelog.clone())
+        .collect();
+    if !slow.is_empty() {
+        println!(
+            "Slow rollouts (over {}s): {}. Consider online DDL for these before running them in production.",
+            slow_threshold.as_secs(),
+            slow.join(", ")
+        );
+    }
+}
+
+/// e.g. `3 changelog(s): 2 applied, 1 failed in 42.0s`.
+fn summary_totals(rows: &[SummaryRow]) -> String {
+    let count = |result| rows.iter().filter(|row| row.result == result).count();
+    let counts: Vec<String> = [
+        (ChangelogResult::Applied, "applied"),
+        (ChangelogResult::Started, "started"),
+        (ChangelogResult::Failed, "failed"),
+        (ChangelogResult::Skipped, "skipped"),
+    ]
+    .into_iter()
+    .map(|(result, label)| (count(result), label))
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, label)| format!("{n} {label}"))
+    .collect();
+    let total: Duration = rows.iter().map(|row| row.duration).sum();
+    format!(
+        "{} changelog(s): {} in {}",
+        rows.len(),
+        counts.join(", "),
+        format_duration(total)
+    )
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn migrate<T: BytebaseApi>(
     api_client: &T,
@@ -190,45 +1917,612 @@ async fn migrate<T: BytebaseApi>(
     source_database: &str,
     target_env: &Environment,
     target_database: &str,
-    target_revision: &Revision,
-    engine: &SQLDialect,
+    current_version: u32,
+    options: &ApplyOptions,
     target_version: u32,
-) -> Option<(IssueName, SheetName, bool)> {
-    let mut last_applied = None;
+) -> MigrateOutcome {
+    let mut outcome = MigrateOutcome::default();
 
-    let mut changelogs = api_client
-        .get_changelogs(&source_env.instance, source_database)
-        .await
-        .map_err(|e| {
-            println!("get_changelogs error: {:?}", e);
-            e
+    let changelogs = match select_changelogs(
+        api_client,
+        source_env,
+        source_database,
+        current_version,
+        target_version,
+        options.source_at,
+        options.verbose,
+    )
+    .await
+    {
+        Ok(changelogs) => changelogs,
+        Err(e) => {
+            outcome.error = Some(e);
+            return outcome;
+        }
+    };
+    // Without a review the whole selection is applied, so the cap is checked before anything
+    // is listed and confirmed; a reviewed selection is checked once it is known.
+    if !options.interactive
+        && let Err(e) = check_change_cap(changelogs.len(), options.max_changes)
+    {
+        outcome.error = Some(e);
+        return outcome;
+    }
+    print_impact_estimate(api_client, target_env, target_database, &changelogs).await;
+    if let Some(target) = &options.confirm_target
+        && !changelogs.is_empty()
+    {
+        let syntax = Syntax::for_engine(&options.engine);
+        match confirm_changelogs(&mut std::io::stdin().lock(), &changelogs, target, syntax) {
+            Ok(true) => {}
+            Ok(false) => {
+                outcome.aborted = true;
+                return outcome;
+            }
+            Err(e) => {
+                outcome.error = Some(AppError::General(e));
+                return outcome;
+            }
+        }
+    }
+    let approved = if options.interactive {
+        let syntax = Syntax::for_engine(&options.engine);
+        match review_changelogs(&mut std::io::stdin().lock(), &changelogs, syntax) {
+            Ok(approved) => approved,
+            Err(e) => {
+                outcome.error = Some(AppError::General(e));
+                return outcome;
+            }
+        }
+    } else {
+        vec![true; changelogs.len()]
+    };
+    outcome.skipped = approved.contains(&false);
+    let approved_count = approved.iter().filter(|a| **a).count();
+    if options.interactive
+        && let Err(e) = check_change_cap(approved_count, options.max_changes)
+    {
+        outcome.error = Some(e);
+        return outcome;
+    }
+    let last_index = approved.iter().rposition(|a| *a).unwrap_or(0);
+    let issues: Vec<u32> = changelogs
+        .iter()
+        .zip(&approved)
+        .filter(|(_, approved)| **approved)
+        .filter_map(|(cl, _)| SourceChange::of(cl).issue())
+        .collect();
+    if let Err(e) = options
+        .record(JournalEvent::Planned {
+            issues: issues.clone(),
         })
-        .ok()?
-        .into_iter()
-        .filter(|c| {
-            c.issue.number > target_revision.version.as_ref().map_or(0, |v| v.number)
-                && c.issue.number <= target_version
+        .and_then(|_| {
+            options.emit(MigrateEvent::SelectionDone {
+                issues: issues.clone(),
+            })
         })
-        .collect::<Vec<_>>();
+    {
+        outcome.error = Some(e);
+        return outcome;
+    }
+    let with_issue_list;
+    let options = match &options.issue_template {
+        Some(template) => {
+            let mut template = template.clone();
+            template.set("issues", issue_template::issue_list(&issues));
+            with_issue_list = ApplyOptions {
+                issue_template: Some(template),
+                ..options.clone()
+            };
+            &with_issue_list
+        }
+        None => options,
+    };
 
-    changelogs.sort_by_key(|c| c.create_time);
-    let total_changelogs = changelogs.len();
-    let mut applied_count = 0;
-
-    for cl in changelogs.into_iter() {
-        match apply_changelog(api_client, target_env, target_database, &cl, engine).await {
-            Ok(sheet) => {
-                println!("Applied changelog: {:?}", cl.name);
-                last_applied = Some((cl.issue.clone(), sheet.name));
-                applied_count += 1;
-            }
-            Err(e) => {
-                eprintln!("Error applying changelog: {e}");
-                return last_applied.map(|(issue, sheet)| (issue, sheet, false));
+    outcome.summary = changelogs
+        .iter()
+        .map(|cl| SummaryRow {
+            changelog: cl.label(),
+            database: target_database.to_string(),
+            sheet: None,
+            rollout: None,
+            result: ChangelogResult::Skipped,
+            duration: Duration::ZERO,
+        })
+        .collect();
+
+    // Sheets do not depend on the target schema, so they are created a few changelogs ahead
+    // while the ones before them roll out. The SQL check and everything after it run in order,
+    // each only once the changelogs before it were applied. A run that stops leaves the sheets
+    // created ahead of it unused.
+    let to_apply: Vec<usize> = (0..changelogs.len()).filter(|i| approved[*i]).collect();
+    let (prepare, mut prepared) = prepare_ahead(to_apply.clone(), SHEET_LOOKAHEAD, |index| {
+        let cl = &changelogs[index];
+        async move {
+            let change = SourceChange::of(cl);
+            let chunks = chunk_changelog(change, &cl.statement, options)?;
+            let sheet_names =
+                create_sheets(api_client, target_env, change, chunks.clone(), options).await?;
+            Ok::<_, AppError>((chunks, sheet_names))
+        }
+    });
+    let apply = async {
+        for index in to_apply {
+            let cl = &changelogs[index];
+            let change = SourceChange::of(cl);
+            let started = Instant::now();
+            let row = &mut outcome.summary[index];
+            let sheet_names = match prepared.recv().await {
+                Some(Ok((chunks, sheet_names))) => {
+                    print_split(&cl.statement, &chunks);
+                    check_chunks(
+                        api_client,
+                        target_env,
+                        target_database,
+                        change,
+                        &chunks,
+                        options,
+                    )
+                    .await
+                    .map(|()| sheet_names)
+                }
+                Some(Err(e)) => Err(e),
+                None => Err(AppError::ApiError(format!(
+                    "Sheets for {} were never created",
+                    cl.label()
+                ))),
+            };
+            // Later changelogs are checked against the schema this one produces, so only the
+            // last rollout may be left running. The baseline has no issue for `resume` to pick
+            // up, so it is always waited for.
+            let wait = options.wait || index != last_index || change.issue().is_none();
+            let result = match sheet_names {
+                Ok(sheet_names) => {
+                    row.sheet = sheet_names.last().cloned();
+                    execute_changelog(
+                        api_client,
+                        target_env,
+                        target_database,
+                        change,
+                        sheet_names,
+                        options,
+                        wait,
+                        &mut row.rollout,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            row.duration = started.elapsed();
+            match result {
+                Ok(sheet) => {
+                    println!("Applied changelog: {:?}", cl.name);
+                    stats::count_applied_changelog();
+                    if !wait {
+                        row.result = ChangelogResult::Started;
+                        outcome.in_flight = change.issue();
+                    } else {
+                        row.result = ChangelogResult::Applied;
+                        // The baseline has no issue to record as the revision; the last
+                        // applied issue stays as it was.
+                        if let Some(issue) = &cl.issue {
+                            outcome.last_applied = Some((issue.clone(), sheet.name));
+                        }
+                    }
+                }
+                Err(e) => {
+                    row.result = ChangelogResult::Failed;
+                    eprintln!("Error applying changelog: {e}");
+                    outcome.error = Some(e);
+                    break;
+                }
             }
         }
+        // Stops the sheets being created ahead of a run that ended.
+        drop(prepared);
+    };
+    tokio::join!(prepare, apply);
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changelog(n: u32, tables: &[&str]) -> Changelog {
+        let tables: Vec<_> = tables
+            .iter()
+            .map(|t| serde_json::json!({ "name": t }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "name": format!("instances/prod/databases/app/changelogs/{n}"),
+            "createTime": "2024-01-01T00:00:00Z",
+            "status": "DONE",
+            "statement": "SELECT 1;",
+            "issue": format!("projects/app/issues/{n}"),
+            "changedResources": {
+                "databases": [{ "name": "app", "schemas": [{ "name": "", "tables": tables }] }]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_expand_targets() {
+        let target = |s: &str| s.parse::<EnvDb>().unwrap();
+        let names =
+            |targets: Vec<EnvDb>| -> Vec<String> { targets.iter().map(EnvDb::to_string).collect() };
+        assert_eq!(
+            names(split_database_lists(&[target("prod/db1, db2"), target("qa/db1")]).unwrap()),
+            ["prod/db1", "prod/db2", "qa/db1"]
+        );
+        assert!(split_database_lists(&[target("prod/db1,")]).is_err());
+
+        let mut config = AppConfig::default();
+        config.environments.insert(
+            "prod".to_string(),
+            Environment {
+                project: "prod-project".to_string(),
+                instance: "prod-instance".to_string(),
+                engine: None,
+                pin_to: None,
+            },
+        );
+        let client = crate::api::clients::tests::FakeApiClient::default();
+        assert_eq!(
+            names(
+                all_databases(&[target("prod/")], &client, &config)
+                    .await
+                    .unwrap()
+            ),
+            ["prod/admin", "prod/bridge"]
+        );
+        assert_eq!(
+            names(
+                all_databases(&[target("prod/b")], &client, &config)
+                    .await
+                    .unwrap()
+            ),
+            ["prod/bridge"]
+        );
+        assert!(
+            all_databases(&[target("prod/x")], &client, &config)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prepare_ahead() {
+        let started = std::sync::Mutex::new(Vec::new());
+        let (prepare, mut prepared) = prepare_ahead(0..100, 2, |n| {
+            started.lock().unwrap().push(n);
+            async move { n * 10 }
+        });
+        let read = async {
+            assert_eq!(prepared.recv().await, Some(0));
+            // The next items are prepared before they are asked for...
+            tokio::task::yield_now().await;
+            assert!(started.lock().unwrap().len() > 1);
+            assert_eq!(prepared.recv().await, Some(10));
+            drop(prepared);
+        };
+        tokio::join!(prepare, read);
+        // ...but only a few, and none once the reader is gone.
+        let started = started.into_inner().unwrap();
+        assert!(started.len() < 10);
+        assert_eq!(started, (0..started.len() as u32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_check_change_cap() {
+        assert!(check_change_cap(500, None).is_ok());
+        assert!(check_change_cap(20, Some(20)).is_ok());
+        let err = check_change_cap(21, Some(20)).unwrap_err();
+        assert!(err.to_string().contains("21 changelogs are pending"));
+    }
+
+    #[test]
+    fn test_run_summary() {
+        let prod_kr: EnvDb = "prod-kr/bridge".parse().unwrap();
+        let prod_jp: EnvDb = "prod-jp/bridge".parse().unwrap();
+        let results = vec![
+            (&prod_kr, Duration::from_millis(62_000), Ok(())),
+            (
+                &prod_jp,
+                Duration::from_millis(9_500),
+                Err(
+                    anyhow::Error::from(AppError::PlanCheckFailed("column exists".to_string()))
+                        .context("Migrating 'prod-jp/bridge' failed"),
+                ),
+            ),
+        ];
+        assert_eq!(
+            run_summary(&results),
+            "--- 1 of 2 target(s) migrated, 1 failed ---\n\
+             TARGET         RESULT   TIME\n\
+             prod-kr/bridge migrated 62.0s\n\
+             prod-jp/bridge failed   9.5s   Migrating 'prod-jp/bridge' failed: Plan check failed: column exists"
+        );
+    }
+
+    #[test]
+    fn test_tracking_issue_and_comment() {
+        assert_eq!(
+            parse_tracking_issue("1234", "dev-project").unwrap(),
+            ("dev-project".to_string(), 1234)
+        );
+        assert_eq!(
+            parse_tracking_issue("release#77", "dev-project").unwrap(),
+            ("release".to_string(), 77)
+        );
+        assert!(parse_tracking_issue("release#", "dev-project").is_err());
+
+        assert_eq!(
+            promotion_comment("dev/bridge", "prod/bridge", 240, 245, 245, true),
+            "shelltide promoted dev/bridge to prod/bridge: issues after #240 up to #245."
+        );
+        let partial = promotion_comment("dev/bridge", "prod/bridge", 240, 243, 245, false);
+        assert!(partial.contains("Stopped before the requested #245"));
+        assert!(partial.ends_with("without waiting for them to finish."));
+    }
+
+    #[test]
+    fn test_jira_comment() {
+        assert_eq!(
+            jira_comment("dev/bridge", "prod/bridge", 240, Some(245), 245, None, "r1"),
+            "shelltide promoted dev/bridge to prod/bridge towards #245: applied issues after #240 up to #245.\nRun id: r1"
+        );
+        let error = AppError::ApiError("rollout failed".to_string());
+        let failed = jira_comment(
+            "dev/bridge",
+            "prod/bridge",
+            240,
+            None,
+            245,
+            Some(&error),
+            "",
+        );
+        assert!(failed.starts_with("shelltide failed to promote dev/bridge to prod/bridge towards #245: applied nothing, then stopped with:"));
+        assert!(failed.ends_with("rollout failed"));
+    }
+
+    #[test]
+    fn test_issue_summary() {
+        let issue: IssueDetails = serde_json::from_value(serde_json::json!({
+            "name": "projects/dev/issues/354",
+            "title": "Add itembox table",
+            "status": "DONE",
+            "createTime": "2026-01-27T09:11:27Z"
+        }))
+        .unwrap();
+        assert_eq!(
+            issue_summary(&issue, &DisplayTimezone::Utc),
+            "#354 \"Add itembox table\" (DONE, created 2026-01-27T09:11:27Z)"
+        );
     }
 
-    let all_successful = applied_count == total_changelogs;
-    last_applied.map(|(issue, sheet)| (issue, sheet, all_successful))
+    #[test]
+    fn test_revision_issue() {
+        assert_eq!(revision_issue(true, None, 105, 110), 110);
+        // --no-wait: #107 is still rolling out, so the revision stays at #105.
+        assert_eq!(revision_issue(true, Some(107), 105, 110), 105);
+        assert_eq!(revision_issue(false, None, 105, 110), 105);
+    }
+
+    #[test]
+    fn test_revision_plan() {
+        let changelogs = [changelog(356, &["t"]), changelog(357, &["u"])];
+        let lines = revision_plan(&changelogs, "prod", 350, 360, true);
+        assert_eq!(
+            lines,
+            [
+                "OUTCOME     VERSION SHEET",
+                "----------- ------- ---------------------------------------------",
+                "all applied app#360 projects/prod/sheets/<last new sheet of #357>",
+                "#356 fails  -       none written, the target stays at #350",
+                "#357 fails  app#356 projects/prod/sheets/<last new sheet of #356>",
+            ]
+        );
+
+        let lines = revision_plan(&changelogs, "prod", 350, 360, false);
+        assert_eq!(
+            lines[2],
+            "all started (--no-wait) app#356 projects/prod/sheets/<last new sheet of #356>"
+        );
+    }
+
+    #[test]
+    fn test_baseline_is_labelled_by_changelog() {
+        let options = ApplyOptions {
+            run_id: Some("r1".to_string()),
+            ..ApplyOptions::from_config(&AppConfig::default())
+        };
+        let mut baseline = changelog(17, &[]);
+        assert_eq!(SourceChange::of(&baseline), SourceChange::Issue(17));
+        baseline.issue = None;
+        let change = SourceChange::of(&baseline);
+        assert_eq!(change, SourceChange::Baseline(17));
+        assert_eq!(change.issue(), None);
+        assert_eq!(
+            options.label(change),
+            "shelltide run r1, baseline changelog 17"
+        );
+        assert_eq!(
+            options.issue_description(change),
+            "Created by shelltide run r1, baseline changelog 17."
+        );
+        let sheet = StringStatement(provenance(change, &options) + "CREATE TABLE t (id INT);");
+        assert!(sheet.is_promoted());
+        assert_eq!(sheet.provenance_issue(), None);
+    }
+
+    #[test]
+    fn test_promoted_changelogs_are_not_selected() {
+        let options = ApplyOptions {
+            run_id: Some("20240101120000-1a2b3c4d".to_string()),
+            ..ApplyOptions::from_config(&AppConfig::default())
+        };
+        let promoted = format!(
+            "{PROVENANCE_MARKER} {}\nALTER TABLE t ADD c INT;\n",
+            options.label(SourceChange::Issue(42))
+        );
+        let mut changelog = changelog(50, &["t"]);
+        changelog.statement = StringStatement(promoted);
+        assert!(changelog.statement.is_promoted());
+        assert_eq!(
+            changelog.statement.without_provenance(),
+            "ALTER TABLE t ADD c INT;"
+        );
+
+        let selector = Selector {
+            source_database: "app",
+            current_version: 10,
+            target_version: 60,
+            baseline: None,
+            source_at: None,
+        };
+        assert_eq!(selector.select(&changelog), Selection::Promoted);
+        changelog.statement = StringStatement("ALTER TABLE t ADD c INT;".to_string());
+        assert!(!changelog.statement.is_promoted());
+        assert_eq!(selector.select(&changelog), Selection::Pending);
+    }
+
+    #[test]
+    fn test_source_at() {
+        let at = |minute: u32, issue: u32| -> Changelog {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("instances/dev/databases/app/changelogs/{minute}"),
+                "createTime": format!("2024-01-01T00:{minute:02}:00Z"),
+                "status": "DONE",
+                "statement": "SELECT 1;",
+                "issue": format!("projects/dev/issues/{issue}"),
+            }))
+            .unwrap()
+        };
+        // #11 was finished in the source only after #12, the release cut.
+        let changelogs = vec![at(1, 10), at(2, 12), at(3, 11), at(4, 13)];
+        let source_at = source_state_at(&changelogs, "app", 12).unwrap();
+        assert_eq!(source_at.1, changelogs[1].create_time);
+        assert_eq!(source_state_at(&changelogs, "app", 14), Err(14));
+        assert_eq!(source_state_at(&changelogs, "log", 12), Err(12));
+
+        let selector = Selector {
+            source_database: "app",
+            current_version: 9,
+            target_version: 12,
+            baseline: None,
+            source_at: Some(source_at),
+        };
+        let selected: Vec<Selection> = changelogs.iter().map(|cl| selector.select(cl)).collect();
+        assert_eq!(
+            selected,
+            [
+                Selection::Pending,
+                Selection::Pending,
+                Selection::AfterSourceAt,
+                Selection::BeyondTarget
+            ]
+        );
+        assert_eq!(
+            selector.exclusion_reason(&changelogs[2]).unwrap(),
+            "created after the changelog of issue #12 (--source-at)"
+        );
+    }
+
+    #[test]
+    fn test_selection_stops_at_baselines() {
+        let at = |minute: u32, issue: &str, kind: &str, statement: &str| -> Changelog {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("instances/dev/databases/app/changelogs/{minute}"),
+                "createTime": format!("2024-01-01T00:{minute:02}:00Z"),
+                "status": "DONE",
+                "statement": statement,
+                "issue": issue,
+                "type": kind,
+                "schema": "CREATE TABLE t (id INT);"
+            }))
+            .unwrap()
+        };
+        let changelogs = vec![
+            at(1, "", "BASELINE", ""),
+            at(2, "projects/dev/issues/10", "MIGRATE", "SELECT 1;"),
+            at(3, "", "BASELINE", ""),
+            at(4, "projects/dev/issues/11", "MIGRATE", "SELECT 1;"),
+        ];
+        assert_eq!(changelogs[0].issue_number(), 0);
+        assert_eq!(changelogs[0].label(), "changelog 1");
+
+        let selector = Selector {
+            source_database: "app",
+            current_version: 10,
+            target_version: 11,
+            baseline: None,
+            source_at: None,
+        };
+        assert_eq!(selector.select(&changelogs[0]), Selection::Baseline);
+        assert_eq!(selector.select(&changelogs[3]), Selection::Pending);
+        let crossed = selector.crossed_baseline(&changelogs).unwrap();
+        assert_eq!(crossed.name.number, 3);
+
+        // The baseline taken before the target's first changelog is where history starts.
+        let from_scratch = Selector {
+            current_version: 0,
+            ..selector
+        };
+        assert!(from_scratch.crossed_baseline(&changelogs).is_none());
+        // A target without revision is rebuilt from the latest baseline instead.
+        let baseline = from_scratch.replay_baseline(&changelogs).unwrap();
+        assert_eq!(baseline.name.number, 3);
+        assert!(selector.replay_baseline(&changelogs).is_none());
+        let replaying = Selector {
+            baseline: Some(baseline),
+            ..from_scratch
+        };
+        assert_eq!(
+            replaying.select(&changelogs[1]),
+            Selection::CoveredByBaseline
+        );
+        assert_eq!(
+            replaying.select(&changelogs[2]),
+            Selection::ReplayedBaseline
+        );
+        assert_eq!(replaying.select(&changelogs[3]), Selection::Pending);
+        let caught_up = Selector {
+            current_version: 11,
+            ..selector
+        };
+        assert!(caught_up.crossed_baseline(&changelogs).is_none());
+    }
+
+    #[test]
+    fn test_summary_totals() {
+        let row = |issue: u32, result, millis| SummaryRow {
+            changelog: format!("#{issue}"),
+            database: "app".to_string(),
+            sheet: None,
+            rollout: None,
+            result,
+            duration: Duration::from_millis(millis),
+        };
+        let rows = vec![
+            row(1, ChangelogResult::Applied, 1500),
+            row(2, ChangelogResult::Failed, 2000),
+            row(3, ChangelogResult::Skipped, 0),
+        ];
+        assert_eq!(
+            summary_totals(&rows),
+            "3 changelog(s): 1 applied, 1 failed, 1 skipped in 3.5s"
+        );
+
+        let mut slow = row(4, ChangelogResult::Applied, 0);
+        slow.rollout = Some(StartedRollout {
+            id: 7,
+            duration: Some(Duration::from_secs(90)),
+        });
+        assert!(slow.is_slow(Duration::from_secs(60)));
+        assert!(!slow.is_slow(Duration::from_secs(120)));
+        assert!(!rows[0].is_slow(Duration::ZERO));
+    }
 }
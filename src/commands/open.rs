@@ -0,0 +1,123 @@
+use crate::cli::{OpenArgs, OpenCommand};
+use crate::config::{
+    AppConfig, BYTEBASE_URL_VAR, ConfigOperations, EnvCredentials, ProductionConfig,
+};
+use crate::error::AppError;
+use anyhow::{Context, Result};
+use std::process::{Command, Stdio};
+
+/// Handles the `open` command.
+pub async fn handle_open_command(args: OpenArgs) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_open_command_with_config(args, &config_ops).await
+}
+
+pub async fn handle_open_command_with_config<C: ConfigOperations>(
+    args: OpenArgs,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let base_url = EnvCredentials::from_env()
+        .url
+        .or_else(|| config.active_credentials().map(|c| c.url.clone()))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No credentials found. please run `shelltide login` or set {BYTEBASE_URL_VAR}"
+            )
+        })?;
+    let url = resource_url(&base_url, &config, &args.command)?;
+
+    println!("{url}");
+    if !args.print {
+        launch_browser(&url)
+            .context("Failed to launch a browser; open the URL above, or pass --print")?;
+    }
+    Ok(())
+}
+
+/// The Bytebase web UI page of the resource `command` names.
+fn resource_url(base_url: &str, config: &AppConfig, command: &OpenCommand) -> Result<String> {
+    let base_url = base_url.trim_end_matches('/');
+    let env = |name: &str| {
+        config
+            .environments
+            .get(name)
+            .ok_or_else(|| AppError::EnvNotFound(name.to_string()))
+    };
+    Ok(match command {
+        OpenCommand::Issue { env: name, number } => {
+            format!("{base_url}/projects/{}/issues/{number}", env(name)?.project)
+        }
+        OpenCommand::Db { target } => format!(
+            "{base_url}/instances/{}/databases/{}",
+            env(&target.env)?.instance,
+            config.mapped_database(&target.env, &target.db)
+        ),
+    })
+}
+
+/// Opens `url` in the default browser without waiting for it.
+fn launch_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Environment;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_resource_url() {
+        let mut config = AppConfig::default();
+        config.environments.insert(
+            "staging".to_string(),
+            Environment {
+                project: "game-staging".to_string(),
+                instance: "staging-mysql".to_string(),
+                engine: None,
+                pin_to: None,
+            },
+        );
+        config.db_map.insert(
+            "staging".to_string(),
+            HashMap::from([("bridge".to_string(), "bridge_stg".to_string())]),
+        );
+        let base_url = "https://bytebase.example.com/";
+
+        let issue = OpenCommand::Issue {
+            env: "staging".to_string(),
+            number: 723,
+        };
+        assert_eq!(
+            resource_url(base_url, &config, &issue).unwrap(),
+            "https://bytebase.example.com/projects/game-staging/issues/723"
+        );
+        let db = OpenCommand::Db {
+            target: "staging/bridge".parse().unwrap(),
+        };
+        assert_eq!(
+            resource_url(base_url, &config, &db).unwrap(),
+            "https://bytebase.example.com/instances/staging-mysql/databases/bridge_stg"
+        );
+        let unknown = OpenCommand::Issue {
+            env: "prod".to_string(),
+            number: 1,
+        };
+        assert!(resource_url(base_url, &config, &unknown).is_err());
+    }
+}
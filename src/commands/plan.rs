@@ -0,0 +1,195 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::{PlanCheckResult, PlanCheckResultStatus};
+use crate::cli::{EnvDb, PlanCommand};
+use crate::commands::migrate::{
+    get_latest_done_issue_no, parse_target_version, resolve_engine, select_changelogs,
+};
+use crate::config::{ConfigOperations, ProductionConfig};
+use crate::error::AppError;
+use crate::input;
+use crate::sql::{self, Syntax};
+use anyhow::Result;
+
+/// Handles the `plan` command.
+pub async fn handle_plan_command<T: BytebaseApi>(
+    command: PlanCommand,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_plan_command_with_config(command, api_client, &config_ops).await
+}
+
+pub async fn handle_plan_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    command: PlanCommand,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    match command {
+        PlanCommand::Preview { target, to } => preview(&target, &to, api_client, config_ops).await,
+    }
+}
+
+/// `plan preview`: selects the changelogs `migrate` would apply to `target`, splits them into
+/// the sheets it would create and asks Bytebase to review each one against the target. Nothing
+/// is created, so the verdict can be had before any sheet, plan or issue exists.
+async fn preview<T: BytebaseApi, C: ConfigOperations>(
+    target: &EnvDb,
+    to: &str,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let source_env_name = config.default_source_env.as_deref().ok_or_else(|| {
+        AppError::Config(
+            "default.source_env not set. Please run: shelltide config set default.source_env <env-name>"
+                .to_string(),
+        )
+    })?;
+    let source_env = config
+        .environments
+        .get(source_env_name)
+        .ok_or_else(|| AppError::EnvNotFound(source_env_name.to_string()))?;
+    let target_env = config
+        .environments
+        .get(&target.env)
+        .ok_or_else(|| AppError::EnvNotFound(target.env.clone()))?;
+    let target_db = config.mapped_database(&target.env, &target.db);
+
+    let current_version = match api_client
+        .latest_revision(&target_env.instance, target_db, config.revision.latest_by())
+        .await
+    {
+        Ok(revision) => revision.version.as_ref().map_or(0, |v| v.number),
+        Err(AppError::NoRevision(database)) => {
+            println!(
+                "{database} has no revision yet; previewing as for `migrate --assume-empty-revision`."
+            );
+            0
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let source_latest = get_latest_done_issue_no(api_client, &source_env.project).await?;
+    let target_version = parse_target_version(&input::resolve_arg(to)?, source_latest)?;
+    println!(
+        "Previewing '{source_env_name}/{}' -> '{}/{target_db}' from #{current_version} to #{target_version}.",
+        target.db, target.env
+    );
+
+    let changelogs = select_changelogs(
+        api_client,
+        source_env,
+        &target.db,
+        current_version,
+        target_version,
+        None,
+        false,
+    )
+    .await?;
+    if changelogs.is_empty() {
+        println!("nothing to migrate");
+        return Ok(());
+    }
+
+    let engine = resolve_engine(api_client, target_env).await?;
+    let syntax = Syntax::for_engine(&engine);
+    let max_sheet_size = config.sheet.max_size();
+    let mut rejected = 0;
+    for cl in &changelogs {
+        println!("\n{} ({} bytes)", cl.label(), cl.statement.0.len());
+        let chunks = match sql::chunk_statements(&cl.statement.0, max_sheet_size, syntax) {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                println!("  shelltide: cannot be split into sheets: {e}");
+                rejected += 1;
+                continue;
+            }
+        };
+        println!(
+            "  shelltide: pending, {} sheet(s) of at most {max_sheet_size} bytes",
+            chunks.len()
+        );
+        if !engine.supports_sql_check() {
+            println!("  Bytebase:  not reviewed, SQL review does not support {engine} targets");
+            continue;
+        }
+
+        let mut advices = Vec::new();
+        for chunk in &chunks {
+            advices.extend(
+                api_client
+                    .review_sql(&target_env.instance, target_db, chunk)
+                    .await?,
+            );
+        }
+        let (errors, warnings) = review_findings(&advices);
+        let verdict = match (errors.len(), warnings.len()) {
+            (0, 0) => "accepted".to_string(),
+            (0, warnings) => format!("accepted with {warnings} warning(s)"),
+            (errors, _) => format!("rejected with {errors} error(s)"),
+        };
+        println!("  Bytebase:  {verdict}");
+        for error in &errors {
+            println!("    ERROR   {error}");
+        }
+        for warning in &warnings {
+            println!("    WARNING {warning}");
+        }
+        if !errors.is_empty() {
+            rejected += 1;
+        }
+    }
+
+    println!();
+    if rejected > 0 {
+        anyhow::bail!(
+            "{rejected} of {} changelog(s) would not get through; nothing was created",
+            changelogs.len()
+        );
+    }
+    println!(
+        "{} changelog(s) would be accepted; nothing was created.",
+        changelogs.len()
+    );
+    Ok(())
+}
+
+/// Splits Bytebase's advices into errors and warnings; passed rules are left out.
+fn review_findings(advices: &[PlanCheckResult]) -> (Vec<String>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    for advice in advices {
+        let finding = if advice.content.is_empty() {
+            advice.title.clone()
+        } else {
+            format!("{}: {}", advice.title, advice.content)
+        };
+        match advice.status {
+            PlanCheckResultStatus::Error => errors.push(finding),
+            PlanCheckResultStatus::Warning => warnings.push(finding),
+            _ => {}
+        }
+    }
+    (errors, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::SqlCheckResponse;
+
+    #[test]
+    fn test_review_findings() {
+        let response: SqlCheckResponse = serde_json::from_value(serde_json::json!({
+            "advices": [
+                {"status": "SUCCESS", "title": "OK"},
+                {"status": "WARNING", "title": "column.no-null", "content": "`c` can be NULL"},
+                {"status": "ERROR", "title": "table.require-pk"}
+            ]
+        }))
+        .unwrap();
+
+        let (errors, warnings) = review_findings(&response.advices);
+        assert_eq!(errors, ["table.require-pk"]);
+        assert_eq!(warnings, ["column.no-null: `c` can be NULL"]);
+    }
+}
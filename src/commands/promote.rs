@@ -0,0 +1,208 @@
+use crate::api::traits::BytebaseApi;
+use crate::cli::{EnvDb, MigrateArgs, PromoteArgs};
+use crate::commands::migrate::{
+    get_latest_done_issue_no, issue_summary, migrate_target, parse_target_version,
+};
+use crate::commands::prompt::confirm;
+use crate::config::{ConfigOperations, ProductionConfig};
+use crate::error::AppError;
+use crate::input;
+use anyhow::{Context, Result};
+use std::io::IsTerminal;
+
+/// Handles the `promote` command.
+pub async fn handle_promote_command<T: BytebaseApi>(
+    args: PromoteArgs,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_promote_command_with_config(args, api_client, &config_ops).await
+}
+
+/// Migrates `args.source_db` from the first environment of the chain to each of the others in
+/// turn. Every hop applies the first environment's changelogs, so the revisions along the chain
+/// count the same issues; the chain only decides the order, and a hop starts once the one
+/// before it has reached the version.
+pub async fn handle_promote_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    args: PromoteArgs,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    let (source, hops) = chain_hops(&args.chain)?;
+    let config = config_ops.load_config().await?;
+    let source_env = config
+        .environments
+        .get(source)
+        .ok_or_else(|| AppError::EnvNotFound(source.to_string()))?;
+    // Checked up front so a typo in the last hop does not surface after the first one.
+    if let Some(env) = hops
+        .iter()
+        .find(|env| !config.environments.contains_key(*env))
+    {
+        return Err(AppError::EnvNotFound(env.clone()).into());
+    }
+
+    let source_latest = get_latest_done_issue_no(api_client, &source_env.project).await?;
+    let version = parse_target_version(&input::resolve_arg(&args.to)?, source_latest)?;
+    println!(
+        "Promoting '{source}/{}' to #{version} through {}.",
+        args.source_db,
+        args.chain.join(" -> ")
+    );
+    // Without a terminal (CI, or `--to -`) there is nobody to ask.
+    if !args.yes && args.to != input::STDIN && std::io::stdin().is_terminal() {
+        match api_client.get_issue(&source_env.project, version).await {
+            Ok(issue) => println!(
+                "Target issue: {}",
+                issue_summary(&issue, &config.display.timezone())
+            ),
+            Err(e) => eprintln!("Warning: Could not read issue #{version}: {e}"),
+        }
+        if !confirm(
+            &mut std::io::stdin().lock(),
+            &format!(
+                "Promote '{}' to #{version} through {}?",
+                args.source_db,
+                hops.join(", ")
+            ),
+            false,
+        )? {
+            println!("Aborted. Nothing was applied.");
+            return Ok(());
+        }
+    }
+
+    let migrate_args = hop_args(&args, version);
+    for (i, env) in hops.iter().enumerate() {
+        println!("\n=== {source} -> {env} ({}/{}) ===", i + 1, hops.len());
+        let target = EnvDb {
+            env: env.clone(),
+            db: args.source_db.clone(),
+        };
+        let not_promoted = &hops[i + 1..];
+        migrate_target(
+            &migrate_args,
+            Some(source),
+            &target,
+            migrate_args.to.as_deref(),
+            None,
+            api_client,
+            config_ops,
+        )
+        .await
+        .with_context(|| stop_message(&target, not_promoted))?;
+
+        // The next hop must not start on a version this one has not reached.
+        let target_env = &config.environments[env];
+        let reached = api_client
+            .latest_revision(
+                &target_env.instance,
+                config.mapped_database(env, &args.source_db),
+                config.revision.latest_by(),
+            )
+            .await
+            .with_context(|| stop_message(&target, not_promoted))?
+            .version
+            .map_or(0, |v| v.number);
+        if reached < version {
+            return Err(
+                anyhow::anyhow!("'{target}' is at issue #{reached}, not #{version}")
+                    .context(stop_message(&target, not_promoted)),
+            );
+        }
+    }
+
+    println!(
+        "\nPromoted '{}' to #{version} through {}.",
+        args.source_db,
+        args.chain.join(" -> ")
+    );
+    Ok(())
+}
+
+/// Splits `chain` into its source and the environments to promote to, in order.
+fn chain_hops(chain: &[String]) -> Result<(&str, &[String]), AppError> {
+    let Some((source, hops)) = chain.split_first().filter(|(_, hops)| !hops.is_empty()) else {
+        return Err(AppError::InvalidArgs(
+            "--chain needs a source and at least one environment to promote to, e.g. dev,staging"
+                .to_string(),
+        ));
+    };
+    for (i, env) in chain.iter().enumerate() {
+        if env.is_empty() {
+            return Err(AppError::InvalidArgs(
+                "Empty environment name in --chain".to_string(),
+            ));
+        }
+        if chain[..i].contains(env) {
+            return Err(AppError::InvalidArgs(format!(
+                "'{env}' appears more than once in --chain"
+            )));
+        }
+    }
+    Ok((source, hops))
+}
+
+/// The `migrate` arguments of every hop: a numbered, already confirmed version, waiting for the
+/// rollouts so the next hop only starts on a finished one.
+fn hop_args(args: &PromoteArgs, version: u32) -> MigrateArgs {
+    MigrateArgs {
+        source_db: args.source_db.clone(),
+        targets: Vec::new(),
+        all_databases: false,
+        concurrency: 1,
+        to: Some(version.to_string()),
+        source_at: None,
+        yes: true,
+        offline: false,
+        out: None,
+        dry_run: false,
+        wait: true,
+        no_wait: false,
+        wait_timeout: args.wait_timeout,
+        verbose: args.verbose,
+        interactive: false,
+        assume_empty_revision: false,
+        baseline: None,
+        no_sync: args.no_sync,
+        notify_issue: None,
+        jira: None,
+        skip_plan_checks: args.skip_plan_checks,
+        max_changes: None,
+        confirm_large_run: false,
+        wait_for_server: None,
+        events: None,
+        events_file: None,
+    }
+}
+
+/// Why the chain stopped at `target`, naming the environments left behind.
+fn stop_message(target: &EnvDb, not_promoted: &[String]) -> String {
+    if not_promoted.is_empty() {
+        format!("Promoting to '{target}' failed")
+    } else {
+        format!(
+            "Promoting to '{target}' failed; stopped before {}",
+            not_promoted.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_hops() {
+        let chain = |envs: &[&str]| envs.iter().map(|env| env.to_string()).collect::<Vec<_>>();
+
+        let envs = chain(&["dev", "staging", "prod"]);
+        let (source, hops) = chain_hops(&envs).unwrap();
+        assert_eq!(source, "dev");
+        assert_eq!(hops, ["staging", "prod"]);
+
+        assert!(chain_hops(&chain(&["dev"])).is_err());
+        assert!(chain_hops(&chain(&["dev", ""])).is_err());
+        assert!(chain_hops(&chain(&["dev", "staging", "dev"])).is_err());
+    }
+}
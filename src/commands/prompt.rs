@@ -0,0 +1,69 @@
+use crate::api::clients::ClientNotice;
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+/// Shows a notice of the Bytebase client on stderr, keeping stdout for command output.
+pub(crate) fn print_notice(notice: &ClientNotice) {
+    eprintln!("  {notice}");
+}
+
+/// Reads one trimmed line, falling back to `default` when the answer is empty.
+pub(crate) fn prompt<R: BufRead>(
+    input: &mut R,
+    question: &str,
+    default: Option<&str>,
+) -> Result<String> {
+    loop {
+        match default {
+            Some(default) if !default.is_empty() => print!("{question} [{default}]: "),
+            _ => print!("{question}: "),
+        }
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            anyhow::bail!("Input closed before an answer was given.");
+        }
+        match (line.trim(), default) {
+            ("", Some(default)) => return Ok(default.to_string()),
+            ("", None) => continue,
+            (answer, _) => return Ok(answer.to_string()),
+        }
+    }
+}
+
+pub(crate) fn confirm<R: BufRead>(input: &mut R, question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    loop {
+        let answer = prompt(input, &format!("{question} ({hint})"), Some(""))?;
+        match answer.to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("Please answer y or n."),
+        }
+    }
+}
+
+/// Lets the user pick one of `options` by number or name. A single option is picked as is.
+pub(crate) fn choose<R: BufRead>(input: &mut R, label: &str, options: &[String]) -> Result<String> {
+    if let [only] = options {
+        println!("{label}: {only}");
+        return Ok(only.clone());
+    }
+
+    for (i, option) in options.iter().enumerate() {
+        println!("  {:>3}) {option}", i + 1);
+    }
+    loop {
+        let answer = prompt(input, label, None)?;
+        let picked = match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => Some(&options[n - 1]),
+            _ => options.iter().find(|o| **o == answer),
+        };
+        match picked {
+            Some(option) => return Ok(option.clone()),
+            None => println!("Please enter a number between 1 and {}.", options.len()),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::{Changelog, ListOptions};
+use crate::cli::ReleaseCommand;
+use crate::commands::dump::find_target_changelog;
+use crate::commands::migrate::display_syntax;
+use crate::config::{AppConfig, ConfigOperations, ProductionConfig, Release};
+use crate::error::AppError;
+use crate::schema::{self, Tables};
+use anyhow::Result;
+
+/// Handles the `release` command.
+pub async fn handle_release_command<T: BytebaseApi>(
+    command: ReleaseCommand,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_release_command_with_config(command, api_client, &config_ops).await
+}
+
+pub async fn handle_release_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    command: ReleaseCommand,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    match command {
+        ReleaseCommand::Diff { from, to, db } => {
+            let config = config_ops.load_config().await?;
+            let old = release_tables(api_client, &config, &from, &db).await?;
+            let new = release_tables(api_client, &config, &to, &db).await?;
+
+            println!();
+            let changes = schema::diff_tables(&old, &new);
+            if changes.is_empty() {
+                println!("No table or column changes in '{db}' from {from} to {to}.");
+                return Ok(());
+            }
+            for change in &changes {
+                println!("{change}");
+            }
+            println!(
+                "\n{} change(s) in '{db}' from {from} to {to}.",
+                changes.len()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// The tables of `database` as of release `name`, read from the schema recorded with the
+/// latest changelog at or before the release's issue.
+async fn release_tables<T: BytebaseApi>(
+    api_client: &T,
+    config: &AppConfig,
+    name: &str,
+    database: &str,
+) -> Result<Tables> {
+    let release: &Release = config
+        .releases
+        .get(name)
+        .ok_or_else(|| AppError::Config(format!("Release '{name}' not found in configuration.")))?;
+    let env = config
+        .environments
+        .get(&release.from_env)
+        .ok_or_else(|| AppError::EnvNotFound(release.from_env.clone()))?;
+
+    let changelogs = api_client
+        .get_changelogs(&env.instance, database, &ListOptions::full())
+        .await?;
+    let changelog: Changelog = find_target_changelog(changelogs, Some(release.issue_number))?
+        .ok_or_else(|| {
+            AppError::Config(format!(
+                "No schema of '{}/{database}' recorded at or before issue #{} of release {name}",
+                release.from_env, release.issue_number
+            ))
+        })?;
+    println!(
+        "Release {name}: issue #{} ('{database}' schema from #{} in '{}')",
+        release.issue_number,
+        changelog.issue_number(),
+        release.from_env
+    );
+
+    let syntax = display_syntax(api_client, env).await;
+    Ok(schema::parse_tables(&changelog.schema, syntax))
+}
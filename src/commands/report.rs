@@ -0,0 +1,195 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::{DatabaseMetadata, TableMetadata};
+use crate::cli::ReportArgs;
+use crate::config::{ConfigOperations, ProductionConfig};
+use crate::error::AppError;
+use anyhow::{Context, Result};
+use chrono::Utc;
+
+/// Output format of `report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// Handles the `report` command.
+pub async fn handle_report_command<T: BytebaseApi>(args: ReportArgs, api_client: &T) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_report_command_with_config(args, api_client, &config_ops).await
+}
+
+/// Renders the tables, columns and comments of a database from its Bytebase metadata.
+pub async fn handle_report_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    args: ReportArgs,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let env = config
+        .environments
+        .get(&args.target.env)
+        .ok_or_else(|| AppError::EnvNotFound(args.target.env.clone()))?;
+    let database = config.mapped_database(&args.target.env, &args.target.db);
+
+    let metadata = api_client
+        .get_database_metadata(&env.instance, database)
+        .await?;
+    let revision = api_client
+        .latest_revision(&env.instance, database, config.revision.latest_by())
+        .await
+        .ok()
+        .and_then(|revision| revision.version)
+        .map_or(String::new(), |version| format!(" at revision {version}"));
+    let title = format!("{} ({})", args.target.db, args.target.env);
+    let summary = format!(
+        "Generated by shelltide on {} from {}/{database}{revision}.",
+        Utc::now().format("%Y-%m-%d"),
+        env.instance
+    );
+
+    let format = args.format.unwrap_or_else(|| {
+        let html = args
+            .out
+            .as_ref()
+            .and_then(|out| out.extension())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+        if html {
+            ReportFormat::Html
+        } else {
+            ReportFormat::Markdown
+        }
+    });
+    let report = match format {
+        ReportFormat::Markdown => markdown_report(&title, &summary, &metadata),
+        ReportFormat::Html => html_report(&title, &summary, &metadata),
+    };
+
+    match &args.out {
+        Some(out) => {
+            std::fs::write(out, report)
+                .with_context(|| format!("Failed to write report to {out:?}"))?;
+            println!("Wrote the schema report of '{title}' to {out:?}");
+        }
+        None => print!("{report}"),
+    }
+    Ok(())
+}
+
+/// Tables of every schema with their display names, `schema.table` where there are schemas.
+fn tables(metadata: &DatabaseMetadata) -> Vec<(String, &TableMetadata)> {
+    metadata
+        .schemas
+        .iter()
+        .flat_map(|schema| {
+            schema.tables.iter().map(move |table| {
+                let name = if schema.name.is_empty() {
+                    table.name.clone()
+                } else {
+                    format!("{}.{}", schema.name, table.name)
+                };
+                (name, table)
+            })
+        })
+        .collect()
+}
+
+fn markdown_report(title: &str, summary: &str, metadata: &DatabaseMetadata) -> String {
+    // Cells must stay on one line and must not end the cell early.
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\r', '\n'], " ");
+
+    let mut out = format!("# Schema of {title}\n\n{summary}\n");
+    for (name, table) in tables(metadata) {
+        out.push_str(&format!("\n## {name}\n\n"));
+        if !table.comment.is_empty() {
+            out.push_str(&format!("{}\n\n", table.comment));
+        }
+        out.push_str("| Column | Type | Nullable | Default | Comment |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for column in &table.columns {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                cell(&column.name),
+                cell(&column.column_type),
+                if column.nullable { "YES" } else { "NO" },
+                cell(&column.default),
+                cell(&column.comment)
+            ));
+        }
+    }
+    out
+}
+
+fn html_report(title: &str, summary: &str, metadata: &DatabaseMetadata) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+
+    let title = escape(title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Schema of {title}</title>\n</head>\n<body>\n<h1>Schema of {title}</h1>\n<p>{}</p>\n",
+        escape(summary)
+    );
+    for (name, table) in tables(metadata) {
+        out.push_str(&format!("<h2>{}</h2>\n", escape(&name)));
+        if !table.comment.is_empty() {
+            out.push_str(&format!("<p>{}</p>\n", escape(&table.comment)));
+        }
+        out.push_str("<table>\n<tr><th>Column</th><th>Type</th><th>Nullable</th><th>Default</th><th>Comment</th></tr>\n");
+        for column in &table.columns {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&column.name),
+                escape(&column.column_type),
+                if column.nullable { "YES" } else { "NO" },
+                escape(&column.default),
+                escape(&column.comment)
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_and_html_reports() {
+        let metadata: DatabaseMetadata = serde_json::from_value(serde_json::json!({
+            "schemas": [{
+                "name": "",
+                "tables": [{
+                    "name": "users",
+                    "comment": "Registered players",
+                    "columns": [
+                        {"name": "id", "type": "bigint", "comment": "Primary key"},
+                        {"name": "nick", "type": "varchar(32)", "nullable": true,
+                         "default": "''", "comment": "Shown in <chat> | lobby"}
+                    ]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let markdown = markdown_report("bridge (prod)", "Generated.", &metadata);
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines[0], "# Schema of bridge (prod)");
+        assert_eq!(lines[4], "## users");
+        assert_eq!(lines[6], "Registered players");
+        assert_eq!(lines[10], "| id | bigint | NO |  | Primary key |");
+        assert_eq!(
+            lines[11],
+            "| nick | varchar(32) | YES | '' | Shown in <chat> \\| lobby |"
+        );
+
+        let html = html_report("bridge (prod)", "Generated.", &metadata);
+        assert!(html.contains("<h2>users</h2>\n<p>Registered players</p>"));
+        assert!(html.contains("<td>Shown in &lt;chat&gt; | lobby</td>"));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::api::polling::{PollOptions, wait_for_rollout};
+use crate::api::traits::BytebaseApi;
+use crate::cli::ResumeArgs;
+use crate::commands::migrate::{ApplyOptions, record_revision};
+use crate::config::{ConfigOperations, Environment, ProductionConfig};
+use crate::error::AppError;
+use crate::journal::{self, Journal, JournalEvent, RunState};
+use anyhow::Result;
+
+pub async fn handle_resume_command<T: BytebaseApi>(args: ResumeArgs, api_client: &T) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_resume_command_with_config(args, api_client, &config_ops).await
+}
+
+pub async fn handle_resume_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    args: ResumeArgs,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let journal = Journal::open(&journal::runs_dir(&config_ops.config_path()?), &args.run_id)?;
+    let poll = ApplyOptions::from_config(&config).poll;
+    resume_run(api_client, &journal, &poll).await?;
+    Ok(())
+}
+
+/// Re-attaches to the rollouts a run left in flight and finishes its revision bookkeeping.
+async fn resume_run<T: BytebaseApi>(
+    api_client: &T,
+    journal: &Journal,
+    poll: &PollOptions,
+) -> Result<(), AppError> {
+    let events = journal.events()?;
+    let Some(JournalEvent::Started {
+        target_env: target_env_name,
+        target_database,
+        target_project,
+        target_instance,
+        source_project,
+        target_version,
+        ..
+    }) = events.first().cloned()
+    else {
+        return Err(AppError::InvalidArgs(format!(
+            "Journal of run '{}' has no start record",
+            journal.run_id
+        )));
+    };
+
+    let mut state = RunState::from_events(&events);
+    if state.finished {
+        println!(
+            "Run {} already finished. Nothing to resume.",
+            journal.run_id
+        );
+        return Ok(());
+    }
+    println!(
+        "Resuming run {} for '{target_env_name}/{target_database}'...",
+        journal.run_id
+    );
+
+    for (issue, rollout_id) in state.in_flight.clone() {
+        println!("Re-attaching to rollout {rollout_id} (issue #{issue})...");
+        let result = wait_for_rollout(api_client, &target_project, rollout_id, poll).await;
+        if let Err(AppError::RolloutTimeout(_)) = result {
+            return result.map(|_| ());
+        }
+        journal.record(&JournalEvent::RolloutFinished {
+            issue,
+            success: result.is_ok(),
+            error: result.as_ref().err().map(ToString::to_string),
+        })?;
+        state.in_flight.retain(|(i, _)| *i != issue);
+        match result {
+            Ok(_) => state.succeeded.push(issue),
+            Err(e) => {
+                eprintln!("Rollout for issue #{issue} failed: {e}");
+                state.failed = true;
+                break;
+            }
+        }
+    }
+
+    let Some(&last_issue) = state.succeeded.last() else {
+        println!("No changelog of this run was applied. Nothing to record.");
+        journal.record(&JournalEvent::Finished)?;
+        return Ok(());
+    };
+    let revision_issue_number = if state.is_complete() {
+        target_version
+    } else {
+        last_issue
+    };
+
+    if state.revision_recorded == Some(revision_issue_number) {
+        println!("Revision for issue #{revision_issue_number} was already recorded.");
+    } else {
+        let sheet = state.sheets.get(&last_issue).ok_or_else(|| {
+            AppError::InvalidArgs(format!("Journal has no sheet for issue #{last_issue}"))
+        })?;
+        let target_env = Environment {
+            project: target_project,
+            instance: target_instance,
+            engine: None,
+            pin_to: None,
+        };
+        println!("Migrated to issue #{last_issue}. Creating revision...");
+        record_revision(
+            api_client,
+            &target_env,
+            &target_database,
+            &source_project,
+            revision_issue_number,
+            sheet,
+        )
+        .await?;
+        journal.record(&JournalEvent::RevisionRecorded {
+            issue: revision_issue_number,
+        })?;
+    }
+    journal.record(&JournalEvent::Finished)?;
+
+    if state.is_complete() {
+        println!("--- Run {} Complete ---\n", journal.run_id);
+    } else {
+        println!(
+            "Run {} stopped before issue #{target_version}. \
+            Run `shelltide migrate` again to apply the rest.",
+            journal.run_id
+        );
+    }
+    Ok(())
+}
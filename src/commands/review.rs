@@ -0,0 +1,215 @@
+use crate::api::types::Changelog;
+use crate::commands::prompt::{confirm, prompt};
+use crate::sql::{self, Syntax};
+use anyhow::Result;
+use std::io::{BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Statements longer than this many lines are shown through the pager.
+const PAGER_THRESHOLD_LINES: usize = 40;
+
+/// Characters of a changelog's first statement shown in the confirmation list.
+const SUMMARY_WIDTH: usize = 72;
+
+const HELP: &str = "y - apply this changelog
+n - skip this changelog
+a - apply this and all remaining changelogs
+q - skip this and all remaining changelogs
+? - print help";
+
+/// Shows each pending changelog and asks whether to apply it, like `git add -p`. Returns one
+/// flag per changelog, `true` for the ones to apply.
+pub(crate) fn review_changelogs<R: BufRead>(
+    input: &mut R,
+    changelogs: &[Changelog],
+    syntax: Syntax,
+) -> Result<Vec<bool>> {
+    let mut approved = vec![false; changelogs.len()];
+    let mut skipped_any = false;
+    for (index, cl) in changelogs.iter().enumerate() {
+        println!(
+            "\n--- Changelog {}/{}: {} ---",
+            index + 1,
+            changelogs.len(),
+            cl.label()
+        );
+        show_statement(&cl.statement.0, syntax);
+        if skipped_any {
+            println!(
+                "Note: applying {} moves the target revision past the changelogs skipped before it.",
+                cl.label()
+            );
+        }
+
+        loop {
+            let question = format!("Apply {}? [y,n,a,q,?]", cl.label());
+            match prompt(input, &question, None)?.to_lowercase().as_str() {
+                "y" | "yes" => approved[index] = true,
+                "n" | "no" | "s" | "skip" => skipped_any = true,
+                "a" | "all" => {
+                    approved[index..].fill(true);
+                    return Ok(approved);
+                }
+                "q" | "quit" => return Ok(approved),
+                _ => {
+                    println!("{HELP}");
+                    continue;
+                }
+            }
+            break;
+        }
+    }
+    Ok(approved)
+}
+
+/// Lists the pending changelogs, one line each, and asks once whether to apply them all to
+/// `target`.
+pub(crate) fn confirm_changelogs<R: BufRead>(
+    input: &mut R,
+    changelogs: &[Changelog],
+    target: &str,
+    syntax: Syntax,
+) -> Result<bool> {
+    println!(
+        "\n{} changelog(s) to apply to '{target}':",
+        changelogs.len()
+    );
+    for cl in changelogs {
+        println!(
+            "  {:<6} {}",
+            cl.label(),
+            statement_summary(&cl.statement.0, syntax)
+        );
+    }
+    confirm(
+        input,
+        &format!("Apply {} changelog(s) to '{target}'?", changelogs.len()),
+        false,
+    )
+}
+
+/// The first statement of `sql` on one line without comments, shortened to
+/// [`SUMMARY_WIDTH`] characters, followed by how many statements come after it.
+pub(crate) fn statement_summary(sql: &str, syntax: Syntax) -> String {
+    let statements: Vec<String> = sql::split_statements(sql, syntax)
+        .into_iter()
+        .map(|statement| {
+            statement
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with("--") && !line.starts_with('#'))
+                .flat_map(str::split_whitespace)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|statement| !statement.is_empty())
+        .collect();
+    let Some(first) = statements.first() else {
+        return "(no statements)".to_string();
+    };
+    let mut summary = if first.chars().count() > SUMMARY_WIDTH {
+        let short: String = first.chars().take(SUMMARY_WIDTH - 3).collect();
+        format!("{short}...")
+    } else {
+        first.clone()
+    };
+    if statements.len() > 1 {
+        summary.push_str(&format!(" (+{} more)", statements.len() - 1));
+    }
+    summary
+}
+
+/// Prints `statement`, highlighted when stdout is a terminal and paged when it is long.
+fn show_statement(statement: &str, syntax: Syntax) {
+    let stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        println!("{}", statement.trim_end());
+        return;
+    }
+
+    let text = if std::env::var_os("NO_COLOR").is_some() {
+        statement.trim_end().to_string()
+    } else {
+        sql::highlight(statement.trim_end(), syntax)
+    };
+    if text.lines().count() <= PAGER_THRESHOLD_LINES || !page(&text) {
+        println!("{text}");
+    }
+}
+
+/// Shows `text` through `$PAGER` (default `less -R`). Returns `false` if no pager could be run.
+fn page(text: &str) -> bool {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let Some(program) = parts.next() else {
+        return false;
+    };
+    let Ok(mut child) = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may exit before reading everything; that is not an error.
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn changelog(issue: u32) -> Changelog {
+        serde_json::from_value(serde_json::json!({
+            "name": format!("instances/dev/databases/app/changelogs/{issue}"),
+            "createTime": "2024-01-01T00:00:00Z",
+            "statement": "ALTER TABLE t ADD COLUMN c INT;",
+            "issue": format!("projects/dev/issues/{issue}")
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_review_answers() {
+        let changelogs: Vec<Changelog> = (1..=4).map(changelog).collect();
+
+        let mut input = Cursor::new("y\nwhat\nn\na\n");
+        let approved = review_changelogs(&mut input, &changelogs, Syntax::MySql).unwrap();
+        assert_eq!(approved, [true, false, true, true]);
+
+        let mut input = Cursor::new("skip\ny\nq\n");
+        let approved = review_changelogs(&mut input, &changelogs, Syntax::MySql).unwrap();
+        assert_eq!(approved, [false, true, false, false]);
+
+        let mut input = Cursor::new("y\n");
+        assert!(review_changelogs(&mut input, &changelogs, Syntax::MySql).is_err());
+    }
+
+    #[test]
+    fn test_statement_summary() {
+        let sql = "-- add the nick column\nALTER TABLE user\n  ADD COLUMN nick VARCHAR(32);\n\
+                   CREATE INDEX idx_nick ON user (nick);\n-- done\n";
+        assert_eq!(
+            statement_summary(sql, Syntax::MySql),
+            "ALTER TABLE user ADD COLUMN nick VARCHAR(32); (+1 more)"
+        );
+        let long = format!("CREATE TABLE t ({});", "c INT, ".repeat(20));
+        let summary = statement_summary(&long, Syntax::MySql);
+        assert_eq!(summary.chars().count(), SUMMARY_WIDTH);
+        assert!(summary.ends_with("..."));
+        assert_eq!(
+            statement_summary("-- nothing\n", Syntax::MySql),
+            "(no statements)"
+        );
+
+        let changelogs: Vec<Changelog> = (1..=2).map(changelog).collect();
+        let mut input = Cursor::new("\n");
+        assert!(!confirm_changelogs(&mut input, &changelogs, "prod/app", Syntax::MySql).unwrap());
+        let mut input = Cursor::new("y\n");
+        assert!(confirm_changelogs(&mut input, &changelogs, "prod/app", Syntax::MySql).unwrap());
+    }
+}
@@ -0,0 +1,416 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::{Changelog, ListOptions, RevisionVersion, SheetName};
+use crate::cli::{EnvDb, RevisionCommand};
+use crate::commands::migrate::{record_revision, select_changelogs};
+use crate::commands::prompt::confirm;
+use crate::config::{ConfigOperations, ProductionConfig};
+use crate::error::AppError;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// Handles the `revision` command.
+pub async fn handle_revision_command<T: BytebaseApi>(
+    command: RevisionCommand,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    let mut input = std::io::stdin().lock();
+    handle_revision_command_with_config(command, api_client, &config_ops, &mut input).await
+}
+
+pub async fn handle_revision_command_with_config<
+    T: BytebaseApi,
+    C: ConfigOperations,
+    R: BufRead,
+>(
+    command: RevisionCommand,
+    api_client: &T,
+    config_ops: &C,
+    input: &mut R,
+) -> Result<()> {
+    match command {
+        RevisionCommand::Set {
+            target,
+            version,
+            sheet,
+            dry_run,
+            yes,
+        } => {
+            set_revision(
+                api_client, config_ops, input, &target, &version, &sheet, dry_run, yes,
+            )
+            .await
+        }
+        RevisionCommand::Repair {
+            target,
+            dry_run,
+            yes,
+        } => repair_revision(api_client, config_ops, input, &target, dry_run, yes).await,
+    }
+}
+
+/// Explicitly records a revision for `target`, e.g. to correct a wrong one left by migrate.
+#[allow(clippy::too_many_arguments)]
+async fn set_revision<T: BytebaseApi, C: ConfigOperations, R: BufRead>(
+    api_client: &T,
+    config_ops: &C,
+    input: &mut R,
+    target: &EnvDb,
+    version: &str,
+    sheet: &str,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let target_env = config
+        .environments
+        .get(&target.env)
+        .ok_or_else(|| AppError::EnvNotFound(target.env.clone()))?;
+    let version = RevisionVersion::new(version.to_string())?;
+    let sheet: SheetName = serde_json::from_value(serde_json::Value::String(sheet.to_string()))
+        .map_err(|e| {
+            AppError::InvalidArgs(format!(
+                "Invalid sheet '{sheet}': {e}. Use 'projects/<project>/sheets/<number>'"
+            ))
+        })?;
+
+    let current = match api_client
+        .latest_revision(
+            &target_env.instance,
+            &target.db,
+            config.revision.latest_by(),
+        )
+        .await
+    {
+        Ok(revision) => revision
+            .version
+            .map_or("no version".to_string(), |v| v.to_string()),
+        Err(_) => "no revision".to_string(),
+    };
+    println!(
+        "Target:   {}/{} ({})",
+        target.env, target.db, target_env.instance
+    );
+    println!("Current:  {current}");
+    println!("New:      {version} (sheet {sheet})");
+
+    if dry_run {
+        println!("Dry run: no revision was created.");
+        return Ok(());
+    }
+    if !yes && !confirm(input, "Create this revision?", false)? {
+        println!("Aborted. No revision was created.");
+        return Ok(());
+    }
+
+    record_revision(
+        api_client,
+        target_env,
+        &target.db,
+        &version.project_name,
+        version.number,
+        &sheet,
+    )
+    .await?;
+    println!(
+        "Revision {version} recorded for '{}/{}'.",
+        target.env, target.db
+    );
+    Ok(())
+}
+
+/// Compares the latest revision of `target` with the changelogs shelltide applied to it and,
+/// if a past run left the revision on the wrong issue, records one for the last issue that
+/// was actually applied.
+async fn repair_revision<T: BytebaseApi, C: ConfigOperations, R: BufRead>(
+    api_client: &T,
+    config_ops: &C,
+    input: &mut R,
+    target: &EnvDb,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let target_env = config
+        .environments
+        .get(&target.env)
+        .ok_or_else(|| AppError::EnvNotFound(target.env.clone()))?;
+    let source_env_name = config
+        .source_env_for(&target.env)
+        .ok_or_else(|| AppError::Config("default.source_env not set".to_string()))?;
+    let source_env = config
+        .environments
+        .get(source_env_name)
+        .ok_or_else(|| AppError::EnvNotFound(source_env_name.to_string()))?;
+    let target_db = config.mapped_database(&target.env, &target.db);
+
+    let revision = match api_client
+        .latest_revision(&target_env.instance, target_db, config.revision.latest_by())
+        .await
+    {
+        Ok(revision) => revision.version,
+        Err(AppError::NoRevision(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let history = api_client
+        .get_changelogs(&target_env.instance, target_db, &ListOptions::full())
+        .await?;
+    println!(
+        "Target:   {}/{target_db} ({})",
+        target.env, target_env.instance
+    );
+    println!(
+        "Revision: {}",
+        revision
+            .as_ref()
+            .map_or("no revision".to_string(), |v| v.to_string())
+    );
+    let Some(applied) = last_applied(&history) else {
+        println!("History:  no changelog applied by shelltide; nothing to compare.");
+        return Ok(());
+    };
+    let applied_issue = applied.statement.provenance_issue().unwrap_or_default();
+    println!(
+        "History:  last applied by shelltide is issue #{applied_issue} ({})",
+        applied.create_time
+    );
+
+    let problem = match &revision {
+        None => Some(format!(
+            "there is no revision, but shelltide applied changelogs up to #{applied_issue}"
+        )),
+        Some(version) if version.number < applied_issue => Some(format!(
+            "the revision is behind: the changelog of #{applied_issue} was applied after it"
+        )),
+        // The revision may rightly be ahead when the issues after the last applied changelog
+        // changed other databases; it is only wrong if it skips changelogs of this one.
+        Some(version) if version.number > applied_issue => {
+            let skipped = select_changelogs(
+                api_client,
+                source_env,
+                &target.db,
+                applied_issue,
+                version.number,
+                None,
+                false,
+            )
+            .await?;
+            (!skipped.is_empty()).then(|| {
+                let labels: Vec<String> = skipped.iter().map(|cl| cl.label()).collect();
+                format!(
+                    "the revision is ahead: {} of '{source_env_name}' never reached the target",
+                    labels.join(", ")
+                )
+            })
+        }
+        Some(_) => None,
+    };
+    let Some(problem) = problem else {
+        println!("The revision matches the changelog history; nothing to repair.");
+        return Ok(());
+    };
+    println!("Problem:  {problem}");
+
+    let project = revision
+        .as_ref()
+        .map_or(source_env.project.clone(), |v| v.project_name.clone());
+    let version = RevisionVersion {
+        project_name: project,
+        number: applied_issue,
+    };
+    let sheet: SheetName = applied
+        .statement_sheet
+        .clone()
+        .and_then(|sheet| serde_json::from_value(serde_json::Value::String(sheet)).ok())
+        .ok_or_else(|| {
+            AppError::ApiError(format!(
+                "The changelog of #{applied_issue} has no sheet; record the revision with `shelltide revision set`"
+            ))
+        })?;
+    println!("New:      {version} (sheet {sheet})");
+
+    if dry_run {
+        println!("Dry run: no revision was created.");
+        return Ok(());
+    }
+    if !yes && !confirm(input, "Create this revision?", false)? {
+        println!("Aborted. No revision was created.");
+        return Ok(());
+    }
+    record_revision(
+        api_client,
+        target_env,
+        target_db,
+        &version.project_name,
+        version.number,
+        &sheet,
+    )
+    .await?;
+    println!(
+        "Revision {version} recorded for '{}/{target_db}'.",
+        target.env
+    );
+    Ok(())
+}
+
+/// The last changelog of the most recent issue in `history` that shelltide applied completely.
+/// A statement split into several sheets leaves one changelog per sheet, all with the same
+/// provenance comment; the issue only counts as applied when every one of them is DONE.
+fn last_applied(history: &[Changelog]) -> Option<&Changelog> {
+    let mut sheets: HashMap<&str, Vec<&Changelog>> = HashMap::new();
+    for cl in history {
+        if let Some(provenance) = cl.statement.provenance()
+            && cl.statement.provenance_issue().is_some()
+        {
+            sheets.entry(provenance).or_default().push(cl);
+        }
+    }
+    sheets
+        .into_values()
+        .filter(|changelogs| changelogs.iter().all(|cl| cl.status == "DONE"))
+        .filter_map(|changelogs| changelogs.into_iter().max_by_key(|cl| cl.create_time))
+        .max_by_key(|cl| cl.create_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::clients::tests::FakeApiClient;
+    use crate::config::{AppConfig, Environment, TestConfig};
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_last_applied() {
+        let changelog = |number: u32, minute: u32, status: &str, statement: &str| -> Changelog {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("instances/prod/databases/app/changelogs/{number}"),
+                "createTime": format!("2024-01-01T00:{minute:02}:00Z"),
+                "status": status,
+                "statement": statement,
+                "statementSheet": format!("projects/prod-project/sheets/{number}"),
+            }))
+            .unwrap()
+        };
+        let promoted = |issue: u32| {
+            format!(
+                "-- shelltide-provenance: shelltide run r1, issue #{issue}\nALTER TABLE t ADD c{issue} INT;"
+            )
+        };
+        let history = [
+            changelog(1, 1, "DONE", &promoted(41)),
+            changelog(2, 2, "DONE", &promoted(42)),
+            changelog(3, 3, "FAILED", &promoted(43)),
+            changelog(4, 4, "DONE", "ALTER TABLE t ADD hotfix INT;"),
+        ];
+
+        let applied = last_applied(&history).unwrap();
+        assert_eq!(applied.statement.provenance_issue(), Some(42));
+        assert_eq!(
+            applied.statement_sheet.as_deref(),
+            Some("projects/prod-project/sheets/2")
+        );
+        assert!(last_applied(&history[3..]).is_none());
+    }
+
+    #[test]
+    fn test_last_applied_multi_sheet_issue() {
+        let changelog = |number: u32, status: &str, run: &str, issue: u32| -> Changelog {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("instances/prod/databases/app/changelogs/{number}"),
+                "createTime": format!("2024-01-01T00:{number:02}:00Z"),
+                "status": status,
+                "statement": format!(
+                    "-- shelltide-provenance: shelltide run {run}, issue #{issue}\nALTER TABLE t ADD c{number} INT;"
+                ),
+                "statementSheet": format!("projects/prod-project/sheets/{number}"),
+            }))
+            .unwrap()
+        };
+        // #43 was split into two sheets and only the first one finished.
+        let history = [
+            changelog(1, "DONE", "r1", 42),
+            changelog(2, "DONE", "r2", 43),
+            changelog(3, "FAILED", "r2", 43),
+        ];
+        let applied = last_applied(&history).unwrap();
+        assert_eq!(applied.statement.provenance_issue(), Some(42));
+
+        // A later run applied both sheets of #43; the revision points at the last one.
+        let history = [
+            changelog(1, "DONE", "r1", 42),
+            changelog(2, "DONE", "r2", 43),
+            changelog(3, "FAILED", "r2", 43),
+            changelog(4, "DONE", "r3", 43),
+            changelog(5, "DONE", "r3", 43),
+        ];
+        let applied = last_applied(&history).unwrap();
+        assert_eq!(applied.statement.provenance_issue(), Some(43));
+        assert_eq!(
+            applied.statement_sheet.as_deref(),
+            Some("projects/prod-project/sheets/5")
+        );
+
+        let pending = [
+            changelog(1, "DONE", "r1", 42),
+            changelog(2, "PENDING", "r1", 42),
+        ];
+        assert!(last_applied(&pending).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_revision_set_dry_run_and_abort() {
+        let temp_dir = tempdir().unwrap();
+        let test_config = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let mut config = AppConfig::default();
+        config.environments.insert(
+            "prod".to_string(),
+            Environment {
+                project: "prod-project".to_string(),
+                instance: "prod-instance".to_string(),
+                engine: None,
+                pin_to: None,
+            },
+        );
+        test_config.save_config(&config).await.unwrap();
+        let fake_client = FakeApiClient::default();
+        let command = |dry_run| RevisionCommand::Set {
+            target: "prod/app".parse().unwrap(),
+            version: "dev-project#42".to_string(),
+            sheet: "projects/prod-project/sheets/7".to_string(),
+            dry_run,
+            yes: false,
+        };
+
+        // Neither a dry run nor a declined confirmation reaches `create_revision`.
+        let mut input = Cursor::new("");
+        handle_revision_command_with_config(command(true), &fake_client, &test_config, &mut input)
+            .await
+            .unwrap();
+        let mut input = Cursor::new("n\n");
+        handle_revision_command_with_config(command(false), &fake_client, &test_config, &mut input)
+            .await
+            .unwrap();
+
+        let bad_version = RevisionCommand::Set {
+            target: "prod/app".parse().unwrap(),
+            version: "42".to_string(),
+            sheet: "projects/prod-project/sheets/7".to_string(),
+            dry_run: true,
+            yes: true,
+        };
+        assert!(
+            handle_revision_command_with_config(
+                bad_version,
+                &fake_client,
+                &test_config,
+                &mut Cursor::new("")
+            )
+            .await
+            .is_err()
+        );
+    }
+}
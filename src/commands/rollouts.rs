@@ -0,0 +1,111 @@
+use crate::api::traits::BytebaseApi;
+use crate::api::types::Rollout;
+use crate::cli::RolloutsArgs;
+use crate::config::{ConfigOperations, DisplayTimezone, ProductionConfig};
+use crate::error::AppError;
+use anyhow::Result;
+
+/// Handles the `rollouts` command.
+pub async fn handle_rollouts_command<T: BytebaseApi>(
+    args: RolloutsArgs,
+    api_client: &T,
+) -> Result<()> {
+    let config_ops = ProductionConfig;
+    handle_rollouts_command_with_config(args, api_client, &config_ops).await
+}
+
+pub async fn handle_rollouts_command_with_config<T: BytebaseApi, C: ConfigOperations>(
+    args: RolloutsArgs,
+    api_client: &T,
+    config_ops: &C,
+) -> Result<()> {
+    let config = config_ops.load_config().await?;
+    let env = config
+        .environments
+        .get(&args.env)
+        .ok_or_else(|| AppError::EnvNotFound(args.env.clone()))?;
+
+    let rollouts = api_client.list_rollouts(&env.project, args.limit).await?;
+    if rollouts.is_empty() {
+        println!("No rollouts in project '{}'.", env.project);
+        return Ok(());
+    }
+    for line in rollout_lines(&rollouts, &config.display.timezone()) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Formats `rollouts` as a table with a header, one line per rollout.
+fn rollout_lines(rollouts: &[Rollout], timezone: &DisplayTimezone) -> Vec<String> {
+    let rows: Vec<[String; 5]> = rollouts
+        .iter()
+        .map(|rollout| {
+            [
+                rollout.name.rollout_id.to_string(),
+                rollout.status().to_string(),
+                rollout
+                    .issue
+                    .as_ref()
+                    .map_or("-".to_string(), |issue| format!("#{}", issue.number)),
+                rollout
+                    .plan
+                    .as_ref()
+                    .map_or("-".to_string(), |plan| format!("#{}", plan.number)),
+                rollout
+                    .create_time
+                    .map_or("-".to_string(), |time| timezone.format(time)),
+            ]
+        })
+        .collect();
+    let header = ["ID", "STATUS", "ISSUE", "PLAN", "CREATED"].map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: &[String; 5]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![line(&header), line(&widths.map(|w| "-".repeat(w)))];
+    lines.extend(rows.iter().map(line));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollout_lines() {
+        let rollout: Rollout = serde_json::from_value(serde_json::json!({
+            "name": "projects/prod/rollouts/1704",
+            "plan": "projects/prod/plans/1705",
+            "issue": "projects/prod/issues/1706",
+            "createTime": "2026-01-27T09:11:27Z",
+            "stages": [{
+                "name": "projects/prod/rollouts/1704/stages/1705",
+                "tasks": [{
+                    "name": "projects/prod/rollouts/1704/stages/1705/tasks/1740",
+                    "status": "RUNNING",
+                    "type": "DATABASE_SCHEMA_UPDATE",
+                    "target": "instances/prod/databases/app"
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let lines = rollout_lines(&[rollout], &DisplayTimezone::Utc);
+        assert_eq!(lines[0], "ID   STATUS  ISSUE PLAN  CREATED");
+        assert_eq!(lines[2], "1704 RUNNING #1706 #1705 2026-01-27T09:11:27Z");
+    }
+}
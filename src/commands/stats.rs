@@ -0,0 +1,124 @@
+use crate::cli::StatsArgs;
+use crate::output;
+use crate::stats::{self, RunStats};
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Handles the `stats` command: summarizes the runs recorded in the stats file.
+pub fn handle_stats_command(args: StatsArgs) -> Result<()> {
+    let path = stats::stats_path()?;
+    let since = Utc::now() - chrono::Duration::days(i64::from(args.days));
+    let runs: Vec<RunStats> = stats::load(&path)?
+        .into_iter()
+        .filter(|run| run.started_at >= since)
+        .filter(|run| args.command.as_ref().is_none_or(|c| run.command == *c))
+        .collect();
+    if runs.is_empty() {
+        println!(
+            "No runs recorded in the last {} day(s) in {path:?}.",
+            args.days
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} run(s) in the last {} day(s), from {path:?}:\n",
+        runs.len(),
+        args.days
+    );
+    for line in stats_lines(&runs) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Formats `runs` as a table with a header and one line per command: how often it ran and
+/// failed, its typical and slowest duration, and its API usage and applied changelogs.
+fn stats_lines(runs: &[RunStats]) -> Vec<String> {
+    let mut by_command: BTreeMap<&str, Vec<&RunStats>> = BTreeMap::new();
+    for run in runs {
+        by_command.entry(&run.command).or_default().push(run);
+    }
+
+    let seconds = |ms: u64| format!("{:.1}s", Duration::from_millis(ms).as_secs_f64());
+    let rows: Vec<[String; 8]> = by_command
+        .into_iter()
+        .map(|(command, runs)| {
+            let mut durations: Vec<u64> = runs.iter().map(|run| run.duration_ms).collect();
+            durations.sort_unstable();
+            let count = runs.len() as u64;
+            let api_calls: u64 = runs.iter().map(|run| run.api_calls).sum();
+            let api_time: u64 = runs.iter().map(|run| run.api_time_ms).sum();
+            // Nearest-rank percentile.
+            let p95 = durations[(durations.len() * 95).div_ceil(100) - 1];
+            [
+                command.to_string(),
+                count.to_string(),
+                runs.iter().filter(|run| !run.success).count().to_string(),
+                seconds(durations.iter().sum::<u64>() / count),
+                seconds(p95),
+                (api_calls / count).to_string(),
+                seconds(api_time / count),
+                runs.iter()
+                    .map(|run| run.changelogs_applied)
+                    .sum::<u32>()
+                    .to_string(),
+            ]
+        })
+        .collect();
+
+    output::table_lines(
+        [
+            "COMMAND",
+            "RUNS",
+            "FAILED",
+            "AVG",
+            "P95",
+            "API CALLS/RUN",
+            "API TIME/RUN",
+            "CHANGELOGS",
+        ],
+        &rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_lines() {
+        let run = |command: &str, duration_ms, api_calls, changelogs_applied, success| RunStats {
+            command: command.to_string(),
+            started_at: Utc::now(),
+            duration_ms,
+            api_calls,
+            api_time_ms: duration_ms / 2,
+            changelogs_applied,
+            success,
+            shelltide_version: "0.1.0".to_string(),
+        };
+        let runs = vec![
+            run("status", 1000, 10, 0, true),
+            run("migrate", 60000, 40, 3, true),
+            run("status", 3000, 20, 0, true),
+            run("migrate", 20000, 20, 1, false),
+        ];
+
+        let lines = stats_lines(&runs);
+        assert_eq!(
+            lines[0],
+            "COMMAND RUNS FAILED AVG   P95   API CALLS/RUN API TIME/RUN CHANGELOGS"
+        );
+        assert_eq!(
+            lines[2],
+            "migrate 2    1      40.0s 60.0s 30            20.0s        4"
+        );
+        assert_eq!(
+            lines[3],
+            "status  2    0      2.0s  3.0s  15            1.0s         0"
+        );
+    }
+}
@@ -1,13 +1,21 @@
 use crate::api::traits::BytebaseApi;
-use crate::cli::StatusArgs;
+use crate::cli::{EnvDb, StatusArgs, StatusCommand};
+use crate::config::{ConfigOperations, DisplayTimezone, Environment};
+use crate::error::AppError;
+use crate::output::{self, OutputFormat};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
 
 pub async fn handle_status_command<T: BytebaseApi>(
     api_client: &mut T,
     args: StatusArgs,
+    output: OutputFormat,
 ) -> Result<()> {
     let config_ops = crate::config::ProductionConfig;
-    handle_status_command_with_config(api_client, args, &config_ops).await
+    handle_status_command_with_config(api_client, args, output, &config_ops).await
 }
 
 pub async fn handle_status_command_with_config<
@@ -16,39 +24,24 @@ pub async fn handle_status_command_with_config<
 >(
     api_client: &mut T,
     args: StatusArgs,
+    output: OutputFormat,
     config_ops: &C,
 ) -> Result<()> {
     let config = config_ops.load_config().await?;
 
-    if config.environments.is_empty() {
-        println!("No environments configured. Use `env add` to add one.");
+    if let Some(StatusCommand::History { target, limit }) = &args.command {
+        let snapshots = load_snapshots(&history_path(config_ops)?)?;
+        print_history(&snapshots, target, *limit, &config.display.timezone());
         return Ok(());
     }
 
-    // Get default source environment for reference - must be configured
-    let default_source_env = config.default_source_env.as_deref()
-        .ok_or_else(|| anyhow::anyhow!(
-            "Configuration error: default.source_env not set. Please run: shelltide config set default.source_env <env-name>"
-        ))?;
-    let default_env = config.environments.get(default_source_env).ok_or_else(|| {
-        anyhow::anyhow!(
-            "Default source environment '{}' not found in config",
-            default_source_env
-        )
-    })?;
-
-    // Get reference issue number from default environment
-    let reference_issue_number = match api_client.get_done_issues(&default_env.project).await {
-        Ok(issues) => issues
-            .iter()
-            .max_by_key(|issue| issue.name.number)
-            .map(|issue| issue.name.number)
-            .unwrap_or(0),
-        Err(e) => {
-            println!("Error getting reference issues from {default_source_env}: {e}");
-            return Ok(());
+    if config.environments.is_empty() {
+        if output == OutputFormat::Json {
+            return output::print_json(&[(); 0]);
         }
-    };
+        println!("No environments configured. Use `env add` to add one.");
+        return Ok(());
+    }
 
     // Parse filter if provided
     let (filter_env, filter_db) = if let Some(filter) = &args.filter {
@@ -67,23 +60,15 @@ pub async fn handle_status_command_with_config<
         (None, None)
     };
 
-    // Get databases that exist in default environment using API
-    let default_databases = match api_client.get_databases(&default_env.instance).await {
-        Ok(databases) => databases,
-        Err(e) => {
-            println!("Error getting databases from {default_source_env}: {e}");
-            return Ok(());
-        }
-    };
-
-    if default_databases.is_empty() {
-        println!("No databases found in default environment '{default_source_env}'");
-        return Ok(());
-    }
-
-    // Collect database status information
-    let mut database_info = Vec::new();
-
+    // Group the environments by the reference environment they are compared with, so
+    // workspaces spanning several projects get one reference per project.
+    let reference_envs: HashSet<&str> = config
+        .default_source_env
+        .iter()
+        .chain(config.source_envs.values())
+        .map(String::as_str)
+        .collect();
+    let mut groups: BTreeMap<&str, Vec<(&String, &Environment)>> = BTreeMap::new();
     for (env_name, env) in &config.environments {
         // Skip environment if filter is specified and doesn't match
         if let Some(filter_env) = filter_env
@@ -92,113 +77,570 @@ pub async fn handle_status_command_with_config<
             continue;
         }
 
-        // Skip default environment when showing all environments (no filter)
-        if filter_env.is_none() && env_name == default_source_env {
+        // Skip reference environments when showing all environments (no filter)
+        if filter_env.is_none()
+            && reference_envs.contains(env_name.as_str())
+            && !config.source_envs.contains_key(env_name)
+        {
             continue;
         }
 
-        let databases_to_check: Vec<String> = if let Some(filter_db) = filter_db {
-            vec![filter_db.to_string()]
-        } else {
-            default_databases.clone()
+        let source_env = config.source_env_for(env_name).ok_or_else(|| anyhow::anyhow!(
+            "Configuration error: default.source_env not set. Please run: shelltide config set default.source_env <env-name>"
+        ))?;
+        groups.entry(source_env).or_default().push((env_name, env));
+    }
+
+    // Collect database status information
+    let mut database_info = Vec::new();
+    let mut references = Vec::new();
+
+    for (source_env_name, envs) in groups {
+        let source_env = config.environments.get(source_env_name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Source environment '{}' not found in config",
+                source_env_name
+            )
+        })?;
+
+        // Get reference issue numbers from the source environment
+        let done_issues: HashSet<u32> = match api_client.get_done_issues(&source_env.project).await
+        {
+            Ok(issues) => issues.iter().map(|issue| issue.name.number).collect(),
+            Err(e) => {
+                println!("Error getting reference issues from {source_env_name}: {e}");
+                continue;
+            }
+        };
+        let reference_issue_number = done_issues.iter().max().copied().unwrap_or(0);
+
+        // Get databases that exist in the source environment using API
+        let source_databases = match api_client.get_databases(&source_env.instance).await {
+            Ok(databases) => databases,
+            Err(e) => {
+                println!("Error getting databases from {source_env_name}: {e}");
+                continue;
+            }
         };
 
-        for database_name in &databases_to_check {
-            match api_client
-                .get_latests_revisions_silent(&env.instance, database_name)
-                .await
-            {
-                Ok(revision) => {
-                    if let Some(version) = revision.version.as_ref() {
-                        let current_issue = version.number;
-                        let status = if current_issue >= reference_issue_number {
-                            "UP TO DATE".to_string()
+        if source_databases.is_empty() {
+            println!("No databases found in source environment '{source_env_name}'");
+            continue;
+        }
+        references.push(StatusReference {
+            environment: source_env_name.to_string(),
+            project: source_env.project.clone(),
+            issue: reference_issue_number,
+        });
+
+        for (env_name, env) in envs {
+            let databases_to_check: Vec<String> = if let Some(filter_db) = filter_db {
+                vec![filter_db.to_string()]
+            } else {
+                source_databases.clone()
+            };
+
+            for database_name in &databases_to_check {
+                // Rows are grouped by the source name; `schema` shows the name in this environment.
+                let env_database = config.mapped_database(env_name, database_name);
+                let mut row = StatusRow {
+                    schema: format!("{}/{}", env.instance, env_database),
+                    environment: env_name.clone(),
+                    database: database_name.clone(),
+                    status: String::new(),
+                    revision_version: None,
+                    current_issue: None,
+                    revision_time: None,
+                    sheet: None,
+                    reference_environment: Some(source_env_name.to_string()),
+                };
+                let sync_error = if args.refresh_revisions {
+                    api_client
+                        .sync_database(&env.instance, env_database)
+                        .await
+                        .err()
+                } else {
+                    None
+                };
+                match api_client
+                    .latest_revision(&env.instance, env_database, config.revision.latest_by())
+                    .await
+                {
+                    Ok(revision) => {
+                        // A missing database fails both calls; only report the sync of existing ones.
+                        if let Some(e) = sync_error {
+                            eprintln!("Warning: Failed to sync {}: {e}", row.schema);
+                        }
+                        row.revision_time = revision.create_time;
+                        row.sheet = Some(revision.sheet.to_string());
+                        if let Some(version) = revision.version.as_ref() {
+                            let current_issue = version.number;
+                            row.status = revision_status(current_issue, &done_issues);
+                            row.revision_version = Some(version.to_string());
+                            row.current_issue = Some(current_issue);
                         } else {
-                            format!("#{current_issue}")
-                        };
-
-                        database_info.push((
-                            format!("{}/{}", env.instance, database_name),
-                            env_name.clone(),
-                            status,
-                        ));
-                    } else {
-                        database_info.push((
-                            format!("{}/{}", env.instance, database_name),
-                            env_name.clone(),
-                            "NO VERSION".to_string(),
-                        ));
+                            row.status = "NO VERSION".to_string();
+                        }
+                    }
+                    Err(_) => {
+                        row.status = "NOT EXIST".to_string();
                     }
                 }
-                Err(_) => {
-                    database_info.push((
-                        format!("{}/{}", env.instance, database_name),
-                        env_name.clone(),
-                        "NOT EXIST".to_string(),
-                    ));
-                }
+                database_info.push(row);
             }
         }
     }
 
-    // Sort by database name (extract from schema path) for consistent display
+    // The default source environment is the main reference; otherwise the first one.
+    let Some(main_reference) = references
+        .iter()
+        .find(|r| config.default_source_env.as_ref() == Some(&r.environment))
+        .or(references.first())
+    else {
+        return Ok(());
+    };
+    let (reference_environment, reference_issue_number) =
+        (main_reference.environment.clone(), main_reference.issue);
+
+    // Sort by database name, then environment, for consistent display
     database_info.sort_by(|a, b| {
-        let db_a = a.0.split('/').next_back().unwrap_or(&a.0);
-        let db_b = b.0.split('/').next_back().unwrap_or(&b.0);
-        db_a.cmp(db_b).then_with(|| a.1.cmp(&b.1))
+        a.database
+            .cmp(&b.database)
+            .then_with(|| a.environment.cmp(&b.environment))
     });
 
-    // Display status table
-    print_status_table(&database_info);
+    let mut report = StatusReport {
+        reference_environment,
+        reference_issue: reference_issue_number,
+        references,
+        computed_at: Utc::now(),
+        databases: database_info,
+    };
+    if !args.read_only
+        && let Err(e) = append_snapshot(&history_path(config_ops)?, &report).await
+    {
+        eprintln!("Warning: Failed to record status history: {e}");
+    }
 
-    println!(
-        "\nReference environment: {default_source_env} (latest issue: #{reference_issue_number})"
-    );
+    if args.only_outdated {
+        let total = report.databases.len();
+        retain_outdated(&mut report.databases, args.by_issue);
+        if report.databases.is_empty() && total > 0 && !args.json && output == OutputFormat::Table {
+            println!("All {total} databases are up to date.");
+        }
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    if output == OutputFormat::Json {
+        return output::print_json(&report.databases);
+    }
+
+    // Environments of several projects are shown in one section per reference.
+    let sectioned = report.references.len() > 1;
+    for reference in &report.references {
+        let rows: Vec<StatusRow> = report
+            .databases
+            .iter()
+            .filter(|row| report.reference_of(row) == reference.environment)
+            .cloned()
+            .collect();
+        if sectioned {
+            println!("== Project {} ==", reference.project);
+        }
+        let lines = if args.by_issue {
+            issue_matrix_lines(&rows)
+        } else {
+            status_table_lines(&rows, &args.columns, report.computed_at)
+        };
+        for line in lines {
+            println!("{line}");
+        }
+        println!(
+            "\nReference environment: {} (latest issue: #{})",
+            reference.environment, reference.issue
+        );
+        if sectioned {
+            println!();
+        }
+    }
 
     Ok(())
 }
 
-fn print_status_table(database_info: &[(String, String, String)]) {
-    if database_info.is_empty() {
-        return;
+/// Drops the rows of up-to-date databases, for `--only-outdated`. With `by_issue`, every row of
+/// a database with an outdated row is kept, so the matrix row stays whole and the odd cell can
+/// be compared with its neighbours.
+fn retain_outdated(rows: &mut Vec<StatusRow>, by_issue: bool) {
+    if by_issue {
+        let outdated: HashSet<String> = rows
+            .iter()
+            .filter(|row| row.status != UP_TO_DATE)
+            .map(|row| row.database.clone())
+            .collect();
+        rows.retain(|row| outdated.contains(&row.database));
+    } else {
+        rows.retain(|row| row.status != UP_TO_DATE);
     }
+}
+
+const UP_TO_DATE: &str = "UP TO DATE";
+/// The latest revision refers to an issue that is not DONE in the reference project.
+const DIVERGED: &str = "DIVERGED";
+
+/// Status of a database whose latest revision is at `current_issue`, compared with the DONE
+/// issues of the reference project: `UP TO DATE`, `#<issue>` or `DIVERGED`.
+pub(crate) fn revision_status(current_issue: u32, done_issues: &HashSet<u32>) -> String {
+    let reference_issue = done_issues.iter().max().copied().unwrap_or(0);
+    if !done_issues.contains(&current_issue) {
+        // Not a routine migrate: the target has a change the reference lacks.
+        DIVERGED.to_string()
+    } else if current_issue >= reference_issue {
+        UP_TO_DATE.to_string()
+    } else {
+        format!("#{current_issue}")
+    }
+}
 
-    // Calculate dynamic column widths
-    let mut max_schema_width = "SCHEMA".len();
-    let mut max_env_width = "ENVIRONMENT".len();
-    let max_status_width = "LATEST CHANGELOG".len();
+/// Migration status of one database in one environment.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct StatusRow {
+    /// `<instance>/<database>`
+    schema: String,
+    environment: String,
+    database: String,
+    /// `UP TO DATE`, `#<issue>`, `DIVERGED`, `NO VERSION` or `NOT EXIST`.
+    status: String,
+    /// Raw version of the latest revision, e.g. `dev-project#101`.
+    revision_version: Option<String>,
+    current_issue: Option<u32>,
+    /// When the latest revision was recorded.
+    revision_time: Option<DateTime<Utc>>,
+    /// Sheet of the latest revision, e.g. `projects/dev-project/sheets/12`.
+    sheet: Option<String>,
+    /// Environment this row was compared with; `None` in snapshots that had only one.
+    #[serde(default)]
+    reference_environment: Option<String>,
+}
+
+/// A column of the `status` table, chosen with `--columns`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatusColumn {
+    /// `<instance>/<database>`
+    Schema,
+    /// Environment name
+    Env,
+    /// Latest changelog compared with the reference, e.g. `UP TO DATE` or `#240`
+    Status,
+    /// Raw version of the latest revision, e.g. `dev-project#240`
+    Version,
+    /// Time since the latest revision was recorded
+    Age,
+    /// Sheet of the latest revision
+    Sheet,
+}
 
-    for (schema_path, env_name, _status) in database_info {
-        max_schema_width = max_schema_width.max(schema_path.len());
-        max_env_width = max_env_width.max(env_name.len());
+impl StatusColumn {
+    fn header(self) -> &'static str {
+        match self {
+            Self::Schema => "SCHEMA",
+            Self::Env => "ENVIRONMENT",
+            Self::Status => "LATEST CHANGELOG",
+            Self::Version => "VERSION",
+            Self::Age => "AGE",
+            Self::Sheet => "SHEET",
+        }
     }
 
-    max_schema_width += 1;
-    max_env_width += 1;
+    fn cell(self, row: &StatusRow, now: DateTime<Utc>) -> String {
+        let or_dash = |value: Option<&String>| value.cloned().unwrap_or_else(|| "-".to_string());
+        match self {
+            Self::Schema => row.schema.clone(),
+            Self::Env => row.environment.clone(),
+            Self::Status => row.status.clone(),
+            Self::Version => or_dash(row.revision_version.as_ref()),
+            Self::Age => row
+                .revision_time
+                .map_or("-".to_string(), |time| format_lag(now - time)),
+            Self::Sheet => or_dash(row.sheet.as_ref()),
+        }
+    }
+}
+
+/// `status --json` output: the rows plus how `UP TO DATE` was decided. Every run is also
+/// appended to the status history.
+#[derive(Serialize, Deserialize, Debug)]
+struct StatusReport {
+    /// The default source environment, or the first reference when it was not used.
+    reference_environment: String,
+    /// Latest DONE issue of the reference environment; rows at or above it are `UP TO DATE`.
+    reference_issue: u32,
+    /// Every reference environment, one per project the shown environments compare with.
+    #[serde(default)]
+    references: Vec<StatusReference>,
+    computed_at: DateTime<Utc>,
+    databases: Vec<StatusRow>,
+}
+
+/// A reference environment of a [`StatusReport`].
+#[derive(Serialize, Deserialize, Debug)]
+struct StatusReference {
+    environment: String,
+    project: String,
+    /// Latest DONE issue of `project`.
+    issue: u32,
+}
+
+impl StatusReport {
+    /// Name of the environment `row` was compared with.
+    fn reference_of<'a>(&'a self, row: &'a StatusRow) -> &'a str {
+        row.reference_environment
+            .as_deref()
+            .unwrap_or(&self.reference_environment)
+    }
+
+    /// Latest DONE issue of the reference `environment` in this snapshot, if it was used.
+    fn reference_issue_of(&self, environment: &str) -> Option<u32> {
+        if environment == self.reference_environment {
+            return Some(self.reference_issue);
+        }
+        self.references
+            .iter()
+            .find(|r| r.environment == environment)
+            .map(|r| r.issue)
+    }
+}
+
+/// Number of status snapshots kept in the history file; older ones are dropped.
+const MAX_STATUS_SNAPSHOTS: usize = 1000;
+
+/// The status history, `~/.shelltide/status-history.jsonl`: one [`StatusReport`] per line.
+fn history_path<C: ConfigOperations>(config_ops: &C) -> Result<PathBuf> {
+    Ok(config_ops
+        .config_path()?
+        .with_file_name("status-history.jsonl"))
+}
+
+async fn append_snapshot(path: &Path, report: &StatusReport) -> Result<(), AppError> {
+    crate::ndjson::append_capped(path, report, MAX_STATUS_SNAPSHOTS).await
+}
+
+/// Reads the status history, oldest first. Lines that cannot be parsed are skipped.
+fn load_snapshots(path: &Path) -> Result<Vec<StatusReport>, AppError> {
+    crate::ndjson::load(path)
+}
+
+/// Status of one database in one snapshot of the history.
+#[derive(Debug, PartialEq)]
+struct HistoryRow {
+    computed_at: DateTime<Utc>,
+    reference_issue: u32,
+    status: String,
+    /// How long the reference has had an issue this database lacks; `None` when up to date.
+    lag: Option<chrono::Duration>,
+}
+
+/// The snapshots of `target`, oldest first. The lag of a snapshot is measured from the first
+/// snapshot whose reference issue was already past the database's issue at that time.
+fn history_rows(snapshots: &[StatusReport], target: &EnvDb) -> Vec<HistoryRow> {
+    snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            let row = snapshot
+                .databases
+                .iter()
+                .find(|row| row.environment == target.env && row.database == target.db)?;
+            let reference = snapshot.reference_of(row);
+            let lag = (row.status != UP_TO_DATE).then(|| {
+                let current = row.current_issue.unwrap_or(0);
+                let behind_since = snapshots
+                    .iter()
+                    .filter(|s| s.computed_at <= snapshot.computed_at)
+                    .find(|s| {
+                        s.reference_issue_of(reference)
+                            .is_some_and(|issue| issue > current)
+                    })
+                    .map_or(snapshot.computed_at, |s| s.computed_at);
+                snapshot.computed_at - behind_since
+            });
+            Some(HistoryRow {
+                computed_at: snapshot.computed_at,
+                reference_issue: snapshot.reference_issue_of(reference)?,
+                status: row.status.clone(),
+                lag,
+            })
+        })
+        .collect()
+}
+
+fn print_history(
+    snapshots: &[StatusReport],
+    target: &EnvDb,
+    limit: usize,
+    timezone: &DisplayTimezone,
+) {
+    let rows = history_rows(snapshots, target);
+    if rows.is_empty() {
+        println!(
+            "No status history for '{}/{}'. History is recorded on every `shelltide status` run.",
+            target.env, target.db
+        );
+        return;
+    }
+
+    let shown = &rows[rows.len().saturating_sub(limit)..];
+    let times: Vec<String> = shown
+        .iter()
+        .map(|row| timezone.format(row.computed_at))
+        .collect();
+    let time_width = times.iter().map(String::len).max().unwrap_or(0).max(4);
     println!(
-        "{:<width1$} {:<width2$} {:<width3$}",
-        "SCHEMA",
-        "ENVIRONMENT",
-        "LATEST CHANGELOG",
-        width1 = max_schema_width,
-        width2 = max_env_width,
-        width3 = max_status_width
+        "{:<time_width$} {:<10} {:<16} LAG",
+        "TIME", "REFERENCE", "LATEST CHANGELOG"
     );
+    println!("{:-<time_width$} {:-<10} {:-<16} {:-<8}", "", "", "", "");
+    for (row, time) in shown.iter().zip(&times) {
+        println!(
+            "{:<time_width$} {:<10} {:<16} {}",
+            time,
+            format!("#{}", row.reference_issue),
+            row.status,
+            row.lag.map_or("-".to_string(), format_lag)
+        );
+    }
+
+    let behind = rows.iter().filter(|row| row.lag.is_some()).count();
     println!(
-        "{:-<width1$} {:-<width2$} {:-<width3$}",
-        "",
-        "",
-        "",
-        width1 = max_schema_width,
-        width2 = max_env_width,
-        width3 = max_status_width
+        "\n'{}/{}' was behind the reference in {behind} of {} snapshot(s).",
+        target.env,
+        target.db,
+        rows.len()
     );
+    if let Some(longest) = rows.iter().filter_map(|row| row.lag).max() {
+        println!("Longest lag: {}", format_lag(longest));
+    }
+}
 
-    for (schema_path, env_name, status) in database_info {
-        println!(
-            "{schema_path:<max_schema_width$} {env_name:<max_env_width$} {status:<max_status_width$}"
+/// e.g. `2d 3h`, `4h 10m` or `5m`.
+pub(crate) fn format_lag(lag: chrono::Duration) -> String {
+    let minutes = lag.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Formats `rows` as a table of the chosen `columns`, with a header.
+fn status_table_lines(
+    rows: &[StatusRow],
+    columns: &[StatusColumn],
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    if rows.is_empty() {
+        return Vec::new();
+    }
+
+    let header: Vec<String> = columns.iter().map(|c| c.header().to_string()).collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| columns.iter().map(|c| c.cell(row, now)).collect())
+        .collect();
+    let mut widths: Vec<usize> = header.iter().map(String::len).collect();
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end()
+            .to_string()
+    };
+    let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+    let mut lines = vec![line(&header), line(&separator)];
+    lines.extend(cells.iter().map(|row| line(row)));
+    lines
+}
+
+/// Formats `rows` as a matrix of databases by environments. Each cell holds the applied issue,
+/// marked with `*` when it is behind the reference and `!` when it diverged from it; `-` means
+/// the database does not exist and `?` that it has no version.
+fn issue_matrix_lines(rows: &[StatusRow]) -> Vec<String> {
+    if rows.is_empty() {
+        return Vec::new();
+    }
+    let environments: Vec<&str> = rows
+        .iter()
+        .map(|row| row.environment.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let mut databases: Vec<&str> = rows.iter().map(|row| row.database.as_str()).collect();
+    databases.dedup();
+
+    let cell = |database: &str, environment: &str| {
+        let Some(row) = rows
+            .iter()
+            .find(|row| row.database == database && row.environment == environment)
+        else {
+            return String::new();
+        };
+        match row.current_issue {
+            Some(issue) if row.status == UP_TO_DATE => format!("#{issue}"),
+            Some(issue) if row.status == DIVERGED => format!("#{issue}!"),
+            Some(issue) => format!("#{issue}*"),
+            None if row.status == "NO VERSION" => "?".to_string(),
+            None => "-".to_string(),
+        }
+    };
+    let mut table = vec![
+        std::iter::once("DATABASE".to_string())
+            .chain(environments.iter().map(|env| env.to_string()))
+            .collect::<Vec<_>>(),
+    ];
+    for database in &databases {
+        table.push(
+            std::iter::once(database.to_string())
+                .chain(environments.iter().map(|env| cell(database, env)))
+                .collect(),
         );
     }
+
+    let mut widths = vec![0; environments.len() + 1];
+    for cells in &table {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end()
+            .to_string()
+    };
+    let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+    let mut lines = vec![line(&table[0]), line(&separator)];
+    lines.extend(table[1..].iter().map(|cells| line(cells)));
+    lines
+        .push("\n* behind the reference, ! diverged, - database missing, ? no version".to_string());
+    lines
 }
 
 #[cfg(test)]
@@ -238,19 +680,23 @@ mod tests {
             let temp_config = crate::config::TestConfig {
                 test_dir: temp_path,
             };
-            let mut test_config = crate::config::AppConfig::default();
-            test_config.default_source_env = Some("dev".to_string());
-            test_config.credentials = Some(Credentials {
-                url: "https://fake-url.com".into(),
-                service_account: "fake-service-account".into(),
-                service_key: Some("fake-service-key".into()),
-                access_token: "fake-access-token".into(),
-            });
+            let mut test_config = crate::config::AppConfig {
+                default_source_env: Some("dev".to_string()),
+                credentials: Some(Credentials {
+                    url: "https://fake-url.com".into(),
+                    service_account: "fake-service-account".into(),
+                    service_key: Some("fake-service-key".into()),
+                    access_token: "fake-access-token".into(),
+                }),
+                ..Default::default()
+            };
             test_config.environments.insert(
                 "dev".into(),
                 Environment {
                     project: "dev-project".into(),
                     instance: "dev-instance".into(),
+                    engine: None,
+                    pin_to: None,
                 },
             );
             test_config.environments.insert(
@@ -258,6 +704,8 @@ mod tests {
                 Environment {
                     project: "prod-project".into(),
                     instance: "prod-instance".into(),
+                    engine: None,
+                    pin_to: None,
                 },
             );
             temp_config.save_config(&test_config).await.unwrap();
@@ -284,13 +732,262 @@ mod tests {
                 projects: projects_data,
             };
 
-            let status_args = crate::cli::StatusArgs { filter: None };
-            let result =
-                handle_status_command_with_config(&mut fake_client, status_args, &temp_config)
-                    .await;
+            let status_args = crate::cli::StatusArgs {
+                command: None,
+                filter: None,
+                only_outdated: false,
+                read_only: false,
+                refresh_revisions: true,
+                json: false,
+                by_issue: false,
+                columns: vec![
+                    StatusColumn::Schema,
+                    StatusColumn::Env,
+                    StatusColumn::Status,
+                ],
+            };
+            let result = handle_status_command_with_config(
+                &mut fake_client,
+                status_args,
+                OutputFormat::Table,
+                &temp_config,
+            )
+            .await;
 
             assert!(result.is_ok());
+            let history = load_snapshots(&history_path(&temp_config).unwrap()).unwrap();
+            assert_eq!(history.len(), 1);
+            // The fake revision is #100, which is not among the reference's DONE issues.
+            assert!(
+                history[0]
+                    .databases
+                    .iter()
+                    .all(|row| row.status == DIVERGED)
+            );
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn test_status_compares_each_project_with_its_reference() {
+        let temp_dir = tempdir().unwrap();
+        let temp_config = crate::config::TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let mut config = crate::config::AppConfig {
+            default_source_env: Some("dev".to_string()),
+            ..Default::default()
+        };
+        for (name, project) in [
+            ("dev", "dev-project"),
+            ("prod", "dev-project"),
+            ("dev-b", "b-project"),
+            ("prod-b", "b-project"),
+        ] {
+            config.environments.insert(
+                name.into(),
+                Environment {
+                    project: project.into(),
+                    instance: format!("{name}-instance"),
+                    engine: None,
+                    pin_to: None,
+                },
+            );
+        }
+        config
+            .source_envs
+            .insert("prod-b".to_string(), "dev-b".to_string());
+        temp_config.save_config(&config).await.unwrap();
+        let mut projects = HashMap::new();
+        projects.insert(
+            "dev-project".to_string(),
+            vec![Issue {
+                name: "projects/dev-project/issues/100".into(),
+            }],
+        );
+        projects.insert(
+            "b-project".to_string(),
+            vec![Issue {
+                name: "projects/b-project/issues/45".into(),
+            }],
+        );
+        let mut fake_client = FakeApiClient { projects };
+        let status_args = crate::cli::StatusArgs {
+            command: None,
+            filter: None,
+            only_outdated: false,
+            read_only: false,
+            refresh_revisions: false,
+            json: false,
+            by_issue: false,
+            columns: vec![StatusColumn::Schema, StatusColumn::Status],
+        };
+
+        handle_status_command_with_config(
+            &mut fake_client,
+            status_args,
+            OutputFormat::Table,
+            &temp_config,
+        )
+        .await
+        .unwrap();
+        let report = load_snapshots(&history_path(&temp_config).unwrap())
+            .unwrap()
+            .remove(0);
+        assert_eq!(report.reference_environment, "dev");
+        let references: Vec<_> = report
+            .references
+            .iter()
+            .map(|r| (r.environment.as_str(), r.project.as_str(), r.issue))
+            .collect();
+        assert_eq!(
+            references,
+            [("dev", "dev-project", 100), ("dev-b", "b-project", 45)]
+        );
+        // Reference environments are not listed; each other one uses its own reference.
+        for row in &report.databases {
+            let (reference, status) = match row.environment.as_str() {
+                "prod" => ("dev", UP_TO_DATE),
+                "prod-b" => ("dev-b", DIVERGED),
+                other => panic!("unexpected environment {other}"),
+            };
+            assert_eq!(report.reference_of(row), reference);
+            assert_eq!(row.status, status);
+        }
+    }
+
+    #[test]
+    fn test_history_rows_track_lag() {
+        let snapshot = |minute: i64, reference_issue, status: &str, current_issue| StatusReport {
+            reference_environment: "dev".to_string(),
+            reference_issue,
+            references: Vec::new(),
+            computed_at: DateTime::from_timestamp(minute * 60, 0).unwrap(),
+            databases: vec![StatusRow {
+                schema: "prod-instance/app".to_string(),
+                environment: "prod".to_string(),
+                database: "app".to_string(),
+                status: status.to_string(),
+                revision_version: None,
+                current_issue,
+                revision_time: None,
+                sheet: None,
+                reference_environment: None,
+            }],
+        };
+        let snapshots = vec![
+            snapshot(0, 100, UP_TO_DATE, Some(100)),
+            snapshot(30, 105, "#100", Some(100)),
+            snapshot(90, 107, "#100", Some(100)),
+            snapshot(120, 107, UP_TO_DATE, Some(107)),
+        ];
+        let target: EnvDb = "prod/app".parse().unwrap();
+
+        let lags: Vec<_> = history_rows(&snapshots, &target)
+            .into_iter()
+            .map(|row| row.lag.map(|lag| lag.num_minutes()))
+            .collect();
+        assert_eq!(lags, vec![None, Some(0), Some(60), None]);
+        assert!(history_rows(&snapshots, &"prod/other".parse().unwrap()).is_empty());
+        assert_eq!(format_lag(chrono::Duration::minutes(60 * 27 + 5)), "1d 3h");
+    }
+
+    #[test]
+    fn test_issue_matrix_lines() {
+        let row = |database: &str, environment: &str, status: &str, current_issue| StatusRow {
+            schema: format!("{environment}-instance/{database}"),
+            environment: environment.to_string(),
+            database: database.to_string(),
+            status: status.to_string(),
+            revision_version: None,
+            current_issue,
+            revision_time: None,
+            sheet: None,
+            reference_environment: None,
+        };
+        let rows = vec![
+            row("app", "prod", UP_TO_DATE, Some(107)),
+            row("app", "qa", UP_TO_DATE, Some(107)),
+            row("log", "prod", "#100", Some(100)),
+            row("log", "qa", "NOT EXIST", None),
+            row("user", "qa", "NO VERSION", None),
+            row("user", "prod", DIVERGED, Some(103)),
+        ];
+
+        let lines = issue_matrix_lines(&rows);
+        assert_eq!(lines[0], "DATABASE prod  qa");
+        assert_eq!(lines[2], "app      #107  #107");
+        assert_eq!(lines[3], "log      #100* -");
+        assert_eq!(lines[4], "user     #103! ?");
+    }
+
+    #[test]
+    fn test_retain_outdated() {
+        let row = |database: &str, environment: &str, status: &str| StatusRow {
+            schema: format!("{environment}-instance/{database}"),
+            environment: environment.to_string(),
+            database: database.to_string(),
+            status: status.to_string(),
+            revision_version: None,
+            current_issue: None,
+            revision_time: None,
+            sheet: None,
+            reference_environment: None,
+        };
+        let rows = vec![
+            row("app", "prod", UP_TO_DATE),
+            row("app", "qa", UP_TO_DATE),
+            row("log", "prod", "#100"),
+            row("log", "qa", UP_TO_DATE),
+            row("user", "prod", DIVERGED),
+        ];
+        let kept = |by_issue| {
+            let mut rows = rows.clone();
+            retain_outdated(&mut rows, by_issue);
+            rows.iter()
+                .map(|row| format!("{}/{}", row.environment, row.database))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kept(false), ["prod/log", "prod/user"]);
+        assert_eq!(kept(true), ["prod/log", "qa/log", "prod/user"]);
+    }
+
+    #[test]
+    fn test_status_table_columns() {
+        let now = DateTime::from_timestamp(3 * 3600, 0).unwrap();
+        let rows = vec![
+            StatusRow {
+                schema: "prod-instance/app".to_string(),
+                environment: "prod".to_string(),
+                database: "app".to_string(),
+                status: "#100".to_string(),
+                revision_version: Some("dev-project#100".to_string()),
+                current_issue: Some(100),
+                revision_time: DateTime::from_timestamp(3600, 0),
+                sheet: Some("projects/prod-project/sheets/7".to_string()),
+                reference_environment: None,
+            },
+            StatusRow {
+                schema: "prod-instance/log".to_string(),
+                environment: "prod".to_string(),
+                database: "log".to_string(),
+                status: "NOT EXIST".to_string(),
+                revision_version: None,
+                current_issue: None,
+                revision_time: None,
+                sheet: None,
+                reference_environment: None,
+            },
+        ];
+
+        let columns = [StatusColumn::Env, StatusColumn::Version, StatusColumn::Age];
+        let lines = status_table_lines(&rows, &columns, now);
+        assert_eq!(lines[0], "ENVIRONMENT VERSION         AGE");
+        assert_eq!(lines[2], "prod        dev-project#100 2h 0m");
+        assert_eq!(lines[3], "prod        -               -");
+
+        let lines = status_table_lines(&rows, &[StatusColumn::Sheet], now);
+        assert_eq!(lines[2], "projects/prod-project/sheets/7");
+    }
 }
@@ -1,30 +1,629 @@
+use crate::api::polling::{DEFAULT_NOT_STARTED_TIMEOUT, DEFAULT_POLL_INTERVAL};
+use crate::api::types::{LatestBy, SQLDialect};
+use crate::error::AppError;
+use crate::keychain::Keychain;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 
 /// Represents the main configuration for the application, stored in `~/.shelltide/config.json`.
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct AppConfig {
+    /// Layout version of this file; older layouts are upgraded on load.
+    #[serde(default)]
+    pub schema_version: SchemaVersion,
     /// Default source environment for `apply` commands.
     pub default_source_env: Option<String>,
     /// Bytebase instance credentials.
     pub credentials: Option<Credentials>,
+    /// Where the secrets of the credentials are kept.
+    #[serde(default)]
+    pub credential_store: CredentialStoreSettings,
+    /// Credentials of further Bytebase instances by profile name, used instead of
+    /// `credentials` when the profile is selected with `--profile`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, Credentials>,
     /// A map of environment names to their configuration details.
     #[serde(default)]
     pub environments: HashMap<String, Environment>,
     /// A map of release names to their details.
     #[serde(default)]
     pub releases: HashMap<String, Release>,
+    /// Settings for the sheets created during migrations.
+    #[serde(default)]
+    pub sheet: SheetSettings,
+    /// Settings for talking to the Bytebase API.
+    #[serde(default)]
+    pub api: ApiSettings,
+    /// Settings for waiting on rollouts.
+    #[serde(default)]
+    pub rollout: RolloutSettings,
+    /// Settings for the shared team configuration.
+    #[serde(default)]
+    pub team: TeamSettings,
+    /// Settings for how output is displayed.
+    #[serde(default)]
+    pub display: DisplaySettings,
+    /// Settings for reporting promotions back to tracking issues.
+    #[serde(default)]
+    pub notify: NotifySettings,
+    /// Settings for the issues created on targets.
+    #[serde(default)]
+    pub issue: IssueSettings,
+    /// Settings for `migrate` runs.
+    #[serde(default)]
+    pub migrate: MigrateSettings,
+    /// Settings for reading database revisions.
+    #[serde(default)]
+    pub revision: RevisionSettings,
+    /// Per-environment database names that differ from the source: `env -> {source_db: db}`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub db_map: HashMap<String, HashMap<String, String>>,
+    /// Reference environment of environments in another project than `default_source_env`:
+    /// `env -> source_env`. `status` compares each environment with its own reference.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub source_envs: BTreeMap<String, String>,
+    /// Naming patterns for `env add --from-template`, by template name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_templates: BTreeMap<String, EnvTemplate>,
+    /// Environments of `team.config_url`, including locally overridden ones. Entries still
+    /// equal to these are not written back to `config.json`, so only local overrides are
+    /// persisted.
+    #[serde(skip)]
+    pub shared_environments: BTreeMap<String, Environment>,
+    /// The profile selected for this run; `None` uses `credentials`.
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+/// Layout version of `config.json`. New configs start at [`CURRENT_SCHEMA_VERSION`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SchemaVersion(pub u32);
+
+impl Default for SchemaVersion {
+    fn default() -> Self {
+        Self(CURRENT_SCHEMA_VERSION)
+    }
+}
+
+/// Default upper bound for a single sheet's content, in bytes.
+pub const DEFAULT_MAX_SHEET_SIZE: usize = 1024 * 1024;
+
+/// Settings for the sheets created during migrations (`sheet.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct SheetSettings {
+    /// Statements larger than this many bytes are split across several sheets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<usize>,
+}
+
+impl SheetSettings {
+    pub fn max_size(&self) -> usize {
+        self.max_size.unwrap_or(DEFAULT_MAX_SHEET_SIZE)
+    }
+}
+
+/// Default total time spent waiting on rate-limited (429) requests before giving up, in seconds.
+pub const DEFAULT_RETRY_BUDGET_SECS: u64 = 60;
+
+/// Settings for talking to the Bytebase API (`api.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ApiSettings {
+    /// Rate-limited requests are retried until this many seconds have been spent waiting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_budget: Option<u64>,
+}
+
+impl ApiSettings {
+    pub fn retry_budget(&self) -> u64 {
+        self.retry_budget.unwrap_or(DEFAULT_RETRY_BUDGET_SECS)
+    }
+}
+
+/// Default for `rollout.slow_threshold`.
+pub const DEFAULT_SLOW_ROLLOUT_SECS: u64 = 60;
+
+/// Settings for waiting on rollouts (`rollout.*` keys), in seconds.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RolloutSettings {
+    /// Initial delay between rollout status polls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval: Option<u64>,
+    /// How long a rollout may sit with every task NOT_STARTED before giving up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_started_timeout: Option<u64>,
+    /// Rollouts taking longer than this are flagged as slow in the migrate summary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_threshold: Option<u64>,
+}
+
+impl RolloutSettings {
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+            .map_or(DEFAULT_POLL_INTERVAL, Duration::from_secs)
+    }
+
+    pub fn not_started_timeout(&self) -> Duration {
+        self.not_started_timeout
+            .map_or(DEFAULT_NOT_STARTED_TIMEOUT, Duration::from_secs)
+    }
+
+    pub fn slow_threshold(&self) -> Duration {
+        Duration::from_secs(self.slow_threshold.unwrap_or(DEFAULT_SLOW_ROLLOUT_SECS))
+    }
+}
+
+/// Settings for the shared team configuration (`team.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct TeamSettings {
+    /// URL of a YAML environment file (as written by `env export`) merged into every load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_url: Option<String>,
+}
+
+/// Settings for reporting promotions (`notify.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct NotifySettings {
+    /// Tracking issue `migrate` comments on after a promotion, as `<number>` in the source
+    /// project or `<project>#<number>`. `--notify-issue` takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// Jira server `migrate --jira` reports promotions to.
+    #[serde(default)]
+    pub jira: JiraSettings,
+}
+
+/// Settings for the issues created on targets during migrations (`issue.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct IssueSettings {
+    /// Markdown file whose placeholders are filled in to describe each created issue, see
+    /// [`crate::issue_template::IssueTemplate`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_template: Option<String>,
+}
+
+/// Settings for `migrate` runs (`migrate.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MigrateSettings {
+    /// Runs that would apply more changelogs than this need `--confirm-large-run`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_changes: Option<u32>,
+}
+
+/// Where the secrets of saved credentials are kept (`credentials.backend` key).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CredentialStoreSettings {
+    /// `plaintext` (default) or `keyring`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+}
+
+impl CredentialStoreSettings {
+    /// The configured backend, falling back to plaintext if it is unset or unrecognized.
+    pub fn backend(&self) -> CredentialsBackend {
+        self.backend
+            .as_deref()
+            .and_then(|backend| backend.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Where `service_key` and `access_token` of saved credentials are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CredentialsBackend {
+    /// In `config.json` itself.
+    #[default]
+    Plaintext,
+    /// In the OS keychain; `config.json` keeps only the URL and service account.
+    Keyring,
+}
+
+impl std::str::FromStr for CredentialsBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plaintext" => Ok(Self::Plaintext),
+            "keyring" => Ok(Self::Keyring),
+            _ => Err(format!(
+                "Unknown credentials backend '{s}'. Use plaintext or keyring."
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for CredentialsBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Plaintext => "plaintext",
+            Self::Keyring => "keyring",
+        })
+    }
+}
+
+/// Keeps secrets outside `config.json` for the keyring backend.
+pub trait SecretStore {
+    /// The secret stored under `key`, if any.
+    fn get(&self, key: &str) -> Result<Option<String>>;
+    /// Stores `secret` under `key`, replacing any earlier one.
+    fn set(&self, key: &str, secret: &str) -> Result<()>;
+}
+
+/// The secrets of [`Credentials`] that the keyring backend keeps out of `config.json`.
+const SECRET_FIELDS: [&str; 2] = ["service_key", "access_token"];
+
+/// Key of secret `field` of the credentials of `service_account` at `url`.
+fn secret_key(field: &str, url: &str, service_account: &str) -> String {
+    format!("{field}:{service_account}@{url}")
+}
+
+/// The credentials objects (`credentials` and every profile) of the serialized config `value`.
+fn credentials_objects(
+    value: &mut serde_json::Value,
+) -> Vec<&mut serde_json::Map<String, serde_json::Value>> {
+    let Some(object) = value.as_object_mut() else {
+        return Vec::new();
+    };
+    object
+        .iter_mut()
+        .flat_map(|(key, entry)| match key.as_str() {
+            "credentials" => vec![entry],
+            "profiles" => entry
+                .as_object_mut()
+                .map(|profiles| profiles.values_mut().collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        })
+        .filter_map(serde_json::Value::as_object_mut)
+        .collect()
+}
+
+/// Drops secret `name` from a credentials object. `access_token` is a required field, so it
+/// stays as an empty string.
+fn remove_secret(credentials: &mut serde_json::Map<String, serde_json::Value>, name: &str) {
+    if name == "access_token" {
+        credentials.insert(name.to_string(), String::new().into());
+    } else {
+        credentials.remove(name);
+    }
+}
+
+fn secret_field(credentials: &serde_json::Map<String, serde_json::Value>, name: &str) -> String {
+    credentials
+        .get(name)
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Moves the secrets of the credentials in the serialized config `value` into `store`. A secret
+/// the store rejects stays in the file, with a warning, rather than being lost.
+fn move_secrets_to(value: &mut serde_json::Value, store: &impl SecretStore) {
+    for credentials in credentials_objects(value) {
+        let url = secret_field(credentials, "url");
+        let service_account = secret_field(credentials, "service_account");
+        for name in SECRET_FIELDS {
+            let secret = secret_field(credentials, name);
+            if secret.is_empty() {
+                continue;
+            }
+            match store.set(&secret_key(name, &url, &service_account), &secret) {
+                Ok(()) => remove_secret(credentials, name),
+                Err(e) => eprintln!(
+                    "Warning: Could not store {name} in the keyring, keeping it in config.json: {e:#}"
+                ),
+            }
+        }
+    }
+}
+
+/// Removes every secret from the serialized config `value`. Returns whether there was any.
+fn strip_secrets(value: &mut serde_json::Value) -> bool {
+    let mut stripped = false;
+    for credentials in credentials_objects(value) {
+        for name in SECRET_FIELDS {
+            if !secret_field(credentials, name).is_empty() {
+                remove_secret(credentials, name);
+                stripped = true;
+            }
+        }
+    }
+    stripped
+}
+
+/// Fills in the secrets `config.json` does not hold from `store`. A keychain that cannot be read
+/// fails the load instead of leaving the credentials empty.
+fn fill_secrets_from(config: &mut AppConfig, store: &impl SecretStore) -> Result<()> {
+    for credentials in config
+        .credentials
+        .iter_mut()
+        .chain(config.profiles.values_mut())
+    {
+        let key = |field| secret_key(field, &credentials.url, &credentials.service_account);
+        let lookup = |field| {
+            store.get(&key(field)).with_context(|| {
+                format!(
+                    "Could not read {field} from the keyring; unlock it, or switch back with `shelltide config set credentials.backend plaintext`"
+                )
+            })
+        };
+        if credentials.service_key.is_none() {
+            credentials.service_key = lookup("service_key")?;
+        }
+        if credentials.access_token.is_empty() {
+            credentials.access_token = lookup("access_token")?.unwrap_or_default();
+        }
+    }
+    Ok(())
+}
+
+/// Settings for reading database revisions (`revision.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RevisionSettings {
+    /// Which revision is a database's current one: `create_time` (default) or `version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_by: Option<String>,
+}
+
+impl RevisionSettings {
+    /// The configured order, falling back to create time if it is unset or unrecognized.
+    pub fn latest_by(&self) -> LatestBy {
+        self.latest_by
+            .as_deref()
+            .and_then(|by| by.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Jira server and workflow used by `migrate --jira` (`notify.jira.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct JiraSettings {
+    /// Base URL of the Jira server, e.g. `https://example.atlassian.net`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// API token, or `<email>:<api-token>` for Jira Cloud.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Transition, by name or id, applied after a successful promotion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_success: Option<String>,
+    /// Transition, by name or id, applied after a failed promotion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>,
+}
+
+/// Settings for how output is displayed (`display.*` keys).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DisplaySettings {
+    /// Timezone timestamps are shown in: `UTC`, `local` or an IANA name such as `Asia/Seoul`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+impl DisplaySettings {
+    /// The configured timezone, falling back to UTC if it is unset or unrecognized.
+    pub fn timezone(&self) -> DisplayTimezone {
+        self.timezone
+            .as_deref()
+            .and_then(|tz| tz.parse().ok())
+            .unwrap_or(DisplayTimezone::Utc)
+    }
+}
+
+/// Timezone used to render timestamps in command output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayTimezone {
+    Utc,
+    /// The system timezone.
+    Local,
+    Named(chrono_tz::Tz),
+}
+
+impl std::str::FromStr for DisplayTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("utc") {
+            Ok(Self::Utc)
+        } else if s.eq_ignore_ascii_case("local") {
+            Ok(Self::Local)
+        } else {
+            s.parse::<chrono_tz::Tz>().map(Self::Named).map_err(|_| {
+                format!(
+                    "Unknown timezone '{s}'. Use UTC, local or an IANA name such as Asia/Seoul."
+                )
+            })
+        }
+    }
+}
+
+impl DisplayTimezone {
+    /// Formats `timestamp` as RFC 3339 in this timezone: `Z` for UTC, the offset otherwise.
+    pub fn format(&self, timestamp: DateTime<Utc>) -> String {
+        match self {
+            Self::Utc => timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            Self::Local => timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%dT%H:%M:%S%:z")
+                .to_string(),
+            Self::Named(tz) => timestamp
+                .with_timezone(tz)
+                .format("%Y-%m-%dT%H:%M:%S%:z")
+                .to_string(),
+        }
+    }
+}
+
+/// Environment map shared as YAML by `env export`/`env import` and `team.config_url`.
+/// Credentials are never part of it.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EnvironmentFile {
+    pub environments: BTreeMap<String, Environment>,
 }
 
 impl AppConfig {
+    /// Adds `shared` environments that are not defined locally; local definitions win.
+    pub fn merge_shared_environments(&mut self, shared: BTreeMap<String, Environment>) {
+        for (name, env) in shared {
+            if !self.environments.contains_key(&name) {
+                self.environments.insert(name.clone(), env.clone());
+            }
+            self.shared_environments.insert(name, env);
+        }
+    }
+
+    /// The name `source_db` has in environment `env`, following `db_map` when it has an entry.
+    pub fn mapped_database<'a>(&'a self, env: &str, source_db: &'a str) -> &'a str {
+        self.db_map
+            .get(env)
+            .and_then(|map| map.get(source_db))
+            .map_or(source_db, String::as_str)
+    }
+
+    /// The issue `env` is pinned to (`env set --pin-to`), following a pinned release to its
+    /// issue. `None` if the environment is not pinned.
+    pub fn pinned_version(&self, env: &str) -> Result<Option<u32>, AppError> {
+        let Some(pin) = self.environments.get(env).and_then(|e| e.pin_to.as_deref()) else {
+            return Ok(None);
+        };
+        self.resolve_pin(pin).map(Some)
+    }
+
+    /// The issue of `pin`: an issue number, or the name of a configured release.
+    pub fn resolve_pin(&self, pin: &str) -> Result<u32, AppError> {
+        if let Ok(issue) = pin.parse() {
+            return Ok(issue);
+        }
+        self.releases
+            .get(pin)
+            .map(|release| release.issue_number)
+            .ok_or_else(|| {
+                AppError::Config(format!(
+                    "Pinned version '{pin}' is neither an issue number nor a configured release."
+                ))
+            })
+    }
+
+    /// The environment `env` is compared with: its `source_envs` entry, else the default.
+    pub fn source_env_for(&self, env: &str) -> Option<&str> {
+        self.source_envs
+            .get(env)
+            .or(self.default_source_env.as_ref())
+            .map(String::as_str)
+    }
+
     pub fn get_credentials(&self) -> Result<&Credentials> {
-        self.credentials
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("No credentials found. please run `shelltide login`"))
+        self.active_credentials().ok_or_else(|| match &self.profile {
+            Some(profile) => anyhow::anyhow!(
+                "No credentials found for profile '{profile}'. please run `shelltide login --profile {profile}`"
+            ),
+            None => anyhow::anyhow!("No credentials found. please run `shelltide login`"),
+        })
+    }
+
+    /// The saved credentials of the selected profile, if any.
+    pub fn active_credentials(&self) -> Option<&Credentials> {
+        match &self.profile {
+            Some(profile) => self.profiles.get(profile),
+            None => self.credentials.as_ref(),
+        }
+    }
+
+    /// Saves `credentials` as those of the selected profile.
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        match &self.profile {
+            Some(profile) => {
+                self.profiles.insert(profile.clone(), credentials);
+            }
+            None => self.credentials = Some(credentials),
+        }
+    }
+
+    /// The saved credentials with any `BYTEBASE_*` environment variables applied on top.
+    pub fn resolve_credentials(&self, env: &EnvCredentials) -> Result<Credentials> {
+        if env.service_account.is_some() != env.service_key.is_some() {
+            anyhow::bail!(
+                "{BYTEBASE_SERVICE_ACCOUNT_VAR} and {BYTEBASE_SERVICE_ACCOUNT_SECRET_VAR} must be set together"
+            );
+        }
+        let saved = self.active_credentials();
+        let url = env
+            .url
+            .clone()
+            .or_else(|| saved.map(|c| c.url.clone()))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No credentials found. please run `shelltide login` or set {BYTEBASE_URL_VAR}"
+                )
+            })?;
+        if let (Some(service_account), Some(service_key)) = (&env.service_account, &env.service_key)
+        {
+            return Ok(Credentials {
+                url,
+                service_account: service_account.clone(),
+                service_key: Some(service_key.clone()),
+                access_token: env.access_token.clone().unwrap_or_default(),
+            });
+        }
+        if let Some(access_token) = &env.access_token {
+            return Ok(Credentials {
+                url,
+                service_account: saved.map(|c| c.service_account.clone()).unwrap_or_default(),
+                service_key: None,
+                access_token: access_token.clone(),
+            });
+        }
+        let mut credentials = self.get_credentials()?.clone();
+        credentials.url = url;
+        Ok(credentials)
+    }
+}
+
+/// Bytebase URL, as read by Bytebase's own `bb` CLI and GitHub Action.
+pub const BYTEBASE_URL_VAR: &str = "BYTEBASE_URL";
+/// Service account email used to log in.
+pub const BYTEBASE_SERVICE_ACCOUNT_VAR: &str = "BYTEBASE_SERVICE_ACCOUNT";
+/// Service account key used to log in.
+pub const BYTEBASE_SERVICE_ACCOUNT_SECRET_VAR: &str = "BYTEBASE_SERVICE_ACCOUNT_SECRET";
+/// Ready-made access token, used as is.
+pub const BYTEBASE_ACCESS_TOKEN_VAR: &str = "BYTEBASE_ACCESS_TOKEN";
+
+/// Credentials given through `BYTEBASE_*` environment variables. They take precedence over
+/// `config.json` and are never written to it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnvCredentials {
+    pub url: Option<String>,
+    pub service_account: Option<String>,
+    pub service_key: Option<String>,
+    pub access_token: Option<String>,
+}
+
+impl EnvCredentials {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let get = |name| lookup(name).filter(|value: &String| !value.trim().is_empty());
+        Self {
+            url: get(BYTEBASE_URL_VAR).map(|url| url.trim_end_matches('/').to_string()),
+            service_account: get(BYTEBASE_SERVICE_ACCOUNT_VAR),
+            service_key: get(BYTEBASE_SERVICE_ACCOUNT_SECRET_VAR),
+            access_token: get(BYTEBASE_ACCESS_TOKEN_VAR),
+        }
+    }
+
+    /// Whether the environment supplies the identity, not just the URL. Such credentials are
+    /// not refreshed through `config.json`.
+    pub fn has_identity(&self) -> bool {
+        self.service_account.is_some() || self.access_token.is_some()
     }
 }
 
@@ -50,19 +649,156 @@ pub struct Credentials {
 }
 
 /// Stores details for a single environment.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Environment {
     /// The corresponding project name or ID in Bytebase.
     pub project: String,
     /// The instance name
     pub instance: String,
+    /// Database engine of the instance; detected from Bytebase when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<SQLDialect>,
+    /// Version `migrate` uses when no `--to` is given: an issue number or a release name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_to: Option<String>,
+}
+
+/// Pairs `(name, earlier)` of environments that use the same instance as an earlier one.
+fn duplicate_instances<'a>(
+    environments: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<(&'a str, &'a str)> {
+    let mut first_by_instance: HashMap<&str, &str> = HashMap::new();
+    let mut duplicates = Vec::new();
+    for (name, instance) in environments {
+        match first_by_instance.get(instance) {
+            Some(first) => duplicates.push((name, *first)),
+            None => {
+                first_by_instance.insert(instance, name);
+            }
+        }
+    }
+    duplicates
+}
+
+/// Naming pattern for a family of environments (`env_template.<template>.*` keys). `{var}`
+/// placeholders are filled from the `KEY=VALUE` arguments of `env add --from-template`, where
+/// `name` is the new environment's name, e.g. `project: "game-{region}"`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct EnvTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<SQLDialect>,
+}
+
+impl EnvTemplate {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The environment this template describes for `vars`.
+    pub fn instantiate(&self, vars: &BTreeMap<String, String>) -> Result<Environment> {
+        let field = |name: &str, pattern: &Option<String>| {
+            let pattern = pattern
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("template has no {name} pattern"))?;
+            fill_placeholders(pattern, vars)
+        };
+        Ok(Environment {
+            project: field("project", &self.project)?,
+            instance: field("instance", &self.instance)?,
+            engine: self.engine.clone(),
+            pin_to: None,
+        })
+    }
+}
+
+/// Replaces every `{var}` in `pattern` with its value from `vars`.
+fn fill_placeholders(pattern: &str, vars: &BTreeMap<String, String>) -> Result<String> {
+    let mut filled = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..]
+            .find('}')
+            .map(|i| open + i)
+            .ok_or_else(|| anyhow::anyhow!("unclosed '{{' in pattern '{pattern}'"))?;
+        let var = &rest[open + 1..close];
+        let value = vars.get(var).ok_or_else(|| {
+            anyhow::anyhow!("pattern '{pattern}' needs '{var}'; pass it as {var}=<value>")
+        })?;
+        filled.push_str(&rest[..open]);
+        filled.push_str(value);
+        rest = &rest[close + 1..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
 }
 
 /// Trait for configuration operations to enable dependency injection
 #[async_trait]
-pub trait ConfigOperations {
+pub trait ConfigOperations: Sync {
     async fn load_config(&self) -> Result<AppConfig>;
     async fn save_config(&self, config: &AppConfig) -> Result<()>;
+    /// Location of the config file these operations read and write.
+    fn config_path(&self) -> Result<PathBuf>;
+
+    /// Takes the config lock; hold it across a load-modify-save cycle.
+    async fn lock_config(&self) -> Result<ConfigLock> {
+        ConfigLock::acquire(&self.config_path()?).await
+    }
+}
+
+/// Longest pause between two attempts to take a held lock, see [`lock_file`].
+const MAX_LOCK_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Exclusive advisory lock on `config.json.lock`, released on drop. Keeps concurrent shelltide
+/// processes (e.g. parallel CI jobs refreshing a token) from overwriting each other's changes.
+#[derive(Debug)]
+pub struct ConfigLock {
+    _file: Option<std::fs::File>,
+}
+
+impl ConfigLock {
+    /// Waits until the lock for the config file at `config_path` is available, see [`lock_file`].
+    pub async fn acquire(config_path: &Path) -> Result<Self> {
+        let config_dir = config_path.parent().unwrap_or_else(|| Path::new(""));
+        std::fs::create_dir_all(config_dir)
+            .with_context(|| format!("Failed to create config directory at {config_dir:?}"))?;
+        let file = lock_file(&config_path.with_extension("json.lock")).await?;
+        Ok(Self { _file: Some(file) })
+    }
+
+    /// A lock that holds nothing, for operations that never write the config.
+    pub fn unlocked() -> Self {
+        Self { _file: None }
+    }
+}
+
+/// Opens (creating it if needed) `lock_path` and waits for an exclusive advisory lock on it,
+/// held until the returned file is dropped. The lock is polled rather than waited on in the OS,
+/// so a wait never blocks a runtime thread.
+pub(crate) async fn lock_file(lock_path: &Path) -> Result<std::fs::File> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)
+        .with_context(|| format!("Failed to open lock file at {lock_path:?}"))?;
+    let mut backoff = Duration::from_millis(10);
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(std::fs::TryLockError::WouldBlock) => {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_LOCK_BACKOFF);
+            }
+            Err(std::fs::TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {lock_path:?}"));
+            }
+        }
+    }
 }
 
 /// Production implementation of ConfigOperations
@@ -71,12 +807,56 @@ pub struct ProductionConfig;
 #[async_trait]
 impl ConfigOperations for ProductionConfig {
     async fn load_config(&self) -> Result<AppConfig> {
-        load_config().await
+        let mut config = load_config().await?;
+        if let Some(url) = &config.team.config_url
+            && let Some(shared) = shared_environments(url).await
+        {
+            config.merge_shared_environments(shared.clone());
+        }
+        Ok(config)
     }
 
     async fn save_config(&self, config: &AppConfig) -> Result<()> {
         save_config(config).await
     }
+
+    fn config_path(&self) -> Result<PathBuf> {
+        get_config_path()
+    }
+
+    /// Fetches the shared environments before taking the lock, so loads under it never wait on
+    /// `team.config_url`.
+    async fn lock_config(&self) -> Result<ConfigLock> {
+        let config_path = self.config_path()?;
+        if let Some(url) = read_team_config_url(&config_path).await {
+            shared_environments(&url).await;
+        }
+        ConfigLock::acquire(&config_path).await
+    }
+}
+
+/// Wraps config operations so that nothing is ever written: saves are dropped and no lock file
+/// is created. A token refreshed through it lives only in the running client, which suits
+/// read-only contexts such as CI status checks on a mounted secret.
+pub struct ReadOnlyConfig<'a, C>(pub &'a C);
+
+#[async_trait]
+impl<C: ConfigOperations + Sync> ConfigOperations for ReadOnlyConfig<'_, C> {
+    async fn load_config(&self) -> Result<AppConfig> {
+        self.0.load_config().await
+    }
+
+    async fn save_config(&self, _config: &AppConfig) -> Result<()> {
+        Ok(())
+    }
+
+    fn config_path(&self) -> Result<PathBuf> {
+        self.0.config_path()
+    }
+
+    async fn lock_config(&self) -> Result<ConfigLock> {
+        Ok(ConfigLock::unlocked())
+    }
 }
 
 #[cfg(test)]
@@ -94,10 +874,21 @@ impl ConfigOperations for TestConfig {
     async fn save_config(&self, config: &AppConfig) -> Result<()> {
         save_test_config(config, &self.test_dir).await
     }
+
+    fn config_path(&self) -> Result<PathBuf> {
+        Ok(get_test_config_path(&self.test_dir))
+    }
 }
 
-/// Returns the path to the shelltide configuration directory, `~/.shelltide`.
-fn get_config_dir() -> Result<PathBuf> {
+/// Environment variable that replaces `~/.shelltide` as the configuration directory.
+pub const CONFIG_DIR_ENV: &str = "SHELLTIDE_HOME";
+
+/// Returns the path to the shelltide configuration directory: `SHELLTIDE_HOME` if set, else
+/// `.shelltide` in the user's home directory (`%USERPROFILE%` on Windows, not `HOME`).
+pub(crate) fn get_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
     let home_dir = dirs::home_dir().context("Failed to find home directory")?;
     Ok(home_dir.join(".shelltide"))
 }
@@ -119,26 +910,123 @@ fn get_config_path() -> Result<PathBuf> {
 
 /// Loads the application configuration from the default path.
 /// If the config file or directory doesn't exist, it returns a default, empty config.
+/// The profile selected with `--profile` is applied to it.
 pub async fn load_config() -> Result<AppConfig> {
-    let config_path = get_config_path()?;
+    let mut config = load_config_from(&get_config_path()?).await?;
+    config.profile = PROFILE.get().cloned();
+    Ok(config)
+}
+
+/// Profile selected with `--profile`, for every config loaded from the default path.
+static PROFILE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// Selects the credentials profile of this run. Only the first selection counts.
+pub fn select_profile(profile: String) {
+    let _ = PROFILE.set(profile);
+}
+
+/// Saves the provided application configuration to the default path.
+/// It will create the necessary directory and file if they don't exist.
+pub async fn save_config(config: &AppConfig) -> Result<()> {
+    save_config_to(config, &get_config_path()?).await
+}
+
+#[cfg(test)]
+pub async fn load_test_config(test_home: &Path) -> Result<AppConfig> {
+    load_config_from(&get_test_config_path(test_home)).await
+}
+
+#[cfg(test)]
+pub async fn save_test_config(config: &AppConfig, test_home: &Path) -> Result<()> {
+    save_config_to(config, &get_test_config_path(test_home)).await
+}
+
+/// Outcome of fetching `team.config_url`, at most once per process. A failed fetch is kept as
+/// `None`, so it is warned about once instead of retried on every load.
+static SHARED_ENVIRONMENTS: tokio::sync::OnceCell<Option<BTreeMap<String, Environment>>> =
+    tokio::sync::OnceCell::const_new();
+
+/// The shared environments at `url`, fetched on first use and reused for the rest of the
+/// process; `None` if that fetch failed.
+async fn shared_environments(url: &str) -> Option<&'static BTreeMap<String, Environment>> {
+    SHARED_ENVIRONMENTS
+        .get_or_init(|| async {
+            fetch_shared_environments(url)
+                .await
+                .inspect_err(|e| eprintln!("Warning: could not load team config from {url}: {e:#}"))
+                .ok()
+        })
+        .await
+        .as_ref()
+}
+
+/// Fetches the shared environment file at `url`. Only https is accepted, redirects included.
+async fn fetch_shared_environments(url: &str) -> Result<BTreeMap<String, Environment>> {
+    if !url.starts_with("https://") {
+        anyhow::bail!("team.config_url must be an https URL");
+    }
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .https_only(true)
+        .build()?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?;
+    let file: EnvironmentFile = serde_yaml::from_str(&response.text().await?)
+        .context("Failed to parse shared environment file")?;
+    Ok(file.environments)
+}
+
+/// `team.config_url` of the config file at `config_path`, without loading the rest of it. A
+/// missing or unreadable file has none; loading it reports the problem.
+async fn read_team_config_url(config_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(config_path).await.ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    value
+        .pointer("/team/config_url")?
+        .as_str()
+        .map(ToString::to_string)
+}
+
+/// Loads the configuration at `config_path`, upgrading older layouts in memory. Loading never
+/// writes; the upgraded layout is persisted by the next save.
+async fn load_config_from(config_path: &Path) -> Result<AppConfig> {
     if !config_path.exists() {
         return Ok(AppConfig::default());
     }
 
-    let content = fs::read_to_string(&config_path)
+    let content = fs::read_to_string(config_path)
         .await
         .with_context(|| format!("Failed to read config file at {config_path:?}"))?;
 
-    let config: AppConfig = serde_json::from_str(&content)
+    let value: serde_json::Value = serde_json::from_str(&content)
         .with_context(|| format!("Failed to parse config file at {config_path:?}"))?;
+    let (value, _) = upgrade_config(value)
+        .with_context(|| format!("Failed to upgrade config file at {config_path:?}"))?;
+    let validation = validate_config(&value);
+    if !validation.errors.is_empty() {
+        anyhow::bail!(
+            "Invalid config file at {config_path:?}:\n{}",
+            format_problems(validation.errors.iter().chain(&validation.warnings))
+        );
+    }
+    if !validation.warnings.is_empty() {
+        eprintln!(
+            "Warning: config file at {config_path:?} has problems:\n{}",
+            format_problems(&validation.warnings)
+        );
+    }
+    let mut config: AppConfig = serde_json::from_value(value)
+        .with_context(|| format!("Failed to parse config file at {config_path:?}"))?;
+    if config.credential_store.backend() == CredentialsBackend::Keyring {
+        fill_secrets_from(&mut config, &Keychain)?;
+    }
 
     Ok(config)
 }
 
-/// Saves the provided application configuration to the default path.
-/// It will create the necessary directory and file if they don't exist.
-pub async fn save_config(config: &AppConfig) -> Result<()> {
-    let config_path = get_config_path()?;
+async fn save_config_to(config: &AppConfig, config_path: &Path) -> Result<()> {
     let config_dir = config_path.parent().unwrap_or_else(|| Path::new(""));
 
     if !config_dir.exists() {
@@ -146,51 +1034,1064 @@ pub async fn save_config(config: &AppConfig) -> Result<()> {
             .await
             .with_context(|| format!("Failed to create config directory at {config_dir:?}"))?;
     }
-
-    let content = serde_json::to_string_pretty(config)
+    let mut value =
+        serde_json::to_value(config).context("Failed to serialize configuration to JSON")?;
+    if let Some(environments) = value
+        .get_mut("environments")
+        .and_then(|envs| envs.as_object_mut())
+    {
+        for (name, shared) in &config.shared_environments {
+            if config.environments.get(name) == Some(shared) {
+                environments.remove(name);
+            }
+        }
+    }
+    // Secrets go to the keychain before any backup is taken, and the backups (which may hold
+    // them from before the switch to the keyring) lose theirs.
+    let keyring = config.credential_store.backend() == CredentialsBackend::Keyring;
+    if keyring {
+        move_secrets_to(&mut value, &Keychain);
+    }
+    let content = serde_json::to_string_pretty(&value)
         .context("Failed to serialize configuration to JSON")?;
+    back_up_legacy_config(config_path, keyring).await?;
+    backup_config(config_path).await?;
+    if keyring {
+        scrub_config_backups(config_path).await?;
+    }
+
+    write_config_atomically(config_path, &content).await
+}
+
+/// Writes `content` to a temp file next to `config_path` and renames it into place, so readers
+/// never observe a partially written config.
+async fn write_config_atomically(config_path: &Path, content: &str) -> Result<()> {
+    let tmp_path = config_path.with_extension(format!("json.tmp.{}", std::process::id()));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // The config holds credentials; keep it private to the user. On Windows the file inherits
+    // the ACL of the profile directory, which already restricts it.
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options
+        .open(&tmp_path)
+        .await
+        .with_context(|| format!("Failed to create temp config file at {tmp_path:?}"))?;
+    file.write_all(content.as_bytes())
+        .await
+        .with_context(|| format!("Failed to write temp config file at {tmp_path:?}"))?;
+    file.sync_all()
+        .await
+        .with_context(|| format!("Failed to flush temp config file at {tmp_path:?}"))?;
+    drop(file);
 
-    fs::write(&config_path, content)
+    replace_file(&tmp_path, config_path)
         .await
         .with_context(|| format!("Failed to write config file to {config_path:?}"))?;
+    Ok(())
+}
+
+/// Renames `from` over `to`. Windows refuses to replace a file another process (a concurrent
+/// reader, an antivirus scan) has open, so there a denied rename is retried for a moment.
+async fn replace_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut attempts = 0;
+    loop {
+        match fs::rename(from, to).await {
+            Err(e)
+                if cfg!(windows)
+                    && e.kind() == std::io::ErrorKind::PermissionDenied
+                    && attempts < 10 =>
+            {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Before a save replaces a config file of an older layout, keeps that file next to it as
+/// `config.json.v<old-version>.bak`, without its secrets when they belong in the keyring.
+async fn back_up_legacy_config(config_path: &Path, keyring: bool) -> Result<()> {
+    let Ok(content) = fs::read_to_string(config_path).await else {
+        return Ok(());
+    };
+    let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Ok(());
+    };
+    let old_version = value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+    if old_version >= CURRENT_SCHEMA_VERSION {
+        return Ok(());
+    }
 
+    let backup_path = config_path.with_extension(format!("json.v{old_version}.bak"));
+    let content = if keyring && strip_secrets(&mut value) {
+        serde_json::to_string_pretty(&value).context("Failed to serialize configuration to JSON")?
+    } else {
+        content
+    };
+    write_config_atomically(&backup_path, &content)
+        .await
+        .with_context(|| format!("Failed to back up config file to {backup_path:?}"))?;
+    eprintln!(
+        "Upgraded config from schema version {old_version} to {CURRENT_SCHEMA_VERSION} (backup: {backup_path:?})"
+    );
     Ok(())
 }
 
-#[cfg(test)]
-pub async fn load_test_config(test_home: &Path) -> Result<AppConfig> {
-    let config_path = get_test_config_path(test_home);
+/// Number of timestamped backups kept in `~/.shelltide/backups`.
+pub const MAX_CONFIG_BACKUPS: usize = 10;
+
+fn get_backups_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join("backups")
+}
+
+/// Copies the current config file (if any) to `backups/config-<timestamp>.json` and prunes all
+/// but the newest [`MAX_CONFIG_BACKUPS`] backups.
+async fn backup_config(config_path: &Path) -> Result<()> {
     if !config_path.exists() {
-        return Ok(AppConfig::default());
+        return Ok(());
     }
 
-    let content = fs::read_to_string(&config_path)
+    let backups_dir = get_backups_dir(config_path);
+    fs::create_dir_all(&backups_dir)
         .await
-        .with_context(|| format!("Failed to read config file at {config_path:?}"))?;
+        .with_context(|| format!("Failed to create backup directory at {backups_dir:?}"))?;
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let backup_path = backups_dir.join(format!("config-{timestamp}.json"));
+    fs::copy(config_path, &backup_path)
+        .await
+        .with_context(|| format!("Failed to back up config file to {backup_path:?}"))?;
 
-    let config: AppConfig = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse config file at {config_path:?}"))?;
+    for old in list_config_backups(config_path)
+        .await?
+        .into_iter()
+        .skip(MAX_CONFIG_BACKUPS)
+    {
+        fs::remove_file(&old)
+            .await
+            .with_context(|| format!("Failed to remove old config backup {old:?}"))?;
+    }
+    Ok(())
+}
 
-    Ok(config)
+/// Removes the secrets from every config backup, so the keyring backend leaves no plaintext
+/// copy of them behind and restoring a backup cannot bring one back.
+async fn scrub_config_backups(config_path: &Path) -> Result<()> {
+    for backup in list_config_backups(config_path).await? {
+        let content = fs::read_to_string(&backup)
+            .await
+            .with_context(|| format!("Failed to read config backup at {backup:?}"))?;
+        // A backup that does not parse cannot be restored either.
+        let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&content) else {
+            continue;
+        };
+        if strip_secrets(&mut value) {
+            let content = serde_json::to_string_pretty(&value)
+                .context("Failed to serialize configuration to JSON")?;
+            write_config_atomically(&backup, &content).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Lists config backups, newest first.
+pub async fn list_config_backups(config_path: &Path) -> Result<Vec<PathBuf>> {
+    let backups_dir = get_backups_dir(config_path);
+    if !backups_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    let mut entries = fs::read_dir(&backups_dir)
+        .await
+        .with_context(|| format!("Failed to read backup directory at {backups_dir:?}"))?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("config-") && name.ends_with(".json") {
+            backups.push(entry.path());
+        }
+    }
+    // Timestamps sort lexically, so the newest backup has the greatest name.
+    backups.sort_by(|a, b| b.cmp(a));
+    Ok(backups)
+}
+
+/// Replaces the config file with `backup`, after checking that the backup is a valid config.
+/// The config being replaced is itself backed up first.
+pub async fn restore_config_backup(config_path: &Path, backup: &Path) -> Result<()> {
+    let content = fs::read_to_string(backup)
+        .await
+        .with_context(|| format!("Failed to read config backup at {backup:?}"))?;
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Config backup at {backup:?} is not valid JSON"))?;
+    let (value, _) = upgrade_config(value)?;
+    serde_json::from_value::<AppConfig>(value)
+        .with_context(|| format!("Config backup at {backup:?} is not a valid config"))?;
+
+    backup_config(config_path).await?;
+    write_config_atomically(config_path, &content).await
+}
+
+/// Current layout version of `config.json`.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrade steps between layouts; `CONFIG_MIGRATIONS[n]` turns version `n` into `n + 1`.
+const CONFIG_MIGRATIONS: [fn(&mut serde_json::Value) -> Result<()>;
+    CURRENT_SCHEMA_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Brings a raw config document up to [`CURRENT_SCHEMA_VERSION`]. Returns the upgraded document
+/// and, when anything changed, the version it started from.
+fn upgrade_config(mut value: serde_json::Value) -> Result<(serde_json::Value, Option<u32>)> {
+    let version = value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+    if version > CURRENT_SCHEMA_VERSION {
+        anyhow::bail!(
+            "config schema version {version} is newer than this shelltide supports ({CURRENT_SCHEMA_VERSION}); please upgrade shelltide"
+        );
+    }
+    if version == CURRENT_SCHEMA_VERSION {
+        return Ok((value, None));
+    }
+
+    for migration in &CONFIG_MIGRATIONS[version as usize..] {
+        migration(&mut value)?;
+    }
+    value["schema_version"] = CURRENT_SCHEMA_VERSION.into();
+    Ok((value, Some(version)))
+}
+
+/// Version 0 is every config written before `schema_version` existed. Its layout is the same
+/// as version 1, so only the version stamp is added.
+fn migrate_v0_to_v1(value: &mut serde_json::Value) -> Result<()> {
+    if !value.is_object() {
+        anyhow::bail!("config root must be a JSON object");
+    }
+    Ok(())
+}
+
+/// One problem found in `config.json`, e.g. `environments.prod.instance: missing field`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProblem {
+    /// Dotted path of the offending field.
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Everything wrong with a config document. Errors prevent loading it; warnings (unknown keys,
+/// references to environments that do not exist) are reported but the config is still used.
+#[derive(Debug, Default)]
+pub struct ConfigValidation {
+    pub errors: Vec<ConfigProblem>,
+    pub warnings: Vec<ConfigProblem>,
+}
+
+fn format_problems<'a>(problems: impl IntoIterator<Item = &'a ConfigProblem>) -> String {
+    problems
+        .into_iter()
+        .map(|problem| format!("  - {problem}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Expected layout of a config value.
+enum Shape {
+    /// A leaf value; the check returns what is wrong with it.
+    Value(fn(&serde_json::Value) -> Option<&'static str>),
+    /// An object with these known keys; `true` marks the required ones.
+    Object(&'static [(&'static str, Shape, bool)]),
+    /// An object with arbitrary keys whose values all have the given shape.
+    Map(&'static Shape),
+}
+
+fn string(value: &serde_json::Value) -> Option<&'static str> {
+    (!value.is_string()).then_some("expected a string")
+}
+
+fn unsigned(value: &serde_json::Value) -> Option<&'static str> {
+    (!value.is_u64()).then_some("expected a non-negative integer")
+}
+
+fn engine(value: &serde_json::Value) -> Option<&'static str> {
+    serde_json::from_value::<SQLDialect>(value.clone())
+        .is_err()
+        .then_some("unknown database engine")
+}
+
+const ENVIRONMENT: Shape = Shape::Object(&[
+    ("project", Shape::Value(string), true),
+    ("instance", Shape::Value(string), true),
+    ("engine", Shape::Value(engine), false),
+    ("pin_to", Shape::Value(string), false),
+]);
+
+const ENV_TEMPLATE: Shape = Shape::Object(&[
+    ("project", Shape::Value(string), false),
+    ("instance", Shape::Value(string), false),
+    ("engine", Shape::Value(engine), false),
+]);
+
+const CREDENTIALS: Shape = Shape::Object(&[
+    ("url", Shape::Value(string), true),
+    ("service_account", Shape::Value(string), true),
+    ("service_key", Shape::Value(string), false),
+    ("access_token", Shape::Value(string), true),
+]);
+
+const RELEASE: Shape = Shape::Object(&[
+    ("from_env", Shape::Value(string), true),
+    ("issue_number", Shape::Value(unsigned), true),
+    ("source_project", Shape::Value(string), true),
+]);
+
+/// Layout of [`AppConfig`] at [`CURRENT_SCHEMA_VERSION`].
+const CONFIG: Shape = Shape::Object(&[
+    ("schema_version", Shape::Value(unsigned), false),
+    ("default_source_env", Shape::Value(string), false),
+    ("credentials", CREDENTIALS, false),
+    ("profiles", Shape::Map(&CREDENTIALS), false),
+    ("environments", Shape::Map(&ENVIRONMENT), false),
+    ("releases", Shape::Map(&RELEASE), false),
+    (
+        "sheet",
+        Shape::Object(&[("max_size", Shape::Value(unsigned), false)]),
+        false,
+    ),
+    (
+        "api",
+        Shape::Object(&[("retry_budget", Shape::Value(unsigned), false)]),
+        false,
+    ),
+    (
+        "rollout",
+        Shape::Object(&[
+            ("poll_interval", Shape::Value(unsigned), false),
+            ("not_started_timeout", Shape::Value(unsigned), false),
+            ("slow_threshold", Shape::Value(unsigned), false),
+        ]),
+        false,
+    ),
+    (
+        "team",
+        Shape::Object(&[("config_url", Shape::Value(string), false)]),
+        false,
+    ),
+    (
+        "display",
+        Shape::Object(&[("timezone", Shape::Value(string), false)]),
+        false,
+    ),
+    (
+        "notify",
+        Shape::Object(&[
+            ("issue", Shape::Value(string), false),
+            (
+                "jira",
+                Shape::Object(&[
+                    ("url", Shape::Value(string), false),
+                    ("token", Shape::Value(string), false),
+                    ("on_success", Shape::Value(string), false),
+                    ("on_failure", Shape::Value(string), false),
+                ]),
+                false,
+            ),
+        ]),
+        false,
+    ),
+    (
+        "issue",
+        Shape::Object(&[("description_template", Shape::Value(string), false)]),
+        false,
+    ),
+    (
+        "migrate",
+        Shape::Object(&[("max_changes", Shape::Value(unsigned), false)]),
+        false,
+    ),
+    (
+        "revision",
+        Shape::Object(&[("latest_by", Shape::Value(string), false)]),
+        false,
+    ),
+    (
+        "credential_store",
+        Shape::Object(&[("backend", Shape::Value(string), false)]),
+        false,
+    ),
+    (
+        "db_map",
+        Shape::Map(&Shape::Map(&Shape::Value(string))),
+        false,
+    ),
+    ("env_templates", Shape::Map(&ENV_TEMPLATE), false),
+    ("source_envs", Shape::Map(&Shape::Value(string)), false),
+]);
+
+/// Checks an upgraded config document against the layout of [`AppConfig`] and reports every
+/// problem at once, instead of only the first one serde runs into.
+pub fn validate_config(value: &serde_json::Value) -> ConfigValidation {
+    let mut validation = ConfigValidation::default();
+    check_shape(value, &CONFIG, "", &mut validation);
+    if !validation.errors.is_empty() {
+        return validation;
+    }
+
+    let instances = value
+        .get("environments")
+        .and_then(|e| e.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, env)| Some((name.as_str(), env.get("instance")?.as_str()?)));
+    for (name, other) in duplicate_instances(instances) {
+        validation.warnings.push(ConfigProblem {
+            path: format!("environments.{name}.instance"),
+            message: format!(
+                "same instance as environment '{other}'; a migration to one also changes the other"
+            ),
+        });
+    }
+
+    // Environments may also come from `team.config_url`, which is only known after loading.
+    if value
+        .pointer("/team/config_url")
+        .is_some_and(|url| !url.is_null())
+    {
+        return validation;
+    }
+    let environments = value.get("environments").and_then(|e| e.as_object());
+    let mut references = Vec::new();
+    if let Some(env) = value.get("default_source_env").and_then(|e| e.as_str()) {
+        references.push(("default_source_env".to_string(), env));
+    }
+    for (name, release) in value
+        .get("releases")
+        .and_then(|r| r.as_object())
+        .into_iter()
+        .flatten()
+    {
+        if let Some(env) = release.get("from_env").and_then(|e| e.as_str()) {
+            references.push((format!("releases.{name}.from_env"), env));
+        }
+    }
+    for env in value
+        .get("db_map")
+        .and_then(|m| m.as_object())
+        .into_iter()
+        .flat_map(|m| m.keys())
+    {
+        references.push((format!("db_map.{env}"), env));
+    }
+    for (env, source_env) in value
+        .get("source_envs")
+        .and_then(|m| m.as_object())
+        .into_iter()
+        .flatten()
+    {
+        references.push((format!("source_envs.{env}"), env));
+        if let Some(source_env) = source_env.as_str() {
+            references.push((format!("source_envs.{env}"), source_env));
+        }
+    }
+    for (path, env) in references {
+        if !environments.is_some_and(|envs| envs.contains_key(env)) {
+            validation.warnings.push(ConfigProblem {
+                path,
+                message: format!("environment '{env}' is not configured"),
+            });
+        }
+    }
+    validation
+}
+
+fn check_shape(value: &serde_json::Value, shape: &Shape, path: &str, out: &mut ConfigValidation) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    let problem = |message: &str| ConfigProblem {
+        path: if path.is_empty() { "(root)" } else { path }.to_string(),
+        message: message.to_string(),
+    };
+    match shape {
+        Shape::Value(check) => {
+            if let Some(message) = check(value) {
+                out.errors.push(problem(message));
+            }
+        }
+        Shape::Object(fields) => {
+            let Some(object) = value.as_object() else {
+                out.errors.push(problem("expected an object"));
+                return;
+            };
+            for (key, shape, required) in fields.iter() {
+                match object.get(*key) {
+                    Some(serde_json::Value::Null) | None if !required => {}
+                    None => out.errors.push(ConfigProblem {
+                        path: join(key),
+                        message: "missing field".to_string(),
+                    }),
+                    Some(field) => check_shape(field, shape, &join(key), out),
+                }
+            }
+            for key in object.keys() {
+                if !fields.iter().any(|(known, _, _)| known == key) {
+                    out.warnings.push(ConfigProblem {
+                        path: join(key),
+                        message: "unknown key (ignored)".to_string(),
+                    });
+                }
+            }
+        }
+        Shape::Map(entry) => {
+            let Some(object) = value.as_object() else {
+                out.errors.push(problem("expected an object"));
+                return;
+            };
+            for (key, field) in object {
+                check_shape(field, entry, &join(key), out);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
-pub async fn save_test_config(config: &AppConfig, test_home: &Path) -> Result<()> {
-    let config_path = get_test_config_path(test_home);
-    let config_dir = config_path.parent().unwrap_or_else(|| Path::new(""));
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
 
-    if !config_dir.exists() {
-        fs::create_dir_all(config_dir)
+    #[tokio::test]
+    async fn test_legacy_config_upgraded_on_load_and_backed_up_on_save() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = get_test_config_path(temp_dir.path());
+        std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        let legacy =
+            r#"{"default_source_env":"dev","environments":{"dev":{"project":"p","instance":"i"}}}"#;
+        std::fs::write(&config_path, legacy).unwrap();
+
+        let config = load_test_config(temp_dir.path()).await.unwrap();
+        assert_eq!(config.schema_version, SchemaVersion(CURRENT_SCHEMA_VERSION));
+        assert_eq!(config.default_source_env.as_deref(), Some("dev"));
+        // Loading upgrades in memory only.
+        assert_eq!(std::fs::read_to_string(&config_path).unwrap(), legacy);
+        let backup_path = config_path.with_extension("json.v0.bak");
+        assert!(!backup_path.exists());
+
+        save_test_config(&config, temp_dir.path()).await.unwrap();
+        let backup = std::fs::read_to_string(&backup_path).unwrap();
+        assert_eq!(backup, legacy);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&backup_path)
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], CURRENT_SCHEMA_VERSION);
+
+        // Later saves leave the backup of the old layout alone.
+        save_test_config(&config, temp_dir.path()).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&backup_path).unwrap(), legacy);
+    }
+
+    #[tokio::test]
+    async fn test_validation_reports_every_problem_with_paths() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = get_test_config_path(temp_dir.path());
+        std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        let broken = r#"{
+            "schema_version": 1,
+            "default_source_env": "dev",
+            "environments": {"prod": {"project": "p", "engine": "NOPE"}},
+            "sheet": {"max_sise": 10},
+            "rollout": {"poll_interval": "5s"}
+        }"#;
+        std::fs::write(&config_path, broken).unwrap();
+
+        let err = load_test_config(temp_dir.path()).await.unwrap_err();
+        let report = err.to_string();
+        for line in [
+            "environments.prod.instance: missing field",
+            "environments.prod.engine: unknown database engine",
+            "rollout.poll_interval: expected a non-negative integer",
+            "sheet.max_sise: unknown key (ignored)",
+        ] {
+            assert!(report.contains(line), "{line:?} missing from:\n{report}");
+        }
+
+        // Unknown keys and dangling references alone do not prevent loading.
+        let value = serde_json::json!({
+            "default_source_env": "dev",
+            "environments": {"prod": {"project": "p", "instance": "i"}},
+            "db_map": {"qa": {"app": "app_qa"}},
+            "source_envs": {"prod": "stage"},
+            "extra": true
+        });
+        let validation = validate_config(&value);
+        assert!(validation.errors.is_empty());
+        let warnings: Vec<String> = validation.warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            [
+                "extra: unknown key (ignored)",
+                "default_source_env: environment 'dev' is not configured",
+                "db_map.qa: environment 'qa' is not configured",
+                "source_envs.prod: environment 'stage' is not configured",
+            ]
+        );
+
+        let value = serde_json::json!({
+            "environments": {
+                "prod": {"project": "p", "instance": "shared"},
+                "prod2": {"project": "q", "instance": "shared"}
+            }
+        });
+        let warnings = validate_config(&value).warnings;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].path, "environments.prod2.instance");
+    }
+
+    #[test]
+    fn test_env_credentials_override_saved_ones() {
+        let config = AppConfig {
+            credentials: Some(Credentials {
+                url: "https://saved.example.com".to_string(),
+                service_account: "saved@service.bytebase.com".to_string(),
+                service_key: Some("saved-key".to_string()),
+                access_token: "saved-token".to_string(),
+            }),
+            ..Default::default()
+        };
+        let env = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            EnvCredentials::from_lookup(|name| vars.get(name).cloned())
+        };
+
+        let url_only = env(&[(BYTEBASE_URL_VAR, "https://ci.example.com/")]);
+        assert!(!url_only.has_identity());
+        let resolved = config.resolve_credentials(&url_only).unwrap();
+        assert_eq!(resolved.url, "https://ci.example.com");
+        assert_eq!(resolved.access_token, "saved-token");
+
+        let service_account = env(&[
+            (BYTEBASE_SERVICE_ACCOUNT_VAR, "ci@service.bytebase.com"),
+            (BYTEBASE_SERVICE_ACCOUNT_SECRET_VAR, "ci-key"),
+        ]);
+        let resolved = config.resolve_credentials(&service_account).unwrap();
+        assert_eq!(resolved.url, "https://saved.example.com");
+        assert_eq!(resolved.service_account, "ci@service.bytebase.com");
+        assert_eq!(resolved.service_key.as_deref(), Some("ci-key"));
+        assert!(resolved.access_token.is_empty());
+
+        let token = env(&[(BYTEBASE_ACCESS_TOKEN_VAR, "ci-token")]);
+        assert_eq!(
+            config.resolve_credentials(&token).unwrap().access_token,
+            "ci-token"
+        );
+
+        let half = env(&[(BYTEBASE_SERVICE_ACCOUNT_VAR, "ci@service.bytebase.com")]);
+        assert!(config.resolve_credentials(&half).is_err());
+        assert!(AppConfig::default().resolve_credentials(&token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_profile_credentials() {
+        let temp_dir = tempdir().unwrap();
+        let credentials = |url: &str| Credentials {
+            url: url.to_string(),
+            service_account: "sa@service.bytebase.com".to_string(),
+            service_key: Some("key".to_string()),
+            access_token: "token".to_string(),
+        };
+        let mut config = AppConfig {
+            credentials: Some(credentials("https://kr.example.com")),
+            profile: Some("eu".to_string()),
+            ..Default::default()
+        };
+        assert!(config.get_credentials().is_err());
+
+        config.set_credentials(credentials("https://eu.example.com"));
+        assert_eq!(
+            config.get_credentials().unwrap().url,
+            "https://eu.example.com"
+        );
+        assert_eq!(
+            config.credentials.as_ref().unwrap().url,
+            "https://kr.example.com"
+        );
+
+        save_test_config(&config, temp_dir.path()).await.unwrap();
+        let mut loaded = load_test_config(temp_dir.path()).await.unwrap();
+        assert_eq!(loaded.profile, None);
+        assert_eq!(
+            loaded.get_credentials().unwrap().url,
+            "https://kr.example.com"
+        );
+        loaded.profile = Some("eu".to_string());
+        assert_eq!(
+            loaded.get_credentials().unwrap().url,
+            "https://eu.example.com"
+        );
+    }
+
+    /// Secrets kept in memory; fails every read and write when `broken`.
+    #[derive(Default)]
+    struct MemoryStore {
+        secrets: std::sync::Mutex<HashMap<String, String>>,
+        broken: bool,
+    }
+
+    impl SecretStore for MemoryStore {
+        fn get(&self, key: &str) -> Result<Option<String>> {
+            if self.broken {
+                anyhow::bail!("keychain is locked");
+            }
+            Ok(self.secrets.lock().unwrap().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, secret: &str) -> Result<()> {
+            if self.broken {
+                anyhow::bail!("no keychain");
+            }
+            self.secrets
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), secret.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_keyring_secrets() {
+        let credentials = |url: &str| Credentials {
+            url: url.to_string(),
+            service_account: "sa@service.bytebase.com".to_string(),
+            service_key: Some(format!("key for {url}")),
+            access_token: format!("token for {url}"),
+        };
+        let config = AppConfig {
+            credentials: Some(credentials("https://kr.example.com")),
+            profiles: HashMap::from([("eu".to_string(), credentials("https://eu.example.com"))]),
+            ..Default::default()
+        };
+        let store = MemoryStore::default();
+
+        let mut value = serde_json::to_value(&config).unwrap();
+        move_secrets_to(&mut value, &store);
+        assert_eq!(
+            value["credentials"],
+            serde_json::json!({
+                "url": "https://kr.example.com",
+                "service_account": "sa@service.bytebase.com",
+                "access_token": ""
+            })
+        );
+        assert_eq!(value["profiles"]["eu"]["access_token"], "");
+        assert_eq!(store.secrets.lock().unwrap().len(), 4);
+        assert_eq!(
+            store
+                .get("service_key:sa@service.bytebase.com@https://eu.example.com")
+                .unwrap()
+                .as_deref(),
+            Some("key for https://eu.example.com")
+        );
+
+        let mut loaded: AppConfig = serde_json::from_value(value).unwrap();
+        fill_secrets_from(&mut loaded, &store).unwrap();
+        let kr = loaded.credentials.as_ref().unwrap();
+        assert_eq!(
+            kr.service_key.as_deref(),
+            Some("key for https://kr.example.com")
+        );
+        assert_eq!(kr.access_token, "token for https://kr.example.com");
+        assert_eq!(
+            loaded.profiles["eu"].access_token,
+            "token for https://eu.example.com"
+        );
+
+        let broken = MemoryStore {
+            broken: true,
+            ..Default::default()
+        };
+        let mut value = serde_json::to_value(&config).unwrap();
+        move_secrets_to(&mut value, &broken);
+        assert_eq!(
+            value["credentials"]["access_token"],
+            "token for https://kr.example.com"
+        );
+        assert_eq!(
+            value["credentials"]["service_key"],
+            "key for https://kr.example.com"
+        );
+
+        let mut stripped = AppConfig {
+            credentials: Some(Credentials {
+                access_token: String::new(),
+                ..credentials("https://kr.example.com")
+            }),
+            ..Default::default()
+        };
+        assert!(fill_secrets_from(&mut stripped, &broken).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scrub_config_backups() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = get_test_config_path(temp_dir.path());
+        let config = AppConfig {
+            credentials: Some(Credentials {
+                url: "https://kr.example.com".to_string(),
+                service_account: "sa@service.bytebase.com".to_string(),
+                service_key: Some("key".to_string()),
+                access_token: "token".to_string(),
+            }),
+            ..Default::default()
+        };
+        save_config_to(&config, &config_path).await.unwrap();
+        save_config_to(&config, &config_path).await.unwrap();
+        let backups = list_config_backups(&config_path).await.unwrap();
+        assert_eq!(backups.len(), 1);
+
+        scrub_config_backups(&config_path).await.unwrap();
+        let content = std::fs::read_to_string(&backups[0]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            value["credentials"],
+            serde_json::json!({
+                "url": "https://kr.example.com",
+                "service_account": "sa@service.bytebase.com",
+                "access_token": ""
+            })
+        );
+        // The live config keeps its secrets; only the backups are scrubbed.
+        let loaded = load_config_from(&config_path).await.unwrap();
+        assert_eq!(loaded.credentials.unwrap().access_token, "token");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_config_never_writes() {
+        let temp_dir = tempdir().unwrap();
+        let config_ops = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let read_only = ReadOnlyConfig(&config_ops);
+
+        let _lock = read_only.lock_config().await.unwrap();
+        let config = AppConfig {
+            default_source_env: Some("dev".to_string()),
+            ..Default::default()
+        };
+        read_only.save_config(&config).await.unwrap();
+        assert!(!temp_dir.path().join(".shelltide").exists());
+    }
+
+    #[tokio::test]
+    async fn test_save_keeps_limited_backups_and_restores() {
+        let temp_dir = tempdir().unwrap();
+        let config_ops = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let config_path = config_ops.config_path().unwrap();
+
+        for i in 0..=MAX_CONFIG_BACKUPS + 1 {
+            let config = AppConfig {
+                default_source_env: Some(format!("env-{i}")),
+                ..Default::default()
+            };
+            config_ops.save_config(&config).await.unwrap();
+            // Backups are named by millisecond timestamp.
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+
+        let backups = list_config_backups(&config_path).await.unwrap();
+        assert_eq!(backups.len(), MAX_CONFIG_BACKUPS);
+        let newest: AppConfig =
+            serde_json::from_str(&std::fs::read_to_string(&backups[0]).unwrap()).unwrap();
+        assert_eq!(
+            newest.default_source_env,
+            Some(format!("env-{}", MAX_CONFIG_BACKUPS))
+        );
+
+        restore_config_backup(&config_path, &backups[0])
             .await
-            .with_context(|| format!("Failed to create config directory at {config_dir:?}"))?;
+            .unwrap();
+        let restored = config_ops.load_config().await.unwrap();
+        assert_eq!(restored.default_source_env, newest.default_source_env);
     }
 
-    let content = serde_json::to_string_pretty(config)
-        .context("Failed to serialize configuration to JSON")?;
+    #[tokio::test]
+    async fn test_save_is_atomic_and_lock_is_exclusive() {
+        let temp_dir = tempdir().unwrap();
+        let config_ops = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let config_path = config_ops.config_path().unwrap();
 
-    fs::write(&config_path, content)
-        .await
-        .with_context(|| format!("Failed to write config file to {config_path:?}"))?;
+        let lock = config_ops.lock_config().await.unwrap();
+        let other = std::fs::File::open(config_path.with_extension("json.lock")).unwrap();
+        assert!(other.try_lock().is_err());
 
-    Ok(())
+        config_ops.save_config(&AppConfig::default()).await.unwrap();
+        let leftovers: Vec<_> = std::fs::read_dir(config_path.parent().unwrap())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().contains(".tmp."))
+            .collect();
+        assert!(leftovers.is_empty());
+
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lock_wait_does_not_block_the_runtime() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let lock = ConfigLock::acquire(&config_path).await.unwrap();
+
+        // The test runtime has a single thread; a blocking wait would starve this task.
+        let waiter = tokio::spawn({
+            let config_path = config_path.clone();
+            async move { ConfigLock::acquire(&config_path).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        drop(lock);
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_replaces_existing_private_file() {
+        let temp_dir = tempdir().unwrap();
+        let config_ops = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let config = AppConfig {
+            default_source_env: Some("dev".to_string()),
+            ..Default::default()
+        };
+        config_ops.save_config(&AppConfig::default()).await.unwrap();
+        // Replacing a file that exists is what fails on Windows without care.
+        config_ops.save_config(&config).await.unwrap();
+        let saved = config_ops.load_config().await.unwrap();
+        assert_eq!(saved.default_source_env.as_deref(), Some("dev"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(config_ops.config_path().unwrap())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shared_environments_are_merged_but_not_saved() {
+        let temp_dir = tempdir().unwrap();
+        let config_ops = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let env = |project: &str| Environment {
+            project: project.to_string(),
+            instance: "instance".to_string(),
+            engine: None,
+            pin_to: None,
+        };
+
+        let mut config = AppConfig::default();
+        config
+            .environments
+            .insert("dev".to_string(), env("local-dev"));
+        config.merge_shared_environments(BTreeMap::from([
+            ("dev".to_string(), env("team-dev")),
+            ("prod".to_string(), env("team-prod")),
+            ("stage".to_string(), env("team-stage")),
+        ]));
+        assert_eq!(config.environments["dev"], env("local-dev"));
+        assert_eq!(config.environments["prod"], env("team-prod"));
+
+        // A shared environment edited locally becomes a local override.
+        config
+            .environments
+            .insert("stage".to_string(), env("local-stage"));
+        config_ops.save_config(&config).await.unwrap();
+
+        let saved = config_ops.load_config().await.unwrap();
+        assert_eq!(saved.environments.len(), 2);
+        assert_eq!(saved.environments["dev"], env("local-dev"));
+        assert_eq!(saved.environments["stage"], env("local-stage"));
+    }
+
+    #[tokio::test]
+    async fn test_team_config_url_is_fetched_once() {
+        let temp_dir = tempdir().unwrap();
+        let config_ops = TestConfig {
+            test_dir: temp_dir.path().to_path_buf(),
+        };
+        let config_path = config_ops.config_path().unwrap();
+        assert_eq!(read_team_config_url(&config_path).await, None);
+
+        let mut config = AppConfig::default();
+        config.team.config_url = Some("http://config.example.com/envs.yaml".to_string());
+        config_ops.save_config(&config).await.unwrap();
+        let url = read_team_config_url(&config_path).await.unwrap();
+        assert_eq!(url, "http://config.example.com/envs.yaml");
+
+        // The failed fetch is kept rather than retried, whatever the URL.
+        assert!(shared_environments(&url).await.is_none());
+        assert!(SHARED_ENVIRONMENTS.initialized());
+        assert!(
+            shared_environments("https://config.example.com/envs.yaml")
+                .await
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_display_timezone_format() {
+        let timestamp = "2025-01-01T00:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let seoul: DisplayTimezone = "Asia/Seoul".parse().unwrap();
+        assert_eq!(seoul.format(timestamp), "2025-01-01T09:30:00+09:00");
+        assert_eq!(
+            DisplaySettings::default().timezone().format(timestamp),
+            "2025-01-01T00:30:00Z"
+        );
+        assert!("Mars/Olympus".parse::<DisplayTimezone>().is_err());
+    }
+
+    #[test]
+    fn test_upgrade_rejects_newer_schema() {
+        let value = serde_json::json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1 });
+        assert!(upgrade_config(value).is_err());
+    }
 }
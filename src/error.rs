@@ -1,3 +1,4 @@
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,6 +12,12 @@ pub enum AppError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    #[error("Bytebase error ({code}): {message}")]
+    BytebaseError {
+        code: BytebaseErrorCode,
+        message: String,
+    },
+
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -23,9 +30,121 @@ pub enum AppError {
     #[error("Invalid command arguments: {0}")]
     InvalidArgs(String),
 
+    #[error("No revision recorded for {0}")]
+    NoRevision(String),
+
     #[error("Invalid revision version: {0}")]
     InvalidRevisionVersion(String),
 
+    #[error("Timed out waiting for rollout: {0}")]
+    RolloutTimeout(String),
+
+    #[error("Plan check failed: {0}")]
+    PlanCheckFailed(String),
+
+    #[error("Bytebase is in maintenance (read-only) mode: {0}")]
+    ServerMaintenance(String),
+
+    #[error("Source history was re-baselined: {0}")]
+    BaselineCrossed(String),
+
+    #[error("SQL statement too large: {0}")]
+    StatementTooLarge(String),
+
     #[error("General error: {0}")]
     General(#[from] anyhow::Error),
 }
+
+/// Exit code of `diff` and `dump` with `--fail-if-empty` when there is nothing to output. Exit
+/// code 2 is clap's, for usage errors.
+pub const NOTHING_TO_OUTPUT_EXIT_CODE: i32 = 8;
+
+impl AppError {
+    /// Process exit code for this error; see [`NOTHING_TO_OUTPUT_EXIT_CODE`] for the codes taken
+    /// elsewhere.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            AppError::BytebaseError { code, .. } => match code {
+                BytebaseErrorCode::NotFound => 3,
+                BytebaseErrorCode::PermissionDenied | BytebaseErrorCode::Unauthenticated => 4,
+                BytebaseErrorCode::FailedPrecondition => 5,
+                _ => 1,
+            },
+            AppError::RolloutTimeout(_) => 6,
+            AppError::ServerMaintenance(_) => 7,
+            _ => 1,
+        }
+    }
+}
+
+/// Canonical status codes carried in the `code` field of Bytebase error payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytebaseErrorCode {
+    Cancelled,
+    Unknown,
+    InvalidArgument,
+    DeadlineExceeded,
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    ResourceExhausted,
+    FailedPrecondition,
+    Aborted,
+    OutOfRange,
+    Unimplemented,
+    Internal,
+    Unavailable,
+    DataLoss,
+    Unauthenticated,
+}
+
+impl BytebaseErrorCode {
+    const ALL: [(BytebaseErrorCode, &'static str); 16] = [
+        (Self::Cancelled, "CANCELLED"),
+        (Self::Unknown, "UNKNOWN"),
+        (Self::InvalidArgument, "INVALID_ARGUMENT"),
+        (Self::DeadlineExceeded, "DEADLINE_EXCEEDED"),
+        (Self::NotFound, "NOT_FOUND"),
+        (Self::AlreadyExists, "ALREADY_EXISTS"),
+        (Self::PermissionDenied, "PERMISSION_DENIED"),
+        (Self::ResourceExhausted, "RESOURCE_EXHAUSTED"),
+        (Self::FailedPrecondition, "FAILED_PRECONDITION"),
+        (Self::Aborted, "ABORTED"),
+        (Self::OutOfRange, "OUT_OF_RANGE"),
+        (Self::Unimplemented, "UNIMPLEMENTED"),
+        (Self::Internal, "INTERNAL"),
+        (Self::Unavailable, "UNAVAILABLE"),
+        (Self::DataLoss, "DATA_LOSS"),
+        (Self::Unauthenticated, "UNAUTHENTICATED"),
+    ];
+
+    /// Maps a numeric gRPC status code (1-16) to its variant.
+    pub fn from_number(code: i64) -> Option<Self> {
+        usize::try_from(code)
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| Self::ALL.get(i))
+            .map(|(code, _)| *code)
+    }
+
+    /// Maps a status name such as `NOT_FOUND` to its variant.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(code, _)| *code)
+    }
+
+    fn name(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(code, _)| code == self)
+            .map_or("UNKNOWN", |(_, name)| name)
+    }
+}
+
+impl fmt::Display for BytebaseErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
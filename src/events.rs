@@ -0,0 +1,190 @@
+use crate::api::types::{IssueName, PlanName, SheetName};
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Machine-readable format for `migrate --events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EventFormat {
+    /// One JSON object per line.
+    Ndjson,
+}
+
+/// A lifecycle event of a migrate run, emitted as soon as it happens.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum MigrateEvent {
+    /// Changelogs to apply were selected, in apply order.
+    SelectionDone {
+        issues: Vec<u32>,
+    },
+    /// The changelog of `issue` passed the SQL check against the target.
+    CheckPassed {
+        issue: u32,
+    },
+    SheetCreated {
+        issue: u32,
+        sheet: SheetName,
+    },
+    /// The plan for `issue` was created on the target; `url` is its page in the Bytebase UI.
+    PlanCreated {
+        issue: u32,
+        plan: PlanName,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+    },
+    /// The target issue that carries `issue` was created, awaiting approval at `url`.
+    IssueCreated {
+        issue: u32,
+        target_issue: IssueName,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+    },
+    RolloutStarted {
+        issue: u32,
+        rollout_id: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+    },
+    RolloutFinished {
+        issue: u32,
+        rollout_id: u32,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        /// Seconds from rollout creation until it finished.
+        duration_secs: f64,
+    },
+    /// The target revision now records `issue`.
+    RevisionWritten {
+        issue: u32,
+    },
+}
+
+#[derive(Serialize)]
+struct EventLine<'a> {
+    timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run_id: Option<&'a str>,
+    #[serde(flatten)]
+    event: &'a MigrateEvent,
+}
+
+/// Writes [`MigrateEvent`]s as NDJSON to a file, flushing after every line. Never stdout, which
+/// carries migrate's human-readable output. The targets of a multi-target run share one file
+/// through [`EventSink::tagged`], so their lines never overwrite each other.
+pub struct EventSink {
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    /// Target (`<env>/<db>`) stamped on every line, telling the targets of one run apart.
+    target: Option<String>,
+    /// Run id stamped on every line, so events can be matched to the journal and Bytebase.
+    run_id: Option<String>,
+}
+
+impl std::fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventSink").finish_non_exhaustive()
+    }
+}
+
+impl EventSink {
+    /// Creates (or truncates) `path` and writes events to it. Open it once per invocation and
+    /// hand each target a [`EventSink::tagged`] copy.
+    pub fn file(path: &Path) -> Result<Self, AppError> {
+        Ok(Self::new(Box::new(File::create(path)?)))
+    }
+
+    fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+            target: None,
+            run_id: None,
+        }
+    }
+
+    /// A sink writing to the same file whose lines carry `target` and `run_id`.
+    pub fn tagged(&self, target: &str, run_id: &str) -> Self {
+        Self {
+            writer: Arc::clone(&self.writer),
+            target: Some(target.to_string()),
+            run_id: Some(run_id.to_string()),
+        }
+    }
+
+    pub fn emit(&self, event: &MigrateEvent) -> Result<(), AppError> {
+        let mut line = serde_json::to_string(&EventLine {
+            timestamp: Utc::now(),
+            target: self.target.as_deref(),
+            run_id: self.run_id.as_deref(),
+            event,
+        })?;
+        line.push('\n');
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer.write_all(line.as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_events_are_written_as_ndjson() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("events.ndjson");
+        let file = EventSink::file(&path).unwrap();
+        let sink = file.tagged("prod/app", "20240101000000-abcd1234");
+        let other = file.tagged("prod/billing", "20240101000000-ef567890");
+        sink.emit(&MigrateEvent::SelectionDone {
+            issues: vec![10, 11],
+        })
+        .unwrap();
+        sink.emit(&MigrateEvent::RolloutStarted {
+            issue: 10,
+            rollout_id: 7,
+            url: Some("https://bytebase.example.com/projects/prod/rollouts/7".to_string()),
+        })
+        .unwrap();
+        sink.emit(&MigrateEvent::RolloutFinished {
+            issue: 10,
+            rollout_id: 7,
+            success: true,
+            error: None,
+            duration_secs: 1.5,
+        })
+        .unwrap();
+        other
+            .emit(&MigrateEvent::CheckPassed { issue: 10 })
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["event"], "selection_done");
+        assert_eq!(lines[0]["issues"], serde_json::json!([10, 11]));
+        assert!(lines[0]["timestamp"].is_string());
+        assert_eq!(lines[0]["target"], "prod/app");
+        assert_eq!(lines[0]["run_id"], "20240101000000-abcd1234");
+        assert_eq!(
+            lines[1]["url"],
+            "https://bytebase.example.com/projects/prod/rollouts/7"
+        );
+        assert_eq!(lines[2]["event"], "rollout_finished");
+        assert!(lines[2].get("error").is_none());
+        assert_eq!(lines[2]["duration_secs"], 1.5);
+        assert_eq!(lines[3]["target"], "prod/billing");
+        assert_eq!(lines[3]["run_id"], "20240101000000-ef567890");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::api::types::{Changelog, DatabaseMetadata};
+
+/// Rough cost of one changelog on one existing table of the target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableImpact {
+    pub issue: u32,
+    /// `schema.table`, or just `table` for engines without schemas.
+    pub table: String,
+    /// `ALTER`, `UPDATE`, `DELETE`, `DROP`, `INSERT`, `INDEX` or `CHANGE` when unknown.
+    pub operation: &'static str,
+    pub row_count: i64,
+    pub data_size: i64,
+}
+
+impl std::fmt::Display for TableImpact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} {} on {} ~{} rows ({})",
+            self.issue,
+            self.operation,
+            self.table,
+            format_count(self.row_count),
+            format_bytes(self.data_size)
+        )
+    }
+}
+
+/// Looks up every table in the `changedResources` of `changelogs` in the target `metadata`.
+/// Tables the target does not have yet (e.g. created by the changelog) are skipped.
+pub fn estimate(changelogs: &[Changelog], metadata: &DatabaseMetadata) -> Vec<TableImpact> {
+    let mut impacts = Vec::new();
+    for cl in changelogs {
+        for database in &cl.changed_resources.databases {
+            for schema in &database.schemas {
+                for table in &schema.tables {
+                    let Some(meta) = metadata.table(&schema.name, &table.name) else {
+                        continue;
+                    };
+                    impacts.push(TableImpact {
+                        issue: cl.issue_number(),
+                        table: if schema.name.is_empty() {
+                            table.name.clone()
+                        } else {
+                            format!("{}.{}", schema.name, table.name)
+                        },
+                        operation: operation_on(&cl.statement.0, &table.name),
+                        row_count: meta.row_count,
+                        data_size: meta.data_size,
+                    });
+                }
+            }
+        }
+    }
+    impacts
+}
+
+/// Guesses what `statement` does to `table` from the first matching clause.
+fn operation_on(statement: &str, table: &str) -> &'static str {
+    let normalized = statement
+        .replace(['`', '"'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase();
+    let table = table.to_uppercase();
+    [
+        ("ALTER", "ALTER TABLE "),
+        ("UPDATE", "UPDATE "),
+        ("DELETE", "DELETE FROM "),
+        ("DROP", "DROP TABLE "),
+        ("INSERT", "INSERT INTO "),
+        ("INDEX", " ON "),
+    ]
+    .into_iter()
+    .find(|(_, clause)| {
+        let needle = format!("{clause}{table}");
+        normalized.match_indices(&needle).any(|(i, _)| {
+            normalized[i + needle.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| !(c.is_alphanumeric() || c == '_'))
+        })
+    })
+    .map_or("CHANGE", |(operation, _)| operation)
+}
+
+/// e.g. `950`, `1.2K`, `40M`.
+fn format_count(count: i64) -> String {
+    match count {
+        n if n >= 1_000_000_000 => format!("{:.0}B", n as f64 / 1e9),
+        n if n >= 1_000_000 => format!("{:.0}M", n as f64 / 1e6),
+        n if n >= 1_000 => format!("{:.1}K", n as f64 / 1e3),
+        n => n.to_string(),
+    }
+}
+
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    if unit == "B" {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {unit}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_uses_target_table_sizes() {
+        let changelog: Changelog = serde_json::from_value(serde_json::json!({
+            "name": "instances/dev/databases/store/changelogs/1",
+            "createTime": "2024-01-01T00:00:00Z",
+            "statement": "ALTER TABLE `stove_purchase_transaction` ADD COLUMN note TEXT;\nCREATE TABLE audit (id INT);",
+            "issue": "projects/store/issues/42",
+            "changedResources": {
+                "databases": [{ "name": "store", "schemas": [{ "name": "", "tables": [
+                    { "name": "stove_purchase_transaction" },
+                    { "name": "audit" }
+                ] }] }]
+            }
+        }))
+        .unwrap();
+        let metadata: DatabaseMetadata = serde_json::from_value(serde_json::json!({
+            "schemas": [{ "name": "", "tables": [
+                { "name": "stove_purchase_transaction", "rowCount": "40213577", "dataSize": "3355443200" },
+                { "name": "stove_purchase" }
+            ] }]
+        }))
+        .unwrap();
+
+        let impacts = estimate(&[changelog], &metadata);
+        assert_eq!(impacts.len(), 1);
+        assert_eq!(
+            impacts[0].to_string(),
+            "#42 ALTER on stove_purchase_transaction ~40M rows (3.1 GiB)"
+        );
+        assert_eq!(
+            operation_on("UPDATE users_archive SET a = 1", "users"),
+            "CHANGE"
+        );
+        assert_eq!(
+            operation_on("create index idx on users (a)", "users"),
+            "INDEX"
+        );
+    }
+}
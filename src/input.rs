@@ -0,0 +1,74 @@
+use crate::error::AppError;
+use std::io::{BufRead, Read};
+use std::path::Path;
+
+/// Argument value that stands for standard input, e.g. `env import -`.
+pub const STDIN: &str = "-";
+
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN
+}
+
+/// Human-readable name of `path` for error messages.
+pub fn describe(path: &Path) -> String {
+    if is_stdin(path) {
+        "stdin".to_string()
+    } else {
+        format!("{path:?}")
+    }
+}
+
+/// Reads the whole file at `path`, or standard input when `path` is `-`.
+pub async fn read(path: &Path) -> std::io::Result<Vec<u8>> {
+    if is_stdin(path) {
+        let mut data = Vec::new();
+        std::io::stdin().lock().read_to_end(&mut data)?;
+        Ok(data)
+    } else {
+        tokio::fs::read(path).await
+    }
+}
+
+/// Like [`read`], for text input.
+pub async fn read_to_string(path: &Path) -> std::io::Result<String> {
+    String::from_utf8(read(path).await?)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Resolves a `--to` value: `-` is replaced by the first non-empty line of standard input,
+/// so a release bot can pipe the version in. Other values are returned as is.
+pub fn resolve_arg(value: &str) -> Result<String, AppError> {
+    if value == STDIN {
+        first_line(std::io::stdin().lock())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+fn first_line<R: BufRead>(reader: R) -> Result<String, AppError> {
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            return Ok(line.to_string());
+        }
+    }
+    Err(AppError::InvalidArgs(
+        "Expected a value on stdin for '-', but it was empty".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_first_line_skips_blanks_and_comments() {
+        let input = Cursor::new("\n# generated by release-bot\n  244  \n245\n");
+        assert_eq!(first_line(input).unwrap(), "244");
+        assert!(first_line(Cursor::new("\n\n")).is_err());
+        assert_eq!(resolve_arg("LATEST").unwrap(), "LATEST");
+        assert_eq!(describe(Path::new("-")), "stdin");
+    }
+}
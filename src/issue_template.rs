@@ -0,0 +1,158 @@
+use crate::config::AppConfig;
+use crate::error::AppError;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Placeholders a description template may use besides `{{env.<VAR>}}`.
+pub const PLACEHOLDERS: [&str; 7] = [
+    "source_env",
+    "target",
+    "issue",
+    "issues",
+    "operator",
+    "run_id",
+    "ticket_url",
+];
+
+/// A Markdown template for the descriptions of the issues created on targets
+/// (`issue.description_template`). `{{name}}` is replaced by the value of placeholder `name`,
+/// `{{env.NAME}}` by environment variable `NAME` (empty if unset).
+#[derive(Debug, Clone)]
+pub struct IssueTemplate {
+    text: String,
+    values: BTreeMap<&'static str, String>,
+}
+
+impl IssueTemplate {
+    /// Reads the template configured in `issue.description_template`, if any.
+    pub fn from_config(config: &AppConfig) -> Result<Option<Self>, AppError> {
+        config
+            .issue
+            .description_template
+            .as_deref()
+            .map(|path| Self::load(Path::new(path)))
+            .transpose()
+    }
+
+    /// Reads and checks the template at `path`.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Config(format!(
+                "Cannot read issue description template {}: {e}",
+                path.display()
+            ))
+        })?;
+        Self::parse(text).map_err(|e| AppError::Config(format!("{}: {e}", path.display())))
+    }
+
+    /// Checks that `text` only uses known placeholders, so a typo fails before anything is
+    /// applied instead of leaving `{{...}}` in every issue.
+    pub fn parse(text: String) -> Result<Self, String> {
+        for name in placeholders(&text) {
+            let known = PLACEHOLDERS.contains(&name)
+                || name.strip_prefix("env.").is_some_and(|var| !var.is_empty());
+            if !known {
+                return Err(format!(
+                    "unknown placeholder '{{{{{name}}}}}' (available: {}, env.<VAR>)",
+                    PLACEHOLDERS.join(", ")
+                ));
+            }
+        }
+        Ok(Self {
+            text,
+            values: BTreeMap::new(),
+        })
+    }
+
+    /// Sets the value of placeholder `name`, one of [`PLACEHOLDERS`].
+    pub fn set(&mut self, name: &'static str, value: impl Into<String>) {
+        self.values.insert(name, value.into());
+    }
+
+    /// The description of the issue created for `change`, such as `#42`, which fills in
+    /// `{{issue}}`.
+    pub fn render(&self, change: &str) -> String {
+        let mut out = String::new();
+        let mut rest = self.text.as_str();
+        while let Some((before, after)) = rest.split_once("{{") {
+            let Some((name, after)) = after.split_once("}}") else {
+                break;
+            };
+            out.push_str(before);
+            match name.trim() {
+                "issue" => out.push_str(change),
+                name => match name.strip_prefix("env.") {
+                    Some(var) => out.push_str(&std::env::var(var).unwrap_or_default()),
+                    None => out.push_str(self.values.get(name).map_or("", String::as_str)),
+                },
+            }
+            rest = after;
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// The trimmed names of the `{{...}}` placeholders in `text`.
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some((_, after)) = rest.split_once("{{") {
+        let Some((name, after)) = after.split_once("}}") else {
+            break;
+        };
+        names.push(name.trim());
+        rest = after;
+    }
+    names
+}
+
+/// Who runs shelltide, for `{{operator}}`: the OS user, or else the service account.
+pub fn operator(config: &AppConfig) -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .or_else(|| {
+            config
+                .active_credentials()
+                .map(|c| c.service_account.clone())
+        })
+        .unwrap_or_default()
+}
+
+/// `#1, #2, #3`, for `{{issues}}`.
+pub fn issue_list(issues: &[u32]) -> String {
+    issues
+        .iter()
+        .map(|issue| format!("#{issue}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_issue_template() {
+        let mut template = IssueTemplate::parse(
+            "Promoted {{ issue }} from {{source_env}} to {{target}} ({{issues}}) by {{operator}}.\n\
+             Ticket: {{ticket_url}}, run {{run_id}}, home {{env.SHELLTIDE_TEMPLATE_TEST}}{{"
+                .to_string(),
+        )
+        .unwrap();
+        template.set("source_env", "dev/bridge");
+        template.set("target", "prod/bridge");
+        template.set("issues", issue_list(&[41, 42]));
+        template.set("operator", "alice");
+        template.set("run_id", "r1");
+
+        assert_eq!(
+            template.render("#42"),
+            "Promoted #42 from dev/bridge to prod/bridge (#41, #42) by alice.\n\
+             Ticket: , run r1, home {{"
+        );
+        assert!(IssueTemplate::parse("{{source}}".to_string()).is_err());
+        assert!(IssueTemplate::parse("{{env.}}".to_string()).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::JiraSettings;
+use crate::error::AppError;
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// Checks that `key` looks like a Jira issue key such as `PROJ-123`.
+pub fn parse_issue_key(key: &str) -> Result<String, String> {
+    let valid = key.split_once('-').is_some_and(|(project, number)| {
+        project.starts_with(|c: char| c.is_ascii_uppercase())
+            && project
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+    });
+    if valid {
+        Ok(key.to_string())
+    } else {
+        Err(format!(
+            "invalid Jira issue key '{key}' (expected e.g. PROJ-123)"
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct Transitions {
+    transitions: Vec<Transition>,
+}
+
+#[derive(Deserialize)]
+struct Transition {
+    id: String,
+    name: String,
+}
+
+/// Posts comments and transitions to a Jira server through its REST API.
+pub struct JiraClient {
+    client: reqwest::Client,
+    url: String,
+    authorization: String,
+}
+
+impl JiraClient {
+    /// A client for the configured Jira server. A token of the form `<email>:<api-token>` is
+    /// sent with basic authentication (Jira Cloud), any other as a bearer token (Data Center).
+    pub fn new(settings: &JiraSettings) -> Result<Self, AppError> {
+        let (Some(url), Some(token)) = (&settings.url, &settings.token) else {
+            return Err(AppError::Config(
+                "Jira is not configured. Set `notify.jira.url` and `notify.jira.token`."
+                    .to_string(),
+            ));
+        };
+        let authorization = if token.contains(':') {
+            format!("Basic {}", general_purpose::STANDARD.encode(token))
+        } else {
+            format!("Bearer {token}")
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            authorization,
+        })
+    }
+
+    /// Adds `comment` to issue `key`.
+    pub async fn comment(&self, key: &str, comment: &str) -> Result<()> {
+        self.client
+            .post(format!("{}/rest/api/2/issue/{key}/comment", self.url))
+            .header(reqwest::header::AUTHORIZATION, &self.authorization)
+            .json(&json!({ "body": comment }))
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to comment on Jira issue {key}"))?;
+        Ok(())
+    }
+
+    /// Moves issue `key` through the transition named or numbered `transition`.
+    pub async fn transition(&self, key: &str, transition: &str) -> Result<()> {
+        let url = format!("{}/rest/api/2/issue/{key}/transitions", self.url);
+        let available: Transitions = self
+            .client
+            .get(&url)
+            .header(reqwest::header::AUTHORIZATION, &self.authorization)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to list the transitions of Jira issue {key}"))?
+            .json()
+            .await?;
+        let Some(found) = available
+            .transitions
+            .iter()
+            .find(|t| t.id == transition || t.name.eq_ignore_ascii_case(transition))
+        else {
+            let names: Vec<&str> = available
+                .transitions
+                .iter()
+                .map(|t| t.name.as_str())
+                .collect();
+            anyhow::bail!(
+                "Jira issue {key} has no transition '{transition}' (available: {})",
+                names.join(", ")
+            );
+        };
+        self.client
+            .post(&url)
+            .header(reqwest::header::AUTHORIZATION, &self.authorization)
+            .json(&json!({ "transition": { "id": found.id } }))
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| {
+                format!("Failed to transition Jira issue {key} to '{}'", found.name)
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_issue_key() {
+        assert_eq!(parse_issue_key("PROJ-123").unwrap(), "PROJ-123");
+        assert!(parse_issue_key("OPS2-7").is_ok());
+        assert!(parse_issue_key("proj-123").is_err());
+        assert!(parse_issue_key("PROJ-").is_err());
+        assert!(parse_issue_key("123").is_err());
+    }
+}
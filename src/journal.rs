@@ -0,0 +1,274 @@
+use crate::api::types::SheetName;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Directory holding run journals: `runs` next to the config file at `config_path`, i.e.
+/// `~/.shelltide/runs`.
+pub fn runs_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join("runs")
+}
+
+/// Whether `run_id` has the `<yyyymmddHHMMSS>-<8 hex digits>` form [`Journal::create`] gives
+/// run ids, so a run id from the command line cannot name a file outside the runs directory.
+fn is_run_id(run_id: &str) -> bool {
+    run_id.split_once('-').is_some_and(|(timestamp, suffix)| {
+        timestamp.len() == 14
+            && timestamp.bytes().all(|b| b.is_ascii_digit())
+            && suffix.len() == 8
+            && suffix
+                .bytes()
+                .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    })
+}
+
+/// One step of a migrate run, appended to the run's journal as soon as it happens.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    Started {
+        source_env: String,
+        source_database: String,
+        target_env: String,
+        target_database: String,
+        /// Bytebase project and instance of the target, so a resume does not depend on config.
+        target_project: String,
+        target_instance: String,
+        /// Project whose issue numbers revisions are recorded against.
+        source_project: String,
+        target_version: u32,
+        started_at: DateTime<Utc>,
+    },
+    /// The issues selected for this run, in apply order.
+    Planned {
+        issues: Vec<u32>,
+    },
+    SheetsCreated {
+        issue: u32,
+        sheets: Vec<SheetName>,
+    },
+    PlanCreated {
+        issue: u32,
+        plan: String,
+    },
+    RolloutStarted {
+        issue: u32,
+        rollout_id: u32,
+    },
+    RolloutFinished {
+        issue: u32,
+        success: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    RevisionRecorded {
+        issue: u32,
+    },
+    Finished,
+}
+
+/// Append-only NDJSON log of a single run, stored at `~/.shelltide/runs/<run-id>.jsonl`.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    pub run_id: String,
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Starts a new journal with a fresh run id in `dir`.
+    pub fn create(dir: &Path) -> Result<Self, AppError> {
+        std::fs::create_dir_all(dir)?;
+        let uuid = uuid::Uuid::new_v4().simple().to_string();
+        let run_id = format!("{}-{}", Utc::now().format("%Y%m%d%H%M%S"), &uuid[..8]);
+        Ok(Self {
+            path: dir.join(format!("{run_id}.jsonl")),
+            run_id,
+        })
+    }
+
+    /// Opens the journal of an earlier run in `dir`.
+    pub fn open(dir: &Path, run_id: &str) -> Result<Self, AppError> {
+        if !is_run_id(run_id) {
+            return Err(AppError::InvalidArgs(format!(
+                "'{run_id}' is not a run id; run ids look like 20240101120000-1a2b3c4d"
+            )));
+        }
+        let path = dir.join(format!("{run_id}.jsonl"));
+        if !path.exists() {
+            return Err(AppError::InvalidArgs(format!(
+                "No journal found for run '{run_id}' at {path:?}"
+            )));
+        }
+        Ok(Self {
+            run_id: run_id.to_string(),
+            path,
+        })
+    }
+
+    /// Appends `event` and flushes it to disk before returning.
+    pub fn record(&self, event: &JournalEvent) -> Result<(), AppError> {
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Reads every recorded event. A truncated last line (from a crash mid-write) is ignored.
+    pub fn events(&self) -> Result<Vec<JournalEvent>, AppError> {
+        let content = std::fs::read_to_string(&self.path)?;
+        let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+        let mut events = Vec::with_capacity(lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(event) => events.push(event),
+                Err(_) if i + 1 == lines.len() => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Where a run stood when its journal ends.
+#[derive(Debug, Default)]
+pub struct RunState {
+    /// Issues selected for the run, in apply order.
+    pub planned: Vec<u32>,
+    /// Last sheet created for each issue.
+    pub sheets: HashMap<u32, SheetName>,
+    /// `(issue, rollout_id)` of rollouts that were started but have no recorded result.
+    pub in_flight: Vec<(u32, u32)>,
+    /// Issues whose rollout succeeded, in the order they finished.
+    pub succeeded: Vec<u32>,
+    pub failed: bool,
+    pub revision_recorded: Option<u32>,
+    pub finished: bool,
+}
+
+impl RunState {
+    pub fn from_events(events: &[JournalEvent]) -> Self {
+        let mut state = RunState::default();
+        for event in events {
+            match event {
+                JournalEvent::Planned { issues } => state.planned = issues.clone(),
+                JournalEvent::SheetsCreated { issue, sheets } => {
+                    if let Some(last) = sheets.last() {
+                        state.sheets.insert(*issue, last.clone());
+                    }
+                }
+                JournalEvent::RolloutStarted { issue, rollout_id } => {
+                    state.in_flight.push((*issue, *rollout_id))
+                }
+                JournalEvent::RolloutFinished { issue, success, .. } => {
+                    state.in_flight.retain(|(i, _)| i != issue);
+                    if *success {
+                        state.succeeded.push(*issue);
+                    } else {
+                        state.failed = true;
+                    }
+                }
+                JournalEvent::RevisionRecorded { issue } => state.revision_recorded = Some(*issue),
+                JournalEvent::Finished => state.finished = true,
+                JournalEvent::Started { .. } | JournalEvent::PlanCreated { .. } => {}
+            }
+        }
+        state
+    }
+
+    /// Whether every planned issue was applied successfully.
+    pub fn is_complete(&self) -> bool {
+        !self.failed && self.planned.iter().all(|i| self.succeeded.contains(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sheet(number: u32) -> SheetName {
+        SheetName {
+            project_name: "p".to_string(),
+            number,
+        }
+    }
+
+    #[test]
+    fn test_journal_round_trip_and_state() {
+        let dir = tempdir().unwrap();
+        let journal = Journal::create(dir.path()).unwrap();
+        let events = vec![
+            JournalEvent::Planned {
+                issues: vec![10, 11, 12],
+            },
+            JournalEvent::SheetsCreated {
+                issue: 10,
+                sheets: vec![sheet(1)],
+            },
+            JournalEvent::RolloutStarted {
+                issue: 10,
+                rollout_id: 100,
+            },
+            JournalEvent::RolloutFinished {
+                issue: 10,
+                success: true,
+                error: None,
+            },
+            JournalEvent::SheetsCreated {
+                issue: 11,
+                sheets: vec![sheet(2)],
+            },
+            JournalEvent::RolloutStarted {
+                issue: 11,
+                rollout_id: 101,
+            },
+        ];
+        for event in &events {
+            journal.record(event).unwrap();
+        }
+        // Simulate a crash in the middle of writing the next line.
+        let mut file = OpenOptions::new().append(true).open(&journal.path).unwrap();
+        file.write_all(b"{\"event\":\"rollout_fin").unwrap();
+
+        let reopened = Journal::open(dir.path(), &journal.run_id).unwrap();
+        let read = reopened.events().unwrap();
+        assert_eq!(read, events);
+
+        let state = RunState::from_events(&read);
+        assert_eq!(state.planned, vec![10, 11, 12]);
+        assert_eq!(state.in_flight, vec![(11, 101)]);
+        assert_eq!(state.succeeded, vec![10]);
+        assert_eq!(state.sheets[&11].number, 2);
+        assert!(!state.is_complete());
+        assert!(!state.finished);
+    }
+
+    #[test]
+    fn test_open_rejects_malformed_run_ids() {
+        let dir = tempdir().unwrap();
+        let journal = Journal::create(dir.path()).unwrap();
+        assert!(is_run_id(&journal.run_id));
+        for run_id in [
+            "../config",
+            "../../etc/passwd",
+            "20240101120000-1a2b3c4d/../x",
+            "20240101120000-1A2B3C4D",
+            "2024010112000-1a2b3c4d",
+            "",
+        ] {
+            assert!(Journal::open(dir.path(), run_id).is_err(), "{run_id}");
+        }
+    }
+}
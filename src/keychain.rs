@@ -0,0 +1,55 @@
+use crate::config::SecretStore;
+use anyhow::{Context, Result};
+use keyring::Entry;
+
+/// Service name the secrets are filed under in the keychain.
+const SERVICE: &str = "shelltide";
+
+/// The OS keychain, through the `keyring` crate: the login keychain on macOS, the Credential
+/// Manager on Windows and the Secret Service (e.g. GNOME Keyring or KWallet) elsewhere.
+pub struct Keychain;
+
+impl SecretStore for Keychain {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        read(&entry(key)?)
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<()> {
+        entry(key)?
+            .set_password(secret)
+            .context("Failed to write to the keychain")
+    }
+}
+
+fn entry(key: &str) -> Result<Entry> {
+    Entry::new(SERVICE, key).context("Failed to open the keychain")
+}
+
+/// The secret of `entry`, or `None` when the keychain has no such entry. Any other failure, such
+/// as a locked keychain or an unreachable Secret Service, is an error rather than a missing secret.
+fn read(entry: &Entry) -> Result<Option<String>> {
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).context("Failed to read from the keychain"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyring::mock::MockCredential;
+
+    #[test]
+    fn test_read_fails_unless_the_entry_is_missing() {
+        let entry = Entry::new_with_credential(Box::new(MockCredential::default()));
+        assert_eq!(read(&entry).unwrap(), None);
+
+        entry.set_password("secret").unwrap();
+        assert_eq!(read(&entry).unwrap().as_deref(), Some("secret"));
+
+        let credential: &MockCredential = entry.get_credential().downcast_ref().unwrap();
+        credential.set_error(keyring::Error::NoStorageAccess("locked".into()));
+        assert!(read(&entry).is_err());
+    }
+}
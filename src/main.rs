@@ -1,12 +1,29 @@
 mod api;
+mod bundle;
 mod cli;
 mod commands;
 mod config;
 mod error;
+mod events;
+mod impact;
+mod input;
+mod issue_template;
+mod jira;
+mod journal;
+mod keychain;
+mod ndjson;
+mod output;
+mod schema;
+mod sql;
+mod stats;
 
 use anyhow::Result;
-use clap::Parser;
-use cli::{Cli, Commands};
+use chrono::Utc;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use cli::{BundleCommand, Cli, Commands, DiffCommand};
+use output::OutputFormat;
+use std::process::ExitCode;
+use std::time::Instant;
 
 #[cfg(not(test))]
 use crate::api::clients::LiveApiClient;
@@ -17,25 +34,108 @@ use crate::api::clients::tests::FakeApiClient;
 #[cfg(not(test))]
 async fn get_client() -> Result<LiveApiClient> {
     let app_config = config::load_config().await?;
-    let credentials = app_config.get_credentials()?;
 
-    // Try to create client and validate/refresh token if needed
-    let mut client = LiveApiClient::new(credentials)?;
-    client.ensure_authenticated().await?;
+    // Create the client and validate/refresh its token if needed
+    let client = LiveApiClient::connect(
+        &app_config,
+        &config::ProductionConfig,
+        commands::prompt::print_notice,
+    )
+    .await?;
 
     Ok(client)
 }
 
+/// Like [`get_client`], but a refreshed token is kept in memory instead of being saved.
+#[cfg(not(test))]
+async fn get_read_only_client() -> Result<LiveApiClient> {
+    let app_config = config::load_config().await?;
+    let config_ops = config::ReadOnlyConfig(&config::ProductionConfig);
+    Ok(LiveApiClient::connect(&app_config, &config_ops, commands::prompt::print_notice).await?)
+}
+
 #[cfg(test)]
 async fn get_client() -> Result<FakeApiClient> {
     let client = FakeApiClient::default();
     Ok(client)
 }
 
+#[cfg(test)]
+async fn get_read_only_client() -> Result<FakeApiClient> {
+    get_client().await
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    match cli.command {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            let code = e
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<error::AppError>())
+                .map_or(1, error::AppError::exit_code);
+            ExitCode::from(code)
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    api::http_log::init(cli.debug_http);
+    if let Some(profile) = cli.profile {
+        config::select_profile(profile);
+    }
+
+    // Runs that must not write to `~/.shelltide`, or that only read the stats, are not counted.
+    let record = !matches!(
+        &cli.command,
+        Commands::Completion(_) | Commands::Stats(_)
+    ) && !matches!(&cli.command, Commands::Status(args) if args.read_only);
+    let started_at = Utc::now();
+    let timer = Instant::now();
+    let result = dispatch(cli.command, cli.output).await;
+    if record {
+        let (api_calls, api_time) = api::http_log::call_totals();
+        let run = stats::RunStats {
+            command: command_path(&matches),
+            started_at,
+            duration_ms: timer.elapsed().as_millis() as u64,
+            api_calls,
+            api_time_ms: api_time.as_millis() as u64,
+            changelogs_applied: stats::applied_changelogs(),
+            success: result.is_ok(),
+            shelltide_version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        // Stats are a by-product; failing to write them must not fail the command.
+        let recorded = match stats::stats_path() {
+            Ok(path) => stats::append(&path, &run).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = recorded {
+            eprintln!("Warning: Failed to record run stats: {e}");
+        }
+    }
+    result
+}
+
+/// The subcommands given on the command line, e.g. `status history`.
+fn command_path(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut matches = matches;
+    while let Some((name, sub)) = matches.subcommand() {
+        names.push(name);
+        matches = sub;
+    }
+    names.join(" ")
+}
+
+async fn dispatch(command: Commands, output: OutputFormat) -> Result<()> {
+    match command {
+        Commands::Init => {
+            commands::init::handle_init().await?;
+        }
         Commands::Login(args) => {
             commands::login::login(args).await?;
         }
@@ -44,25 +144,94 @@ async fn main() -> Result<()> {
         }
         Commands::Env(args) => {
             let client = get_client().await?;
-            commands::env::handle_env_command(args.command, &client).await?;
+            commands::env::handle_env_command(args.command, &client, output).await?;
         }
         Commands::Migrate(args) => {
             let client = get_client().await?;
             commands::migrate::handle_migrate_command(args, &client).await?;
         }
-        Commands::Status(args) => {
-            let mut client = get_client().await?;
-            commands::status::handle_status_command(&mut client, args).await?;
-        }
-        Commands::Completion(args) => {
-            commands::completion::handle_completion_command(args.shell)?;
+        Commands::Promote(args) => {
+            let client = get_client().await?;
+            commands::promote::handle_promote_command(args, &client).await?;
         }
-        Commands::Diff(args) => {
-            commands::diff::handle_diff(args).await?;
+        Commands::Status(args) => {
+            let mut client = if args.read_only {
+                get_read_only_client().await?
+            } else {
+                get_client().await?
+            };
+            commands::status::handle_status_command(&mut client, args, output).await?;
         }
+        Commands::Completion(args) => match (args.complete_target, args.shell) {
+            (Some(word), _) => {
+                commands::completion::handle_complete_target(&word, get_read_only_client())
+                    .await?;
+            }
+            (None, Some(shell)) => commands::completion::handle_completion_command(shell)?,
+            (None, None) => unreachable!("clap requires a shell without --complete-target"),
+        },
+        Commands::Diff(args) => match args.command {
+            Some(DiffCommand::Verify { dir }) => {
+                commands::diff::verify_dir(&dir).await?;
+            }
+            None => {
+                commands::diff::handle_diff(args).await?;
+            }
+        },
         Commands::Dump(args) => {
             commands::dump::handle_dump(args).await?;
         }
+        Commands::Resume(args) => {
+            let client = get_client().await?;
+            commands::resume::handle_resume_command(args, &client).await?;
+        }
+        Commands::Revision(args) => {
+            let client = get_client().await?;
+            commands::revision::handle_revision_command(args.command, &client).await?;
+        }
+        Commands::Rollouts(args) => {
+            let client = get_client().await?;
+            commands::rollouts::handle_rollouts_command(args, &client).await?;
+        }
+        Commands::History(args) => {
+            let client = get_client().await?;
+            commands::history::handle_history_command(args, &client).await?;
+        }
+        Commands::Explain(args) => {
+            let client = get_client().await?;
+            commands::explain::handle_explain_command(args, &client).await?;
+        }
+        Commands::Issues(args) => {
+            let client = get_client().await?;
+            commands::issues::handle_issues_command(args.command, &client).await?;
+        }
+        Commands::Release(args) => {
+            let client = get_client().await?;
+            commands::release::handle_release_command(args.command, &client).await?;
+        }
+        Commands::Plan(args) => {
+            let client = get_client().await?;
+            commands::plan::handle_plan_command(args.command, &client).await?;
+        }
+        Commands::Open(args) => {
+            commands::open::handle_open_command(args).await?;
+        }
+        Commands::Report(args) => {
+            let client = get_client().await?;
+            commands::report::handle_report_command(args, &client).await?;
+        }
+        Commands::Bundle(args) => match args.command {
+            BundleCommand::Verify { archive } => {
+                commands::bundle::verify_bundle(&archive).await?;
+            }
+            command => {
+                let client = get_client().await?;
+                commands::bundle::handle_bundle_command(command, &client).await?;
+            }
+        },
+        Commands::Stats(args) => {
+            commands::stats::handle_stats_command(args)?;
+        }
     }
 
     Ok(())
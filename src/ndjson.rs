@@ -0,0 +1,103 @@
+use crate::error::AppError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::Write;
+use std::path::Path;
+
+/// Appends `record` as one line to the NDJSON file at `path`, keeping roughly the newest `max`
+/// lines. Concurrent shelltide processes take turns through a `<file>.lock` next to it. The line
+/// is appended in place; only once the file is a tenth over `max` is it trimmed to the newest
+/// `max` lines, through a temp file and a rename so no reader sees it half written.
+pub async fn append_capped<T: Serialize>(
+    path: &Path,
+    record: &T,
+    max: usize,
+) -> Result<(), AppError> {
+    let line = serde_json::to_string(record)? + "\n";
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let _lock = crate::config::lock_file(&path.with_extension("jsonl.lock")).await?;
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+
+    let content = std::fs::read_to_string(path)?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() <= max + max / 10 {
+        return Ok(());
+    }
+    let tmp_path = path.with_extension(format!("jsonl.tmp.{}", std::process::id()));
+    let mut file = std::fs::File::create(&tmp_path)?;
+    file.write_all((lines[lines.len() - max..].join("\n") + "\n").as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Reads the NDJSON file at `path`, oldest first. Lines that cannot be parsed are skipped; a
+/// missing file reads as empty.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, AppError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_append_capped() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("nested").join("log.jsonl");
+        assert!(load::<u32>(&path).unwrap().is_empty());
+
+        for n in 0..22u32 {
+            append_capped(&path, &n, 20).await.unwrap();
+        }
+        // Two over the cap of 20 is within its slack...
+        assert_eq!(load::<u32>(&path).unwrap(), (0..22).collect::<Vec<_>>());
+        // ...one more trims it back to the newest 20.
+        append_capped(&path, &22u32, 20).await.unwrap();
+        assert_eq!(load::<u32>(&path).unwrap(), (3..23).collect::<Vec<_>>());
+        let leftovers = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter(|e| {
+                let name = e.as_ref().unwrap().file_name();
+                name.to_string_lossy().contains(".tmp.")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_appends_keep_every_line() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("log.jsonl");
+        let writers: Vec<_> = (0..32u32)
+            .map(|n| {
+                let path = path.clone();
+                tokio::spawn(async move { append_capped(&path, &n, 10).await })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap().unwrap();
+        }
+        let lines = load::<u32>(&path).unwrap();
+        assert!((10..=11).contains(&lines.len()));
+    }
+}
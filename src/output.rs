@@ -0,0 +1,45 @@
+use anyhow::Result;
+use serde::Serialize;
+
+/// How commands print their results (`--output`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns, for people.
+    #[default]
+    Table,
+    /// A JSON array of the rows, for scripts.
+    Json,
+}
+
+/// Prints `value` to stdout as pretty JSON.
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Formats `rows` under `header` and a dashed rule, padding every column to its widest cell.
+pub fn table_lines<const N: usize>(header: [&str; N], rows: &[[String; N]]) -> Vec<String> {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![
+        line(&header.map(String::from)),
+        line(&widths.map(|w| "-".repeat(w))),
+    ];
+    lines.extend(rows.iter().map(|row| line(row)));
+    lines
+}
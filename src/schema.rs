@@ -0,0 +1,250 @@
+use crate::sql::{Syntax, split_statements};
+use std::collections::BTreeMap;
+
+/// Columns of each table in a schema dump: `table -> column -> definition`.
+pub type Tables = BTreeMap<String, BTreeMap<String, String>>;
+
+/// A difference between two schema dumps.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaChange {
+    TableAdded(String),
+    TableDropped(String),
+    ColumnAdded {
+        table: String,
+        column: String,
+        definition: String,
+    },
+    ColumnDropped {
+        table: String,
+        column: String,
+    },
+    ColumnChanged {
+        table: String,
+        column: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl std::fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TableAdded(table) => write!(f, "+ table {table}"),
+            Self::TableDropped(table) => write!(f, "- table {table}"),
+            Self::ColumnAdded {
+                table,
+                column,
+                definition,
+            } => write!(f, "+ {table}.{column} {definition}"),
+            Self::ColumnDropped { table, column } => write!(f, "- {table}.{column}"),
+            Self::ColumnChanged {
+                table,
+                column,
+                from,
+                to,
+            } => write!(f, "~ {table}.{column} {from} -> {to}"),
+        }
+    }
+}
+
+/// Reads the `CREATE TABLE` statements of a schema dump, such as the `schema` of a changelog.
+/// Keys, indexes and constraints are left out; only columns are kept.
+pub fn parse_tables(schema: &str, syntax: Syntax) -> Tables {
+    let mut tables = Tables::new();
+    for statement in split_statements(schema, syntax) {
+        let statement = skip_comments(statement);
+        let Some(rest) = strip_keywords(statement, &["CREATE", "TABLE"]) else {
+            continue;
+        };
+        let rest = strip_keywords(rest, &["IF", "NOT", "EXISTS"]).unwrap_or(rest);
+        let Some(open) = rest.find('(') else {
+            continue;
+        };
+        let name = unquote(rest[..open].trim());
+        let Some(body) = enclosed(&rest[open..]) else {
+            continue;
+        };
+
+        let columns = tables.entry(name).or_default();
+        for item in split_top_level(body) {
+            let item = item.split_whitespace().collect::<Vec<_>>().join(" ");
+            let Some((column, definition)) = item.split_once(' ') else {
+                continue;
+            };
+            let keyword = column.to_uppercase();
+            if [
+                "PRIMARY",
+                "KEY",
+                "INDEX",
+                "UNIQUE",
+                "CONSTRAINT",
+                "FOREIGN",
+                "CHECK",
+                "FULLTEXT",
+                "SPATIAL",
+                "EXCLUDE",
+            ]
+            .contains(&keyword.as_str())
+            {
+                continue;
+            }
+            columns.insert(unquote(column), definition.to_string());
+        }
+    }
+    tables
+}
+
+/// The tables and columns added, dropped or changed from `old` to `new`.
+pub fn diff_tables(old: &Tables, new: &Tables) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    for table in old.keys().filter(|table| !new.contains_key(*table)) {
+        changes.push(SchemaChange::TableDropped(table.clone()));
+    }
+    for (table, columns) in new {
+        let Some(old_columns) = old.get(table) else {
+            changes.push(SchemaChange::TableAdded(table.clone()));
+            continue;
+        };
+        for column in old_columns.keys().filter(|c| !columns.contains_key(*c)) {
+            changes.push(SchemaChange::ColumnDropped {
+                table: table.clone(),
+                column: column.clone(),
+            });
+        }
+        for (column, definition) in columns {
+            match old_columns.get(column) {
+                None => changes.push(SchemaChange::ColumnAdded {
+                    table: table.clone(),
+                    column: column.clone(),
+                    definition: definition.clone(),
+                }),
+                Some(old_definition) if old_definition != definition => {
+                    changes.push(SchemaChange::ColumnChanged {
+                        table: table.clone(),
+                        column: column.clone(),
+                        from: old_definition.clone(),
+                        to: definition.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    changes
+}
+
+/// `statement` without leading whitespace and `--`, `#` or `/* */` comments.
+fn skip_comments(mut statement: &str) -> &str {
+    loop {
+        statement = statement.trim_start();
+        if statement.starts_with("--") || statement.starts_with('#') {
+            statement = statement.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(rest) = statement.strip_prefix("/*") {
+            statement = rest.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            return statement;
+        }
+    }
+}
+
+/// What follows `keywords` at the start of `text`, matched case-insensitively.
+fn strip_keywords<'a>(mut text: &'a str, keywords: &[&str]) -> Option<&'a str> {
+    for keyword in keywords {
+        text = text.trim_start();
+        let head = text.get(..keyword.len())?;
+        if !head.eq_ignore_ascii_case(keyword)
+            || text[keyword.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_')
+        {
+            return None;
+        }
+        text = &text[keyword.len()..];
+    }
+    Some(text)
+}
+
+/// The text inside the parentheses `text` starts with, up to the matching `)`.
+fn enclosed(text: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[1..i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a table body at the commas that are not inside parentheses or quotes.
+fn split_top_level(body: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut depth, mut quote, mut start) = (0, None, 0);
+    for (i, c) in body.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                items.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&body[start..]);
+    items
+}
+
+/// An identifier without its quotes, keeping a `schema.` prefix.
+fn unquote(name: &str) -> String {
+    name.replace(['`', '"'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_diff_tables() {
+        let old = "--\n-- Table structure for `users`\n--\nCREATE TABLE `users` (\n  `id` bigint NOT NULL,\n  `nick` varchar(32) DEFAULT 'a,b',\n  `level` int,\n  PRIMARY KEY (`id`),\n  KEY `idx_nick` (`nick`)\n) ENGINE=InnoDB;\nCREATE TABLE `legacy` (`id` int);\n";
+        let new = "CREATE TABLE IF NOT EXISTS `users` (\n  `id` bigint NOT NULL,\n  `nick` varchar(64) DEFAULT 'a,b',\n  `email` varchar(255) NOT NULL,\n  PRIMARY KEY (`id`)\n);\nCREATE TABLE `item_box` (`id` bigint, `price` decimal(10,2));\nCREATE INDEX idx ON users (email);\n";
+
+        let old = parse_tables(old, Syntax::MySql);
+        assert_eq!(
+            old["users"].keys().collect::<Vec<_>>(),
+            ["id", "level", "nick"]
+        );
+        assert_eq!(old["users"]["nick"], "varchar(32) DEFAULT 'a,b'");
+        let new = parse_tables(new, Syntax::MySql);
+        assert_eq!(new["item_box"]["price"], "decimal(10,2)");
+
+        let changes: Vec<String> = diff_tables(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "- table legacy",
+                "+ table item_box",
+                "- users.level",
+                "+ users.email varchar(255) NOT NULL",
+                "~ users.nick varchar(32) DEFAULT 'a,b' -> varchar(64) DEFAULT 'a,b'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use crate::api::types::SQLDialect;
+use crate::error::AppError;
+
+/// Lexical rules used to find statement boundaries in a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    /// MySQL and compatible engines (TiDB, MariaDB, OceanBase, ...): backslash escapes,
+    /// backtick identifiers and `#` comments.
+    MySql,
+    /// PostgreSQL and compatible engines: standard strings, `E'...'` escape strings,
+    /// dollar-quoted bodies and nested block comments.
+    Postgres,
+}
+
+impl Syntax {
+    pub fn for_engine(engine: &SQLDialect) -> Self {
+        match engine {
+            SQLDialect::PostgreSQL
+            | SQLDialect::Postgres
+            | SQLDialect::Redshift
+            | SQLDialect::CockroachDB => Self::Postgres,
+            _ => Self::MySql,
+        }
+    }
+}
+
+/// Splits a script into individual statements, each keeping its terminating `;`.
+///
+/// Semicolons inside quoted strings, quoted identifiers and comments are ignored. Text after
+/// the last semicolon (typically trailing whitespace or comments) is kept as its own piece
+/// when it contains anything but whitespace, otherwise it is appended to the last statement.
+/// MySQL `DELIMITER` changes are not understood.
+pub fn split_statements(sql: &str, syntax: Syntax) -> Vec<&str> {
+    match syntax {
+        Syntax::MySql => split_mysql(sql),
+        Syntax::Postgres => split_postgres(sql),
+    }
+}
+
+fn split_mysql(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' && quote != b'`' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'#' => i = skip_line(bytes, i),
+            b'-' if bytes.get(i + 1) == Some(&b'-')
+                && bytes.get(i + 2).is_none_or(|c| c.is_ascii_whitespace()) =>
+            {
+                i = skip_line(bytes, i)
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b';' => {
+                statements.push(&sql[start..=i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    finish(sql, statements, start)
+}
+
+fn split_postgres(sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' => {
+                // E'...' strings honour backslash escapes; standard strings only double quotes.
+                let escapes = i > 0
+                    && matches!(bytes[i - 1], b'E' | b'e')
+                    && (i < 2 || !is_ident_byte(bytes[i - 2]));
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'\'' {
+                    if escapes && bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += 1;
+                }
+            }
+            b'$' if i == 0 || !is_ident_byte(bytes[i - 1]) => {
+                if let Some(tag_len) = dollar_tag_len(&bytes[i..]) {
+                    let tag = &bytes[i..i + tag_len];
+                    i += tag_len;
+                    while i < bytes.len() && !bytes[i..].starts_with(tag) {
+                        i += 1;
+                    }
+                    i += tag_len - 1;
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => i = skip_line(bytes, i),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let mut depth = 1;
+                i += 2;
+                while i < bytes.len() && depth > 0 {
+                    if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'*') {
+                        depth += 1;
+                        i += 1;
+                    } else if bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/') {
+                        depth -= 1;
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i -= 1;
+            }
+            b';' => {
+                statements.push(&sql[start..=i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    finish(sql, statements, start)
+}
+
+/// Length of the `$tag$` opening a dollar-quoted string at the start of `bytes`, if any.
+/// Positional parameters such as `$1` are not dollar quotes.
+fn dollar_tag_len(bytes: &[u8]) -> Option<usize> {
+    let end = bytes[1..].iter().position(|b| *b == b'$')? + 1;
+    let tag = &bytes[1..end];
+    let valid =
+        tag.first().is_none_or(|b| !b.is_ascii_digit()) && tag.iter().all(|b| is_ident_byte(*b));
+    valid.then_some(end + 1)
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || !b.is_ascii()
+}
+
+/// Adds whatever follows the last `;` to `statements`, as described on [`split_statements`].
+fn finish<'a>(sql: &'a str, mut statements: Vec<&'a str>, start: usize) -> Vec<&'a str> {
+    let rest = &sql[start.min(sql.len())..];
+    if !rest.trim().is_empty() {
+        statements.push(rest);
+    } else if let Some(last) = statements.last_mut() {
+        *last = &sql[sql.len() - rest.len() - last.len()..];
+    }
+    statements
+}
+
+fn skip_line(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i] != b'\n' {
+        i += 1;
+    }
+    i
+}
+
+/// Groups the statements of `sql` into consecutive chunks of at most `max_bytes` each, keeping
+/// their order. A script that already fits is returned unchanged as a single chunk.
+pub fn chunk_statements(
+    sql: &str,
+    max_bytes: usize,
+    syntax: Syntax,
+) -> Result<Vec<String>, AppError> {
+    if sql.len() <= max_bytes {
+        return Ok(vec![sql.to_string()]);
+    }
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for statement in split_statements(sql, syntax) {
+        if statement.len() > max_bytes {
+            let preview: String = statement.trim().chars().take(60).collect();
+            return Err(AppError::StatementTooLarge(format!(
+                "a single statement is {} bytes, above the {max_bytes} byte sheet limit: {preview}...",
+                statement.len()
+            )));
+        }
+        if !current.is_empty() && current.len() + statement.len() > max_bytes {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(statement);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    Ok(chunks)
+}
+
+/// Keywords emphasized by [`highlight`].
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALTER",
+    "AND",
+    "AS",
+    "BY",
+    "CASCADE",
+    "CHECK",
+    "COLUMN",
+    "CONSTRAINT",
+    "CREATE",
+    "DEFAULT",
+    "DELETE",
+    "DROP",
+    "EXISTS",
+    "FOREIGN",
+    "FROM",
+    "FUNCTION",
+    "GRANT",
+    "IF",
+    "INDEX",
+    "INSERT",
+    "INTO",
+    "JOIN",
+    "KEY",
+    "MODIFY",
+    "NOT",
+    "NULL",
+    "ON",
+    "OR",
+    "PRIMARY",
+    "REFERENCES",
+    "RENAME",
+    "REPLACE",
+    "SELECT",
+    "SET",
+    "TABLE",
+    "TO",
+    "TRIGGER",
+    "TRUNCATE",
+    "UNIQUE",
+    "UPDATE",
+    "VALUES",
+    "VIEW",
+    "WHERE",
+];
+
+const BOLD_BLUE: &str = "\x1b[1;34m";
+const GREEN: &str = "\x1b[32m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Colors `sql` for a terminal with ANSI escapes: keywords, string literals and comments.
+/// Quoting follows `syntax` loosely; this is for display only.
+pub fn highlight(sql: &str, syntax: Syntax) -> String {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len() * 2);
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let style = match bytes[i] {
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' && syntax == Syntax::MySql {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+                Some(GREEN)
+            }
+            b'#' if syntax == Syntax::MySql => {
+                i = skip_line(bytes, i);
+                Some(DIM)
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = skip_line(bytes, i);
+                Some(DIM)
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                Some(DIM)
+            }
+            b if is_ident_byte(b) => {
+                while i < bytes.len() && is_ident_byte(bytes[i]) {
+                    i += 1;
+                }
+                let word = &sql[start..i];
+                KEYWORDS
+                    .iter()
+                    .any(|k| k.eq_ignore_ascii_case(word))
+                    .then_some(BOLD_BLUE)
+            }
+            _ => {
+                i += sql[i..].chars().next().map_or(1, char::len_utf8);
+                None
+            }
+        };
+        match style {
+            Some(style) => {
+                out.push_str(style);
+                out.push_str(&sql[start..i]);
+                out.push_str(RESET);
+            }
+            None => out.push_str(&sql[start..i]),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements_respects_quotes_and_comments() {
+        let sql = "INSERT INTO t VALUES ('a;b', \"c\\\";d\");\n\
+                   -- comment; here\n\
+                   CREATE TABLE `we;ird` (id int); /* block; comment */\n\
+                   # hash; comment\n\
+                   SELECT 1;\n";
+        let statements = split_statements(sql, Syntax::MySql);
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], "INSERT INTO t VALUES ('a;b', \"c\\\";d\");");
+        assert!(statements[1].trim().starts_with("-- comment; here"));
+        assert!(statements[1].trim_end().ends_with("(id int);"));
+        assert!(statements[2].trim().ends_with("SELECT 1;"));
+        assert_eq!(statements.concat(), sql);
+    }
+
+    #[test]
+    fn test_split_statements_keeps_unterminated_tail() {
+        let statements = split_statements("SELECT 1; SELECT 2", Syntax::MySql);
+        assert_eq!(statements, vec!["SELECT 1;", " SELECT 2"]);
+    }
+
+    #[test]
+    fn test_split_statements_postgres() {
+        let sql = "CREATE FUNCTION f() RETURNS int AS $body$ BEGIN RETURN 1; END; $body$ LANGUAGE plpgsql;\n\
+                   INSERT INTO t VALUES ('it''s; fine', E'back\\'slash;', 'c:\\');\n\
+                   /* outer /* nested; */ still; comment */ SELECT $1, \"we;ird\" FROM t;\n\
+                   DO $$ BEGIN PERFORM 1; END $$;\n";
+        let statements = split_statements(sql, Syntax::Postgres);
+        assert_eq!(statements.len(), 4);
+        assert!(statements[0].ends_with("LANGUAGE plpgsql;"));
+        assert!(statements[1].trim_end().ends_with("'c:\\');"));
+        assert!(statements[2].trim_end().ends_with("FROM t;"));
+        assert!(statements[3].trim().starts_with("DO $$"));
+        assert_eq!(statements.concat(), sql);
+
+        // A `#` is an operator in PostgreSQL, not a comment.
+        assert_eq!(
+            split_statements("SELECT 1 # 2; SELECT 3;", Syntax::Postgres).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_syntax_for_engine() {
+        assert_eq!(Syntax::for_engine(&SQLDialect::TiDB), Syntax::MySql);
+        assert_eq!(Syntax::for_engine(&SQLDialect::MySQL), Syntax::MySql);
+        assert_eq!(Syntax::for_engine(&SQLDialect::Postgres), Syntax::Postgres);
+    }
+
+    #[test]
+    fn test_chunk_statements() {
+        let sql = "SELECT 1;\nSELECT 2;\nSELECT 3;\n";
+        assert_eq!(
+            chunk_statements(sql, 1024, Syntax::MySql).unwrap(),
+            vec![sql.to_string()]
+        );
+
+        let chunks = chunk_statements(sql, 21, Syntax::MySql).unwrap();
+        assert_eq!(chunks, vec!["SELECT 1;\nSELECT 2;", "\nSELECT 3;\n"]);
+        assert_eq!(chunks.concat(), sql);
+
+        assert!(chunk_statements(sql, 5, Syntax::MySql).is_err());
+    }
+
+    #[test]
+    fn test_highlight_marks_keywords_strings_and_comments() {
+        let sql = "alter table t add note text default 'a;b'; -- done";
+        assert_eq!(
+            highlight(sql, Syntax::MySql),
+            "\x1b[1;34malter\x1b[0m \x1b[1;34mtable\x1b[0m t \x1b[1;34madd\x1b[0m note text \x1b[1;34mdefault\x1b[0m \x1b[32m'a;b'\x1b[0m; \x1b[2m-- done\x1b[0m"
+        );
+        assert_eq!(
+            highlight("SELECT 'é' #x", Syntax::Postgres),
+            "\x1b[1;34mSELECT\x1b[0m \x1b[32m'é'\x1b[0m #x"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Number of runs kept in the stats file; older ones are dropped.
+const MAX_RUNS: usize = 5000;
+
+/// Changelogs applied by this process, counted as their rollouts succeed.
+static CHANGELOGS_APPLIED: AtomicU32 = AtomicU32::new(0);
+
+/// Counts a changelog applied to a target towards this run's stats.
+pub fn count_applied_changelog() {
+    CHANGELOGS_APPLIED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Changelogs applied so far by this process.
+pub fn applied_changelogs() -> u32 {
+    CHANGELOGS_APPLIED.load(Ordering::Relaxed)
+}
+
+/// Metrics of one shelltide invocation, one line of the stats file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunStats {
+    /// Subcommand path, e.g. `migrate` or `status history`.
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub api_calls: u64,
+    /// Time spent waiting on those API calls.
+    pub api_time_ms: u64,
+    #[serde(default)]
+    pub changelogs_applied: u32,
+    pub success: bool,
+    pub shelltide_version: String,
+}
+
+/// The stats file, `~/.shelltide/stats.jsonl`: one [`RunStats`] per line.
+pub fn stats_path() -> Result<PathBuf, AppError> {
+    Ok(crate::config::get_config_dir()?.join("stats.jsonl"))
+}
+
+/// Appends `run` to the stats file at `path`, keeping only the newest [`MAX_RUNS`] runs.
+pub async fn append(path: &Path, run: &RunStats) -> Result<(), AppError> {
+    crate::ndjson::append_capped(path, run, MAX_RUNS).await
+}
+
+/// Reads the stats file, oldest first. Lines that cannot be parsed are skipped.
+pub fn load(path: &Path) -> Result<Vec<RunStats>, AppError> {
+    crate::ndjson::load(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_append_and_load() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("stats.jsonl");
+        assert!(load(&path).unwrap().is_empty());
+
+        let run = RunStats {
+            command: "status".to_string(),
+            started_at: Utc::now(),
+            duration_ms: 1200,
+            api_calls: 14,
+            api_time_ms: 900,
+            changelogs_applied: 0,
+            success: true,
+            shelltide_version: "0.1.0".to_string(),
+        };
+        append(&path, &run).await.unwrap();
+        std::fs::write(
+            &path,
+            std::fs::read_to_string(&path).unwrap() + "not json\n",
+        )
+        .unwrap();
+        append(&path, &run).await.unwrap();
+        assert_eq!(load(&path).unwrap(), [run.clone(), run]);
+    }
+}